@@ -0,0 +1,181 @@
+use crate::CACHE_DIR;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const AUDIT_LOG_MAX_BYTES: u64 = 256 * 1024;
+pub const AUDIT_TAIL_MAX_ENTRIES: usize = 50;
+
+/// Outcome of the allowlist/session checks applied to a remote command request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditDecision {
+    /// The program passed the allowlist and a spawn was attempted.
+    Allowed,
+    /// The program is not on `command_allowlist`.
+    Denied,
+    /// Another command was still running, so the request was answered with `Busy`.
+    Busy,
+    /// The command line could not be split into program + argv.
+    Invalid,
+}
+
+/// One JSONL record describing a single remote command request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp_ms: u64,
+    pub channel: String,
+    pub request_id: u32,
+    pub program: Option<String>,
+    pub argv: Vec<String>,
+    pub decision: AuditDecision,
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    pub stdout_bytes: u64,
+    pub stderr_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Append-only audit trail stored in `/run/serial_lcd_cache/tunnel/audit.jsonl`.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    max_bytes: u64,
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AuditLog {
+    pub fn new() -> Self {
+        Self::with_path(
+            PathBuf::from(CACHE_DIR).join("tunnel").join("audit.jsonl"),
+            AUDIT_LOG_MAX_BYTES,
+        )
+    }
+
+    pub fn with_path(path: impl Into<PathBuf>, max_bytes: u64) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes,
+        }
+    }
+
+    /// Append an entry, rotating the current file to `audit.jsonl.1` once it reaches the size cap.
+    pub fn append(&self, entry: &AuditEntry) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if let Ok(meta) = fs::metadata(&self.path) {
+            if meta.len() >= self.max_bytes {
+                fs::rename(&self.path, self.rotated_path())?;
+            }
+        }
+        let line = serde_json::to_string(entry).map_err(io::Error::other)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{line}")
+    }
+
+    /// Return up to `limit` of the most recent raw JSONL lines, oldest first.
+    pub fn tail(&self, limit: usize) -> io::Result<Vec<String>> {
+        let limit = limit.min(AUDIT_TAIL_MAX_ENTRIES);
+        let mut lines = read_lines(&self.path)?;
+        if lines.len() < limit {
+            let mut older = read_lines(&self.rotated_path())?;
+            older.append(&mut lines);
+            lines = older;
+        }
+        let skip = lines.len().saturating_sub(limit);
+        Ok(lines.into_iter().skip(skip).collect())
+    }
+
+    fn rotated_path(&self) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(".1");
+        PathBuf::from(name)
+    }
+}
+
+fn read_lines(path: &Path) -> io::Result<Vec<String>> {
+    match fs::read_to_string(path) {
+        Ok(raw) => Ok(raw
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::to_string)
+            .collect()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn entry(request_id: u32) -> AuditEntry {
+        AuditEntry {
+            timestamp_ms: now_ms(),
+            channel: "tunnel".into(),
+            request_id,
+            program: Some("echo".into()),
+            argv: vec!["hello".into()],
+            decision: AuditDecision::Allowed,
+            exit_code: Some(0),
+            duration_ms: 3,
+            stdout_bytes: 6,
+            stderr_bytes: 0,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn appends_jsonl_and_tails_latest_entries() {
+        let dir = tempdir().unwrap();
+        let log = AuditLog::with_path(dir.path().join("audit.jsonl"), AUDIT_LOG_MAX_BYTES);
+        for id in 1..=5 {
+            log.append(&entry(id)).unwrap();
+        }
+        let tail = log.tail(2).unwrap();
+        assert_eq!(tail.len(), 2);
+        let last: AuditEntry = serde_json::from_str(&tail[1]).unwrap();
+        assert_eq!(last.request_id, 5);
+        assert_eq!(last.decision, AuditDecision::Allowed);
+        assert_eq!(last.argv, vec!["hello".to_string()]);
+    }
+
+    #[test]
+    fn rotates_when_size_cap_reached_and_tail_spans_both_files() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let log = AuditLog::with_path(&path, 64);
+        log.append(&entry(1)).unwrap();
+        log.append(&entry(2)).unwrap();
+        assert!(dir.path().join("audit.jsonl.1").exists());
+        let current = fs::read_to_string(&path).unwrap();
+        assert_eq!(current.lines().count(), 1);
+
+        let tail = log.tail(10).unwrap();
+        let ids: Vec<u32> = tail
+            .iter()
+            .map(|line| serde_json::from_str::<AuditEntry>(line).unwrap().request_id)
+            .collect();
+        assert_eq!(ids, vec![1, 2]);
+    }
+}
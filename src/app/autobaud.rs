@@ -0,0 +1,387 @@
+//! `--autobaud`: find the peer's baud, parity and stop bits by listening at each candidate
+//! and counting how many lines decode as something the daemon understands.
+//!
+//! A wrong rate or framing turns the peer's bytes into noise that rarely contains a newline and
+//! never passes a JSON or CRC check, so the candidate with the most valid lines wins. Before
+//! listening we send `INIT` and a `hello` so a silent server answers; bus nodes only listen,
+//! since they may not transmit until polled.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use super::negotiation::Negotiator;
+use super::Logger;
+use crate::{
+    config::{Config, NegotiationConfig},
+    negotiation::ControlFrame,
+    payload::{decode_command_frame, decode_tunnel_frame, RenderFrame},
+    serial::{LineIo, ParityMode, SerialOptions, StopBitsMode, STANDARD_BAUD_RATES},
+    Result,
+};
+
+/// How long to listen at each candidate before scoring it.
+const LISTEN_WINDOW_MS: u64 = 1_200;
+/// Reads are capped so a long `serial_timeout_ms` cannot overrun the listen window.
+const MAX_READ_TIMEOUT_MS: u64 = 200;
+/// Stop scanning once a candidate produces this many valid lines and no garbage.
+const LOCK_VALID_LINES: u32 = 3;
+const PARITY_CANDIDATES: [ParityMode; 3] = [ParityMode::None, ParityMode::Even, ParityMode::Odd];
+const STOP_BITS_CANDIDATES: [StopBitsMode; 2] = [StopBitsMode::One, StopBitsMode::Two];
+/// Link-layer wrappers from `ReliableLink` and `BusLink`; their payloads are checked later.
+const LINK_PREFIXES: [&str; 5] = ["@rel:", "@ack:", "@bus:", "@poll", "@done"];
+
+/// Baud rate plus character framing, shown in the usual `115200 8E1` notation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LineSettings {
+    pub baud: u32,
+    pub parity: ParityMode,
+    pub stop_bits: StopBitsMode,
+}
+
+impl LineSettings {
+    fn apply(self, mut options: SerialOptions) -> SerialOptions {
+        options.baud = self.baud;
+        options.parity = self.parity;
+        options.stop_bits = self.stop_bits;
+        options
+    }
+}
+
+impl fmt::Display for LineSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parity = match self.parity {
+            ParityMode::None => 'N',
+            ParityMode::Odd => 'O',
+            ParityMode::Even => 'E',
+        };
+        let stop_bits = match self.stop_bits {
+            StopBitsMode::One => 1,
+            StopBitsMode::Two => 2,
+        };
+        write!(f, "{} 8{parity}{stop_bits}", self.baud)
+    }
+}
+
+/// What one candidate heard during its listen window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CandidateScore {
+    pub settings: LineSettings,
+    pub valid: u32,
+    pub garbage: u32,
+    /// Set when the port could not be opened or read at these settings.
+    pub error: Option<String>,
+}
+
+impl CandidateScore {
+    fn beats(&self, other: &CandidateScore) -> bool {
+        (self.valid, std::cmp::Reverse(self.garbage))
+            > (other.valid, std::cmp::Reverse(other.garbage))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AutobaudReport {
+    pub scores: Vec<CandidateScore>,
+    /// Best candidate with at least one valid line, if any.
+    pub chosen: Option<LineSettings>,
+}
+
+/// The configured settings first (a correct config locks on the first try), then every
+/// standard rate with each parity and stop-bit combination.
+pub(crate) fn candidates(configured: LineSettings) -> Vec<LineSettings> {
+    let mut out = vec![configured];
+    for &baud in STANDARD_BAUD_RATES {
+        for parity in PARITY_CANDIDATES {
+            for stop_bits in STOP_BITS_CANDIDATES {
+                let settings = LineSettings {
+                    baud,
+                    parity,
+                    stop_bits,
+                };
+                if settings != configured {
+                    out.push(settings);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Scan `candidates` on `device`, opening the port through `connect` for each one.
+pub(crate) fn detect_with<IO, Connect>(
+    device: &str,
+    base_options: SerialOptions,
+    candidates: &[LineSettings],
+    solicitation: &[String],
+    listen: Duration,
+    mut connect: Connect,
+) -> AutobaudReport
+where
+    IO: LineIo,
+    Connect: FnMut(&str, SerialOptions) -> Result<IO>,
+{
+    let mut base_options = base_options;
+    base_options.timeout_ms = base_options.timeout_ms.clamp(1, MAX_READ_TIMEOUT_MS);
+
+    let mut scores: Vec<CandidateScore> = Vec::new();
+    for &settings in candidates {
+        let mut score = CandidateScore {
+            settings,
+            valid: 0,
+            garbage: 0,
+            error: None,
+        };
+        match connect(device, settings.apply(base_options)) {
+            Ok(mut port) => {
+                if let Err(err) = listen_and_score(&mut port, solicitation, listen, &mut score) {
+                    score.error = Some(err.to_string());
+                }
+            }
+            Err(err) => score.error = Some(err.to_string()),
+        }
+        let locked = score.valid >= LOCK_VALID_LINES && score.garbage == 0;
+        scores.push(score);
+        if locked {
+            break;
+        }
+    }
+
+    let chosen = scores
+        .iter()
+        .filter(|score| score.valid > 0)
+        .fold(None::<&CandidateScore>, |best, score| match best {
+            Some(best) if !score.beats(best) => Some(best),
+            _ => Some(score),
+        })
+        .map(|score| score.settings);
+    AutobaudReport { scores, chosen }
+}
+
+fn listen_and_score<IO: LineIo>(
+    port: &mut IO,
+    solicitation: &[String],
+    listen: Duration,
+    score: &mut CandidateScore,
+) -> Result<()> {
+    for line in solicitation {
+        port.send_command_line(line)?;
+    }
+    let deadline = Instant::now() + listen;
+    let mut buffer = String::new();
+    while Instant::now() < deadline && score.valid < LOCK_VALID_LINES {
+        match port.read_message_line(&mut buffer) {
+            Ok(0) => continue,
+            Ok(_) => match classify_line(buffer.trim()) {
+                LineClass::Valid => score.valid += 1,
+                LineClass::Garbage => score.garbage += 1,
+                LineClass::Blank => {}
+            },
+            // Oversized or non-UTF-8 lines are exactly what a wrong rate produces.
+            Err(crate::Error::Parse(_)) => score.garbage += 1,
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineClass {
+    Valid,
+    Garbage,
+    Blank,
+}
+
+fn classify_line(line: &str) -> LineClass {
+    if line.is_empty() {
+        return LineClass::Blank;
+    }
+    let valid = line == "INIT"
+        || LINK_PREFIXES.iter().any(|prefix| line.starts_with(prefix))
+        || serde_json::from_str::<ControlFrame>(line).is_ok()
+        || decode_tunnel_frame(line).is_ok()
+        || decode_command_frame(line).is_ok()
+        || RenderFrame::from_payload_json(line).is_ok();
+    if valid {
+        LineClass::Valid
+    } else {
+        LineClass::Garbage
+    }
+}
+
+/// Lines that prompt the peer to talk: `INIT` and a `hello`, as a normal connect would send.
+pub(crate) fn solicitation(
+    negotiation: &NegotiationConfig,
+    compression_enabled: bool,
+) -> Vec<String> {
+    let hello = Negotiator::new(negotiation, compression_enabled).hello_frame();
+    let mut lines = vec!["INIT".to_string()];
+    if let Ok(hello) = serde_json::to_string(&hello) {
+        lines.push(hello);
+    }
+    lines
+}
+
+/// Run detection for the daemon and apply the winner to `config`. Keeps the configured
+/// settings when nothing on the wire decodes.
+pub(crate) fn run_autobaud<IO, Connect>(
+    config: &mut super::AppConfig,
+    device: &str,
+    logger: &Logger,
+    connect: Connect,
+) -> Option<LineSettings>
+where
+    IO: LineIo,
+    Connect: FnMut(&str, SerialOptions) -> Result<IO>,
+{
+    let configured = LineSettings {
+        baud: config.baud,
+        parity: config.parity,
+        stop_bits: config.stop_bits,
+    };
+    let solicit = if config.bus.is_enabled() {
+        Vec::new()
+    } else {
+        solicitation(&config.negotiation, config.compression_enabled)
+    };
+    let list = candidates(configured);
+    logger.info(format!(
+        "autobaud: scanning {} line settings on {device}",
+        list.len()
+    ));
+    let report = detect_with(
+        device,
+        config.serial_options(),
+        &list,
+        &solicit,
+        Duration::from_millis(LISTEN_WINDOW_MS),
+        connect,
+    );
+    for score in &report.scores {
+        match &score.error {
+            Some(err) => logger.debug(format!("autobaud: {} failed: {err}", score.settings)),
+            None => logger.debug(format!(
+                "autobaud: {} valid={} garbage={}",
+                score.settings, score.valid, score.garbage
+            )),
+        }
+    }
+    match report.chosen {
+        Some(settings) => {
+            logger.info(format!("autobaud: locked onto {settings}"));
+            config.baud = settings.baud;
+            config.parity = settings.parity;
+            config.stop_bits = settings.stop_bits;
+        }
+        None => logger.warn(format!(
+            "autobaud: no line settings produced valid frames; keeping {configured}"
+        )),
+    }
+    report.chosen
+}
+
+/// `--autobaud-save`: write the detected settings into `~/.serial_lcd/config.toml`.
+pub(crate) fn persist(settings: LineSettings) -> Result<()> {
+    let mut cfg = Config::load_or_default()?;
+    cfg.baud = settings.baud;
+    cfg.parity = settings.parity;
+    cfg.stop_bits = settings.stop_bits;
+    cfg.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::fake::FakeSerialPort;
+
+    const PAYLOAD: &str = r#"{"schema_version":1,"line1":"CPU 42%","line2":"up 3d"}"#;
+
+    fn settings(baud: u32, parity: ParityMode, stop_bits: StopBitsMode) -> LineSettings {
+        LineSettings {
+            baud,
+            parity,
+            stop_bits,
+        }
+    }
+
+    #[test]
+    fn candidates_start_with_configured_and_cover_every_framing() {
+        let configured = settings(57_600, ParityMode::Even, StopBitsMode::One);
+        let list = candidates(configured);
+        assert_eq!(list[0], configured);
+        assert_eq!(
+            list.len(),
+            STANDARD_BAUD_RATES.len() * PARITY_CANDIDATES.len() * STOP_BITS_CANDIDATES.len()
+        );
+        assert_eq!(list.iter().filter(|s| **s == configured).count(), 1);
+        assert_eq!(configured.to_string(), "57600 8E1");
+    }
+
+    #[test]
+    fn classifies_protocol_lines_and_noise() {
+        assert_eq!(classify_line(PAYLOAD), LineClass::Valid);
+        assert_eq!(classify_line("INIT"), LineClass::Valid);
+        assert_eq!(classify_line("@bus:2:1:{}"), LineClass::Valid);
+        assert_eq!(classify_line("\u{fffd}x\u{7f}~"), LineClass::Garbage);
+        assert_eq!(classify_line(r#"{"line1":"#), LineClass::Garbage);
+        assert_eq!(classify_line(""), LineClass::Blank);
+    }
+
+    #[test]
+    fn locks_onto_the_settings_that_decode_and_stops_scanning() {
+        let target = settings(115_200, ParityMode::Even, StopBitsMode::One);
+        let list = vec![
+            settings(9_600, ParityMode::None, StopBitsMode::One),
+            settings(19_200, ParityMode::None, StopBitsMode::One),
+            target,
+            settings(230_400, ParityMode::None, StopBitsMode::One),
+        ];
+        let mut opened = Vec::new();
+        let report = detect_with(
+            "/dev/fake",
+            SerialOptions::default(),
+            &list,
+            &["INIT".to_string()],
+            Duration::from_millis(20),
+            |_, options: SerialOptions| {
+                opened.push(options.baud);
+                let script = if options.baud == target.baud && options.parity == target.parity {
+                    vec![Ok(PAYLOAD.into()), Ok("INIT".into()), Ok(PAYLOAD.into())]
+                } else if options.baud == 19_200 {
+                    vec![Ok(PAYLOAD.into()), Ok("\u{fffd}\u{fffd}q".into())]
+                } else {
+                    vec![Ok("x\u{7f}\u{fffd}".into())]
+                };
+                Ok(FakeSerialPort::new(script))
+            },
+        );
+        assert_eq!(report.chosen, Some(target));
+        assert_eq!(opened, vec![9_600, 19_200, 115_200]);
+        assert_eq!(report.scores[1].valid, 1);
+        assert_eq!(report.scores[1].garbage, 1);
+    }
+
+    #[test]
+    fn silence_and_open_failures_choose_nothing() {
+        let list = vec![
+            settings(9_600, ParityMode::None, StopBitsMode::One),
+            settings(19_200, ParityMode::None, StopBitsMode::One),
+        ];
+        let report = detect_with(
+            "/dev/fake",
+            SerialOptions::default(),
+            &list,
+            &[],
+            Duration::from_millis(5),
+            |_, options: SerialOptions| {
+                if options.baud == 9_600 {
+                    Err(crate::Error::Io(std::io::Error::other("busy")))
+                } else {
+                    Ok(FakeSerialPort::new(Vec::new()))
+                }
+            },
+        );
+        assert_eq!(report.chosen, None);
+        assert!(report.scores[0].error.is_some());
+        assert_eq!(report.scores[1].valid, 0);
+    }
+}
@@ -0,0 +1,97 @@
+//! Backlight level policy: configured brightness, time-of-day schedule, idle timeout, and
+//! wake-ups from the button or alert frames.
+
+use std::time::{Duration, Instant};
+
+use crate::{config::BacklightConfig, display::backlight::local_minute_of_day};
+
+/// How long a button press or alert overrides an off/dim schedule window.
+const WAKE_HOLD_MS: u64 = 60_000;
+
+pub(super) struct BacklightPolicy {
+    config: BacklightConfig,
+    last_activity: Instant,
+    woken_until: Option<Instant>,
+}
+
+impl BacklightPolicy {
+    pub(super) fn new(config: BacklightConfig, now: Instant) -> Self {
+        Self {
+            config,
+            last_activity: now,
+            woken_until: None,
+        }
+    }
+
+    pub(super) fn set_config(&mut self, config: BacklightConfig) {
+        self.config = config;
+    }
+
+    /// A new payload arrived; restart the idle timer.
+    pub(super) fn activity(&mut self, now: Instant) {
+        self.last_activity = now;
+    }
+
+    /// Button press or alert: light the panel even inside an off window.
+    pub(super) fn wake(&mut self, now: Instant) {
+        self.last_activity = now;
+        self.woken_until = Some(now + Duration::from_millis(WAKE_HOLD_MS));
+    }
+
+    /// Brightness percent the panels should show right now.
+    pub(super) fn level(&self, now: Instant) -> u8 {
+        let minute = if self.config.schedule.is_empty() {
+            0
+        } else {
+            local_minute_of_day()
+        };
+        self.level_at(now, minute)
+    }
+
+    fn level_at(&self, now: Instant, minute_of_day: u16) -> u8 {
+        if self.woken_until.is_some_and(|until| now < until) {
+            return self.config.brightness;
+        }
+        let idle = Duration::from_millis(self.config.idle_timeout_ms);
+        if !idle.is_zero() && now.duration_since(self.last_activity) >= idle {
+            return 0;
+        }
+        self.config
+            .schedule
+            .brightness_at(minute_of_day)
+            .unwrap_or(self.config.brightness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schedule_idle_timeout_and_wake() {
+        let start = Instant::now();
+        let mut policy = BacklightPolicy::new(
+            BacklightConfig {
+                brightness: 80,
+                idle_timeout_ms: 600_000,
+                schedule: "22:00-06:00=off,18:00-22:00=30".parse().unwrap(),
+            },
+            start,
+        );
+        let noon = 12 * 60;
+        let night = 23 * 60;
+        assert_eq!(policy.level_at(start, noon), 80);
+        assert_eq!(policy.level_at(start, 19 * 60), 30);
+        assert_eq!(policy.level_at(start, night), 0);
+
+        let idle = start + Duration::from_secs(601);
+        assert_eq!(policy.level_at(idle, noon), 0);
+        policy.activity(idle);
+        assert_eq!(policy.level_at(idle, noon), 80);
+
+        policy.wake(idle);
+        assert_eq!(policy.level_at(idle, night), 80);
+        let later = idle + Duration::from_millis(WAKE_HOLD_MS);
+        assert_eq!(policy.level_at(later, night), 0);
+    }
+}
@@ -1,58 +1,111 @@
+use super::identity::{check_peer_pin, NodeIdentity, PeerIdentity, PinCheck};
 use super::Logger;
 use crate::{
     app::negotiation::{NegotiationLog, Negotiator},
-    config::NegotiationConfig,
-    negotiation::{Capabilities, ControlCaps, ControlFrame, Role},
-    serial::{classify_error, LineIo, SerialFailureKind, SerialOptions, SerialPort},
+    config::{BusConfig, NegotiationConfig},
+    negotiation::{
+        highest_common_baud, select_protocol_version, Capabilities, ControlCaps, ControlFrame,
+        ProtocolVersion, Role,
+    },
+    serial::{
+        bus::{BusLink, BusStation},
+        classify_error,
+        discovery::DeviceSelector,
+        reliable::{ReliableConfig, ReliableLink},
+        LineIo, SerialFailureKind, SerialOptions, SerialPort,
+    },
 };
 use serde_json;
 use std::str::FromStr;
 use std::time::{Duration, Instant};
 
+/// How often a peer repeats its baud check while waiting for the other side's.
+const BAUD_CHECK_RESEND_MS: u64 = 100;
+
 struct NegotiationResult {
     role: Role,
     remote_caps: Option<Capabilities>,
     fallback: bool,
+    /// Rate both peers switched to after the handshake; `None` stays at the configured baud.
+    upgraded_baud: Option<u32>,
+    protocol: ProtocolVersion,
+    peer: Option<PeerIdentity>,
 }
 
 pub(crate) struct ConnectOutcome {
-    pub port: SerialPort,
+    pub port: ReliableLink<BusLink<SerialPort>>,
+    pub remote_caps: Option<Capabilities>,
+    /// Selects the command/payload schema versions accepted on this link.
+    pub protocol: ProtocolVersion,
+    /// Elected role; `Role::Server` when the peer skipped negotiation.
+    pub role: Role,
+    /// Name and UUID from the peer's `hello`; `None` for legacy peers.
+    pub peer: Option<PeerIdentity>,
+    /// Set when the peer is not the pinned one and `peer_mismatch = "alert"`.
+    pub peer_alert: Option<String>,
+    /// Rate the port runs at after the handshake, including any baud upgrade.
+    pub baud: u32,
+}
+
+/// Session parameters from a handshake rerun on an already open link.
+pub(crate) struct Renegotiated {
     pub remote_caps: Option<Capabilities>,
+    pub protocol: ProtocolVersion,
+    pub role: Role,
+    pub peer: Option<PeerIdentity>,
+    pub pin: PinCheck,
 }
 
 /// Attempt to open the serial port, send the INIT handshake, and log outcomes.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn attempt_serial_connect(
     logger: &Logger,
     device: &str,
+    device_match: Option<&DeviceSelector>,
     options: SerialOptions,
     negotiation: &NegotiationConfig,
+    identity: &NodeIdentity,
     compression_enabled: bool,
+    bus: &BusConfig,
     log: &mut NegotiationLog,
 ) -> Result<ConnectOutcome, SerialFailureKind> {
     attempt_serial_connect_with(
         logger,
         device,
+        device_match,
         options,
         negotiation,
+        identity,
         compression_enabled,
+        bus,
         log,
         SerialPort::connect,
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn attempt_serial_connect_with<F>(
     logger: &Logger,
     device: &str,
+    device_match: Option<&DeviceSelector>,
     options: SerialOptions,
     negotiation: &NegotiationConfig,
+    identity: &NodeIdentity,
     compression_enabled: bool,
+    bus: &BusConfig,
     log: &mut NegotiationLog,
     connect: F,
 ) -> Result<ConnectOutcome, SerialFailureKind>
 where
     F: FnOnce(&str, SerialOptions) -> crate::Result<SerialPort>,
 {
+    let device = resolve_device(device, device_match, logger)?;
+    let device = device.as_str();
     match connect(device, options) {
+        Ok(serial_connection) if bus.is_enabled() => {
+            logger.info("serial connected");
+            Ok(join_bus(serial_connection, bus, options.baud, logger, log))
+        }
         Ok(mut serial_connection) => {
             if let Err(err) = serial_connection.send_command_line("INIT") {
                 let reason = classify_error(&err);
@@ -69,7 +122,9 @@ where
                 &mut serial_connection,
                 logger,
                 negotiation,
+                identity,
                 compression_enabled,
+                options.baud,
                 log,
             );
             if negotiation_result.fallback {
@@ -82,17 +137,57 @@ where
                     .map(|caps| caps.bits())
                     .unwrap_or(0);
                 logger.info(format!(
-                    "negotiation: role decided as {} remote_caps=0x{caps_bits:08x}",
-                    negotiation_result.role.as_str()
+                    "negotiation: role decided as {} protocol={} remote_caps=0x{caps_bits:08x}",
+                    negotiation_result.role.as_str(),
+                    negotiation_result.protocol
                 ));
                 log.record(format!(
-                    "negotiation: role={} remote_caps=0x{caps_bits:08x}",
-                    negotiation_result.role.as_str()
+                    "negotiation: role={} protocol={} remote_caps=0x{caps_bits:08x}",
+                    negotiation_result.role.as_str(),
+                    negotiation_result.protocol
                 ));
             }
+            let peer_alert = match check_peer_pin(negotiation, negotiation_result.peer.as_ref()) {
+                PinCheck::Accepted => None,
+                PinCheck::Alert(reason) => {
+                    logger.warn(format!("negotiation: {reason}"));
+                    log.record(format!("negotiation: pin alert: {reason}"));
+                    Some(reason)
+                }
+                PinCheck::Refused(reason) => {
+                    logger.warn(format!("negotiation: refusing session: {reason}"));
+                    log.record(format!("negotiation: refusing session: {reason}"));
+                    let _ = send_control_frame(
+                        &mut serial_connection,
+                        &ControlFrame::LegacyFallback {
+                            reason: Some(reason),
+                        },
+                        "legacy_fallback",
+                        logger,
+                        log,
+                    );
+                    return Err(SerialFailureKind::PeerRejected);
+                }
+            };
+            let baud = negotiation_result.upgraded_baud.unwrap_or(options.baud);
+            let port = wrap_link(
+                BusLink::passthrough(serial_connection),
+                negotiation,
+                negotiation_result.remote_caps.as_ref(),
+                baud,
+            );
+            if port.is_reliable() {
+                logger.info("negotiation: reliable delivery enabled");
+                log.record("negotiation: reliable delivery enabled");
+            }
             Ok(ConnectOutcome {
-                port: serial_connection,
+                port,
                 remote_caps: negotiation_result.remote_caps,
+                protocol: negotiation_result.protocol,
+                role: negotiation_result.role,
+                peer: negotiation_result.peer,
+                peer_alert,
+                baud,
             })
         }
         Err(err) => {
@@ -107,6 +202,189 @@ where
     }
 }
 
+/// A replugged adapter may come back under another kernel name, so `device_match` is looked
+/// up again on every attempt.
+pub(super) fn resolve_device(
+    device: &str,
+    device_match: Option<&DeviceSelector>,
+    logger: &Logger,
+) -> Result<String, SerialFailureKind> {
+    match lookup_device(device, device_match) {
+        Ok(path) => {
+            if let Some(selector) = device_match {
+                logger.info(format!("device_match \"{selector}\" resolved to {path}"));
+            }
+            Ok(path)
+        }
+        Err(reason) => {
+            logger.warn(format!("{reason}; will retry"));
+            Err(SerialFailureKind::DeviceMissing)
+        }
+    }
+}
+
+/// The port to open: `device_match` looked up now when set, else the configured `device`.
+pub(super) fn lookup_device(
+    device: &str,
+    device_match: Option<&DeviceSelector>,
+) -> Result<String, String> {
+    let Some(selector) = device_match else {
+        return Ok(device.to_string());
+    };
+    selector
+        .resolve()
+        .map(|path| path.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("no serial device matches device_match \"{selector}\""))
+}
+
+/// Bus mode skips `INIT` and the hello exchange: every node would answer at once, and a node
+/// may not transmit before the master polls it. The master produces payloads like a client;
+/// nodes render and run commands like a server.
+fn join_bus(
+    port: SerialPort,
+    bus: &BusConfig,
+    baud: u32,
+    logger: &Logger,
+    log: &mut NegotiationLog,
+) -> ConnectOutcome {
+    let station = bus.station().unwrap_or(BusStation::Node {
+        master: bus.master_address,
+    });
+    let (role, summary) = match &station {
+        BusStation::Master { nodes } => (
+            Role::Client,
+            format!("master address={} polling {nodes:?}", bus.address),
+        ),
+        BusStation::Node { master } => (
+            Role::Server,
+            format!("node address={} master={master}", bus.address),
+        ),
+    };
+    logger.info(format!("bus: joined as {summary}; negotiation skipped"));
+    log.record(format!("negotiation: skipped in bus mode ({summary})"));
+    let link = BusLink::new(
+        port,
+        bus.address,
+        station,
+        Duration::from_millis(bus.turn_timeout_ms),
+        baud,
+    );
+    ConnectOutcome {
+        port: ReliableLink::passthrough(link),
+        remote_caps: None,
+        protocol: ProtocolVersion::LEGACY,
+        role,
+        peer: None,
+        peer_alert: None,
+        baud,
+    }
+}
+
+/// Sequence outgoing frames only when both peers advertised reliable delivery.
+fn wrap_link<IO: LineIo>(
+    io: IO,
+    config: &NegotiationConfig,
+    remote_caps: Option<&Capabilities>,
+    baud: u32,
+) -> ReliableLink<IO> {
+    if use_reliable(config, remote_caps) {
+        ReliableLink::new(io, ReliableConfig::for_baud(baud))
+    } else {
+        ReliableLink::passthrough(io)
+    }
+}
+
+fn use_reliable(config: &NegotiationConfig, remote_caps: Option<&Capabilities>) -> bool {
+    config.reliable_delivery && remote_caps.is_some_and(|caps| caps.supports_reliable)
+}
+
+/// Rerun the hello exchange without closing the port, after the peer sent `renegotiate` or
+/// a fresh `hello` (it restarted). `trigger` is that peer `hello`, replayed as the first line
+/// the handshake reads. The link stays at `baud`, the rate the port currently runs at, and
+/// reliable delivery restarts from scratch when both sides still advertise it.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn renegotiate<IO: LineIo>(
+    port: &mut ReliableLink<IO>,
+    logger: &Logger,
+    negotiation: &NegotiationConfig,
+    identity: &NodeIdentity,
+    compression_enabled: bool,
+    baud: u32,
+    trigger: Option<&str>,
+    log: &mut NegotiationLog,
+) -> Renegotiated {
+    log.record("negotiation: renegotiating in place");
+    // A restarted peer speaks plain lines until the new handshake says otherwise.
+    port.reset(false, ReliableConfig::for_baud(baud));
+    let config = NegotiationConfig {
+        max_baud: None,
+        ..negotiation.clone()
+    };
+    let mut io = ReplayFirst {
+        first: trigger.map(str::to_string),
+        io: port,
+    };
+    let result = negotiate_handshake(
+        &mut io,
+        logger,
+        &config,
+        identity,
+        compression_enabled,
+        baud,
+        log,
+    );
+    let pin = check_peer_pin(negotiation, result.peer.as_ref());
+    let reliable = !result.fallback && use_reliable(negotiation, result.remote_caps.as_ref());
+    port.reset(reliable, ReliableConfig::for_baud(baud));
+    let caps_bits = result
+        .remote_caps
+        .as_ref()
+        .map(|caps| caps.bits())
+        .unwrap_or(0);
+    logger.info(format!(
+        "negotiation: renegotiated role={} protocol={} remote_caps=0x{caps_bits:08x} reliable={reliable}",
+        result.role.as_str(),
+        result.protocol
+    ));
+    log.record(format!(
+        "negotiation: renegotiated role={} protocol={} remote_caps=0x{caps_bits:08x}",
+        result.role.as_str(),
+        result.protocol
+    ));
+    Renegotiated {
+        remote_caps: result.remote_caps,
+        protocol: result.protocol,
+        role: result.role,
+        peer: result.peer,
+        pin,
+    }
+}
+
+/// Hands the handshake a line that was already read before falling through to `io`.
+struct ReplayFirst<'a, IO> {
+    first: Option<String>,
+    io: &'a mut IO,
+}
+
+impl<IO: LineIo> LineIo for ReplayFirst<'_, IO> {
+    fn send_command_line(&mut self, line: &str) -> crate::Result<()> {
+        self.io.send_command_line(line)
+    }
+
+    fn read_message_line(&mut self, buf: &mut String) -> crate::Result<usize> {
+        if let Some(line) = self.first.take() {
+            buf.clear();
+            buf.push_str(&line);
+            return Ok(buf.len());
+        }
+        self.io.read_message_line(buf)
+    }
+
+    fn set_baud(&mut self, baud: u32) -> crate::Result<()> {
+        self.io.set_baud(baud)
+    }
+}
+
 fn connect_failure_hint(reason: SerialFailureKind, device: &str) -> Option<String> {
     match reason {
         SerialFailureKind::PermissionDenied => Some(format!(
@@ -120,13 +398,20 @@ fn negotiate_handshake<IO>(
     io: &mut IO,
     logger: &Logger,
     config: &NegotiationConfig,
+    identity: &NodeIdentity,
     compression_enabled: bool,
+    base_baud: u32,
     log: &mut NegotiationLog,
 ) -> NegotiationResult
 where
     IO: LineIo,
 {
-    let negotiator = Negotiator::new(config, compression_enabled);
+    let negotiator = Negotiator::new(config, compression_enabled)
+        .with_baud_upgrade(base_baud, config.max_baud)
+        .with_identity(identity);
+    let mut peer: Option<PeerIdentity> = None;
+    let mut remote_bauds = Vec::new();
+    let mut protocol: Option<ProtocolVersion> = None;
     let hello_frame = negotiator.hello_frame();
     log.record("negotiation: sending hello");
     if !send_control_frame(io, &hello_frame, "hello", logger, log) {
@@ -148,11 +433,48 @@ where
                 }
                 match serde_json::from_str::<ControlFrame>(trimmed) {
                     Ok(ControlFrame::Hello {
+                        proto_version,
+                        proto_min,
                         node_id,
                         caps,
                         pref,
-                        ..
+                        bauds,
+                        node_name,
+                        node_uuid,
                     }) => {
+                        remote_bauds = bauds;
+                        let identity = PeerIdentity {
+                            name: node_name,
+                            uuid: node_uuid.map(|uuid| uuid.to_ascii_lowercase()),
+                        };
+                        log.record(format!(
+                            "negotiation: peer name={} uuid={}",
+                            identity.name.as_deref().unwrap_or("-"),
+                            identity.uuid.as_deref().unwrap_or("-")
+                        ));
+                        peer = Some(identity);
+                        let remote_range = (proto_min.unwrap_or(proto_version), proto_version);
+                        let selected = match select_protocol_version(
+                            negotiator.protocol_range(),
+                            remote_range,
+                        ) {
+                            Ok(selected) => selected,
+                            Err(reason) => {
+                                logger.warn(format!("negotiation: {reason}"));
+                                log.record(format!("negotiation: {reason}"));
+                                let _ = send_control_frame(
+                                    io,
+                                    &ControlFrame::LegacyFallback {
+                                        reason: Some(reason),
+                                    },
+                                    "legacy_fallback",
+                                    logger,
+                                    log,
+                                );
+                                return fallback_result();
+                            }
+                        };
+                        protocol = Some(selected);
                         let (remote, pref_err) = crate::app::negotiation::RemoteHello::from_parts(
                             node_id, &pref, caps.bits,
                         );
@@ -176,6 +498,7 @@ where
                             peer_caps: ControlCaps {
                                 bits: negotiator.local_caps().bits(),
                             },
+                            proto_version: Some(selected.get()),
                         };
                         if !send_control_frame(io, &ack, "hello_ack", logger, log) {
                             logger.warn("negotiation: failed to send hello_ack");
@@ -192,23 +515,61 @@ where
                     Ok(ControlFrame::HelloAck {
                         chosen_role,
                         peer_caps,
+                        proto_version,
                     }) => {
                         let role = Role::from_str(&chosen_role).unwrap_or(Role::Server);
+                        // Prefer our own selection from both hellos; an ack without a preceding
+                        // hello (or from an older peer) tells us what the peer picked.
+                        let protocol = protocol
+                            .or_else(|| proto_version.and_then(ProtocolVersion::new))
+                            .unwrap_or(ProtocolVersion::LEGACY);
                         log.record(format!(
-                            "negotiation: hello_ack received role={} caps=0x{:08x}",
+                            "negotiation: hello_ack received role={} caps=0x{:08x} protocol={protocol}",
                             role.as_str(),
                             peer_caps.bits
                         ));
+                        let upgraded_baud =
+                            match highest_common_baud(negotiator.bauds(), &remote_bauds) {
+                                Some(target) if target > base_baud => upgrade_baud(
+                                    io,
+                                    target,
+                                    base_baud,
+                                    config.timeout_ms,
+                                    logger,
+                                    log,
+                                ),
+                                _ => None,
+                            };
                         return NegotiationResult {
                             role,
                             remote_caps: Some(Capabilities::from_bits(peer_caps.bits)),
                             fallback: false,
+                            upgraded_baud,
+                            protocol,
+                            peer,
                         };
                     }
-                    Ok(ControlFrame::LegacyFallback) => {
-                        log.record("negotiation: legacy_fallback received");
+                    Ok(ControlFrame::LegacyFallback { reason }) => {
+                        match reason {
+                            Some(reason) => {
+                                logger.warn(format!("negotiation: peer fell back: {reason}"));
+                                log.record(format!(
+                                    "negotiation: legacy_fallback received: {reason}"
+                                ));
+                            }
+                            None => log.record("negotiation: legacy_fallback received"),
+                        }
                         return fallback_result();
                     }
+                    Ok(ControlFrame::BaudCheck { .. } | ControlFrame::BaudCommit { .. }) => {
+                        log.record("negotiation: ignoring baud_check before hello_ack");
+                        continue;
+                    }
+                    Ok(ControlFrame::Renegotiate { .. }) => {
+                        // Already exchanging hellos; the peer's own hello follows.
+                        log.record("negotiation: ignoring renegotiate during handshake");
+                        continue;
+                    }
                     Err(_) => {
                         log.record(format!(
                             "negotiation: ignoring non-control frame during handshake: {trimmed}"
@@ -228,7 +589,9 @@ where
     log.record("negotiation: timed out");
     let _ = send_control_frame(
         io,
-        &ControlFrame::LegacyFallback,
+        &ControlFrame::LegacyFallback {
+            reason: Some("handshake timed out".into()),
+        },
         "legacy_fallback",
         logger,
         log,
@@ -241,7 +604,113 @@ fn fallback_result() -> NegotiationResult {
         role: Role::Server,
         remote_caps: None,
         fallback: true,
+        upgraded_baud: None,
+        protocol: ProtocolVersion::LEGACY,
+        peer: None,
+    }
+}
+
+/// Switch to `target` and prove it with a CRC round trip; on failure drop back to `base`.
+fn upgrade_baud<IO: LineIo>(
+    io: &mut IO,
+    target: u32,
+    base: u32,
+    timeout_ms: u64,
+    logger: &Logger,
+    log: &mut NegotiationLog,
+) -> Option<u32> {
+    log.record(format!("negotiation: switching to {target} baud"));
+    if let Err(err) = io.set_baud(target) {
+        logger.warn(format!(
+            "negotiation: cannot switch to {target} baud: {err}"
+        ));
+        log.record(format!(
+            "negotiation: cannot switch to {target} baud: {err}"
+        ));
+        return None;
+    }
+    if baud_check_round_trip(io, target, timeout_ms, logger, log) {
+        logger.info(format!("negotiation: link upgraded to {target} baud"));
+        log.record(format!("negotiation: link upgraded to {target} baud"));
+        return Some(target);
+    }
+    logger.warn(format!(
+        "negotiation: {target} baud check failed; falling back to {base} baud"
+    ));
+    log.record(format!(
+        "negotiation: {target} baud check failed; falling back to {base}"
+    ));
+    if let Err(err) = io.set_baud(base) {
+        logger.warn(format!("negotiation: failed to restore {base} baud: {err}"));
+    }
+    None
+}
+
+/// Exchange `baud_check` frames until the peer's arrives intact, then `baud_commit` frames until
+/// the peer's commit arrives, so neither side keeps the new rate while the other gives up on
+/// it. Frames are repeated because the peer may still be listening at the old rate when the
+/// first ones go out. A commit from the peer also proves the link, since it crossed at the new
+/// rate after the peer checked ours.
+fn baud_check_round_trip<IO: LineIo>(
+    io: &mut IO,
+    baud: u32,
+    timeout_ms: u64,
+    logger: &Logger,
+    log: &mut NegotiationLog,
+) -> bool {
+    // Every printable ASCII byte, so framing or bit errors at the new rate change the CRC.
+    let probe: String = (0x20u8..0x7f).map(char::from).collect();
+    let check = ControlFrame::BaudCheck {
+        baud,
+        crc32: crc32fast::hash(probe.as_bytes()),
+        probe: probe.clone(),
+    };
+    let commit = ControlFrame::BaudCommit { baud };
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+    let mut next_send = Instant::now();
+    let mut verified = false;
+    let mut buffer = String::new();
+    while Instant::now() < deadline {
+        if Instant::now() >= next_send {
+            if verified {
+                send_control_frame(io, &commit, "baud_commit", logger, log);
+            } else {
+                send_control_frame(io, &check, "baud_check", logger, log);
+            }
+            next_send = Instant::now() + Duration::from_millis(BAUD_CHECK_RESEND_MS);
+        }
+        match io.read_message_line(&mut buffer) {
+            Ok(0) => continue,
+            Ok(_) => {}
+            Err(err) => {
+                log.record(format!("negotiation: read during baud check failed: {err}"));
+                continue;
+            }
+        }
+        match serde_json::from_str::<ControlFrame>(buffer.trim()) {
+            Ok(ControlFrame::BaudCheck {
+                baud: peer_baud,
+                probe: peer_probe,
+                crc32,
+            }) if peer_baud == baud
+                && peer_probe == probe
+                && crc32fast::hash(peer_probe.as_bytes()) == crc32 =>
+            {
+                if !verified {
+                    log.record(format!("negotiation: {baud} baud check passed"));
+                    verified = true;
+                    next_send = Instant::now();
+                }
+            }
+            Ok(ControlFrame::BaudCommit { baud: peer_baud }) if peer_baud == baud => {
+                // Confirm once more in case the peer has not seen our commit yet.
+                send_control_frame(io, &commit, "baud_commit", logger, log);
+                return true;
+            }
+            _ => log.record("negotiation: ignoring unexpected line during baud check"),
+        }
     }
+    false
 }
 
 fn send_control_frame<IO>(
@@ -287,6 +756,7 @@ mod tests {
     struct FakeLineIo {
         responses: VecDeque<String>,
         sent: Vec<String>,
+        bauds: Vec<u32>,
     }
 
     impl FakeLineIo {
@@ -297,6 +767,7 @@ mod tests {
                     .map(String::from)
                     .collect::<VecDeque<_>>(),
                 sent: Vec::new(),
+                bauds: Vec::new(),
             }
         }
 
@@ -319,6 +790,18 @@ mod tests {
             }
             Ok(0)
         }
+
+        fn set_baud(&mut self, baud: u32) -> crate::Result<()> {
+            self.bauds.push(baud);
+            Ok(())
+        }
+    }
+
+    fn identity() -> NodeIdentity {
+        NodeIdentity {
+            name: "bench".into(),
+            uuid: "0f8e2c1a-5b7d-4e3f-9a10-c2d4e6f80123".into(),
+        }
     }
 
     fn new_logger() -> Logger {
@@ -342,15 +825,43 @@ mod tests {
         let result = attempt_serial_connect_with(
             &logger,
             "/dev/ttyUSB0",
+            None,
             SerialOptions::default(),
             &NegotiationConfig::default(),
+            &identity(),
             false,
+            &BusConfig::default(),
             &mut log,
             |_device, _options| Err(Error::Io(io::Error::new(ErrorKind::PermissionDenied, "no"))),
         );
         assert!(matches!(result, Err(SerialFailureKind::PermissionDenied)));
     }
 
+    #[test]
+    fn unmatched_device_selector_reports_missing_device_without_opening() {
+        let logger = new_logger();
+        let mut log = NegotiationLog::disabled();
+        let selector: DeviceSelector = "usb=ffff:fffe,serial=no-such-adapter".parse().unwrap();
+        let result = attempt_serial_connect_with(
+            &logger,
+            "/dev/ttyUSB0",
+            Some(&selector),
+            SerialOptions::default(),
+            &NegotiationConfig::default(),
+            &identity(),
+            false,
+            &BusConfig::default(),
+            &mut log,
+            |_device, _options| panic!("nothing matched, so nothing should be opened"),
+        );
+        assert!(matches!(result, Err(SerialFailureKind::DeviceMissing)));
+
+        // One-shot tools share the lookup and get the reason instead of a retry.
+        let reason = lookup_device("/dev/ttyUSB0", Some(&selector)).unwrap_err();
+        assert!(reason.contains("no-such-adapter"));
+        assert_eq!(lookup_device("/dev/ttyS1", None).unwrap(), "/dev/ttyS1");
+    }
+
     #[test]
     fn negotiation_success_sets_role() {
         let ack = r#"{"type":"hello_ack","chosen_role":"client","peer_caps":{"bits":3}}"#;
@@ -361,7 +872,9 @@ mod tests {
             &mut io,
             &logger,
             &NegotiationConfig::default(),
+            &identity(),
             false,
+            9600,
             &mut log,
         );
         assert!(!result.fallback);
@@ -387,14 +900,173 @@ mod tests {
             &mut io,
             &logger,
             &NegotiationConfig::default(),
+            &identity(),
             false,
+            9600,
             &mut log,
         );
         assert!(!result.fallback);
+        assert_eq!(result.protocol, ProtocolVersion::default());
         assert!(io
             .sent()
             .iter()
-            .any(|line| line.contains("\"type\":\"hello_ack\"")));
+            .any(|line| line.contains("\"type\":\"hello_ack\"")
+                && line.contains("\"proto_version\":1")));
+    }
+
+    #[test]
+    fn hello_exchanges_node_name_and_uuid() {
+        let hello = r#"{"type":"hello","proto_version":1,"node_id":99,"caps":{"bits":1},"pref":"none","node_name":"lab-pi","node_uuid":"11111111-2222-4333-8444-555555555555"}"#;
+        let ack = r#"{"type":"hello_ack","chosen_role":"server","peer_caps":{"bits":1}}"#;
+        let mut io = FakeLineIo::with_responses(vec![hello, ack]);
+        let logger = new_logger();
+        let mut log = NegotiationLog::disabled();
+        let result = negotiate_handshake(
+            &mut io,
+            &logger,
+            &NegotiationConfig::default(),
+            &identity(),
+            false,
+            9600,
+            &mut log,
+        );
+        let peer = result.peer.expect("peer identity");
+        assert_eq!(peer.label(), "lab-pi");
+        assert_eq!(peer.short_uuid(), "11111111");
+        assert!(io.sent()[0].contains(r#""node_name":"bench""#));
+        assert!(io.sent()[0].contains(r#""node_uuid":"0f8e2c1a-5b7d-4e3f-9a10-c2d4e6f80123""#));
+    }
+
+    #[test]
+    fn disjoint_protocol_ranges_fall_back_with_reason() {
+        let hello = r#"{"type":"hello","proto_version":4,"proto_min":3,"node_id":99,"caps":{"bits":1},"pref":"none"}"#;
+        let mut io = FakeLineIo::with_responses(vec![hello]);
+        let logger = new_logger();
+        let mut log = NegotiationLog::disabled();
+        let result = negotiate_handshake(
+            &mut io,
+            &logger,
+            &NegotiationConfig::default(),
+            &identity(),
+            false,
+            9600,
+            &mut log,
+        );
+        assert!(result.fallback);
+        assert_eq!(result.protocol, ProtocolVersion::LEGACY);
+        let fallback = io.sent().last().unwrap();
+        assert!(fallback.contains("legacy_fallback"));
+        assert!(fallback.contains("no common protocol version"));
+        assert!(fallback.contains("peer supports 3-4"));
+    }
+
+    fn upgrade_config() -> NegotiationConfig {
+        NegotiationConfig {
+            max_baud: Some(115_200),
+            timeout_ms: 250,
+            ..NegotiationConfig::default()
+        }
+    }
+
+    #[test]
+    fn negotiation_upgrades_to_highest_common_baud() {
+        let hello = r#"{"type":"hello","proto_version":1,"node_id":99,"caps":{"bits":1},"pref":"none","bauds":[9600,57600,230400]}"#;
+        let ack = r#"{"type":"hello_ack","chosen_role":"client","peer_caps":{"bits":1}}"#;
+        let probe: String = (0x20u8..0x7f).map(char::from).collect();
+        let check = serde_json::to_string(&ControlFrame::BaudCheck {
+            baud: 57_600,
+            crc32: crc32fast::hash(probe.as_bytes()),
+            probe,
+        })
+        .unwrap();
+        let commit = r#"{"type":"baud_commit","baud":57600}"#;
+        let mut io =
+            FakeLineIo::with_responses(vec![hello, ack, "\u{fffd}garbage", &check, commit]);
+        let logger = new_logger();
+        let mut log = NegotiationLog::disabled();
+        let result = negotiate_handshake(
+            &mut io,
+            &logger,
+            &upgrade_config(),
+            &identity(),
+            false,
+            9600,
+            &mut log,
+        );
+        assert_eq!(result.upgraded_baud, Some(57_600));
+        assert_eq!(io.bauds, vec![57_600]);
+        assert!(io.sent()[0].contains("\"bauds\":[9600,19200,38400,57600,115200]"));
+        assert!(io.sent().iter().any(|line| line.contains("baud_check")));
+        assert!(io.sent().last().unwrap().contains("baud_commit"));
+    }
+
+    #[test]
+    fn baud_upgrade_reverts_without_peer_commit() {
+        // The peer's check arrives but its commit never does: it may have given up on the new
+        // rate, so this side must not stay there alone.
+        let hello = r#"{"type":"hello","proto_version":1,"node_id":99,"caps":{"bits":1},"pref":"none","bauds":[9600,57600]}"#;
+        let ack = r#"{"type":"hello_ack","chosen_role":"client","peer_caps":{"bits":1}}"#;
+        let probe: String = (0x20u8..0x7f).map(char::from).collect();
+        let check = serde_json::to_string(&ControlFrame::BaudCheck {
+            baud: 57_600,
+            crc32: crc32fast::hash(probe.as_bytes()),
+            probe,
+        })
+        .unwrap();
+        let stale_commit = r#"{"type":"baud_commit","baud":115200}"#;
+        let mut io = FakeLineIo::with_responses(vec![hello, ack, &check, stale_commit]);
+        let logger = new_logger();
+        let mut log = NegotiationLog::disabled();
+        let result = negotiate_handshake(
+            &mut io,
+            &logger,
+            &upgrade_config(),
+            &identity(),
+            false,
+            9600,
+            &mut log,
+        );
+        assert_eq!(result.upgraded_baud, None);
+        assert_eq!(io.bauds, vec![57_600, 9600]);
+        assert!(io.sent().iter().any(|line| line.contains("baud_commit")));
+    }
+
+    #[test]
+    fn failed_baud_check_falls_back_to_configured_rate() {
+        let hello = r#"{"type":"hello","proto_version":1,"node_id":99,"caps":{"bits":1},"pref":"none","bauds":[9600,115200]}"#;
+        let ack = r#"{"type":"hello_ack","chosen_role":"client","peer_caps":{"bits":1}}"#;
+        let bad_check = r#"{"type":"baud_check","baud":115200,"probe":"corrupted","crc32":1}"#;
+        let mut io = FakeLineIo::with_responses(vec![hello, ack, bad_check]);
+        let logger = new_logger();
+        let mut log = NegotiationLog::disabled();
+        let result = negotiate_handshake(
+            &mut io,
+            &logger,
+            &upgrade_config(),
+            &identity(),
+            false,
+            9600,
+            &mut log,
+        );
+        assert!(!result.fallback);
+        assert_eq!(result.upgraded_baud, None);
+        assert_eq!(io.bauds, vec![115_200, 9600]);
+
+        // Peers that do not advertise rates never switch.
+        let legacy_hello =
+            r#"{"type":"hello","proto_version":1,"node_id":99,"caps":{"bits":1},"pref":"none"}"#;
+        let mut io = FakeLineIo::with_responses(vec![legacy_hello, ack]);
+        let result = negotiate_handshake(
+            &mut io,
+            &logger,
+            &upgrade_config(),
+            &identity(),
+            false,
+            9600,
+            &mut log,
+        );
+        assert_eq!(result.upgraded_baud, None);
+        assert!(io.bauds.is_empty());
     }
 
     #[test]
@@ -407,9 +1079,78 @@ mod tests {
             &mut io,
             &logger,
             &NegotiationConfig::default(),
+            &identity(),
             false,
+            9600,
             &mut log,
         );
         assert!(result.fallback);
     }
+
+    #[test]
+    fn renegotiation_replays_peer_hello_and_reapplies_caps() {
+        let config = NegotiationConfig {
+            reliable_delivery: true,
+            ..NegotiationConfig::default()
+        };
+        let hello =
+            r#"{"type":"hello","proto_version":1,"node_id":99,"caps":{"bits":41},"pref":"none"}"#;
+        let ack = r#"{"type":"hello_ack","chosen_role":"client","peer_caps":{"bits":41}}"#;
+        let mut port = ReliableLink::passthrough(FakeLineIo::with_responses(vec![ack]));
+        let logger = new_logger();
+        let mut log = NegotiationLog::disabled();
+        let session = renegotiate(
+            &mut port,
+            &logger,
+            &config,
+            &identity(),
+            false,
+            9600,
+            Some(hello),
+            &mut log,
+        );
+        assert_eq!(session.role, Role::Client);
+        let caps = session.remote_caps.expect("caps");
+        assert!(caps.supports_heartbeat && caps.supports_reliable);
+        assert!(port.is_reliable());
+        // The handshake itself went out as plain lines the restarted peer can read.
+        let sent = port.get_ref().sent();
+        assert!(sent[0].starts_with("{\"type\":\"hello\""));
+        assert!(sent[1].starts_with("{\"type\":\"hello_ack\""));
+        assert!(port.get_ref().bauds.is_empty());
+    }
+
+    #[test]
+    fn reliable_link_requires_both_peers() {
+        let enabled = NegotiationConfig {
+            reliable_delivery: true,
+            ..NegotiationConfig::default()
+        };
+        let reliable = Capabilities::from_bits(Capabilities::RELIABLE_V1);
+        let plain = Capabilities::from_bits(Capabilities::HANDSHAKE_V1);
+
+        let link = wrap_link(
+            FakeLineIo::with_responses(vec![]),
+            &enabled,
+            Some(&reliable),
+            9600,
+        );
+        assert!(link.is_reliable());
+        let link = wrap_link(
+            FakeLineIo::with_responses(vec![]),
+            &enabled,
+            Some(&plain),
+            9600,
+        );
+        assert!(!link.is_reliable());
+        let link = wrap_link(FakeLineIo::with_responses(vec![]), &enabled, None, 9600);
+        assert!(!link.is_reliable());
+        let link = wrap_link(
+            FakeLineIo::with_responses(vec![]),
+            &NegotiationConfig::default(),
+            Some(&reliable),
+            9600,
+        );
+        assert!(!link.is_reliable());
+    }
 }
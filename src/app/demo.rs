@@ -74,6 +74,7 @@ pub fn run_demo(lcd: &mut Lcd, config: &mut AppConfig, logger: &Logger) -> Resul
 
     lcd.clear()?;
     lcd.set_backlight(current_frame.backlight_on)?;
+    lcd.set_backlight_color(current_frame.backlight_color.unwrap_or_default())?;
     lcd.set_blink(current_frame.blink)?;
     let palette = render_if_allowed(
         lcd,
@@ -100,6 +101,7 @@ pub fn run_demo(lcd: &mut Lcd, config: &mut AppConfig, logger: &Logger) -> Resul
             backlight_state = current_frame.backlight_on;
             lcd.clear()?;
             lcd.set_backlight(current_frame.backlight_on)?;
+            lcd.set_backlight_color(current_frame.backlight_color.unwrap_or_default())?;
             lcd.set_blink(current_frame.blink)?;
             let palette = render_if_allowed(
                 lcd,
@@ -158,6 +160,7 @@ pub fn run_demo(lcd: &mut Lcd, config: &mut AppConfig, logger: &Logger) -> Resul
         if current_frame.blink && now >= next_blink {
             backlight_state = !backlight_state;
             lcd.set_backlight(backlight_state)?;
+            lcd.finish_frame()?;
             next_blink = now + blink_interval;
         }
 
@@ -0,0 +1,344 @@
+//! Per-display queues and timers so one daemon can drive several LCDs.
+//!
+//! Slot 0 is always the primary display configured by the top-level keys; extra slots come
+//! from `[display.<name>]` sections. Payloads pick a slot with their `display` field.
+
+use std::time::{Duration, Instant};
+
+use super::events::ScrollOffsets;
+use super::Logger;
+use crate::{
+    config::PRIMARY_DISPLAY_NAME,
+    display::{
+        icon_bank::{IconBank, IconPalette},
+        overlays::{advance_offset, line_needs_scroll, render_if_allowed},
+    },
+    lcd::Lcd,
+    negotiation::ProtocolVersion,
+    payload::{
+        check_payload_schema, normalize_payload_json_with_policy, payload_display_target,
+        CompressionPolicy, Defaults as PayloadDefaults, RenderFrame,
+    },
+    state::RenderState,
+    Error, Result,
+};
+
+const MIN_RENDER_INTERVAL_MS: u64 = 200;
+const BLINK_INTERVAL_MS: u64 = 500;
+
+/// One LCD with its own frame queue, icon bank, and page/scroll/blink timers.
+pub(super) struct DisplaySlot {
+    pub(super) name: String,
+    pub(super) lcd: Lcd,
+    pub(super) state: RenderState,
+    pub(super) current_frame: Option<RenderFrame>,
+    icon_bank: IconBank,
+    next_page: Instant,
+    next_scroll: Instant,
+    scroll_offsets: ScrollOffsets,
+    backlight_state: bool,
+    next_blink: Instant,
+    last_render: Instant,
+}
+
+impl DisplaySlot {
+    fn new(
+        name: impl Into<String>,
+        lcd: Lcd,
+        defaults: PayloadDefaults,
+        policy: CompressionPolicy,
+    ) -> Self {
+        let now = Instant::now();
+        Self {
+            name: name.into(),
+            lcd,
+            state: RenderState::new_with_compression(Some(defaults), policy),
+            current_frame: None,
+            icon_bank: IconBank::new(),
+            next_page: now,
+            next_scroll: now,
+            scroll_offsets: ScrollOffsets::zero(),
+            backlight_state: true,
+            next_blink: now,
+            last_render: now,
+        }
+    }
+
+    /// Show a frame that just arrived for this display.
+    pub(super) fn show_frame(
+        &mut self,
+        frame: RenderFrame,
+        now: Instant,
+        heartbeat_on: bool,
+        logger: &Logger,
+    ) -> Result<()> {
+        self.show_page(frame, now, heartbeat_on, logger)
+    }
+
+    /// Advance to the next queued page immediately (manual button press).
+    pub(super) fn advance_page(
+        &mut self,
+        now: Instant,
+        heartbeat_on: bool,
+        logger: &Logger,
+    ) -> Result<()> {
+        match self.state.next_page() {
+            Some(frame) => self.show_page(frame, now, heartbeat_on, logger),
+            None => Ok(()),
+        }
+    }
+
+    /// Keep the backlight lit after an overlay and restart the blink timer.
+    pub(super) fn hold_backlight(&mut self, now: Instant) {
+        self.backlight_state = true;
+        self.next_blink = now + Duration::from_millis(BLINK_INTERVAL_MS);
+    }
+
+    /// Rotate pages, scroll long lines, and drive blink for the current frame.
+    pub(super) fn tick(&mut self, now: Instant, heartbeat_on: bool, logger: &Logger) -> Result<()> {
+        if self.state.len() > 1 && now >= self.next_page {
+            self.advance_page(now, heartbeat_on, logger)?;
+        }
+
+        let Some(frame) = self.current_frame.as_ref() else {
+            return Ok(());
+        };
+        let width = self.lcd.cols() as usize;
+        let needs_scroll = match frame.bar_row {
+            Some(0) => frame.scroll_enabled && line_needs_scroll(&frame.line2, width),
+            Some(1) => frame.scroll_enabled && line_needs_scroll(&frame.line1, width),
+            _ => {
+                frame.scroll_enabled
+                    && (line_needs_scroll(&frame.line1, width)
+                        || line_needs_scroll(&frame.line2, width))
+            }
+        };
+        // Scroll long lines forward when allowed by the frame.
+        if needs_scroll && now >= self.next_scroll {
+            self.scroll_offsets = self.scroll_offsets.update(
+                advance_offset(&frame.line1, width, self.scroll_offsets.top),
+                advance_offset(&frame.line2, width, self.scroll_offsets.bottom),
+            );
+            self.next_scroll = now + Duration::from_millis(frame.scroll_speed_ms);
+            let palette = render_if_allowed(
+                &mut self.lcd,
+                frame,
+                &mut self.last_render,
+                Duration::from_millis(MIN_RENDER_INTERVAL_MS),
+                (self.scroll_offsets.top, self.scroll_offsets.bottom),
+                heartbeat_on,
+                &mut self.icon_bank,
+            )?;
+            log_icon_fallbacks(logger, palette);
+        }
+
+        if frame.blink {
+            // Drive periodic blink by toggling backlight.
+            if now >= self.next_blink {
+                self.backlight_state = !self.backlight_state;
+                self.lcd.set_backlight(self.backlight_state)?;
+                self.lcd.finish_frame()?;
+                self.next_blink = now + Duration::from_millis(BLINK_INTERVAL_MS);
+            }
+        } else if self.backlight_state != frame.backlight_on {
+            self.backlight_state = frame.backlight_on;
+            self.lcd.set_backlight(self.backlight_state)?;
+            self.lcd.finish_frame()?;
+        }
+        Ok(())
+    }
+
+    fn show_page(
+        &mut self,
+        frame: RenderFrame,
+        now: Instant,
+        heartbeat_on: bool,
+        logger: &Logger,
+    ) -> Result<()> {
+        self.scroll_offsets = ScrollOffsets::zero();
+        self.next_scroll = now + Duration::from_millis(frame.scroll_speed_ms);
+        self.next_page = now + Duration::from_millis(frame.page_timeout_ms);
+        self.lcd.clear()?;
+        self.backlight_state = frame.backlight_on;
+        self.lcd.set_backlight(self.backlight_state)?;
+        self.lcd
+            .set_backlight_color(frame.backlight_color.unwrap_or_default())?;
+        self.lcd.set_blink(frame.blink)?;
+        self.next_blink = now + Duration::from_millis(BLINK_INTERVAL_MS);
+        let frame = self.current_frame.insert(frame);
+        let palette = render_if_allowed(
+            &mut self.lcd,
+            frame,
+            &mut self.last_render,
+            Duration::from_millis(MIN_RENDER_INTERVAL_MS),
+            (self.scroll_offsets.top, self.scroll_offsets.bottom),
+            heartbeat_on,
+            &mut self.icon_bank,
+        )?;
+        log_icon_fallbacks(logger, palette);
+        Ok(())
+    }
+}
+
+/// Every display the daemon drives; never empty.
+pub(super) struct Displays {
+    slots: Vec<DisplaySlot>,
+    compression_policy: CompressionPolicy,
+    /// Newest payload `schema_version` the negotiated protocol accepts.
+    max_payload_schema: u8,
+}
+
+impl Displays {
+    pub(super) fn new(
+        primary: Lcd,
+        extras: Vec<(String, Lcd)>,
+        defaults: PayloadDefaults,
+        compression_policy: CompressionPolicy,
+    ) -> Self {
+        let mut slots = vec![DisplaySlot::new(
+            PRIMARY_DISPLAY_NAME,
+            primary,
+            defaults,
+            compression_policy,
+        )];
+        slots.extend(
+            extras
+                .into_iter()
+                .map(|(name, lcd)| DisplaySlot::new(name, lcd, defaults, compression_policy)),
+        );
+        Self {
+            slots,
+            compression_policy,
+            max_payload_schema: ProtocolVersion::LEGACY.max_payload_schema(),
+        }
+    }
+
+    pub(super) fn primary_mut(&mut self) -> &mut DisplaySlot {
+        &mut self.slots[0]
+    }
+
+    pub(super) fn slot_mut(&mut self, idx: usize) -> &mut DisplaySlot {
+        &mut self.slots[idx]
+    }
+
+    pub(super) fn iter_mut(&mut self) -> impl Iterator<Item = &mut DisplaySlot> {
+        self.slots.iter_mut()
+    }
+
+    /// Route a payload line to its display's queue. Returns the slot index with the frame
+    /// when it is new, or `None` for a duplicate.
+    pub(super) fn ingest(&mut self, raw: &str) -> Result<Option<(usize, RenderFrame)>> {
+        let normalized = normalize_payload_json_with_policy(raw, self.compression_policy)?;
+        check_payload_schema(&normalized, self.max_payload_schema)?;
+        let idx = match payload_display_target(&normalized)? {
+            None => 0,
+            Some(name) => self
+                .slots
+                .iter()
+                .position(|slot| slot.name == name)
+                .ok_or_else(|| Error::Parse(format!("unknown display '{name}'")))?,
+        };
+        let frame = self.slots[idx].state.ingest_normalized(&normalized)?;
+        Ok(frame.map(|frame| (idx, frame)))
+    }
+
+    pub(super) fn set_defaults(&mut self, defaults: PayloadDefaults) {
+        for slot in &mut self.slots {
+            slot.state.set_defaults(defaults);
+        }
+    }
+
+    pub(super) fn set_protocol(&mut self, protocol: ProtocolVersion) {
+        self.max_payload_schema = protocol.max_payload_schema();
+    }
+
+    pub(super) fn set_compression_policy(&mut self, policy: CompressionPolicy) {
+        self.compression_policy = policy;
+        for slot in &mut self.slots {
+            slot.state.set_compression_policy(policy);
+        }
+    }
+}
+
+pub(super) fn log_icon_fallbacks(logger: &Logger, palette: Option<IconPalette>) {
+    let Some(palette) = palette else {
+        return;
+    };
+    if palette.missing_icons.is_empty() {
+        return;
+    }
+    let joined = palette
+        .missing_icons
+        .iter()
+        .map(|icon| format!("{icon:?}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    logger.debug(format!(
+        "icon bank saturated; leaving blanks for missing icons [{joined}]"
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn displays() -> Displays {
+        Displays::new(
+            Lcd::new_stub(16, 2),
+            vec![("alerts".into(), Lcd::new_stub(20, 4))],
+            PayloadDefaults {
+                scroll_speed_ms: crate::payload::DEFAULT_SCROLL_MS,
+                page_timeout_ms: crate::payload::DEFAULT_PAGE_TIMEOUT_MS,
+            },
+            CompressionPolicy::disabled(),
+        )
+    }
+
+    #[test]
+    fn routes_payloads_by_display_name() {
+        let mut displays = displays();
+        let (idx, _) = displays
+            .ingest(r#"{"schema_version":1,"line1":"A","line2":"B"}"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(idx, 0);
+        let (idx, frame) = displays
+            .ingest("schema_version=1 line1=Disk line2=Full display=alerts")
+            .unwrap()
+            .unwrap();
+        assert_eq!(idx, 1);
+        assert_eq!(frame.display.as_deref(), Some("alerts"));
+        let (idx, _) = displays
+            .ingest(r#"{"schema_version":1,"line1":"C","line2":"D","display":"main"}"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(idx, 0);
+        assert_eq!(displays.primary_mut().state.len(), 2);
+        assert_eq!(displays.slot_mut(1).state.len(), 1);
+
+        let err = displays
+            .ingest(r#"{"schema_version":1,"line1":"A","line2":"B","display":"lobby"}"#)
+            .unwrap_err();
+        assert!(format!("{err}").contains("unknown display 'lobby'"));
+    }
+
+    #[test]
+    fn duplicates_are_tracked_per_display() {
+        let mut displays = displays();
+        let main = r#"{"schema_version":1,"line1":"A","line2":"B"}"#;
+        let alerts = r#"{"schema_version":1,"line1":"A","line2":"B","display":"alerts"}"#;
+        assert!(displays.ingest(main).unwrap().is_some());
+        assert!(displays.ingest(alerts).unwrap().is_some());
+        assert!(displays.ingest(alerts).unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_payload_schema_newer_than_protocol() {
+        let mut displays = displays();
+        displays.set_protocol(ProtocolVersion::default());
+        let err = displays
+            .ingest(r#"{"schema_version":9,"line1":"A","line2":"B"}"#)
+            .unwrap_err();
+        assert!(format!("{err}").contains("unsupported payload schema_version=9"));
+    }
+}
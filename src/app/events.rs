@@ -1,8 +1,12 @@
+use super::audit::{now_ms, AuditDecision, AuditEntry, AuditLog};
 use crate::{
-    payload::{decode_command_frame, CommandMessage, CommandStream},
+    payload::{
+        decode_command_frame_with_schema, CommandMessage, CommandStream, COMMAND_SCHEMA_VERSION,
+    },
     Result,
 };
 use serde_bytes::ByteBuf;
+use std::collections::HashMap;
 use std::io::Read;
 use std::path::Path;
 use std::process::{Command, Stdio};
@@ -12,6 +16,7 @@ use std::sync::{
     Arc,
 };
 use std::thread;
+use std::time::Instant;
 
 /// Stores scroll offsets for the two LCD lines to avoid ad-hoc tuples.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -119,20 +124,32 @@ impl From<CommandMessage> for CommandEvent {
 }
 
 /// CommandBridge ingests newline-delimited JSON command frames and emits structured events.
-#[derive(Default)]
 pub struct CommandBridge {
     last_seen_request: Option<u32>,
+    schema_version: u8,
+}
+
+impl Default for CommandBridge {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CommandBridge {
     pub fn new() -> Self {
         Self {
             last_seen_request: None,
+            schema_version: COMMAND_SCHEMA_VERSION,
         }
     }
 
+    /// Accept command frames at the schema version the negotiated protocol selects.
+    pub fn set_schema_version(&mut self, schema_version: u8) {
+        self.schema_version = schema_version;
+    }
+
     pub fn ingest_line(&mut self, raw: &str) -> Result<Option<CommandEvent>> {
-        let message = decode_command_frame(raw)?;
+        let message = decode_command_frame_with_schema(raw, self.schema_version)?;
         if let Some(request_id) = message_request_id(&message) {
             self.last_seen_request = Some(request_id);
         }
@@ -160,7 +177,18 @@ fn message_request_id(msg: &CommandMessage) -> Option<u32> {
     }
 }
 
-const COMMAND_STREAM_CHUNK_SIZE: usize = 512;
+pub(crate) const COMMAND_STREAM_CHUNK_SIZE: usize = 512;
+
+/// Audit bookkeeping for a request whose `Exit` has not been observed yet.
+struct PendingAudit {
+    started: Instant,
+    program: Option<String>,
+    argv: Vec<String>,
+    decision: AuditDecision,
+    error: Option<String>,
+    stdout_bytes: u64,
+    stderr_bytes: u64,
+}
 
 pub struct CommandExecutor {
     allowlist: Vec<String>,
@@ -168,6 +196,9 @@ pub struct CommandExecutor {
     current_request: Option<u32>,
     outgoing_tx: Sender<CommandMessage>,
     outgoing_rx: Receiver<CommandMessage>,
+    audit: Option<(AuditLog, &'static str)>,
+    audit_pending: HashMap<u32, PendingAudit>,
+    chunk_size: usize,
 }
 
 impl CommandExecutor {
@@ -179,6 +210,23 @@ impl CommandExecutor {
             current_request: None,
             outgoing_tx: tx,
             outgoing_rx: rx,
+            audit: None,
+            audit_pending: HashMap::new(),
+            chunk_size: COMMAND_STREAM_CHUNK_SIZE,
+        }
+    }
+
+    /// Cap stdout/stderr chunks for commands started from now on; a noisy link loses less
+    /// per corrupted frame.
+    pub fn set_chunk_size(&mut self, bytes: usize) {
+        self.chunk_size = bytes.clamp(1, COMMAND_STREAM_CHUNK_SIZE);
+    }
+
+    /// Build an executor that appends every request it sees to `audit`, tagged with `channel`.
+    pub fn with_audit(allowlist: Vec<String>, audit: AuditLog, channel: &'static str) -> Self {
+        Self {
+            audit: Some((audit, channel)),
+            ..Self::new(allowlist)
         }
     }
 
@@ -190,12 +238,26 @@ impl CommandExecutor {
                 scratch_path: _,
             } => {
                 if self.session_active {
+                    // Written straight away: the running command may share this id, and its
+                    // pending entry must survive until its own exit.
+                    let tokens = split_command_line(&cmd).ok();
+                    if let Some(pending) =
+                        self.pending_audit(tokens.as_deref(), AuditDecision::Busy, None)
+                    {
+                        self.write_audit(request_id, pending, None);
+                    }
                     return Some(CommandMessage::Busy { request_id });
                 }
                 let tokens = match split_command_line(&cmd) {
                     Ok(tokens) => tokens,
                     Err(err) => {
                         let msg = format!("command parse error: {err}");
+                        self.begin_audit(
+                            request_id,
+                            None,
+                            AuditDecision::Invalid,
+                            Some(msg.clone()),
+                        );
                         self.queue(CommandMessage::Error {
                             request_id: Some(request_id),
                             message: msg.clone(),
@@ -213,6 +275,12 @@ impl CommandExecutor {
                 let program = tokens[0].clone();
                 if !command_allowed(&program, &self.allowlist) {
                     let msg = format!("command not allowed: {program}");
+                    self.begin_audit(
+                        request_id,
+                        Some(&tokens),
+                        AuditDecision::Denied,
+                        Some(msg.clone()),
+                    );
                     self.queue(CommandMessage::Error {
                         request_id: Some(request_id),
                         message: msg.clone(),
@@ -234,6 +302,7 @@ impl CommandExecutor {
                     .spawn()
                 {
                     Ok(mut child) => {
+                        self.begin_audit(request_id, Some(&tokens), AuditDecision::Allowed, None);
                         self.session_active = true;
                         self.current_request = Some(request_id);
                         let tx = self.outgoing_tx.clone();
@@ -246,6 +315,7 @@ impl CommandExecutor {
                                 request_id,
                                 stdout_seq,
                                 tx.clone(),
+                                self.chunk_size,
                             )
                         });
                         let stderr_handle = child.stderr.take().map(|stderr| {
@@ -255,6 +325,7 @@ impl CommandExecutor {
                                 request_id,
                                 stderr_seq,
                                 tx.clone(),
+                                self.chunk_size,
                             )
                         });
                         let tx_exit = self.outgoing_tx.clone();
@@ -275,6 +346,12 @@ impl CommandExecutor {
                     }
                     Err(err) => {
                         let msg = format!("failed to spawn '{program}': {err}");
+                        self.begin_audit(
+                            request_id,
+                            Some(&tokens),
+                            AuditDecision::Allowed,
+                            Some(msg.clone()),
+                        );
                         self.queue(CommandMessage::Error {
                             request_id: Some(request_id),
                             message: msg.clone(),
@@ -297,9 +374,26 @@ impl CommandExecutor {
     pub fn next_outgoing(&mut self) -> Option<CommandMessage> {
         match self.outgoing_rx.try_recv() {
             Ok(msg) => {
-                if matches!(msg, CommandMessage::Exit { .. }) {
-                    self.session_active = false;
-                    self.current_request = None;
+                match &msg {
+                    CommandMessage::Exit { request_id, code } => {
+                        self.session_active = false;
+                        self.current_request = None;
+                        self.finish_audit(*request_id, Some(*code));
+                    }
+                    CommandMessage::Chunk {
+                        request_id,
+                        stream,
+                        data,
+                        ..
+                    } => {
+                        if let Some(pending) = self.audit_pending.get_mut(request_id) {
+                            match stream {
+                                CommandStream::Stdout => pending.stdout_bytes += data.len() as u64,
+                                CommandStream::Stderr => pending.stderr_bytes += data.len() as u64,
+                            }
+                        }
+                    }
+                    _ => {}
                 }
                 Some(msg)
             }
@@ -310,6 +404,68 @@ impl CommandExecutor {
     fn queue(&self, msg: CommandMessage) {
         let _ = self.outgoing_tx.send(msg);
     }
+
+    fn begin_audit(
+        &mut self,
+        request_id: u32,
+        tokens: Option<&[String]>,
+        decision: AuditDecision,
+        error: Option<String>,
+    ) {
+        if let Some(pending) = self.pending_audit(tokens, decision, error) {
+            self.audit_pending.insert(request_id, pending);
+        }
+    }
+
+    fn finish_audit(&mut self, request_id: u32, exit_code: Option<i32>) {
+        if let Some(pending) = self.audit_pending.remove(&request_id) {
+            self.write_audit(request_id, pending, exit_code);
+        }
+    }
+
+    /// A fresh audit record, or `None` when auditing is off.
+    fn pending_audit(
+        &self,
+        tokens: Option<&[String]>,
+        decision: AuditDecision,
+        error: Option<String>,
+    ) -> Option<PendingAudit> {
+        self.audit.as_ref()?;
+        let (program, argv) = match tokens {
+            Some([program, argv @ ..]) => (Some(program.clone()), argv.to_vec()),
+            _ => (None, Vec::new()),
+        };
+        Some(PendingAudit {
+            started: Instant::now(),
+            program,
+            argv,
+            decision,
+            error,
+            stdout_bytes: 0,
+            stderr_bytes: 0,
+        })
+    }
+
+    fn write_audit(&self, request_id: u32, pending: PendingAudit, exit_code: Option<i32>) {
+        let Some((log, channel)) = self.audit.as_ref() else {
+            return;
+        };
+        let entry = AuditEntry {
+            timestamp_ms: now_ms(),
+            channel: (*channel).to_string(),
+            request_id,
+            program: pending.program,
+            argv: pending.argv,
+            decision: pending.decision,
+            exit_code,
+            duration_ms: pending.started.elapsed().as_millis() as u64,
+            stdout_bytes: pending.stdout_bytes,
+            stderr_bytes: pending.stderr_bytes,
+            error: pending.error,
+        };
+        // Best effort: a read-only cache dir must not block command execution.
+        let _ = log.append(&entry);
+    }
 }
 
 fn spawn_stream_reader<R>(
@@ -318,12 +474,13 @@ fn spawn_stream_reader<R>(
     request_id: u32,
     seq_counter: Arc<AtomicU32>,
     tx: Sender<CommandMessage>,
+    chunk_size: usize,
 ) -> thread::JoinHandle<()>
 where
     R: Read + Send + 'static,
 {
     thread::spawn(move || {
-        let mut buf = [0u8; COMMAND_STREAM_CHUNK_SIZE];
+        let mut buf = vec![0u8; chunk_size];
         loop {
             match reader.read(&mut buf) {
                 Ok(0) => break,
@@ -485,13 +642,11 @@ mod tests {
         let deadline = Instant::now() + Duration::from_secs(2);
         let mut exit_seen = false;
         while Instant::now() < deadline {
-            if let Some(msg) = executor.next_outgoing() {
-                if let CommandMessage::Exit { request_id, code } = msg {
-                    assert_eq!(request_id, 7);
-                    assert_eq!(code, 0);
-                    exit_seen = true;
-                    break;
-                }
+            if let Some(CommandMessage::Exit { request_id, code }) = executor.next_outgoing() {
+                assert_eq!(request_id, 7);
+                assert_eq!(code, 0);
+                exit_seen = true;
+                break;
             }
             thread::sleep(Duration::from_millis(10));
         }
@@ -524,6 +679,90 @@ mod tests {
         }
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn command_executor_audits_allowed_and_denied_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::with_path(dir.path().join("audit.jsonl"), 64 * 1024);
+        let mut executor = CommandExecutor::with_audit(vec!["echo".into()], log.clone(), "tunnel");
+
+        let _ = executor.handle_event(CommandEvent::Request {
+            request_id: 1,
+            cmd: "echo 'hello world'".into(),
+            scratch_path: None,
+        });
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while Instant::now() < deadline {
+            if let Some(CommandMessage::Exit { .. }) = executor.next_outgoing() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let _ = executor.handle_event(CommandEvent::Request {
+            request_id: 2,
+            cmd: "whoami".into(),
+            scratch_path: None,
+        });
+        while executor.next_outgoing().is_some() {}
+
+        let entries: Vec<AuditEntry> = log
+            .tail(10)
+            .unwrap()
+            .iter()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].request_id, 1);
+        assert_eq!(entries[0].program.as_deref(), Some("echo"));
+        assert_eq!(entries[0].argv, vec!["hello world".to_string()]);
+        assert_eq!(entries[0].decision, AuditDecision::Allowed);
+        assert_eq!(entries[0].exit_code, Some(0));
+        assert_eq!(entries[0].stdout_bytes, "hello world\n".len() as u64);
+        assert_eq!(entries[1].program.as_deref(), Some("whoami"));
+        assert_eq!(entries[1].decision, AuditDecision::Denied);
+        assert_eq!(entries[1].exit_code, Some(1));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn busy_retry_with_the_running_id_keeps_its_audit_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::with_path(dir.path().join("audit.jsonl"), 64 * 1024);
+        let mut executor = CommandExecutor::with_audit(Vec::new(), log.clone(), "tunnel");
+        let _ = executor.handle_event(CommandEvent::Request {
+            request_id: 4,
+            cmd: "sh -c 'sleep 0.2; echo done'".into(),
+            scratch_path: None,
+        });
+        let busy = executor.handle_event(CommandEvent::Request {
+            request_id: 4,
+            cmd: "sh -c 'sleep 0.2; echo done'".into(),
+            scratch_path: None,
+        });
+        assert!(matches!(busy, Some(CommandMessage::Busy { request_id: 4 })));
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while Instant::now() < deadline {
+            if let Some(CommandMessage::Exit { .. }) = executor.next_outgoing() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let entries: Vec<AuditEntry> = log
+            .tail(10)
+            .unwrap()
+            .iter()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].decision, AuditDecision::Busy);
+        assert_eq!(entries[0].exit_code, None);
+        assert_eq!(entries[1].decision, AuditDecision::Allowed);
+        assert_eq!(entries[1].exit_code, Some(0));
+        assert_eq!(entries[1].stdout_bytes, "done\n".len() as u64);
+    }
+
     #[test]
     fn split_command_line_handles_quotes() {
         let args = split_command_line("echo 'hello world'").unwrap();
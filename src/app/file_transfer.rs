@@ -1,9 +1,15 @@
 use crate::{Error, Result};
-use std::path::Path;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
 
-/// File transfer manager placeholder for Milestone C.
-/// This module provides a minimal in-repo API to build tests and further
-/// implementation around chunking, checksums and resume functionality.
+/// Largest file the tunnel will push or pull in one transfer; keeps the RAM disk safe.
+pub const FILE_TRANSFER_MAX_BYTES: u64 = 1024 * 1024;
+
+/// File transfer manager backing the serial shell's `:push` / `:pull` built-ins.
+///
+/// Every remote path is resolved relative to `<cache_dir>/transfers` so peers can never read
+/// or write outside the RAM disk.
 pub struct FileTransferManager {
     /// path to a cache directory (should be CACHE_DIR in production)
     pub cache_dir: String,
@@ -16,24 +22,81 @@ impl FileTransferManager {
         }
     }
 
-    /// Prepare sending a local file by validating it exists and returning a
-    /// transfer id (stubbed in this skeleton).
-    pub fn prepare_send(&self, path: &str) -> Result<String> {
-        if !Path::new(path).exists() {
-            return Err(Error::Parse(format!("file not found: {path}")));
+    /// Resolve a peer-supplied relative name inside the transfer jail. Symlinks already inside
+    /// the jail are followed and must still land under it.
+    pub fn resolve(&self, name: &str) -> Result<PathBuf> {
+        let relative = Path::new(name);
+        let clean = !name.is_empty()
+            && relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+        if !clean {
+            return Err(Error::InvalidArgs(format!(
+                "transfer path must be relative without '..': {name}"
+            )));
+        }
+        let root = Path::new(&self.cache_dir).join("transfers");
+        let path = root.join(relative);
+        // Nothing can exist under a jail that does not exist yet.
+        let Ok(canonical_root) = fs::canonicalize(&root) else {
+            return Ok(path);
+        };
+        // Only the part that exists can hold a symlink; the rest is plain `Normal` components.
+        let existing = path
+            .ancestors()
+            .find(|ancestor| fs::symlink_metadata(ancestor).is_ok())
+            .unwrap_or(&root);
+        let resolved = fs::canonicalize(existing)?;
+        if !resolved.starts_with(&canonical_root) {
+            return Err(Error::InvalidArgs(format!(
+                "transfer path escapes the transfer directory: {name}"
+            )));
         }
-        // Real implementation will stage the file into cache and create a resume manifest
-        Ok("transfer-id-stub".to_string())
+        Ok(path)
     }
 
-    /// Accept a chunk into the receive pipeline (stubbed). Real code will
-    /// validate chunk id, crc and append to a temporary file in the cache dir.
-    pub fn receive_chunk(
-        &self,
-        _transfer_id: &str,
-        _chunk_idx: u64,
-        _payload: &[u8],
-    ) -> Result<()> {
+    /// Open a jailed file so it can be streamed back to the peer; returns the handle and the
+    /// number of bytes to send.
+    pub fn prepare_send(&self, name: &str) -> Result<(File, u64)> {
+        let path = self.resolve(name)?;
+        let file =
+            File::open(&path).map_err(|_| Error::Parse(format!("file not found: {name}")))?;
+        let meta = file.metadata()?;
+        if !meta.is_file() {
+            return Err(Error::InvalidArgs(format!("{name} is not a regular file")));
+        }
+        if meta.len() > FILE_TRANSFER_MAX_BYTES {
+            return Err(Error::InvalidArgs(format!(
+                "{name} exceeds {FILE_TRANSFER_MAX_BYTES} bytes"
+            )));
+        }
+        Ok((file, meta.len()))
+    }
+
+    /// Write one pushed chunk at `offset`; offset 0 truncates any previous file.
+    pub fn receive_chunk(&self, name: &str, offset: u64, payload: &[u8]) -> Result<()> {
+        let end = offset.checked_add(payload.len() as u64);
+        if end.is_none_or(|end| end > FILE_TRANSFER_MAX_BYTES) {
+            return Err(Error::InvalidArgs(format!(
+                "{name} exceeds {FILE_TRANSFER_MAX_BYTES} bytes"
+            )));
+        }
+        let path = self.resolve(name)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(offset == 0)
+            .open(&path)?;
+        if file.metadata()?.len() < offset {
+            return Err(Error::InvalidArgs(format!(
+                "chunk offset {offset} skips past the end of {name}"
+            )));
+        }
+        file.seek(SeekFrom::Start(offset))?;
+        file.write_all(payload)?;
         Ok(())
     }
 }
@@ -41,23 +104,58 @@ impl FileTransferManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::File;
     use tempfile::tempdir;
 
     #[test]
     fn prepare_send_rejects_missing_file() {
-        let m = FileTransferManager::new("/tmp");
-        let err = m.prepare_send("/path/does/not/exist").unwrap_err();
+        let dir = tempdir().unwrap();
+        let m = FileTransferManager::new(dir.path().to_str().unwrap());
+        let err = m.prepare_send("does/not/exist").unwrap_err();
         assert!(format!("{err}").contains("file not found"));
     }
 
     #[test]
-    fn prepare_send_accepts_existing_file() {
+    fn pushed_chunks_round_trip_through_prepare_send() {
+        let dir = tempdir().unwrap();
+        let m = FileTransferManager::new(dir.path().to_str().unwrap());
+        m.receive_chunk("logs/f.txt", 0, b"hello ").unwrap();
+        m.receive_chunk("logs/f.txt", 6, b"world").unwrap();
+        let (mut file, len) = m.prepare_send("logs/f.txt").unwrap();
+        let mut body = Vec::new();
+        std::io::Read::read_to_end(&mut file, &mut body).unwrap();
+        assert_eq!((body.as_slice(), len), (&b"hello world"[..], 11));
+        assert!(m.prepare_send("logs").is_err(), "directories are not sent");
+        assert!(dir.path().join("transfers/logs/f.txt").exists());
+    }
+
+    #[test]
+    fn resolve_rejects_paths_outside_the_jail() {
+        let m = FileTransferManager::new("/tmp");
+        for bad in ["", "/etc/passwd", "../escape", "a/../../b"] {
+            assert!(m.resolve(bad).is_err(), "{bad} should be rejected");
+        }
+    }
+
+    #[test]
+    fn resolve_rejects_symlinks_out_of_the_jail() {
+        let dir = tempdir().unwrap();
+        let outside = tempdir().unwrap();
+        let m = FileTransferManager::new(dir.path().to_str().unwrap());
+        let jail = dir.path().join("transfers");
+        fs::create_dir_all(jail.join("inner")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), jail.join("link")).unwrap();
+
+        assert!(m.resolve("link/secret").is_err());
+        assert!(m.receive_chunk("link/secret", 0, b"x").is_err());
+        assert!(!outside.path().join("secret").exists());
+        assert!(m.resolve("inner/ok.txt").is_ok());
+    }
+
+    #[test]
+    fn receive_chunk_rejects_overflowing_offsets() {
         let dir = tempdir().unwrap();
-        let fpath = dir.path().join("f.txt");
-        File::create(&fpath).unwrap();
         let m = FileTransferManager::new(dir.path().to_str().unwrap());
-        let id = m.prepare_send(fpath.to_str().unwrap()).unwrap();
-        assert_eq!(id, "transfer-id-stub");
+        let err = m.receive_chunk("f.bin", u64::MAX, b"ab").unwrap_err();
+        assert!(format!("{err}").contains("exceeds"));
     }
 }
@@ -0,0 +1,654 @@
+use crate::{
+    app::AppConfig,
+    cli::{ForwardSpec, RunOptions},
+    config::{parse_host_port, Config},
+    payload::{decode_tunnel_frame, encode_tunnel_msg, TunnelMsgOwned},
+    serial::{LineIo, SerialPort},
+    Error, Result,
+};
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+/// Bytes either side may have in flight per stream before the receiver grants more credit.
+pub const FORWARD_WINDOW_BYTES: u32 = 4096;
+/// Payload bytes per `ForwardData` frame; keeps encoded frames well under the tunnel cap.
+pub const FORWARD_CHUNK_BYTES: usize = 512;
+/// Data frames produced per poll so forwarded traffic cannot starve LCD payloads.
+pub const FORWARD_FRAMES_PER_POLL: usize = 4;
+const FORWARD_CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+/// Serial read timeout used by the forwarding client so sockets are serviced promptly.
+const FORWARD_SERIAL_POLL_MS: u64 = 20;
+
+struct ForwardStream {
+    /// `None` while the connect to the target still runs on its worker thread.
+    socket: Option<TcpStream>,
+    connecting: Option<Receiver<std::result::Result<TcpStream, String>>>,
+    /// Bytes we may still send to the peer.
+    send_credit: u32,
+    /// Bytes written to the local socket since the last window grant.
+    ungranted: u32,
+    /// Peer bytes waiting for the non-blocking socket to accept them.
+    outbox: VecDeque<u8>,
+    /// Peer closed the stream; drop it once the outbox drains.
+    peer_closed: bool,
+    /// Peer half-closed its side; shut down our write half once the outbox drains.
+    peer_eof: bool,
+    /// Our write half is shut down after delivering everything before the peer's EOF.
+    write_shutdown: bool,
+    /// The local socket hit EOF and the peer was told; keep relaying the other direction.
+    local_eof: bool,
+}
+
+impl ForwardStream {
+    fn new(socket: TcpStream) -> io::Result<Self> {
+        configure(&socket)?;
+        Ok(Self::with_socket(Some(socket), None))
+    }
+
+    /// A stream whose socket arrives on `connecting`; peer data is buffered until then.
+    fn connecting(connecting: Receiver<std::result::Result<TcpStream, String>>) -> Self {
+        Self::with_socket(None, Some(connecting))
+    }
+
+    fn with_socket(
+        socket: Option<TcpStream>,
+        connecting: Option<Receiver<std::result::Result<TcpStream, String>>>,
+    ) -> Self {
+        Self {
+            socket,
+            connecting,
+            send_credit: FORWARD_WINDOW_BYTES,
+            ungranted: 0,
+            outbox: VecDeque::new(),
+            peer_closed: false,
+            peer_eof: false,
+            write_shutdown: false,
+            local_eof: false,
+        }
+    }
+}
+
+fn configure(socket: &TcpStream) -> io::Result<()> {
+    socket.set_nonblocking(true)?;
+    let _ = socket.set_nodelay(true);
+    Ok(())
+}
+
+/// Multiplexes TCP streams over tunnel frames with per-stream credit windows.
+///
+/// The same type runs on both ends: the listener side opens streams from accepted sockets,
+/// while the far side (constructed with an allowlist) connects to the requested targets.
+pub struct ForwardMux {
+    streams: BTreeMap<u32, ForwardStream>,
+    allowlist: Option<Vec<String>>,
+    next_id: u32,
+    outgoing: VecDeque<TunnelMsgOwned>,
+}
+
+impl ForwardMux {
+    /// Far side: accept `ForwardOpen` for targets listed in `forward_allowlist`.
+    pub fn server(allowlist: Vec<String>) -> Self {
+        Self {
+            allowlist: Some(allowlist),
+            ..Self::client()
+        }
+    }
+
+    /// Listener side: only streams opened locally via [`ForwardMux::open`].
+    pub fn client() -> Self {
+        Self {
+            streams: BTreeMap::new(),
+            allowlist: None,
+            next_id: 1,
+            outgoing: VecDeque::new(),
+        }
+    }
+
+    pub fn active_streams(&self) -> usize {
+        self.streams.len()
+    }
+
+    /// Register an accepted local connection and ask the peer to connect to `target`.
+    pub fn open(&mut self, socket: TcpStream, target: &str) -> Result<u32> {
+        let stream_id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        self.streams.insert(stream_id, ForwardStream::new(socket)?);
+        self.outgoing.push_back(TunnelMsgOwned::ForwardOpen {
+            stream_id,
+            target: target.to_string(),
+        });
+        Ok(stream_id)
+    }
+
+    /// Apply a forwarding frame from the peer. Returns `false` for non-forwarding frames.
+    pub fn handle(&mut self, msg: TunnelMsgOwned) -> bool {
+        match msg {
+            TunnelMsgOwned::ForwardOpen { stream_id, target } => {
+                if let Err(reason) = self.accept_open(stream_id, &target) {
+                    self.outgoing.push_back(TunnelMsgOwned::ForwardClose {
+                        stream_id,
+                        reason: Some(reason),
+                    });
+                }
+            }
+            TunnelMsgOwned::ForwardData { stream_id, chunk } => {
+                let Some(stream) = self.streams.get_mut(&stream_id) else {
+                    return true;
+                };
+                // An empty chunk is the peer's half-close: no more bytes follow in that direction.
+                if chunk.is_empty() {
+                    stream.peer_eof = true;
+                    return true;
+                }
+                if stream.outbox.len() + chunk.len() > FORWARD_WINDOW_BYTES as usize {
+                    self.close(stream_id, Some("peer exceeded flow-control window".into()));
+                    return true;
+                }
+                stream.outbox.extend(chunk);
+            }
+            TunnelMsgOwned::ForwardWindow { stream_id, credit } => {
+                if let Some(stream) = self.streams.get_mut(&stream_id) {
+                    stream.send_credit = stream
+                        .send_credit
+                        .saturating_add(credit)
+                        .min(FORWARD_WINDOW_BYTES);
+                }
+            }
+            TunnelMsgOwned::ForwardClose { stream_id, .. } => {
+                if let Some(stream) = self.streams.get_mut(&stream_id) {
+                    stream.peer_closed = true;
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Service every socket once: drain peer data, grant credit, and read up to
+    /// [`FORWARD_FRAMES_PER_POLL`] data frames worth of local bytes.
+    pub fn poll(&mut self) {
+        let mut budget = FORWARD_FRAMES_PER_POLL;
+        let ids: Vec<u32> = self.streams.keys().copied().collect();
+        for stream_id in ids {
+            match self.service(stream_id, &mut budget) {
+                Ok(true) => {}
+                Ok(false) => self.close(stream_id, None),
+                Err(err) => self.close(stream_id, Some(err.to_string())),
+            }
+        }
+    }
+
+    pub fn next_outgoing(&mut self) -> Option<TunnelMsgOwned> {
+        self.outgoing.pop_front()
+    }
+
+    fn accept_open(&mut self, stream_id: u32, target: &str) -> std::result::Result<(), String> {
+        let allowlist = self
+            .allowlist
+            .as_ref()
+            .ok_or_else(|| "forwarding is not accepted by this peer".to_string())?;
+        if !allowlist.iter().any(|entry| entry == target) {
+            return Err(format!("{target} is not on forward_allowlist"));
+        }
+        if self.streams.contains_key(&stream_id) {
+            return Err(format!("stream {stream_id} already open"));
+        }
+        let (host, port) =
+            parse_host_port(target).ok_or_else(|| format!("invalid target {target}"))?;
+        // Connecting may block for seconds per address; keep it off the caller's loop and let
+        // `service` pick the socket up once it is ready.
+        let (done, connecting) = mpsc::channel();
+        let host = host.to_string();
+        let target = target.to_string();
+        thread::spawn(move || {
+            let result = connect(&host, port).map_err(|err| format!("connect {target}: {err}"));
+            let _ = done.send(result);
+        });
+        self.streams
+            .insert(stream_id, ForwardStream::connecting(connecting));
+        Ok(())
+    }
+
+    /// Returns `Ok(false)` once the stream should be torn down.
+    fn service(&mut self, stream_id: u32, budget: &mut usize) -> io::Result<bool> {
+        let Some(stream) = self.streams.get_mut(&stream_id) else {
+            return Ok(true);
+        };
+        if let Some(connecting) = &stream.connecting {
+            match connecting.try_recv() {
+                Ok(Ok(socket)) => {
+                    configure(&socket)?;
+                    stream.socket = Some(socket);
+                    stream.connecting = None;
+                }
+                Ok(Err(reason)) => return Err(io::Error::other(reason)),
+                Err(TryRecvError::Empty) => {
+                    // The peer gave up before the target answered; nothing is left to deliver.
+                    if stream.peer_closed && stream.outbox.is_empty() {
+                        self.streams.remove(&stream_id);
+                    }
+                    return Ok(true);
+                }
+                Err(TryRecvError::Disconnected) => {
+                    return Err(io::Error::other("connect worker exited"))
+                }
+            }
+        }
+        let Some(socket) = stream.socket.as_mut() else {
+            return Ok(false);
+        };
+
+        while !stream.outbox.is_empty() {
+            let (front, _) = stream.outbox.as_slices();
+            match socket.write(front) {
+                Ok(0) => return Ok(false),
+                Ok(written) => {
+                    stream.outbox.drain(..written);
+                    stream.ungranted += written as u32;
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        if stream.ungranted >= FORWARD_WINDOW_BYTES / 2 {
+            self.outgoing.push_back(TunnelMsgOwned::ForwardWindow {
+                stream_id,
+                credit: stream.ungranted,
+            });
+            stream.ungranted = 0;
+        }
+        if stream.peer_closed {
+            if stream.outbox.is_empty() {
+                let _ = socket.shutdown(Shutdown::Both);
+                self.streams.remove(&stream_id);
+            }
+            return Ok(true);
+        }
+        if stream.peer_eof && stream.outbox.is_empty() && !stream.write_shutdown {
+            let _ = socket.shutdown(Shutdown::Write);
+            stream.write_shutdown = true;
+        }
+
+        while !stream.local_eof && *budget > 0 && stream.send_credit > 0 {
+            let limit = FORWARD_CHUNK_BYTES.min(stream.send_credit as usize);
+            let mut buf = vec![0u8; limit];
+            match socket.read(&mut buf) {
+                // Half-close: tell the peer, but keep relaying its replies until it is done too.
+                Ok(0) => {
+                    stream.local_eof = true;
+                    self.outgoing.push_back(TunnelMsgOwned::ForwardData {
+                        stream_id,
+                        chunk: Vec::new(),
+                    });
+                }
+                Ok(read) => {
+                    buf.truncate(read);
+                    stream.send_credit -= read as u32;
+                    *budget -= 1;
+                    self.outgoing.push_back(TunnelMsgOwned::ForwardData {
+                        stream_id,
+                        chunk: buf,
+                    });
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(!(stream.local_eof && stream.write_shutdown))
+    }
+
+    fn close(&mut self, stream_id: u32, reason: Option<String>) {
+        if let Some(stream) = self.streams.remove(&stream_id) {
+            if let Some(socket) = &stream.socket {
+                let _ = socket.shutdown(Shutdown::Both);
+            }
+            self.outgoing
+                .push_back(TunnelMsgOwned::ForwardClose { stream_id, reason });
+        }
+    }
+}
+
+fn connect(host: &str, port: u16) -> io::Result<TcpStream> {
+    let mut last_err = io::Error::new(ErrorKind::NotFound, "no addresses resolved");
+    for addr in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, FORWARD_CONNECT_TIMEOUT) {
+            Ok(socket) => return Ok(socket),
+            Err(err) => last_err = err,
+        }
+    }
+    Err(last_err)
+}
+
+/// Entry point for `lifelinetty --forward`: listen locally and relay through the serial tunnel.
+pub fn run_port_forward(opts: RunOptions) -> Result<()> {
+    super::wizard::maybe_run(&opts)?;
+    let specs = opts.forwards.clone();
+    let cfg = Config::load_or_default()?;
+    let merged = AppConfig::from_sources(cfg, opts);
+    let mut options = merged.serial_options();
+    options.timeout_ms = options.timeout_ms.min(FORWARD_SERIAL_POLL_MS);
+    let device = merged.resolved_device()?;
+    let mut serial = SerialPort::connect(&device, options)?;
+    let listeners = bind_listeners(&specs)?;
+    for spec in &specs {
+        eprintln!(
+            "forwarding 127.0.0.1:{} -> {} via {}",
+            spec.local_port, spec.target, device
+        );
+    }
+    serial.send_command_line("INIT")?;
+    let mut mux = ForwardMux::client();
+    loop {
+        pump_forward_client(&mut serial, &listeners, &mut mux)?;
+    }
+}
+
+fn bind_listeners(specs: &[ForwardSpec]) -> Result<Vec<(TcpListener, String)>> {
+    specs
+        .iter()
+        .map(|spec| {
+            let listener = TcpListener::bind(("127.0.0.1", spec.local_port)).map_err(|err| {
+                Error::InvalidArgs(format!("cannot listen on port {}: {err}", spec.local_port))
+            })?;
+            listener.set_nonblocking(true)?;
+            Ok((listener, spec.target.clone()))
+        })
+        .collect()
+}
+
+/// One iteration of the listener-side loop: accept, flush frames, then read one serial line.
+fn pump_forward_client<L: LineIo>(
+    serial: &mut L,
+    listeners: &[(TcpListener, String)],
+    mux: &mut ForwardMux,
+) -> Result<()> {
+    for (listener, target) in listeners {
+        loop {
+            match listener.accept() {
+                Ok((socket, _)) => {
+                    mux.open(socket, target)?;
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+    mux.poll();
+    while let Some(msg) = mux.next_outgoing() {
+        serial.send_command_line(&encode_tunnel_msg(&msg)?)?;
+    }
+    let mut line = String::new();
+    if serial.read_message_line(&mut line)? > 0 {
+        let trimmed = line.trim();
+        if trimmed.contains("\"msg\"") && trimmed.contains("\"crc32\"") {
+            if let Ok(msg) = decode_tunnel_frame(trimmed) {
+                if let TunnelMsgOwned::ForwardClose {
+                    stream_id,
+                    reason: Some(reason),
+                } = &msg
+                {
+                    eprintln!("forward stream {stream_id} closed: {reason}");
+                }
+                mux.handle(msg);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Instant;
+
+    /// Shuttle frames between two muxes (as if over serial) until `done` or timeout.
+    fn pump_until(
+        client: &mut ForwardMux,
+        server: &mut ForwardMux,
+        mut done: impl FnMut(&mut ForwardMux, &mut ForwardMux) -> bool,
+    ) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !done(client, server) {
+            assert!(Instant::now() < deadline, "forwarding stalled");
+            client.poll();
+            while let Some(msg) = client.next_outgoing() {
+                let wire = encode_tunnel_msg(&msg).unwrap();
+                assert!(server.handle(decode_tunnel_frame(&wire).unwrap()));
+            }
+            server.poll();
+            while let Some(msg) = server.next_outgoing() {
+                let wire = encode_tunnel_msg(&msg).unwrap();
+                assert!(client.handle(decode_tunnel_frame(&wire).unwrap()));
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+    }
+
+    fn echo_server() -> (String, thread::JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let target = listener.local_addr().unwrap().to_string();
+        let handle = thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            loop {
+                match socket.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => socket.write_all(&buf[..n]).unwrap(),
+                }
+            }
+        });
+        (target, handle)
+    }
+
+    fn socket_pair() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let user = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (accepted, _) = listener.accept().unwrap();
+        (user, accepted)
+    }
+
+    #[test]
+    fn relays_bytes_larger_than_the_window_in_both_directions() {
+        let (target, echo) = echo_server();
+        let mut server = ForwardMux::server(vec![target.clone()]);
+        let mut client = ForwardMux::client();
+        let (mut user, accepted) = socket_pair();
+        client.open(accepted, &target).unwrap();
+
+        let payload: Vec<u8> = (0..3 * FORWARD_WINDOW_BYTES).map(|i| i as u8).collect();
+        let writer = {
+            let mut user = user.try_clone().unwrap();
+            let payload = payload.clone();
+            thread::spawn(move || user.write_all(&payload).unwrap())
+        };
+        user.set_nonblocking(true).unwrap();
+        let mut echoed = Vec::new();
+        pump_until(&mut client, &mut server, |_, _| {
+            let mut buf = [0u8; 2048];
+            while let Ok(n) = user.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+                echoed.extend_from_slice(&buf[..n]);
+            }
+            echoed.len() >= payload.len()
+        });
+        writer.join().unwrap();
+        assert_eq!(echoed, payload);
+
+        drop(user);
+        pump_until(&mut client, &mut server, |client, server| {
+            client.active_streams() == 0 && server.active_streams() == 0
+        });
+        echo.join().unwrap();
+    }
+
+    #[test]
+    fn rejects_targets_missing_from_allowlist() {
+        let mut server = ForwardMux::server(vec!["127.0.0.1:22".into()]);
+        assert!(server.handle(TunnelMsgOwned::ForwardOpen {
+            stream_id: 9,
+            target: "10.0.0.1:80".into(),
+        }));
+        match server.next_outgoing() {
+            Some(TunnelMsgOwned::ForwardClose {
+                stream_id: 9,
+                reason: Some(reason),
+            }) => assert!(reason.contains("forward_allowlist"), "{reason}"),
+            other => panic!("expected ForwardClose, got {other:?}"),
+        }
+        assert_eq!(server.active_streams(), 0);
+
+        let mut client = ForwardMux::client();
+        client.handle(TunnelMsgOwned::ForwardOpen {
+            stream_id: 1,
+            target: "127.0.0.1:22".into(),
+        });
+        assert!(matches!(
+            client.next_outgoing(),
+            Some(TunnelMsgOwned::ForwardClose { stream_id: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn poll_caps_data_frames_per_call() {
+        let (target, _echo) = echo_server();
+        let mut client = ForwardMux::client();
+        let mut users = Vec::new();
+        for _ in 0..3 {
+            let (mut user, accepted) = socket_pair();
+            user.write_all(&vec![7u8; 2 * FORWARD_CHUNK_BYTES]).unwrap();
+            client.open(accepted, &target).unwrap();
+            users.push(user);
+        }
+        while client.next_outgoing().is_some() {}
+        thread::sleep(Duration::from_millis(50));
+
+        client.poll();
+        let mut data_frames = 0;
+        while let Some(msg) = client.next_outgoing() {
+            if matches!(msg, TunnelMsgOwned::ForwardData { .. }) {
+                data_frames += 1;
+            }
+        }
+        assert_eq!(data_frames, FORWARD_FRAMES_PER_POLL);
+    }
+
+    #[test]
+    fn unreachable_target_fails_from_poll_without_blocking_handle() {
+        let target = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().to_string()
+        };
+        let mut server = ForwardMux::server(vec![target.clone()]);
+        assert!(server.handle(TunnelMsgOwned::ForwardOpen {
+            stream_id: 4,
+            target,
+        }));
+        assert_eq!(server.next_outgoing(), None);
+        assert_eq!(server.active_streams(), 1);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let reason = loop {
+            assert!(Instant::now() < deadline, "connect failure never surfaced");
+            server.poll();
+            if let Some(TunnelMsgOwned::ForwardClose {
+                stream_id: 4,
+                reason: Some(reason),
+            }) = server.next_outgoing()
+            {
+                break reason;
+            }
+            thread::sleep(Duration::from_millis(2));
+        };
+        assert!(reason.starts_with("connect "), "{reason}");
+        assert_eq!(server.active_streams(), 0);
+    }
+
+    #[test]
+    fn local_eof_delivers_buffered_peer_data_before_closing() {
+        let mut client = ForwardMux::client();
+        let (mut user, accepted) = socket_pair();
+        let stream_id = client.open(accepted, "127.0.0.1:1").unwrap();
+        while client.next_outgoing().is_some() {}
+
+        // Fill the socket buffers until a write would block and peer data stays queued.
+        let chunk = vec![9u8; FORWARD_WINDOW_BYTES as usize];
+        let mut sent = 0;
+        while client.streams[&stream_id].outbox.is_empty() {
+            assert!(client.handle(TunnelMsgOwned::ForwardData {
+                stream_id,
+                chunk: chunk.clone(),
+            }));
+            sent += chunk.len();
+            client.poll();
+        }
+        user.shutdown(Shutdown::Write).unwrap();
+        assert!(client.handle(TunnelMsgOwned::ForwardData {
+            stream_id,
+            chunk: Vec::new(),
+        }));
+
+        let reader = thread::spawn(move || {
+            let mut received = Vec::new();
+            user.read_to_end(&mut received).unwrap();
+            received.len()
+        });
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while client.active_streams() > 0 {
+            assert!(Instant::now() < deadline, "stream never closed");
+            client.poll();
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(std::iter::from_fn(|| client.next_outgoing())
+            .any(|msg| matches!(msg, TunnelMsgOwned::ForwardClose { reason: None, .. })));
+        assert_eq!(reader.join().unwrap(), sent);
+    }
+
+    #[test]
+    fn target_replies_after_local_half_close() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let target = listener.local_addr().unwrap().to_string();
+        let responder = thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            socket.read_to_end(&mut request).unwrap();
+            socket.write_all(b"reply to ").unwrap();
+            socket.write_all(&request).unwrap();
+        });
+        let mut server = ForwardMux::server(vec![target.clone()]);
+        let mut client = ForwardMux::client();
+        let (mut user, accepted) = socket_pair();
+        client.open(accepted, &target).unwrap();
+
+        user.write_all(b"request").unwrap();
+        user.shutdown(Shutdown::Write).unwrap();
+        user.set_nonblocking(true).unwrap();
+        let mut reply = Vec::new();
+        let mut user_eof = false;
+        pump_until(&mut client, &mut server, |client, server| {
+            let mut buf = [0u8; 256];
+            loop {
+                match user.read(&mut buf) {
+                    Ok(0) => user_eof = true,
+                    Ok(n) => {
+                        reply.extend_from_slice(&buf[..n]);
+                        continue;
+                    }
+                    Err(_) => {}
+                }
+                break;
+            }
+            user_eof && client.active_streams() == 0 && server.active_streams() == 0
+        });
+        assert_eq!(reply, b"reply to request");
+        responder.join().unwrap();
+    }
+}
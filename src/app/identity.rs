@@ -0,0 +1,207 @@
+//! Node identity for negotiation: a human-readable name and a random UUID that survives
+//! restarts in `~/.serial_lcd/node_uuid`, plus the pinning check against the expected peer.
+
+use std::{
+    collections::hash_map::RandomState,
+    fs,
+    hash::{BuildHasher, Hasher},
+    io::Read,
+    path::Path,
+};
+
+use crate::{
+    config::{is_uuid, loader::config_dir, NegotiationConfig, PeerMismatchAction},
+    Result,
+};
+
+const NODE_UUID_FILE_NAME: &str = "node_uuid";
+const FALLBACK_NODE_NAME: &str = "lifelinetty";
+
+/// How this node introduces itself in `hello`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct NodeIdentity {
+    pub name: String,
+    pub uuid: String,
+}
+
+impl NodeIdentity {
+    /// Load the UUID from `~/.serial_lcd`, creating it on first run.
+    pub(crate) fn load_or_create(name: Option<&str>) -> Result<Self> {
+        Self::load_or_create_in(&config_dir()?, name)
+    }
+
+    pub(crate) fn load_or_create_in(dir: &Path, name: Option<&str>) -> Result<Self> {
+        let path = dir.join(NODE_UUID_FILE_NAME);
+        let uuid = match fs::read_to_string(&path) {
+            Ok(text) if is_uuid(text.trim()) => text.trim().to_ascii_lowercase(),
+            _ => {
+                let uuid = random_uuid();
+                fs::create_dir_all(dir)?;
+                fs::write(&path, format!("{uuid}\n"))?;
+                uuid
+            }
+        };
+        Ok(Self {
+            name: resolve_name(name),
+            uuid,
+        })
+    }
+
+    /// Identity for this process only, when `~/.serial_lcd` cannot be written.
+    pub(crate) fn ephemeral(name: Option<&str>) -> Self {
+        Self {
+            name: resolve_name(name),
+            uuid: random_uuid(),
+        }
+    }
+}
+
+/// What the peer said about itself; both fields are absent for peers that predate naming.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct PeerIdentity {
+    pub name: Option<String>,
+    pub uuid: Option<String>,
+}
+
+impl PeerIdentity {
+    /// Name for the LCD and logs, falling back to the UUID prefix.
+    pub(crate) fn label(&self) -> String {
+        match (&self.name, &self.uuid) {
+            (Some(name), _) => name.clone(),
+            (None, Some(uuid)) => uuid.chars().take(8).collect(),
+            (None, None) => "unknown".into(),
+        }
+    }
+
+    /// First UUID group, short enough for a status line.
+    pub(crate) fn short_uuid(&self) -> &str {
+        self.uuid
+            .as_deref()
+            .and_then(|uuid| uuid.split('-').next())
+            .unwrap_or("--------")
+    }
+}
+
+/// Result of comparing the peer on the cable with `negotiation.peer_uuid`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PinCheck {
+    Accepted,
+    /// Different or anonymous peer; keep the session but warn.
+    Alert(String),
+    /// Different or anonymous peer; drop the session.
+    Refused(String),
+}
+
+pub(crate) fn check_peer_pin(config: &NegotiationConfig, peer: Option<&PeerIdentity>) -> PinCheck {
+    let Some(expected) = config.peer_uuid.as_deref() else {
+        return PinCheck::Accepted;
+    };
+    let reason = match peer.and_then(|peer| peer.uuid.as_deref()) {
+        Some(uuid) if uuid.eq_ignore_ascii_case(expected) => return PinCheck::Accepted,
+        Some(uuid) => format!("peer {uuid} is not the pinned peer {expected}"),
+        None => format!("peer did not identify itself; pinned peer is {expected}"),
+    };
+    match config.peer_mismatch {
+        PeerMismatchAction::Refuse => PinCheck::Refused(reason),
+        PeerMismatchAction::Alert => PinCheck::Alert(reason),
+    }
+}
+
+fn resolve_name(configured: Option<&str>) -> String {
+    configured
+        .map(str::to_string)
+        .or_else(|| {
+            fs::read_to_string("/proc/sys/kernel/hostname")
+                .or_else(|_| fs::read_to_string("/etc/hostname"))
+                .ok()
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+        })
+        .unwrap_or_else(|| FALLBACK_NODE_NAME.into())
+}
+
+/// Version 4 UUID from the OS entropy pool, or from std's randomly seeded hasher when
+/// `/dev/urandom` is unavailable.
+fn random_uuid() -> String {
+    let mut bytes = [0u8; 16];
+    let from_os = fs::File::open("/dev/urandom")
+        .and_then(|mut file| file.read_exact(&mut bytes))
+        .is_ok();
+    if !from_os {
+        for half in bytes.chunks_mut(8) {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u128(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_nanos())
+                    .unwrap_or(0),
+            );
+            half.copy_from_slice(&hasher.finish().to_le_bytes());
+        }
+    }
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uuid_persists_across_loads() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = NodeIdentity::load_or_create_in(dir.path(), Some("rack-pi")).unwrap();
+        assert!(is_uuid(&first.uuid));
+        assert_eq!(&first.uuid[14..15], "4");
+        assert_eq!(first.name, "rack-pi");
+        let again = NodeIdentity::load_or_create_in(dir.path(), Some("rack-pi")).unwrap();
+        assert_eq!(again.uuid, first.uuid);
+    }
+
+    #[test]
+    fn pin_check_refuses_or_alerts_on_other_peers() {
+        let pinned = "0f8e2c1a-5b7d-4e3f-9a10-c2d4e6f80123";
+        let mut config = NegotiationConfig {
+            peer_uuid: Some(pinned.into()),
+            ..NegotiationConfig::default()
+        };
+        let expected = PeerIdentity {
+            name: Some("bench".into()),
+            uuid: Some(pinned.to_ascii_uppercase()),
+        };
+        let other = PeerIdentity {
+            name: None,
+            uuid: Some("11111111-2222-4333-8444-555555555555".into()),
+        };
+        assert_eq!(check_peer_pin(&config, Some(&expected)), PinCheck::Accepted);
+        assert!(matches!(
+            check_peer_pin(&config, Some(&other)),
+            PinCheck::Refused(reason) if reason.contains("11111111-2222")
+        ));
+        assert!(matches!(
+            check_peer_pin(&config, None),
+            PinCheck::Refused(_)
+        ));
+
+        config.peer_mismatch = PeerMismatchAction::Alert;
+        assert!(matches!(
+            check_peer_pin(&config, Some(&other)),
+            PinCheck::Alert(_)
+        ));
+        assert_eq!(
+            check_peer_pin(&NegotiationConfig::default(), Some(&other)),
+            PinCheck::Accepted
+        );
+        assert_eq!(other.label(), "11111111");
+        assert_eq!(expected.short_uuid(), "0F8E2C1A");
+    }
+}
@@ -0,0 +1,217 @@
+//! Round-trip latency from tunnel `ping`/`pong` frames. Each ping carries a sequence number
+//! and the sender's own clock, which the peer echoes back, so no clock sync is needed.
+//! Smoothing follows RFC 6298 (srtt/rttvar) and jitter follows RFC 3550.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::payload::TunnelMsgOwned;
+
+/// Pings in flight before the oldest is written off as lost.
+const MAX_OUTSTANDING: usize = 8;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct LatencySnapshot {
+    pub samples: u64,
+    pub lost: u64,
+    pub last_ms: Option<f64>,
+    pub min_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+    /// Smoothed RTT.
+    pub srtt_ms: Option<f64>,
+    /// Mean deviation between consecutive samples.
+    pub jitter_ms: Option<f64>,
+}
+
+impl LatencySnapshot {
+    /// One-line form for logs.
+    pub fn summary(&self) -> String {
+        let ms = |value: Option<f64>| {
+            value
+                .map(|ms| format!("{ms:.1}ms"))
+                .unwrap_or_else(|| "--".into())
+        };
+        format!(
+            "rtt={} min={} max={} jitter={} samples={} lost={}",
+            ms(self.srtt_ms),
+            ms(self.min_ms),
+            ms(self.max_ms),
+            ms(self.jitter_ms),
+            self.samples,
+            self.lost
+        )
+    }
+}
+
+pub struct LatencyTracker {
+    epoch: Instant,
+    next_seq: u32,
+    outstanding: VecDeque<(u32, u64)>,
+    srtt: Option<Duration>,
+    rttvar: Duration,
+    jitter: Duration,
+    last: Option<Duration>,
+    min: Option<Duration>,
+    max: Option<Duration>,
+    samples: u64,
+    lost: u64,
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            next_seq: 0,
+            outstanding: VecDeque::new(),
+            srtt: None,
+            rttvar: Duration::ZERO,
+            jitter: Duration::ZERO,
+            last: None,
+            min: None,
+            max: None,
+            samples: 0,
+            lost: 0,
+        }
+    }
+
+    /// Forget the estimate after a reconnect; the new link may run at another rate. The
+    /// sequence keeps counting so late pongs from the old link never match.
+    pub fn reset_link(&mut self) {
+        self.outstanding.clear();
+        self.srtt = None;
+        self.rttvar = Duration::ZERO;
+        self.jitter = Duration::ZERO;
+        self.last = None;
+    }
+
+    /// Build the next ping and remember it until its pong arrives.
+    pub fn ping(&mut self, now: Instant) -> TunnelMsgOwned {
+        let seq = self.next_seq;
+        self.next_seq = self.next_seq.wrapping_add(1);
+        let sent_ms = self.clock_ms(now);
+        if self.outstanding.len() == MAX_OUTSTANDING {
+            self.outstanding.pop_front();
+            self.lost += 1;
+        }
+        self.outstanding.push_back((seq, sent_ms));
+        TunnelMsgOwned::Ping { seq, sent_ms }
+    }
+
+    /// Match a pong against the pings in flight and return its RTT. Pongs arrive in order,
+    /// so older pings still waiting are lost; unknown or altered pongs are ignored.
+    pub fn pong(&mut self, seq: u32, sent_ms: u64, now: Instant) -> Option<Duration> {
+        let index = self
+            .outstanding
+            .iter()
+            .position(|&(pending, at)| pending == seq && at == sent_ms)?;
+        self.lost += index as u64;
+        self.outstanding.drain(..=index);
+        let rtt = Duration::from_millis(self.clock_ms(now).saturating_sub(sent_ms));
+        self.update(rtt);
+        Some(rtt)
+    }
+
+    fn update(&mut self, rtt: Duration) {
+        match self.srtt {
+            None => {
+                self.srtt = Some(rtt);
+                self.rttvar = rtt / 2;
+            }
+            Some(srtt) => {
+                self.rttvar = (self.rttvar * 3 + srtt.abs_diff(rtt)) / 4;
+                self.srtt = Some((srtt * 7 + rtt) / 8);
+            }
+        }
+        if let Some(last) = self.last {
+            let deviation = last.abs_diff(rtt);
+            self.jitter = (self.jitter * 15 + deviation) / 16;
+        }
+        self.last = Some(rtt);
+        self.min = Some(self.min.map_or(rtt, |min| min.min(rtt)));
+        self.max = Some(self.max.map_or(rtt, |max| max.max(rtt)));
+        self.samples += 1;
+    }
+
+    /// Retransmission timeout (`srtt + 4 * rttvar`) once the current link has a sample.
+    pub fn rto(&self) -> Option<Duration> {
+        self.srtt.map(|srtt| srtt + self.rttvar * 4)
+    }
+
+    pub fn snapshot(&self) -> LatencySnapshot {
+        let ms = |value: Duration| value.as_secs_f64() * 1_000.0;
+        LatencySnapshot {
+            samples: self.samples,
+            lost: self.lost,
+            last_ms: self.last.map(ms),
+            min_ms: self.min.map(ms),
+            max_ms: self.max.map(ms),
+            srtt_ms: self.srtt.map(ms),
+            jitter_ms: self.srtt.map(|_| ms(self.jitter)),
+        }
+    }
+
+    fn clock_ms(&self, now: Instant) -> u64 {
+        now.saturating_duration_since(self.epoch).as_millis() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sent(msg: TunnelMsgOwned) -> (u32, u64) {
+        match msg {
+            TunnelMsgOwned::Ping { seq, sent_ms } => (seq, sent_ms),
+            other => panic!("expected ping, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn pongs_yield_smoothed_rtt_and_jitter() {
+        let mut tracker = LatencyTracker::new();
+        let start = tracker.epoch;
+        for (i, rtt_ms) in [40u64, 60, 40].into_iter().enumerate() {
+            let at = start + Duration::from_secs(i as u64);
+            let (seq, sent_ms) = sent(tracker.ping(at));
+            let rtt = tracker.pong(seq, sent_ms, at + Duration::from_millis(rtt_ms));
+            assert_eq!(rtt, Some(Duration::from_millis(rtt_ms)));
+        }
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot.samples, 3);
+        assert_eq!(snapshot.min_ms, Some(40.0));
+        assert_eq!(snapshot.max_ms, Some(60.0));
+        assert_eq!(snapshot.last_ms, Some(40.0));
+        // srtt: 40 -> 42.5 -> 42.1875; jitter: 20/16 -> (1.25 * 15 + 20) / 16.
+        assert!((snapshot.srtt_ms.unwrap() - 42.1875).abs() < 0.01);
+        assert!((snapshot.jitter_ms.unwrap() - 2.421875).abs() < 0.01);
+        assert!(tracker.rto().unwrap() > Duration::from_millis(42));
+    }
+
+    #[test]
+    fn skipped_and_stale_pongs_count_as_lost_or_ignored() {
+        let mut tracker = LatencyTracker::new();
+        let now = tracker.epoch;
+        let first = sent(tracker.ping(now));
+        let second = sent(tracker.ping(now));
+        assert!(tracker.pong(second.0, second.1, now).is_some());
+        assert_eq!(tracker.snapshot().lost, 1);
+        // The first ping's pong arriving late no longer matches anything.
+        assert_eq!(tracker.pong(first.0, first.1, now), None);
+        // Neither does a pong whose timestamp was altered.
+        let third = sent(tracker.ping(now));
+        assert_eq!(tracker.pong(third.0, third.1 + 1, now), None);
+
+        tracker.reset_link();
+        assert_eq!(tracker.rto(), None);
+        assert_eq!(tracker.pong(third.0, third.1, now), None);
+        assert_eq!(tracker.snapshot().samples, 1);
+    }
+}
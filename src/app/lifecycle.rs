@@ -23,5 +23,5 @@ pub(super) fn render_shutdown(lcd: &mut Lcd) -> Result<()> {
     lcd.set_blink(false)?;
     lcd.write_line(0, "offline")?;
     lcd.write_line(1, "")?;
-    Ok(())
+    lcd.finish_frame()
 }
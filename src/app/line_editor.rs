@@ -0,0 +1,410 @@
+use crate::Result;
+use rustix::termios::{self, OptionalActions, Termios};
+use std::io::{self, Read, Write};
+use std::os::fd::AsFd;
+
+/// Keys understood by the serial shell line editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Char(char),
+    Enter,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Home,
+    End,
+    Up,
+    Down,
+    KillToStart,
+    KillToEnd,
+    Search,
+    Cancel,
+    Eof,
+    Escape,
+    Ignored,
+}
+
+/// Puts a terminal into raw mode and restores the previous settings on drop.
+pub struct RawMode<Fd: AsFd> {
+    fd: Fd,
+    original: Termios,
+}
+
+impl<Fd: AsFd> RawMode<Fd> {
+    pub fn enable(fd: Fd) -> Result<Self> {
+        let original = termios::tcgetattr(&fd).map_err(io::Error::from)?;
+        let mut raw = original.clone();
+        raw.make_raw();
+        termios::tcsetattr(&fd, OptionalActions::Flush, &raw).map_err(io::Error::from)?;
+        Ok(Self { fd, original })
+    }
+}
+
+impl<Fd: AsFd> Drop for RawMode<Fd> {
+    fn drop(&mut self) {
+        let _ = termios::tcsetattr(&self.fd, OptionalActions::Flush, &self.original);
+    }
+}
+
+/// Minimal emacs-style line editor: cursor movement, history recall, and Ctrl-R search.
+///
+/// The editor only decodes bytes and redraws; callers are responsible for putting the
+/// terminal into raw mode (see [`RawMode`]).
+pub struct LineEditor<R: Read> {
+    input: R,
+}
+
+struct EditState {
+    line: Vec<char>,
+    cursor: usize,
+    history_pos: Option<usize>,
+    draft: Vec<char>,
+    search: Option<Search>,
+}
+
+struct Search {
+    query: String,
+    hit: Option<usize>,
+}
+
+impl<R: Read> LineEditor<R> {
+    pub fn new(input: R) -> Self {
+        Self { input }
+    }
+
+    /// Read one line, echoing edits to `out`. Returns `None` on Ctrl-D at an empty prompt or EOF.
+    pub fn read_line<W: Write>(
+        &mut self,
+        prompt: &str,
+        history: &[String],
+        out: &mut W,
+    ) -> Result<Option<String>> {
+        let mut state = EditState {
+            line: Vec::new(),
+            cursor: 0,
+            history_pos: None,
+            draft: Vec::new(),
+            search: None,
+        };
+        redraw(prompt, &state, history, out)?;
+        loop {
+            let key = match self.read_key()? {
+                Some(key) => key,
+                None if state.line.is_empty() => return Ok(None),
+                None => Key::Enter,
+            };
+            if state.search.is_some() {
+                if let Some(done) = handle_search_key(&mut state, key, history) {
+                    if done {
+                        return finish(state, out);
+                    }
+                    redraw(prompt, &state, history, out)?;
+                    continue;
+                }
+            }
+            match key {
+                Key::Enter => return finish(state, out),
+                Key::Eof if state.line.is_empty() => {
+                    out.write_all(b"\r\n")?;
+                    out.flush()?;
+                    return Ok(None);
+                }
+                Key::Eof | Key::Delete => {
+                    if state.cursor < state.line.len() {
+                        state.line.remove(state.cursor);
+                    }
+                }
+                Key::Cancel => {
+                    out.write_all(b"^C\r\n")?;
+                    out.flush()?;
+                    return Ok(Some(String::new()));
+                }
+                Key::Char(ch) => {
+                    state.line.insert(state.cursor, ch);
+                    state.cursor += 1;
+                }
+                Key::Backspace => {
+                    if state.cursor > 0 {
+                        state.cursor -= 1;
+                        state.line.remove(state.cursor);
+                    }
+                }
+                Key::Left => state.cursor = state.cursor.saturating_sub(1),
+                Key::Right => state.cursor = (state.cursor + 1).min(state.line.len()),
+                Key::Home => state.cursor = 0,
+                Key::End => state.cursor = state.line.len(),
+                Key::KillToStart => {
+                    state.line.drain(..state.cursor);
+                    state.cursor = 0;
+                }
+                Key::KillToEnd => state.line.truncate(state.cursor),
+                Key::Up => recall(&mut state, history, true),
+                Key::Down => recall(&mut state, history, false),
+                Key::Search => {
+                    state.search = Some(Search {
+                        query: String::new(),
+                        hit: None,
+                    })
+                }
+                Key::Escape | Key::Ignored => {}
+            }
+            redraw(prompt, &state, history, out)?;
+        }
+    }
+
+    fn read_byte(&mut self) -> Result<Option<u8>> {
+        let mut byte = [0u8; 1];
+        loop {
+            match self.input.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(byte[0])),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    fn read_key(&mut self) -> Result<Option<Key>> {
+        let Some(byte) = self.read_byte()? else {
+            return Ok(None);
+        };
+        let key = match byte {
+            b'\r' | b'\n' => Key::Enter,
+            0x7f | 0x08 => Key::Backspace,
+            0x01 => Key::Home,
+            0x02 => Key::Left,
+            0x03 => Key::Cancel,
+            0x04 => Key::Eof,
+            0x05 => Key::End,
+            0x06 => Key::Right,
+            0x0b => Key::KillToEnd,
+            0x0e => Key::Down,
+            0x10 => Key::Up,
+            0x12 => Key::Search,
+            0x15 => Key::KillToStart,
+            0x07 => Key::Escape,
+            0x1b => self.read_escape()?,
+            byte if byte < 0x20 => Key::Ignored,
+            byte => self.read_utf8(byte)?,
+        };
+        Ok(Some(key))
+    }
+
+    fn read_escape(&mut self) -> Result<Key> {
+        let Some(kind) = self.read_byte()? else {
+            return Ok(Key::Escape);
+        };
+        if kind != b'[' && kind != b'O' {
+            return Ok(Key::Escape);
+        }
+        let mut params = Vec::new();
+        loop {
+            let Some(byte) = self.read_byte()? else {
+                return Ok(Key::Escape);
+            };
+            if byte.is_ascii_digit() || byte == b';' {
+                params.push(byte);
+                continue;
+            }
+            return Ok(match (byte, params.as_slice()) {
+                (b'A', _) => Key::Up,
+                (b'B', _) => Key::Down,
+                (b'C', _) => Key::Right,
+                (b'D', _) => Key::Left,
+                (b'H', _) => Key::Home,
+                (b'F', _) => Key::End,
+                (b'~', b"1") | (b'~', b"7") => Key::Home,
+                (b'~', b"4") | (b'~', b"8") => Key::End,
+                (b'~', b"3") => Key::Delete,
+                _ => Key::Ignored,
+            });
+        }
+    }
+
+    fn read_utf8(&mut self, lead: u8) -> Result<Key> {
+        let width = match lead {
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 1,
+        };
+        let mut bytes = vec![lead];
+        for _ in 1..width {
+            match self.read_byte()? {
+                Some(byte) => bytes.push(byte),
+                None => break,
+            }
+        }
+        Ok(std::str::from_utf8(&bytes)
+            .ok()
+            .and_then(|text| text.chars().next())
+            .map(Key::Char)
+            .unwrap_or(Key::Ignored))
+    }
+}
+
+/// Apply a key while Ctrl-R search is active. Returns `Some(true)` to submit the line,
+/// `Some(false)` when the key was consumed, and `None` to leave search and re-handle the key.
+fn handle_search_key(state: &mut EditState, key: Key, history: &[String]) -> Option<bool> {
+    let search = state.search.as_mut()?;
+    match key {
+        Key::Char(ch) => {
+            search.query.push(ch);
+            search.hit = find_match(history, &search.query, search.hit.map(|hit| hit + 1));
+            Some(false)
+        }
+        Key::Backspace => {
+            search.query.pop();
+            search.hit = find_match(history, &search.query, None);
+            Some(false)
+        }
+        Key::Search => {
+            let before = search.hit.unwrap_or(history.len());
+            if let Some(hit) = find_match(history, &search.query, Some(before)) {
+                search.hit = Some(hit);
+            }
+            Some(false)
+        }
+        Key::Cancel | Key::Escape => {
+            state.search = None;
+            Some(false)
+        }
+        other => {
+            if let Some(hit) = search.hit {
+                state.line = history[hit].chars().collect();
+                state.cursor = state.line.len();
+            }
+            state.search = None;
+            (other == Key::Enter).then_some(true)
+        }
+    }
+}
+
+/// Newest history index strictly before `before` whose entry contains `query`.
+fn find_match(history: &[String], query: &str, before: Option<usize>) -> Option<usize> {
+    if query.is_empty() {
+        return None;
+    }
+    let end = before.unwrap_or(history.len()).min(history.len());
+    history[..end]
+        .iter()
+        .rposition(|entry| entry.contains(query))
+}
+
+fn recall(state: &mut EditState, history: &[String], older: bool) {
+    if history.is_empty() {
+        return;
+    }
+    let next = match (state.history_pos, older) {
+        (None, true) => {
+            state.draft = state.line.clone();
+            Some(history.len() - 1)
+        }
+        (None, false) => return,
+        (Some(pos), true) => Some(pos.saturating_sub(1)),
+        (Some(pos), false) if pos + 1 < history.len() => Some(pos + 1),
+        (Some(_), false) => None,
+    };
+    state.history_pos = next;
+    state.line = match next {
+        Some(pos) => history[pos].chars().collect(),
+        None => std::mem::take(&mut state.draft),
+    };
+    state.cursor = state.line.len();
+}
+
+fn finish<W: Write>(state: EditState, out: &mut W) -> Result<Option<String>> {
+    out.write_all(b"\r\n")?;
+    out.flush()?;
+    Ok(Some(state.line.into_iter().collect()))
+}
+
+fn redraw<W: Write>(
+    prompt: &str,
+    state: &EditState,
+    history: &[String],
+    out: &mut W,
+) -> Result<()> {
+    let (prefix, body, cursor) = match &state.search {
+        Some(search) => {
+            let hit = search.hit.map(|hit| history[hit].as_str()).unwrap_or("");
+            (
+                format!("(reverse-i-search)'{}': ", search.query),
+                hit.to_string(),
+                hit.chars().count(),
+            )
+        }
+        None => (
+            prompt.to_string(),
+            state.line.iter().collect(),
+            state.cursor,
+        ),
+    };
+    write!(out, "\r{prefix}{body}\x1b[K\r")?;
+    let column = prefix.chars().count() + cursor;
+    if column > 0 {
+        write!(out, "\x1b[{column}C")?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn edit(keys: &[u8], history: &[&str]) -> Option<String> {
+        let history: Vec<String> = history.iter().map(|s| s.to_string()).collect();
+        let mut editor = LineEditor::new(Cursor::new(keys.to_vec()));
+        let mut out = Vec::new();
+        editor.read_line("> ", &history, &mut out).unwrap()
+    }
+
+    #[test]
+    fn cursor_movement_inserts_mid_line() {
+        // type "uptme", move left twice, insert 'i'
+        assert_eq!(
+            edit(b"uptme\x1b[D\x1b[Di\r", &[]).as_deref(),
+            Some("uptime")
+        );
+        // Ctrl-A jumps home, Delete removes the first char
+        assert_eq!(edit(b"xls\x01\x1b[3~\r", &[]).as_deref(), Some("ls"));
+    }
+
+    #[test]
+    fn arrows_recall_history() {
+        let history = ["df -h", "uptime"];
+        assert_eq!(edit(b"\x1b[A\x1b[A\r", &history).as_deref(), Some("df -h"));
+        assert_eq!(
+            edit(b"w\x1b[A\x1b[B\r", &history).as_deref(),
+            Some("w"),
+            "Down past the newest entry restores the draft"
+        );
+    }
+
+    #[test]
+    fn ctrl_r_searches_backwards() {
+        let history = ["cat /etc/hostname", "uptime", "cat /proc/loadavg"];
+        assert_eq!(
+            edit(b"\x12cat\r", &history).as_deref(),
+            Some("cat /proc/loadavg")
+        );
+        assert_eq!(
+            edit(b"\x12cat\x12\r", &history).as_deref(),
+            Some("cat /etc/hostname")
+        );
+        // Leaving search with an arrow keeps the match for further editing.
+        assert_eq!(
+            edit(b"\x12up\x1b[C -p\r", &history).as_deref(),
+            Some("uptime -p")
+        );
+    }
+
+    #[test]
+    fn ctrl_d_on_empty_line_is_eof() {
+        assert_eq!(edit(b"\x04", &[]), None);
+        assert_eq!(edit(b"", &[]), None);
+    }
+}
@@ -0,0 +1,652 @@
+//! Rolling link quality over the last `link_quality.window_ms`: frames, frame errors, CRC
+//! failures, timeouts and round-trip time. Published to `link_status.json` in the cache dir
+//! and, with `link_quality.adapt`, used to back the link off when it degrades and to undo
+//! those steps once it has stayed healthy for `link_quality.recovery_windows`.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use super::latency::LatencySnapshot;
+use crate::{config::LinkQualityConfig, serial::reliable::ReliableStats, Error, CACHE_DIR};
+
+/// Fewer frames than this say too little about the error rate to act on.
+const MIN_FRAMES_FOR_VERDICT: u64 = 20;
+/// Smallest command/tunnel chunk the monitor shrinks to.
+pub(crate) const MIN_CHUNK_BYTES: usize = 64;
+const STATUS_FILE_NAME: &str = "link_status.json";
+const STATUS_WRITE_INTERVAL_MS: u64 = 5_000;
+const ADAPTATION_HISTORY: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LinkEvent {
+    /// A line that decoded as a frame.
+    Frame,
+    /// A line that failed to parse or validate.
+    FrameError,
+    /// A frame whose CRC did not match, including reliable-link frames dropped as corrupt.
+    CrcFailure,
+    /// The serial watchdog expired or the reliable link had to retransmit.
+    Timeout,
+}
+
+impl LinkEvent {
+    /// How a rejected frame counts: CRC mismatches separately from other parse failures.
+    pub(crate) fn from_error(err: &Error) -> Self {
+        if matches!(err, Error::ChecksumMismatch) {
+            LinkEvent::CrcFailure
+        } else {
+            LinkEvent::FrameError
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub(crate) struct LinkQualitySnapshot {
+    pub frames: u64,
+    pub frame_errors: u64,
+    pub crc_failures: u64,
+    pub timeouts: u64,
+    /// Errors, CRC failures and timeouts as a share of everything counted.
+    pub error_rate_pct: f64,
+    /// Mean of the round-trip samples in the window.
+    pub rtt_ms: Option<u64>,
+}
+
+impl LinkQualitySnapshot {
+    fn failures(&self) -> u64 {
+        self.frame_errors + self.crc_failures + self.timeouts
+    }
+
+    /// One-line form for logs.
+    pub(crate) fn summary(&self) -> String {
+        format!(
+            "frames={} errors={} crc={} timeouts={} error_rate={:.1}% rtt={}",
+            self.frames,
+            self.frame_errors,
+            self.crc_failures,
+            self.timeouts,
+            self.error_rate_pct,
+            self.rtt_ms
+                .map(|rtt| format!("{rtt}ms"))
+                .unwrap_or_else(|| "--".into())
+        )
+    }
+}
+
+/// How the current window looks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verdict {
+    /// Too few frames to judge, and no slow round trips.
+    Inconclusive,
+    Healthy,
+    /// The error rate is over the limit; a slower line may help.
+    Lossy,
+    /// Only the round-trip time is over the limit; a slower line would make it worse.
+    Slow,
+}
+
+/// One step back from a degraded link, tried in the order listed, or the undo of an earlier
+/// step once the link has recovered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Adaptation {
+    /// Fewer bytes on the wire per payload.
+    EnableCompression,
+    /// Smaller command and tunnel chunks, so a corrupted frame costs less to resend.
+    ShrinkChunks(usize),
+    /// Cap `negotiation.max_baud` at this rate and reconnect. Only for lossy links.
+    LowerBaud(u32),
+    /// Undo [`Adaptation::EnableCompression`].
+    DisableCompression,
+    /// Undo [`Adaptation::ShrinkChunks`]: back to this many bytes.
+    RestoreChunks(usize),
+    /// Undo [`Adaptation::LowerBaud`]: put `negotiation.max_baud` back and reconnect.
+    RestoreMaxBaud(Option<u32>),
+}
+
+impl Adaptation {
+    /// True for the undo steps taken after a run of healthy windows.
+    pub(crate) fn is_recovery(&self) -> bool {
+        matches!(
+            self,
+            Adaptation::DisableCompression
+                | Adaptation::RestoreChunks(_)
+                | Adaptation::RestoreMaxBaud(_)
+        )
+    }
+}
+
+impl fmt::Display for Adaptation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Adaptation::EnableCompression => f.write_str("enabling compression"),
+            Adaptation::ShrinkChunks(bytes) => write!(f, "shrinking chunks to {bytes} bytes"),
+            Adaptation::LowerBaud(baud) => write!(f, "lowering the link to {baud} baud"),
+            Adaptation::DisableCompression => f.write_str("disabling compression"),
+            Adaptation::RestoreChunks(bytes) => write!(f, "restoring chunks to {bytes} bytes"),
+            Adaptation::RestoreMaxBaud(Some(baud)) => {
+                write!(f, "raising the baud cap back to {baud}")
+            }
+            Adaptation::RestoreMaxBaud(None) => f.write_str("lifting the baud cap"),
+        }
+    }
+}
+
+/// What the current session still allows the monitor to change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AdaptOptions {
+    /// Compression is off and the link can renegotiate (not on a bus).
+    pub compression_available: bool,
+    pub chunk_bytes: usize,
+    /// Rate the port runs at now, after any negotiated upgrade.
+    pub link_baud: u32,
+    /// Configured rate both peers fall back to; never lowered below.
+    pub base_baud: u32,
+    /// `negotiation.max_baud` in effect, restored when a lowered baud is undone.
+    pub max_baud: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct StatusPage<'a> {
+    ts_ms: u128,
+    connected: bool,
+    link_baud: Option<u32>,
+    compression: bool,
+    chunk_bytes: usize,
+    window_ms: u64,
+    adapt: bool,
+    max_error_rate_pct: u8,
+    max_rtt_ms: Option<u64>,
+    recovery_windows: u32,
+    degraded: bool,
+    #[serde(flatten)]
+    quality: LinkQualitySnapshot,
+    latency: LatencySnapshot,
+    serial_watchdog_ms: u128,
+    tunnel_watchdog_ms: u128,
+    adaptations: &'a VecDeque<String>,
+}
+
+/// Session facts shown next to the counters on the status page.
+pub(crate) struct StatusContext {
+    pub link_baud: Option<u32>,
+    pub compression: bool,
+    pub chunk_bytes: usize,
+    /// Ping/pong measurements since the daemon started.
+    pub latency: LatencySnapshot,
+    /// Serial and tunnel watchdog expiries in effect, scaled to the RTT once measured.
+    pub watchdog_timeouts: (Duration, Duration),
+}
+
+pub(crate) struct LinkQualityMonitor {
+    config: LinkQualityConfig,
+    events: VecDeque<(Instant, LinkEvent, u64)>,
+    rtts: VecDeque<(Instant, Duration)>,
+    reliable_seen: ReliableStats,
+    last_adaptation: Option<Instant>,
+    /// Inverse of every step taken, most recent last.
+    undo: Vec<Adaptation>,
+    /// Start of the current unbroken run of healthy windows.
+    healthy_since: Option<Instant>,
+    adaptations: VecDeque<String>,
+    status_path: PathBuf,
+    next_status_write: Instant,
+}
+
+impl LinkQualityMonitor {
+    pub(crate) fn new(config: LinkQualityConfig) -> Self {
+        Self {
+            config,
+            events: VecDeque::new(),
+            rtts: VecDeque::new(),
+            reliable_seen: ReliableStats::default(),
+            last_adaptation: None,
+            undo: Vec::new(),
+            healthy_since: None,
+            adaptations: VecDeque::new(),
+            status_path: PathBuf::from(CACHE_DIR).join(STATUS_FILE_NAME),
+            next_status_write: Instant::now(),
+        }
+    }
+
+    pub(crate) fn set_config(&mut self, config: LinkQualityConfig) {
+        self.config = config;
+    }
+
+    pub(crate) fn record(&mut self, event: LinkEvent, now: Instant) {
+        self.record_many(event, 1, now);
+    }
+
+    fn record_many(&mut self, event: LinkEvent, count: u64, now: Instant) {
+        if count > 0 {
+            self.events.push_back((now, event, count));
+        }
+    }
+
+    pub(crate) fn record_rtt(&mut self, rtt: Duration, now: Instant) {
+        self.rtts.push_back((now, rtt));
+    }
+
+    /// A new link starts its reliable-delivery counters from zero.
+    pub(crate) fn reset_link(&mut self) {
+        self.reliable_seen = ReliableStats::default();
+    }
+
+    /// Count what the reliable layer absorbed since the last call: corrupt frames it dropped
+    /// and retransmissions its timer forced.
+    pub(crate) fn sync_reliable(&mut self, stats: &ReliableStats, now: Instant) {
+        let corrupt = stats
+            .corrupt_dropped
+            .saturating_sub(self.reliable_seen.corrupt_dropped);
+        let retransmits = stats
+            .retransmits
+            .saturating_sub(self.reliable_seen.retransmits);
+        self.record_many(LinkEvent::CrcFailure, corrupt, now);
+        self.record_many(LinkEvent::Timeout, retransmits, now);
+        self.reliable_seen = *stats;
+    }
+
+    pub(crate) fn snapshot(&mut self, now: Instant) -> LinkQualitySnapshot {
+        self.prune(now);
+        let mut snapshot = LinkQualitySnapshot::default();
+        for &(_, event, count) in &self.events {
+            match event {
+                LinkEvent::Frame => snapshot.frames += count,
+                LinkEvent::FrameError => snapshot.frame_errors += count,
+                LinkEvent::CrcFailure => snapshot.crc_failures += count,
+                LinkEvent::Timeout => snapshot.timeouts += count,
+            }
+        }
+        let total = snapshot.frames + snapshot.failures();
+        if total > 0 {
+            snapshot.error_rate_pct = snapshot.failures() as f64 * 100.0 / total as f64;
+        }
+        if !self.rtts.is_empty() {
+            let sum: Duration = self.rtts.iter().map(|(_, rtt)| *rtt).sum();
+            snapshot.rtt_ms = Some((sum / self.rtts.len() as u32).as_millis() as u64);
+        }
+        snapshot
+    }
+
+    fn verdict(&self, snapshot: &LinkQualitySnapshot) -> Verdict {
+        let enough_frames = snapshot.frames + snapshot.failures() >= MIN_FRAMES_FOR_VERDICT;
+        let slow = matches!(
+            (snapshot.rtt_ms, self.config.max_rtt_ms),
+            (Some(rtt), Some(max)) if rtt > max
+        );
+        if enough_frames && snapshot.error_rate_pct > f64::from(self.config.max_error_rate_pct) {
+            Verdict::Lossy
+        } else if slow {
+            Verdict::Slow
+        } else if enough_frames {
+            Verdict::Healthy
+        } else {
+            Verdict::Inconclusive
+        }
+    }
+
+    fn is_degraded(&self, snapshot: &LinkQualitySnapshot) -> bool {
+        matches!(self.verdict(snapshot), Verdict::Lossy | Verdict::Slow)
+    }
+
+    /// Pick the next step back when the window shows a degraded link, or undo the latest
+    /// step once `recovery_windows` windows in a row were healthy. At most one change per
+    /// window; the counters restart afterwards so the next verdict measures the new settings,
+    /// and the returned snapshot is the one that triggered the change.
+    pub(crate) fn check(
+        &mut self,
+        now: Instant,
+        options: &AdaptOptions,
+    ) -> Option<(Adaptation, LinkQualitySnapshot)> {
+        if !self.config.adapt {
+            return None;
+        }
+        let window = Duration::from_millis(self.config.window_ms);
+        if self
+            .last_adaptation
+            .is_some_and(|at| now.duration_since(at) < window)
+        {
+            return None;
+        }
+        let snapshot = self.snapshot(now);
+        let adaptation = match self.verdict(&snapshot) {
+            Verdict::Inconclusive => return None,
+            Verdict::Healthy => {
+                let since = *self.healthy_since.get_or_insert(now);
+                let needed = window * self.config.recovery_windows;
+                if self.config.recovery_windows == 0 || now.duration_since(since) < needed {
+                    return None;
+                }
+                self.undo.pop()?
+            }
+            verdict => {
+                self.healthy_since = None;
+                let (step, undo) = step_back(options, verdict == Verdict::Lossy)?;
+                self.undo.push(undo);
+                step
+            }
+        };
+        self.healthy_since = None;
+        self.last_adaptation = Some(now);
+        self.events.clear();
+        self.rtts.clear();
+        if self.adaptations.len() == ADAPTATION_HISTORY {
+            self.adaptations.pop_front();
+        }
+        self.adaptations.push_back(format!(
+            "{}: {adaptation} ({})",
+            unix_ms(),
+            snapshot.summary()
+        ));
+        Some((adaptation, snapshot))
+    }
+
+    /// Rewrite the status page every few seconds; a read-only cache dir only costs the page.
+    pub(crate) fn write_status(&mut self, now: Instant, context: &StatusContext) {
+        if now < self.next_status_write {
+            return;
+        }
+        self.next_status_write = now + Duration::from_millis(STATUS_WRITE_INTERVAL_MS);
+        let quality = self.snapshot(now);
+        let page = StatusPage {
+            ts_ms: unix_ms(),
+            connected: context.link_baud.is_some(),
+            link_baud: context.link_baud,
+            compression: context.compression,
+            chunk_bytes: context.chunk_bytes,
+            window_ms: self.config.window_ms,
+            adapt: self.config.adapt,
+            max_error_rate_pct: self.config.max_error_rate_pct,
+            max_rtt_ms: self.config.max_rtt_ms,
+            recovery_windows: self.config.recovery_windows,
+            degraded: self.is_degraded(&quality),
+            quality,
+            latency: context.latency,
+            serial_watchdog_ms: context.watchdog_timeouts.0.as_millis(),
+            tunnel_watchdog_ms: context.watchdog_timeouts.1.as_millis(),
+            adaptations: &self.adaptations,
+        };
+        if let Ok(json) = serde_json::to_string_pretty(&page) {
+            if let Some(parent) = self.status_path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            let _ = fs::write(&self.status_path, json);
+        }
+    }
+
+    fn prune(&mut self, now: Instant) {
+        let window = Duration::from_millis(self.config.window_ms);
+        let expired = |at: Instant| now.duration_since(at) > window;
+        while self.events.front().is_some_and(|(at, _, _)| expired(*at)) {
+            self.events.pop_front();
+        }
+        while self.rtts.front().is_some_and(|(at, _)| expired(*at)) {
+            self.rtts.pop_front();
+        }
+    }
+}
+
+/// The next step back for a degraded link and the step that undoes it. Lowering the baud
+/// only helps a lossy line; round trips over a slower line just get longer.
+fn step_back(options: &AdaptOptions, lossy: bool) -> Option<(Adaptation, Adaptation)> {
+    if options.compression_available {
+        return Some((
+            Adaptation::EnableCompression,
+            Adaptation::DisableCompression,
+        ));
+    }
+    if options.chunk_bytes > MIN_CHUNK_BYTES {
+        return Some((
+            Adaptation::ShrinkChunks((options.chunk_bytes / 2).max(MIN_CHUNK_BYTES)),
+            Adaptation::RestoreChunks(options.chunk_bytes),
+        ));
+    }
+    if !lossy {
+        return None;
+    }
+    let lower = crate::serial::STANDARD_BAUD_RATES
+        .iter()
+        .rev()
+        .copied()
+        .find(|baud| *baud < options.link_baud && *baud >= options.base_baud)?;
+    Some((
+        Adaptation::LowerBaud(lower),
+        Adaptation::RestoreMaxBaud(options.max_baud),
+    ))
+}
+
+fn unix_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adaptive() -> LinkQualityMonitor {
+        LinkQualityMonitor::new(LinkQualityConfig {
+            adapt: true,
+            window_ms: 10_000,
+            max_error_rate_pct: 10,
+            max_rtt_ms: Some(200),
+            recovery_windows: 2,
+        })
+    }
+
+    fn options() -> AdaptOptions {
+        AdaptOptions {
+            compression_available: true,
+            chunk_bytes: 512,
+            link_baud: 115_200,
+            base_baud: 9_600,
+            max_baud: None,
+        }
+    }
+
+    /// One window's worth of traffic with `failures` CRC errors out of 20 lines.
+    fn traffic(monitor: &mut LinkQualityMonitor, failures: usize, now: Instant) {
+        for _ in failures..20 {
+            monitor.record(LinkEvent::Frame, now);
+        }
+        for _ in 0..failures {
+            monitor.record(LinkEvent::CrcFailure, now);
+        }
+    }
+
+    fn apply(opts: &mut AdaptOptions, step: Adaptation) {
+        match step {
+            Adaptation::EnableCompression => opts.compression_available = false,
+            Adaptation::DisableCompression => opts.compression_available = true,
+            Adaptation::ShrinkChunks(bytes) | Adaptation::RestoreChunks(bytes) => {
+                opts.chunk_bytes = bytes
+            }
+            Adaptation::LowerBaud(baud) => {
+                opts.link_baud = baud;
+                opts.max_baud = Some(baud);
+            }
+            Adaptation::RestoreMaxBaud(cap) => {
+                opts.link_baud = cap.unwrap_or(115_200);
+                opts.max_baud = cap;
+            }
+        }
+    }
+
+    #[test]
+    fn snapshot_counts_window_and_reliable_deltas() {
+        let mut monitor = adaptive();
+        let start = Instant::now();
+        for _ in 0..18 {
+            monitor.record(LinkEvent::Frame, start);
+        }
+        monitor.record(LinkEvent::FrameError, start);
+        let stats = ReliableStats {
+            corrupt_dropped: 1,
+            retransmits: 2,
+            ..ReliableStats::default()
+        };
+        monitor.sync_reliable(&stats, start);
+        monitor.sync_reliable(&stats, start);
+        monitor.record_rtt(Duration::from_millis(40), start);
+        monitor.record_rtt(Duration::from_millis(60), start);
+
+        let snapshot = monitor.snapshot(start);
+        assert_eq!(snapshot.frames, 18);
+        assert_eq!(snapshot.crc_failures, 1);
+        assert_eq!(snapshot.timeouts, 2);
+        assert!((snapshot.error_rate_pct - 18.181).abs() < 0.01);
+        assert_eq!(snapshot.rtt_ms, Some(50));
+
+        let later = monitor.snapshot(start + Duration::from_millis(10_001));
+        assert_eq!(later, LinkQualitySnapshot::default());
+    }
+
+    #[test]
+    fn adapts_one_step_per_window_in_order() {
+        let mut monitor = adaptive();
+        let mut now = Instant::now();
+        let mut opts = options();
+        let mut steps = Vec::new();
+        for _ in 0..6 {
+            for _ in 0..15 {
+                monitor.record(LinkEvent::Frame, now);
+            }
+            for _ in 0..5 {
+                monitor.record(LinkEvent::CrcFailure, now);
+            }
+            let (step, trigger) = monitor.check(now, &opts).expect("degraded link adapts");
+            assert_eq!(trigger.crc_failures, 5);
+            assert_eq!(monitor.check(now, &opts), None, "cooldown");
+            apply(&mut opts, step);
+            steps.push(step);
+            now += Duration::from_millis(10_000);
+        }
+        assert_eq!(
+            steps,
+            vec![
+                Adaptation::EnableCompression,
+                Adaptation::ShrinkChunks(256),
+                Adaptation::ShrinkChunks(128),
+                Adaptation::ShrinkChunks(64),
+                Adaptation::LowerBaud(57_600),
+                Adaptation::LowerBaud(38_400),
+            ]
+        );
+    }
+
+    #[test]
+    fn healthy_sparse_or_report_only_links_are_left_alone() {
+        let mut monitor = adaptive();
+        let now = Instant::now();
+        for _ in 0..5 {
+            monitor.record(LinkEvent::FrameError, now);
+        }
+        assert_eq!(monitor.check(now, &options()), None, "too few frames");
+
+        monitor.record_rtt(Duration::from_millis(350), now);
+        assert_eq!(
+            monitor.check(now, &options()).map(|(step, _)| step),
+            Some(Adaptation::EnableCompression),
+            "slow round trips count on their own"
+        );
+
+        let mut report_only = LinkQualityMonitor::new(LinkQualityConfig::default());
+        for _ in 0..40 {
+            report_only.record(LinkEvent::Timeout, now);
+        }
+        assert_eq!(report_only.check(now, &options()), None);
+
+        let mut at_base = adaptive();
+        for _ in 0..40 {
+            at_base.record(LinkEvent::Timeout, now);
+        }
+        let exhausted = AdaptOptions {
+            compression_available: false,
+            chunk_bytes: MIN_CHUNK_BYTES,
+            link_baud: 9_600,
+            base_baud: 9_600,
+            max_baud: None,
+        };
+        assert_eq!(at_base.check(now, &exhausted), None);
+    }
+
+    #[test]
+    fn slow_round_trips_never_lower_the_baud() {
+        let mut monitor = adaptive();
+        let now = Instant::now();
+        traffic(&mut monitor, 0, now);
+        monitor.record_rtt(Duration::from_millis(350), now);
+        let tuned = AdaptOptions {
+            compression_available: false,
+            chunk_bytes: MIN_CHUNK_BYTES,
+            ..options()
+        };
+        assert_eq!(monitor.check(now, &tuned), None);
+
+        traffic(&mut monitor, 10, now);
+        assert_eq!(
+            monitor.check(now, &tuned).map(|(step, _)| step),
+            Some(Adaptation::LowerBaud(57_600)),
+            "a lossy window still may"
+        );
+    }
+
+    #[test]
+    fn healthy_windows_undo_adaptations_newest_first() {
+        let mut monitor = adaptive();
+        let mut now = Instant::now();
+        let window = Duration::from_millis(10_000);
+        let mut opts = AdaptOptions {
+            chunk_bytes: 128,
+            ..options()
+        };
+        let mut steps = Vec::new();
+        for _ in 0..4 {
+            traffic(&mut monitor, 5, now);
+            let (step, _) = monitor.check(now, &opts).expect("lossy link adapts");
+            apply(&mut opts, step);
+            steps.push(step);
+            now += window;
+        }
+        assert_eq!(opts.max_baud, Some(38_400));
+
+        // Two healthy windows in a row per undo; a bad window restarts the count.
+        for _ in 0..12 {
+            traffic(&mut monitor, 0, now);
+            if let Some((step, trigger)) = monitor.check(now, &opts) {
+                assert!(step.is_recovery(), "{step}");
+                assert_eq!(trigger.crc_failures, 0);
+                apply(&mut opts, step);
+                steps.push(step);
+            }
+            now += window;
+        }
+        assert_eq!(
+            steps,
+            vec![
+                Adaptation::EnableCompression,
+                Adaptation::ShrinkChunks(64),
+                Adaptation::LowerBaud(57_600),
+                Adaptation::LowerBaud(38_400),
+                Adaptation::RestoreMaxBaud(Some(57_600)),
+                Adaptation::RestoreMaxBaud(None),
+                Adaptation::RestoreChunks(128),
+                Adaptation::DisableCompression,
+            ]
+        );
+        assert_eq!(
+            opts,
+            AdaptOptions {
+                chunk_bytes: 128,
+                ..options()
+            }
+        );
+        assert_eq!(monitor.check(now, &opts), None, "nothing left to undo");
+    }
+}
@@ -1,44 +1,71 @@
 use crate::{
-    cli::{RunMode, RunOptions},
+    cli::{DisplayBackend, RunMode, RunOptions},
     compression::CompressionCodec,
     config::Pcf8574Addr,
     config::{
-        Config, DisplayDriver, NegotiationConfig, DEFAULT_BAUD, DEFAULT_COLS, DEFAULT_DEVICE,
-        DEFAULT_ROWS, DEFAULT_SERIAL_TIMEOUT_MS,
+        BusConfig, Config, DisplayConfig, DisplayDriver, LinkQualityConfig, NegotiationConfig,
+        DEFAULT_BAUD, DEFAULT_COLS, DEFAULT_DEVICE, DEFAULT_ROWS, DEFAULT_SERIAL_TIMEOUT_MS,
+    },
+    display::{
+        backlight::RgbBacklightConfig,
+        capture::{self, FrameCapture, FrameRecorder},
     },
     lcd::Lcd,
+    lcd::LcdWiring,
+    lcd_driver::{
+        backpack::{Backpack, PinMap},
+        parallel::ParallelPins,
+    },
     payload::{CompressionPolicy, Defaults as PayloadDefaults, RenderFrame},
-    serial::{DtrBehavior, FlowControlMode, ParityMode, SerialOptions, StopBitsMode},
-    Result,
+    serial::{
+        discovery::DeviceSelector, DtrBehavior, FlowControlMode, ParityMode, SerialOptions,
+        SerialPort, StopBitsMode,
+    },
+    Error, Result,
 };
 use std::{fs, path::Path, str::FromStr, time::Instant};
 
+mod audit;
+mod autobaud;
+mod backlight;
 mod connection;
 mod demo;
+mod displays;
 mod events;
+mod file_transfer;
+pub mod forward;
+mod identity;
 mod input;
+pub mod latency;
 mod lifecycle;
+mod line_editor;
+mod link_quality;
 mod logger;
 mod negotiation;
 mod polling;
 mod render_loop;
 pub mod serial_shell;
 mod tunnel;
-mod watchdog;
+pub mod watchdog;
 mod wizard;
 
+use crate::display::charset::Charset;
 use crate::display::overlays::{render_frame_once, render_reconnecting};
 use crate::serial::backoff::BackoffController;
-use connection::attempt_serial_connect;
+use connection::{attempt_serial_connect, lookup_device, resolve_device};
 use demo::run_demo;
+use displays::Displays;
+use identity::NodeIdentity;
 pub(crate) use logger::{LogLevel, Logger};
 use negotiation::NegotiationLog;
-use render_loop::run_render_loop;
+use render_loop::{compression_policy_from_config, run_render_loop};
 
 /// Config for the daemon.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AppConfig {
     pub device: String,
+    /// Re-resolved on every connect attempt; `--device` on the command line disables it.
+    pub device_match: Option<DeviceSelector>,
     pub baud: u32,
     pub flow_control: FlowControlMode,
     pub parity: ParityMode,
@@ -57,23 +84,43 @@ pub struct AppConfig {
     pub backoff_max_ms: u64,
     pub negotiation: NegotiationConfig,
     pub pcf8574_addr: Pcf8574Addr,
+    pub i2c_bus: Option<u8>,
     pub display_driver: DisplayDriver,
+    pub gpio_pins: Option<ParallelPins>,
+    pub lcd_backpack: Backpack,
+    pub lcd_pin_map: Option<PinMap>,
+    pub backlight_rgb: Option<RgbBacklightConfig>,
+    pub charset: Charset,
     pub lcd_present: bool,
+    pub display_backend: DisplayBackend,
+    pub record_path: Option<String>,
+    pub screenshot_path: Option<String>,
     pub log_level: LogLevel,
     pub log_file: Option<String>,
     pub demo: bool,
     pub command_allowlist: Vec<String>,
+    pub forward_allowlist: Vec<String>,
     pub serialsh: bool,
     pub protocol_schema_version: u8,
     pub compression_enabled: bool,
     pub compression_codec: CompressionCodec,
     pub watchdog: crate::config::WatchdogConfig,
+    pub backlight: crate::config::BacklightConfig,
+    pub bus: BusConfig,
+    pub link_quality: LinkQualityConfig,
+    /// Detect line settings before the first connect (`--autobaud`).
+    pub autobaud: bool,
+    /// Persist what `autobaud` found (`--autobaud-save`).
+    pub autobaud_save: bool,
+    /// Extra displays from `[display.<name>]` config sections.
+    pub displays: Vec<DisplayConfig>,
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             device: DEFAULT_DEVICE.to_string(),
+            device_match: None,
             baud: DEFAULT_BAUD,
             flow_control: FlowControlMode::default(),
             parity: ParityMode::default(),
@@ -92,17 +139,33 @@ impl Default for AppConfig {
             backoff_max_ms: crate::config::DEFAULT_BACKOFF_MAX_MS,
             negotiation: NegotiationConfig::default(),
             pcf8574_addr: crate::config::DEFAULT_PCF8574_ADDR,
+            i2c_bus: None,
             display_driver: crate::config::DEFAULT_DISPLAY_DRIVER,
+            gpio_pins: None,
+            lcd_backpack: Backpack::default(),
+            lcd_pin_map: None,
+            backlight_rgb: None,
+            charset: Charset::default(),
             lcd_present: crate::config::DEFAULT_LCD_PRESENT,
+            display_backend: DisplayBackend::default(),
+            record_path: None,
+            screenshot_path: None,
             log_level: LogLevel::default(),
             log_file: None,
             demo: false,
             command_allowlist: Vec::new(),
+            forward_allowlist: Vec::new(),
             serialsh: false,
             protocol_schema_version: crate::config::DEFAULT_PROTOCOL_SCHEMA_VERSION,
             compression_enabled: crate::config::DEFAULT_PROTOCOL_COMPRESSION_ENABLED,
             compression_codec: crate::config::DEFAULT_PROTOCOL_COMPRESSION_CODEC,
             watchdog: crate::config::WatchdogConfig::default(),
+            backlight: crate::config::BacklightConfig::default(),
+            bus: BusConfig::default(),
+            link_quality: LinkQualityConfig::default(),
+            autobaud: false,
+            autobaud_save: false,
+            displays: Vec::new(),
         }
     }
 }
@@ -134,21 +197,24 @@ impl App {
     pub fn run(&self) -> Result<()> {
         let mut config = self.config.clone();
 
-        let mut lcd = if config.lcd_present {
-            Lcd::new(
-                config.cols,
-                config.rows,
-                config.pcf8574_addr.clone(),
-                config.display_driver,
-            )?
-        } else {
-            Lcd::new_stub(config.cols, config.rows)
-        };
+        let mut lcd = open_lcd(&config, config.cols, config.rows, &config.lcd_wiring())?;
+        attach_capture(&mut lcd, &config, None)?;
+        lcd.set_charset(config.charset);
         lcd.render_boot_message()?;
+        let mut extra_lcds = open_extra_displays(&config)?;
         self.logger.info(format!(
             "daemon start (device={}, baud={}, cols={}, rows={})",
             config.device, config.baud, config.cols, config.rows
         ));
+        for display in &config.displays {
+            self.logger.info(format!(
+                "extra display '{}' ({}x{}, driver={})",
+                display.name,
+                display.cols,
+                display.rows,
+                crate::config::format_display_driver(&display.display_driver)
+            ));
+        }
 
         if config.demo {
             self.logger
@@ -172,9 +238,18 @@ impl App {
                     CompressionPolicy::disabled()
                 },
             )?;
-            lcd.set_backlight(frame.backlight_on)?;
-            lcd.set_blink(frame.blink)?;
-            return render_frame_once(&mut lcd, &frame);
+            let target = match frame.display.as_deref() {
+                None | Some(crate::config::PRIMARY_DISPLAY_NAME) => &mut lcd,
+                Some(name) => extra_lcds
+                    .iter_mut()
+                    .find(|(extra, _)| extra == name)
+                    .map(|(_, lcd)| lcd)
+                    .ok_or_else(|| crate::Error::Parse(format!("unknown display '{name}'")))?,
+            };
+            target.set_backlight(frame.backlight_on)?;
+            target.set_backlight_color(frame.backlight_color.unwrap_or_default())?;
+            target.set_blink(frame.blink)?;
+            return render_frame_once(target, &frame);
         }
 
         let mut negotiation_log = NegotiationLog::try_create().unwrap_or_else(|err| {
@@ -183,52 +258,154 @@ impl App {
             NegotiationLog::disabled()
         });
 
-        let (serial_connection, initial_disconnect_reason, supports_heartbeat) =
-            match attempt_serial_connect(
-                &self.logger,
-                &config.device,
-                config.serial_options(),
-                &config.negotiation,
-                config.compression_enabled,
-                &mut negotiation_log,
-            ) {
-                Ok(outcome) => (
-                    Some(outcome.port),
-                    None,
-                    outcome
-                        .remote_caps
-                        .as_ref()
-                        .map(|caps| caps.supports_heartbeat)
-                        .unwrap_or(false),
-                ),
-                Err(reason) => (None, Some(reason), false),
-            };
-        if serial_connection.is_none() {
+        let node_name = config.negotiation.node_name.clone();
+        let identity = NodeIdentity::load_or_create(node_name.as_deref()).unwrap_or_else(|err| {
+            self.logger
+                .warn(format!("node identity not persisted: {err}"));
+            NodeIdentity::ephemeral(node_name.as_deref())
+        });
+        negotiation_log.record(format!(
+            "negotiation: local name={} uuid={}",
+            identity.name, identity.uuid
+        ));
+
+        if config.autobaud {
+            self.detect_line_settings(&mut config);
+        }
+
+        let initial = attempt_serial_connect(
+            &self.logger,
+            &config.device,
+            config.device_match.as_ref(),
+            config.serial_options(),
+            &config.negotiation,
+            &identity,
+            config.compression_enabled,
+            &config.bus,
+            &mut negotiation_log,
+        );
+        let mut displays = Displays::new(
+            lcd,
+            extra_lcds,
+            PayloadDefaults {
+                scroll_speed_ms: config.scroll_speed_ms,
+                page_timeout_ms: config.page_timeout_ms,
+            },
+            compression_policy_from_config(&config),
+        );
+        if initial.is_err() {
             let now = Instant::now();
             backoff.mark_failure(now);
-            render_reconnecting(&mut lcd, config.cols)?;
+            for slot in displays.iter_mut() {
+                let cols = slot.lcd.cols();
+                render_reconnecting(&mut slot.lcd, cols)?;
+            }
         }
 
         run_render_loop(
-            &mut lcd,
+            &mut displays,
             &mut config,
             &self.logger,
             backoff,
-            serial_connection,
-            initial_disconnect_reason,
-            supports_heartbeat,
+            initial,
+            &identity,
             &mut negotiation_log,
         )
     }
 
+    /// `--autobaud`: scan line settings on the resolved device and keep the best for this run.
+    fn detect_line_settings(&self, config: &mut AppConfig) {
+        // resolve_device already logged why nothing matched; the normal connect path retries.
+        let Ok(device) = resolve_device(&config.device, config.device_match.as_ref(), &self.logger)
+        else {
+            return;
+        };
+        let detected = autobaud::run_autobaud(config, &device, &self.logger, SerialPort::connect);
+        if let (Some(settings), true) = (detected, config.autobaud_save) {
+            match autobaud::persist(settings) {
+                Ok(()) => self
+                    .logger
+                    .info(format!("autobaud: saved {settings} to config.toml")),
+                Err(err) => self
+                    .logger
+                    .warn(format!("autobaud: could not save {settings}: {err}")),
+            }
+        }
+    }
+
     pub fn config(&self) -> &AppConfig {
         &self.config
     }
 }
 
+/// Open one panel on the selected backend: the terminal simulator, the wired hardware, or a
+/// silent stub when `lcd_present = false`.
+fn open_lcd(config: &AppConfig, cols: u8, rows: u8, wiring: &LcdWiring) -> Result<Lcd> {
+    match config.display_backend {
+        DisplayBackend::Virtual => Lcd::new_virtual(cols, rows),
+        DisplayBackend::Hardware if config.lcd_present => Lcd::new(cols, rows, wiring),
+        DisplayBackend::Hardware | DisplayBackend::Headless => Ok(Lcd::new_stub(cols, rows)),
+    }
+}
+
+/// Hook `--record`/`--screenshot` up to a panel; extra displays write next to the primary's
+/// files with their name appended.
+fn attach_capture(lcd: &mut Lcd, config: &AppConfig, display: Option<&str>) -> Result<()> {
+    if config.record_path.is_none() && config.screenshot_path.is_none() {
+        return Ok(());
+    }
+    let path_for = |raw: &String| {
+        let path = Path::new(raw);
+        match display {
+            Some(name) => capture::path_for_display(path, name),
+            None => path.to_path_buf(),
+        }
+    };
+    let recorder = config
+        .record_path
+        .as_ref()
+        .map(|raw| FrameRecorder::create(&path_for(raw), lcd.cols(), lcd.rows()))
+        .transpose()?;
+    let screenshot = config.screenshot_path.as_ref().map(path_for);
+    lcd.set_capture(FrameCapture::new(recorder, screenshot));
+    Ok(())
+}
+
+/// Open every `[display.<name>]` LCD (stubs when no hardware is present) and show the boot
+/// message on each.
+fn open_extra_displays(config: &AppConfig) -> Result<Vec<(String, Lcd)>> {
+    config
+        .displays
+        .iter()
+        .map(|display| {
+            let mut lcd = open_lcd(config, display.cols, display.rows, &display.lcd_wiring())?;
+            attach_capture(&mut lcd, config, Some(&display.name))?;
+            lcd.set_charset(config.charset);
+            lcd.render_boot_message()?;
+            Ok((display.name.clone(), lcd))
+        })
+        .collect()
+}
+
 impl AppConfig {
+    pub fn lcd_wiring(&self) -> LcdWiring {
+        LcdWiring {
+            pcf8574_addr: self.pcf8574_addr.clone(),
+            display_driver: self.display_driver,
+            backpack: self.lcd_backpack,
+            pin_map: self.lcd_pin_map,
+            gpio_pins: self.gpio_pins.clone(),
+            rgb_backlight: self.backlight_rgb.clone(),
+            i2c_bus: self.i2c_bus,
+        }
+    }
+
     pub fn from_sources(config: Config, opts: RunOptions) -> Self {
         Self {
+            device_match: config
+                .device_match
+                .clone()
+                .filter(|_| opts.device.is_none()),
             device: opts.device.unwrap_or_else(|| config.device.clone()),
             baud: opts.baud.unwrap_or(config.baud),
             flow_control: opts.flow_control.unwrap_or(config.flow_control),
@@ -250,8 +427,17 @@ impl AppConfig {
             pcf8574_addr: opts
                 .pcf8574_addr
                 .unwrap_or_else(|| config.pcf8574_addr.clone()),
+            i2c_bus: config.i2c_bus,
             display_driver: config.display_driver,
+            gpio_pins: config.gpio_pins.clone(),
+            lcd_backpack: config.lcd_backpack,
+            lcd_pin_map: config.lcd_pin_map,
+            backlight_rgb: config.backlight_rgb.clone(),
+            charset: Charset::new(config.character_rom, config.replacement_char),
             lcd_present: config.lcd_present,
+            display_backend: opts.display_backend.unwrap_or_default(),
+            record_path: opts.record_path.clone(),
+            screenshot_path: opts.screenshot_path.clone(),
             log_level: opts
                 .log_level
                 .as_deref()
@@ -260,6 +446,7 @@ impl AppConfig {
             log_file: opts.log_file,
             demo: opts.demo,
             command_allowlist: config.command_allowlist.clone(),
+            forward_allowlist: config.forward_allowlist.clone(),
             serialsh: matches!(opts.mode, RunMode::SerialShell),
             protocol_schema_version: config.protocol.schema_version,
             compression_enabled: opts
@@ -269,6 +456,26 @@ impl AppConfig {
                 .compression_codec
                 .unwrap_or(config.protocol.compression_codec),
             watchdog: config.watchdog,
+            backlight: config.backlight.clone(),
+            bus: config.bus.clone(),
+            link_quality: config.link_quality,
+            autobaud: opts.autobaud,
+            autobaud_save: opts.autobaud_save,
+            displays: config.displays.clone(),
+        }
+    }
+
+    /// Port for one-shot tools (`--serialsh`, `--forward`), which report a `device_match`
+    /// that finds nothing instead of waiting for the adapter.
+    pub fn resolved_device(&self) -> Result<String> {
+        lookup_device(&self.device, self.device_match.as_ref()).map_err(Error::InvalidArgs)
+    }
+
+    /// The configured port for logs: the `device_match` selector when set, else the path.
+    pub fn device_label(&self) -> String {
+        match &self.device_match {
+            Some(selector) => format!("match:{selector}"),
+            None => self.device.clone(),
         }
     }
 
@@ -280,6 +487,7 @@ impl AppConfig {
             parity: self.parity,
             stop_bits: self.stop_bits,
             dtr: self.dtr_on_open,
+            rts_driver_enable: self.bus.is_enabled() && self.bus.rts_driver_enable,
         }
     }
 }
@@ -338,6 +546,7 @@ mod tests {
     fn config_prefers_file_values_when_cli_missing() {
         let cfg_file = Config {
             device: "/dev/ttyS0".into(),
+            device_match: None,
             baud: 9_600,
             flow_control: FlowControlMode::default(),
             parity: ParityMode::default(),
@@ -355,11 +564,23 @@ mod tests {
             backoff_initial_ms: crate::config::DEFAULT_BACKOFF_INITIAL_MS,
             backoff_max_ms: crate::config::DEFAULT_BACKOFF_MAX_MS,
             pcf8574_addr: crate::config::DEFAULT_PCF8574_ADDR,
+            i2c_bus: None,
             display_driver: crate::config::DEFAULT_DISPLAY_DRIVER,
+            gpio_pins: None,
+            lcd_backpack: Backpack::default(),
+            lcd_pin_map: None,
+            backlight_rgb: None,
+            character_rom: crate::config::DEFAULT_CHARACTER_ROM,
+            replacement_char: crate::config::DEFAULT_REPLACEMENT_CHAR,
             lcd_present: crate::config::DEFAULT_LCD_PRESENT,
             command_allowlist: Vec::new(),
+            forward_allowlist: Vec::new(),
             protocol: crate::config::ProtocolConfig::default(),
             watchdog: crate::config::WatchdogConfig::default(),
+            backlight: crate::config::BacklightConfig::default(),
+            bus: BusConfig::default(),
+            link_quality: crate::config::LinkQualityConfig::default(),
+            displays: Vec::new(),
         };
         let opts = RunOptions::default();
         let merged = AppConfig::from_sources(cfg_file.clone(), opts);
@@ -1,8 +1,11 @@
+use super::identity::NodeIdentity;
 use crate::{
     config::NegotiationConfig,
     negotiation::{
-        Capabilities, ControlCaps, ControlFrame, Role, RolePreference, PROTOCOL_VERSION,
+        Capabilities, ControlCaps, ControlFrame, Role, RolePreference, MIN_PROTOCOL_VERSION,
+        PROTOCOL_VERSION,
     },
+    serial::STANDARD_BAUD_RATES,
     CACHE_DIR,
 };
 use std::{
@@ -18,6 +21,8 @@ pub struct Negotiator {
     local_caps: Capabilities,
     preference: RolePreference,
     node_id: u32,
+    bauds: Vec<u32>,
+    identity: Option<NodeIdentity>,
 }
 
 impl Negotiator {
@@ -27,23 +32,63 @@ impl Negotiator {
                 supports_tunnel: true,
                 supports_compression: compression_enabled,
                 supports_heartbeat: true,
+                supports_reliable: config.reliable_delivery,
+                supports_latency: true,
             },
             preference: config.preference,
             node_id: config.node_id,
+            bauds: Vec::new(),
+            identity: None,
         }
     }
 
+    /// Offer every standard rate from `current` up to `max_baud`; `None` keeps the link at
+    /// `current`.
+    pub fn with_baud_upgrade(mut self, current: u32, max_baud: Option<u32>) -> Self {
+        self.bauds = match max_baud {
+            Some(max) if max > current => std::iter::once(current)
+                .chain(
+                    STANDARD_BAUD_RATES
+                        .iter()
+                        .copied()
+                        .filter(|baud| *baud > current && *baud <= max),
+                )
+                .collect(),
+            _ => Vec::new(),
+        };
+        self
+    }
+
+    /// Introduce this node by name and UUID in `hello`.
+    pub(crate) fn with_identity(mut self, identity: &NodeIdentity) -> Self {
+        self.identity = Some(identity.clone());
+        self
+    }
+
+    pub fn bauds(&self) -> &[u32] {
+        &self.bauds
+    }
+
     pub fn hello_frame(&self) -> ControlFrame {
         ControlFrame::Hello {
             proto_version: PROTOCOL_VERSION,
+            proto_min: Some(MIN_PROTOCOL_VERSION),
             node_id: self.node_id,
             caps: ControlCaps {
                 bits: self.local_caps.bits(),
             },
             pref: self.preference.as_str().to_string(),
+            bauds: self.bauds.clone(),
+            node_name: self.identity.as_ref().map(|id| id.name.clone()),
+            node_uuid: self.identity.as_ref().map(|id| id.uuid.clone()),
         }
     }
 
+    /// Oldest and newest protocol versions this node speaks.
+    pub fn protocol_range(&self) -> (u8, u8) {
+        (MIN_PROTOCOL_VERSION, PROTOCOL_VERSION)
+    }
+
     pub fn local_caps(&self) -> &Capabilities {
         &self.local_caps
     }
@@ -10,10 +10,16 @@ use std::{
     time::{Duration, Instant},
 };
 
-use super::connection::attempt_serial_connect;
-use super::events::{CommandBridge, CommandEvent, CommandExecutor, ScrollOffsets};
+use super::audit::AuditLog;
+use super::backlight::BacklightPolicy;
+use super::connection::{attempt_serial_connect, renegotiate, ConnectOutcome};
+use super::displays::Displays;
+use super::events::{CommandBridge, CommandEvent, CommandExecutor, COMMAND_STREAM_CHUNK_SIZE};
+use super::identity::{NodeIdentity, PeerIdentity, PinCheck};
 use super::input::Button;
+use super::latency::LatencyTracker;
 use super::lifecycle::{create_shutdown_flag, render_shutdown};
+use super::link_quality::{AdaptOptions, Adaptation, LinkEvent, LinkQualityMonitor, StatusContext};
 use super::negotiation::NegotiationLog;
 use super::polling::{start_polling, PollEvent, PollSnapshot, PollingHandle};
 use super::tunnel::TunnelController;
@@ -22,22 +28,25 @@ use super::{AppConfig, LogLevel, Logger};
 use crate::{
     config::Config,
     display::{
-        icon_bank::{IconBank, IconPalette},
+        backlight::Rgb,
         overlays::{
-            advance_offset, line_needs_scroll, render_if_allowed, render_offline_message,
-            render_parse_error, render_reconnecting,
+            render_offline_message, render_parse_error, render_peer_status, render_reconnecting,
         },
     },
     lcd::Lcd,
+    negotiation::{ControlFrame, ProtocolVersion, Role},
     payload::{
-        decode_tunnel_frame, encode_command_frame, encode_tunnel_msg, CommandMessage,
-        CompressionPolicy, Defaults as PayloadDefaults, RenderFrame, TunnelMsgOwned,
+        decode_tunnel_frame, encode_command_frame_with_schema, encode_tunnel_msg, CommandMessage,
+        CompressionPolicy, Defaults as PayloadDefaults, TunnelMsgOwned,
     },
     serial::{
         backoff::BackoffController,
+        bus::BusLink,
         classify_io_error,
+        discovery::HotplugMonitor,
+        reliable::{ReliableConfig, ReliableLink},
         telemetry::{log_backoff_event, BackoffPhase},
-        SerialFailureKind, SerialPort,
+        LineIo, SerialFailureKind, SerialPort,
     },
     Error, Result, CACHE_DIR,
 };
@@ -49,7 +58,11 @@ const HEARTBEAT_BLINK_MS: u64 = 1_000;
 const HEARTBEAT_MIN_TX_MS: u64 = 500;
 const HEARTBEAT_INTERVAL_DIVISOR: u64 = 3;
 const POLLING_OVERLAY_MIN_INTERVAL_MS: u64 = 1_500;
+/// Line time per loop iteration granted to queued tunnel frames.
+const TUNNEL_FLUSH_SLICE_MS: u64 = 100;
 const PROTOCOL_ERROR_LOG_MAX_BYTES: u64 = 256 * 1024;
+/// Sent back when the peer asks a client to do server work.
+const CLIENT_ROLE_REFUSAL: &str = "this node is the client; commands run on the server";
 
 struct PollingState {
     handle: PollingHandle,
@@ -217,24 +230,6 @@ fn heartbeat_interval(timeout_ms: u64) -> Duration {
     Duration::from_millis(millis)
 }
 
-fn log_icon_fallbacks(logger: &Logger, palette: Option<IconPalette>) {
-    let Some(palette) = palette else {
-        return;
-    };
-    if palette.missing_icons.is_empty() {
-        return;
-    }
-    let joined = palette
-        .missing_icons
-        .iter()
-        .map(|icon| format!("{icon:?}"))
-        .collect::<Vec<_>>()
-        .join(", ");
-    logger.debug(format!(
-        "icon bank saturated; leaving blanks for missing icons [{joined}]"
-    ));
-}
-
 fn log_backoff(
     logger: &Logger,
     phase: BackoffPhase,
@@ -258,7 +253,7 @@ fn log_backoff(
     }
 }
 
-fn compression_policy_from_config(config: &AppConfig) -> CompressionPolicy {
+pub(super) fn compression_policy_from_config(config: &AppConfig) -> CompressionPolicy {
     if config.compression_enabled {
         CompressionPolicy::only(config.compression_codec)
     } else {
@@ -269,35 +264,53 @@ fn compression_policy_from_config(config: &AppConfig) -> CompressionPolicy {
 /// Drive the main render loop: reads serial, rotates pages, scrolls text, handles reconnects.
 #[allow(clippy::too_many_arguments)] // Wiring layer; keeping args explicit avoids hidden global state.
 pub(super) fn run_render_loop(
-    lcd: &mut Lcd,
+    displays: &mut Displays,
     config: &mut AppConfig,
     logger: &Logger,
     mut backoff: BackoffController,
-    mut serial_connection: Option<SerialPort>,
-    initial_disconnect_reason: Option<SerialFailureKind>,
-    mut supports_heartbeat: bool,
+    initial: std::result::Result<ConnectOutcome, SerialFailureKind>,
+    identity: &NodeIdentity,
     negotiation_log: &mut NegotiationLog,
 ) -> Result<()> {
-    let mut compression_policy = compression_policy_from_config(config);
-    let mut state = crate::state::RenderState::new_with_compression(
-        Some(PayloadDefaults {
-            scroll_speed_ms: config.scroll_speed_ms,
-            page_timeout_ms: config.page_timeout_ms,
-        }),
-        compression_policy,
-    );
-    let mut icon_bank = IconBank::new();
+    let mut backlight = BacklightPolicy::new(config.backlight.clone(), Instant::now());
+    let mut serial_connection: Option<ReliableLink<BusLink<SerialPort>>> = None;
+    let mut supports_heartbeat = false;
+    let mut supports_latency = false;
+    let mut protocol = ProtocolVersion::LEGACY;
+    let mut role = Role::Server;
+    // Rate the port runs at now; a baud upgrade raises it above `config.baud`.
+    let mut link_baud = config.baud;
+    let mut last_disconnect_reason = None;
+    match initial {
+        Ok(outcome) => {
+            show_peer_status(
+                displays,
+                outcome.peer.as_ref(),
+                outcome.role,
+                outcome.peer_alert.as_deref(),
+            )?;
+            if outcome.peer_alert.is_some() {
+                backlight.wake(Instant::now());
+            }
+            serial_connection = Some(outcome.port);
+            supports_heartbeat = outcome
+                .remote_caps
+                .as_ref()
+                .map(|caps| caps.supports_heartbeat)
+                .unwrap_or(false);
+            supports_latency = outcome
+                .remote_caps
+                .as_ref()
+                .is_some_and(|caps| caps.supports_latency);
+            protocol = outcome.protocol;
+            role = outcome.role;
+            link_baud = outcome.baud;
+        }
+        Err(reason) => last_disconnect_reason = Some(reason),
+    }
     let mut incoming_line = String::new();
-    let mut last_render = Instant::now();
-    let min_render_interval = Duration::from_millis(200);
-    let mut current_frame: Option<RenderFrame> = None;
-    let mut next_page = Instant::now();
-    let mut next_scroll = Instant::now();
-    let mut scroll_offsets = ScrollOffsets::zero();
+    let mut hotplug = HotplugMonitor::new();
     let mut button_input = Button::new(config.button_gpio_pin).ok();
-    let mut backlight_state = true;
-    let blink_interval = Duration::from_millis(500);
-    let mut next_blink = Instant::now();
     let mut reconnect_displayed = serial_connection.is_none();
     let mut last_frame_at = Instant::now();
     let heartbeat_grace = Duration::from_millis(HEARTBEAT_GRACE_MS);
@@ -306,16 +319,22 @@ pub(super) fn run_render_loop(
     let mut stats = LoopStats::default();
     let mut offline_displayed = false;
     let mut max_backoff_warned = false;
-    let mut last_disconnect_reason = initial_disconnect_reason;
     let mut serial_watchdog_active = false;
     let mut tunnel_watchdog_active = false;
-    let mut tunnel = TunnelController::new(config.command_allowlist.clone())?;
+    let mut tunnel = TunnelController::new(config.command_allowlist.clone())?
+        .with_forward_allowlist(config.forward_allowlist.clone());
     let mut command_bridge = CommandBridge::new();
-    let mut command_executor = CommandExecutor::new(config.command_allowlist.clone());
+    command_bridge.set_schema_version(protocol.command_schema());
+    displays.set_protocol(protocol);
+    let mut command_executor =
+        CommandExecutor::with_audit(config.command_allowlist.clone(), AuditLog::new(), "command");
     let protocol_errors = ProtocolErrorLog::new();
+    let mut link_quality = LinkQualityMonitor::new(config.link_quality);
+    let mut latency = LatencyTracker::new();
+    let mut chunk_bytes = COMMAND_STREAM_CHUNK_SIZE;
 
     if reconnect_displayed {
-        render_reconnecting(lcd, config.cols)?;
+        render_reconnecting_all(displays)?;
     }
 
     let running: Arc<AtomicBool> = create_shutdown_flag()?;
@@ -342,11 +361,70 @@ pub(super) fn run_render_loop(
         watchdog.touch_tunnel();
     }
 
+    // Set when the peer restarted (its `hello` is kept) or either side asked to renegotiate.
+    let mut pending_renegotiation: Option<Option<String>> = None;
+
     while running.load(Ordering::SeqCst) {
+        // Re-apply capabilities in place; the port and every display's page deck stay intact.
+        if let Some(trigger) = pending_renegotiation.take() {
+            if let Some(serial_ref) = serial_connection.as_mut() {
+                let session = renegotiate(
+                    serial_ref,
+                    logger,
+                    &config.negotiation,
+                    identity,
+                    config.compression_enabled,
+                    link_baud,
+                    trigger.as_deref(),
+                    negotiation_log,
+                );
+                protocol = session.protocol;
+                role = session.role;
+                supports_heartbeat = session
+                    .remote_caps
+                    .as_ref()
+                    .map(|caps| caps.supports_heartbeat)
+                    .unwrap_or(false);
+                supports_latency = session
+                    .remote_caps
+                    .as_ref()
+                    .is_some_and(|caps| caps.supports_latency);
+                command_bridge.set_schema_version(protocol.command_schema());
+                displays.set_protocol(protocol);
+                watchdog.touch_serial();
+                watchdog.touch_tunnel();
+                match session.pin {
+                    PinCheck::Accepted => {}
+                    PinCheck::Alert(reason) => {
+                        show_peer_status(displays, session.peer.as_ref(), role, Some(&reason))?;
+                        backlight.wake(Instant::now());
+                    }
+                    PinCheck::Refused(reason) => {
+                        logger.warn(format!("negotiation: dropping session: {reason}"));
+                        serial_connection = None;
+                        backoff.mark_failure(Instant::now());
+                        reconnect_displayed = false;
+                        last_disconnect_reason = Some(SerialFailureKind::PeerRejected);
+                    }
+                }
+            }
+        }
+
         if let Some(polling_state) = polling.as_mut() {
             while let Ok(event) = polling_state.handle.receiver().try_recv() {
                 match event {
                     PollEvent::Snapshot(snapshot) => {
+                        if role.originates_payloads() {
+                            if let Some(serial_ref) = serial_connection.as_mut() {
+                                forward_poll_snapshot(
+                                    serial_ref,
+                                    &snapshot,
+                                    config.cols,
+                                    protocol,
+                                    logger,
+                                );
+                            }
+                        }
                         polling_state.record_snapshot(snapshot, logger);
                     }
                     PollEvent::Error(err) => {
@@ -365,12 +443,19 @@ pub(super) fn run_render_loop(
                     send_command_frame(
                         serial_ref,
                         CommandMessage::Heartbeat { request_id: None },
+                        protocol.command_schema(),
                         logger,
                     );
                     next_serial_heartbeat = now + serial_heartbeat_interval;
                 }
                 if now >= next_tunnel_heartbeat {
-                    send_tunnel_frame(serial_ref, TunnelMsgOwned::Heartbeat, logger);
+                    // A ping doubles as the tunnel heartbeat for peers that answer it.
+                    let beat = if supports_latency {
+                        latency.ping(now)
+                    } else {
+                        TunnelMsgOwned::Heartbeat
+                    };
+                    send_tunnel_frame(serial_ref, beat, logger);
                     next_tunnel_heartbeat = now + tunnel_heartbeat_interval;
                 }
             }
@@ -379,8 +464,18 @@ pub(super) fn run_render_loop(
         // Track heartbeat visibility when frames stop arriving for a grace period.
         let current_time = Instant::now();
         if let Some(serial_ref) = serial_connection.as_mut() {
-            flush_tunnel_messages(serial_ref, &mut tunnel, logger);
-            flush_command_messages(serial_ref, &mut command_executor, logger);
+            flush_tunnel_messages(
+                serial_ref,
+                &mut tunnel,
+                tunnel_flush_budget(link_baud),
+                logger,
+            );
+            flush_command_messages(
+                serial_ref,
+                &mut command_executor,
+                protocol.command_schema(),
+                logger,
+            );
         }
         let heartbeat_active = current_time.duration_since(last_frame_at) >= heartbeat_grace;
         if heartbeat_active && current_time >= next_heartbeat {
@@ -392,37 +487,32 @@ pub(super) fn run_render_loop(
         }
         let heartbeat_on = heartbeat_active && heartbeat_visible;
 
-        // Manual page advance via GPIO button when configured.
+        // Manual page advance via GPIO button when configured; a press on a dark panel only
+        // wakes the backlight.
         if let Some(button) = button_input.as_mut() {
             if button.is_pressed() {
-                if let Some(frame) = state.next_page() {
-                    current_frame = Some(frame);
-                    scroll_offsets = ScrollOffsets::zero();
-                    next_scroll = current_time + Duration::from_millis(config.scroll_speed_ms);
-                    lcd.clear()?;
-                    if let Some(frame) = current_frame.as_ref() {
-                        next_page = current_time + Duration::from_millis(frame.page_timeout_ms);
-                        let palette = render_if_allowed(
-                            lcd,
-                            frame,
-                            &mut last_render,
-                            min_render_interval,
-                            (scroll_offsets.top, scroll_offsets.bottom),
-                            heartbeat_on,
-                            &mut icon_bank,
-                        )?;
-                        log_icon_fallbacks(logger, palette);
-                    }
+                let was_dark = backlight.level(current_time) == 0;
+                backlight.wake(current_time);
+                if !was_dark {
+                    displays
+                        .primary_mut()
+                        .advance_page(current_time, heartbeat_on, logger)?;
                 }
             }
         }
 
         // Show reconnect status as soon as we know the serial link is gone.
         if serial_connection.is_none() && !reconnect_displayed {
-            render_reconnecting(lcd, config.cols)?;
+            render_reconnecting_all(displays)?;
             reconnect_displayed = true;
         }
 
+        // A freshly plugged adapter is worth trying right away rather than after the backoff.
+        if hotplug.tty_added() && serial_connection.is_none() {
+            logger.info("hot-plug: serial device added; reconnecting now");
+            backoff.retry_now(current_time);
+        }
+
         // Attempt reconnect when backoff allows; reset indicators on success.
         if serial_connection.is_none() && backoff.should_retry(current_time) {
             let delay = backoff.current_delay_ms();
@@ -441,7 +531,11 @@ pub(super) fn run_render_loop(
                 .unwrap_or_default();
             logger.info(format!(
                 "reconnect attempt #{}, delay={}ms device={} baud={}{}",
-                stats.reconnects, delay, config.device, config.baud, reason_suffix
+                stats.reconnects,
+                delay,
+                config.device_label(),
+                config.baud,
+                reason_suffix
             ));
             if delay >= backoff.max_delay_ms() && !max_backoff_warned {
                 logger.warn(format!(
@@ -453,9 +547,12 @@ pub(super) fn run_render_loop(
             match attempt_serial_connect(
                 logger,
                 &config.device,
+                config.device_match.as_ref(),
                 config.serial_options(),
                 &config.negotiation,
+                identity,
                 config.compression_enabled,
+                &config.bus,
                 negotiation_log,
             ) {
                 Ok(outcome) => {
@@ -469,17 +566,39 @@ pub(super) fn run_render_loop(
                         None,
                     );
                     serial_connection = Some(outcome.port);
+                    protocol = outcome.protocol;
+                    role = outcome.role;
+                    link_baud = outcome.baud;
+                    command_bridge.set_schema_version(protocol.command_schema());
+                    displays.set_protocol(protocol);
                     supports_heartbeat = outcome
                         .remote_caps
                         .as_ref()
                         .map(|caps| caps.supports_heartbeat)
                         .unwrap_or(false);
+                    supports_latency = outcome
+                        .remote_caps
+                        .as_ref()
+                        .is_some_and(|caps| caps.supports_latency);
                     backoff.mark_success(current_time);
+                    link_quality.reset_link();
+                    latency.reset_link();
                     watchdog.touch_serial();
                     watchdog.touch_tunnel();
                     next_serial_heartbeat = Instant::now() + serial_heartbeat_interval;
                     next_tunnel_heartbeat = Instant::now() + tunnel_heartbeat_interval;
-                    lcd.clear()?;
+                    for slot in displays.iter_mut() {
+                        slot.lcd.clear()?;
+                    }
+                    show_peer_status(
+                        displays,
+                        outcome.peer.as_ref(),
+                        role,
+                        outcome.peer_alert.as_deref(),
+                    )?;
+                    if outcome.peer_alert.is_some() {
+                        backlight.wake(current_time);
+                    }
                     reconnect_displayed = false;
                     offline_displayed = false;
                     heartbeat_visible = false;
@@ -507,19 +626,90 @@ pub(super) fn run_render_loop(
             incoming_line.clear();
             match serial_connection_ref.read_message_line(&mut incoming_line) {
                 Ok(read) => {
+                    if serial_connection_ref.get_mut().take_activity() {
+                        // Polls and turn hand-backs show the bus is alive between payloads.
+                        watchdog.touch_serial();
+                    }
+                    link_quality.sync_reliable(serial_connection_ref.stats(), current_time);
+                    if let Some(rtt) = serial_connection_ref.take_rtt_sample() {
+                        link_quality.record_rtt(rtt, current_time);
+                    }
                     if read > 0 {
                         let line = incoming_line.trim_end_matches(&['\r', '\n'][..]).trim();
                         if !line.is_empty() {
+                            if looks_like_control_frame(line) {
+                                link_quality.record(LinkEvent::Frame, current_time);
+                                match serde_json::from_str::<ControlFrame>(line) {
+                                    // A bus has no single peer to renegotiate with.
+                                    Ok(ControlFrame::Hello { .. }) if !config.bus.is_enabled() => {
+                                        logger.info("negotiation: peer sent hello mid-session");
+                                        pending_renegotiation = Some(Some(line.to_string()));
+                                    }
+                                    Ok(ControlFrame::Renegotiate { reason })
+                                        if !config.bus.is_enabled() =>
+                                    {
+                                        logger.info(format!(
+                                            "negotiation: peer requested renegotiation{}",
+                                            reason.map(|r| format!(" ({r})")).unwrap_or_default()
+                                        ));
+                                        pending_renegotiation = Some(None);
+                                    }
+                                    _ => logger.debug(format!(
+                                        "ignoring stray control frame {}",
+                                        preview_frame(line, 80)
+                                    )),
+                                }
+                                continue;
+                            }
                             if looks_like_tunnel_frame(line) {
                                 match decode_tunnel_frame(line) {
                                     Ok(msg) => {
-                                        if matches!(msg, TunnelMsgOwned::Heartbeat) {
-                                            watchdog.touch_serial();
-                                            watchdog.touch_tunnel();
-                                            continue;
+                                        link_quality.record(LinkEvent::Frame, current_time);
+                                        match msg {
+                                            TunnelMsgOwned::Heartbeat => {
+                                                watchdog.touch_serial();
+                                                watchdog.touch_tunnel();
+                                                continue;
+                                            }
+                                            TunnelMsgOwned::Ping { seq, sent_ms } => {
+                                                watchdog.touch_serial();
+                                                watchdog.touch_tunnel();
+                                                send_tunnel_frame(
+                                                    serial_connection_ref,
+                                                    TunnelMsgOwned::Pong { seq, sent_ms },
+                                                    logger,
+                                                );
+                                                continue;
+                                            }
+                                            TunnelMsgOwned::Pong { seq, sent_ms } => {
+                                                watchdog.touch_serial();
+                                                watchdog.touch_tunnel();
+                                                // The read may block for a while; time the
+                                                // pong from when it actually arrived.
+                                                let arrived = Instant::now();
+                                                if let Some(rtt) =
+                                                    latency.pong(seq, sent_ms, arrived)
+                                                {
+                                                    link_quality.record_rtt(rtt, arrived);
+                                                }
+                                                continue;
+                                            }
+                                            _ => {}
                                         }
                                         watchdog.touch_serial();
                                         watchdog.touch_tunnel();
+                                        if !role.executes_commands()
+                                            && tunnel.refuse_request(&msg, CLIENT_ROLE_REFUSAL)
+                                        {
+                                            logger.debug("client role: refused tunnel request");
+                                            flush_tunnel_messages(
+                                                serial_connection_ref,
+                                                &mut tunnel,
+                                                tunnel_flush_budget(link_baud),
+                                                logger,
+                                            );
+                                            continue;
+                                        }
                                         if let Some(response) = tunnel.handle_msg(msg, logger) {
                                             send_tunnel_frame(
                                                 serial_connection_ref,
@@ -530,10 +720,13 @@ pub(super) fn run_render_loop(
                                         flush_tunnel_messages(
                                             serial_connection_ref,
                                             &mut tunnel,
+                                            tunnel_flush_budget(link_baud),
                                             logger,
                                         );
                                     }
                                     Err(err) => {
+                                        link_quality
+                                            .record(LinkEvent::from_error(&err), current_time);
                                         logger.warn(format!("tunnel frame error: {err}"));
                                         tunnel.log_frame_error(&format!("tunnel: {err}"), line);
                                     }
@@ -543,6 +736,7 @@ pub(super) fn run_render_loop(
                             if looks_like_command_frame(line) {
                                 match command_bridge.ingest_line(line) {
                                     Ok(Some(event)) => {
+                                        link_quality.record(LinkEvent::Frame, current_time);
                                         let label =
                                             if let Some(id) = command_bridge.last_request_id() {
                                                 format!("cmd#{id} {}", event.kind())
@@ -559,22 +753,43 @@ pub(super) fn run_render_loop(
                                         }
                                         watchdog.touch_serial();
                                         watchdog.touch_tunnel();
+                                        if let CommandEvent::Request { request_id, .. } = &event {
+                                            if !role.executes_commands() {
+                                                logger.debug(format!(
+                                                    "client role: refused command #{request_id}"
+                                                ));
+                                                send_command_frame(
+                                                    serial_connection_ref,
+                                                    CommandMessage::Error {
+                                                        request_id: Some(*request_id),
+                                                        message: CLIENT_ROLE_REFUSAL.into(),
+                                                    },
+                                                    protocol.command_schema(),
+                                                    logger,
+                                                );
+                                                continue;
+                                            }
+                                        }
                                         if let Some(response) = command_executor.handle_event(event)
                                         {
                                             send_command_frame(
                                                 serial_connection_ref,
                                                 response,
+                                                protocol.command_schema(),
                                                 logger,
                                             );
                                             flush_command_messages(
                                                 serial_connection_ref,
                                                 &mut command_executor,
+                                                protocol.command_schema(),
                                                 logger,
                                             );
                                         }
                                     }
                                     Ok(None) => {}
                                     Err(err) => {
+                                        link_quality
+                                            .record(LinkEvent::from_error(&err), current_time);
                                         logger.warn(format!("command frame error: {err}"));
                                     }
                                 }
@@ -583,7 +798,8 @@ pub(super) fn run_render_loop(
                             if !looks_like_payload_frame(line) {
                                 // Ignore obvious garbage/diagnostic chatter (e.g., "INIT", noise
                                 // bytes that survived UTF-8 decoding, etc.) so we don't spam the LCD
-                                // with parse errors.
+                                // with parse errors. Still a sign of a noisy line.
+                                link_quality.record(LinkEvent::FrameError, current_time);
                                 logger.debug(format!(
                                     "ignoring non-payload serial frame len={} preview={}",
                                     line.len(),
@@ -591,20 +807,36 @@ pub(super) fn run_render_loop(
                                 ));
                                 continue;
                             }
+                            if !role.owns_display() {
+                                // The server owns the display; a client only produces payloads.
+                                link_quality.record(LinkEvent::Frame, current_time);
+                                watchdog.touch_serial();
+                                logger.debug("client role: ignoring display payload from peer");
+                                continue;
+                            }
                             let mut hasher = Hasher::new();
                             hasher.update(line.as_bytes());
                             let crc = hasher.finalize();
                             if logger.level() >= LogLevel::Debug {
                                 logger.debug(format!("frame crc={crc:08x} len={}", line.len()));
                             }
-                            match state.ingest(line) {
-                                Ok(Some(frame)) if frame.config_reload => {
+                            let ingested = displays.ingest(line);
+                            link_quality.record(
+                                match &ingested {
+                                    Ok(_) => LinkEvent::Frame,
+                                    Err(err) => LinkEvent::from_error(err),
+                                },
+                                current_time,
+                            );
+                            match ingested {
+                                Ok(Some((_, frame))) if frame.config_reload => {
                                     stats.frames_accepted += 1;
                                     watchdog.touch_serial();
                                     logger.info("config reload requested");
                                     match Config::load_or_default() {
                                         Ok(new_cfg) => {
-                                            let old_device = config.device.clone();
+                                            let old_compression = config.compression_enabled;
+                                            let old_device = config.device_label();
                                             let old_serial = config.serial_options();
                                             let old_scroll = config.scroll_speed_ms;
                                             let old_page = config.page_timeout_ms;
@@ -614,6 +846,7 @@ pub(super) fn run_render_loop(
                                             config.backoff_initial_ms = new_cfg.backoff_initial_ms;
                                             config.backoff_max_ms = new_cfg.backoff_max_ms;
                                             config.device = new_cfg.device;
+                                            config.device_match = new_cfg.device_match;
                                             config.baud = new_cfg.baud;
                                             config.flow_control = new_cfg.flow_control;
                                             config.parity = new_cfg.parity;
@@ -625,10 +858,14 @@ pub(super) fn run_render_loop(
                                             config.compression_codec =
                                                 new_cfg.protocol.compression_codec;
                                             config.watchdog = new_cfg.watchdog;
+                                            config.link_quality = new_cfg.link_quality;
+                                            link_quality.set_config(config.link_quality);
+                                            config.backlight = new_cfg.backlight;
+                                            backlight.set_config(config.backlight.clone());
 
-                                            compression_policy =
-                                                compression_policy_from_config(config);
-                                            state.set_compression_policy(compression_policy);
+                                            displays.set_compression_policy(
+                                                compression_policy_from_config(config),
+                                            );
 
                                             watchdog = WatchdogMonitor::new(
                                                 config.watchdog.serial_timeout_ms,
@@ -647,12 +884,12 @@ pub(super) fn run_render_loop(
 
                                             let new_serial = config.serial_options();
 
-                                            if old_device != config.device
+                                            if old_device != config.device_label()
                                                 || old_serial != new_serial
                                             {
                                                 logger.info(format!(
                                                     "config reload updating serial to {} @ {} (flow={}, parity={}, stop_bits={}, dtr={}, timeout={}ms)",
-                                                    config.device,
+                                                    config.device_label(),
                                                     config.baud,
                                                     config.flow_control,
                                                     config.parity,
@@ -663,6 +900,17 @@ pub(super) fn run_render_loop(
                                                 serial_connection = None;
                                                 reconnect_displayed = false;
                                                 offline_displayed = false;
+                                            } else if old_compression != config.compression_enabled
+                                                && !config.bus.is_enabled()
+                                            {
+                                                // Advertised capabilities changed; tell the peer
+                                                // and redo the hello exchange on this link.
+                                                request_renegotiation(
+                                                    serial_connection_ref,
+                                                    "capabilities changed",
+                                                    logger,
+                                                );
+                                                pending_renegotiation = Some(None);
                                             }
                                             if old_scroll != new_cfg.scroll_speed_ms
                                                 || old_page != new_cfg.page_timeout_ms
@@ -676,7 +924,7 @@ pub(super) fn run_render_loop(
                                                 config.backoff_initial_ms,
                                                 config.backoff_max_ms,
                                             );
-                                            state.set_defaults(PayloadDefaults {
+                                            displays.set_defaults(PayloadDefaults {
                                                 scroll_speed_ms: config.scroll_speed_ms,
                                                 page_timeout_ms: config.page_timeout_ms,
                                             });
@@ -687,34 +935,22 @@ pub(super) fn run_render_loop(
                                         }
                                     }
                                 }
-                                Ok(Some(frame)) => {
+                                Ok(Some((idx, frame))) => {
                                     stats.frames_accepted += 1;
-                                    current_frame = Some(frame.clone());
-                                    scroll_offsets = ScrollOffsets::zero();
-                                    next_scroll = current_time
-                                        + Duration::from_millis(config.scroll_speed_ms);
-                                    lcd.clear()?;
-                                    backlight_state = frame.backlight_on;
-                                    lcd.set_backlight(backlight_state)?;
-                                    lcd.set_blink(frame.blink)?;
-                                    next_blink = current_time + blink_interval;
                                     last_frame_at = current_time;
+                                    if frame.alert {
+                                        backlight.wake(current_time);
+                                    } else {
+                                        backlight.activity(current_time);
+                                    }
                                     watchdog.touch_serial();
                                     heartbeat_visible = false;
-                                    if let Some(frame) = current_frame.as_ref() {
-                                        next_page = current_time
-                                            + Duration::from_millis(frame.page_timeout_ms);
-                                        let palette = render_if_allowed(
-                                            lcd,
-                                            frame,
-                                            &mut last_render,
-                                            min_render_interval,
-                                            (scroll_offsets.top, scroll_offsets.bottom),
-                                            heartbeat_on,
-                                            &mut icon_bank,
-                                        )?;
-                                        log_icon_fallbacks(logger, palette);
-                                    }
+                                    displays.slot_mut(idx).show_frame(
+                                        frame,
+                                        current_time,
+                                        heartbeat_on,
+                                        logger,
+                                    )?;
                                 }
                                 Ok(None) => {
                                     stats.duplicates += 1;
@@ -730,9 +966,9 @@ pub(super) fn run_render_loop(
                                         protocol_errors.log(&err, line, crc, logger);
                                     }
                                     logger.warn(format!("frame error: {err}"));
-                                    render_parse_error(lcd, config.cols, &err)?;
-                                    backlight_state = true;
-                                    next_blink = current_time + blink_interval;
+                                    let primary = displays.primary_mut();
+                                    render_parse_error(&mut primary.lcd, config.cols, &err)?;
+                                    primary.hold_backlight(current_time);
                                     continue;
                                 }
                             }
@@ -744,12 +980,25 @@ pub(super) fn run_render_loop(
                     logger.warn(format!(
                         "serial read error [{reason}]: {e}; scheduling reconnect"
                     ));
+                    if serial_connection_ref.is_reliable() {
+                        let link = serial_connection_ref.stats();
+                        logger.info(format!(
+                            "reliable link stats: sent={} retransmits={} delivered={} dup={} out_of_order={} corrupt={} resyncs={}",
+                            link.frames_sent,
+                            link.retransmits,
+                            link.delivered,
+                            link.duplicates_dropped,
+                            link.out_of_order_dropped,
+                            link.corrupt_dropped,
+                            link.resyncs
+                        ));
+                    }
                     serial_connection = None;
                     backoff.mark_failure(current_time);
                     reconnect_displayed = false;
                     last_disconnect_reason = Some(reason);
                     if !offline_displayed {
-                        render_offline_message(lcd, config.cols)?;
+                        render_offline_all(displays)?;
                         offline_displayed = true;
                     }
                 }
@@ -759,7 +1008,37 @@ pub(super) fn run_render_loop(
             thread::sleep(Duration::from_millis(50));
         }
 
+        // A restarted peer says hello at the configured rate, so an upgraded link that has
+        // missed two heartbeats drops back there to hear it.
+        if link_baud != config.baud
+            && supports_heartbeat
+            && watchdog.serial_idle(current_time) >= serial_heartbeat_interval * 2
+        {
+            if let Some(serial_ref) = serial_connection.as_mut() {
+                logger.warn(format!(
+                    "peer silent at {link_baud} baud; dropping back to {} baud",
+                    config.baud
+                ));
+                match serial_ref.set_baud(config.baud) {
+                    Ok(()) => {
+                        serial_ref.reset(false, ReliableConfig::for_baud(config.baud));
+                        link_baud = config.baud;
+                    }
+                    Err(err) => {
+                        logger.warn(format!(
+                            "cannot return to {} baud: {err}; reconnecting",
+                            config.baud
+                        ));
+                        serial_connection = None;
+                        backoff.mark_failure(current_time);
+                        reconnect_displayed = false;
+                    }
+                }
+            }
+        }
+
         // Evaluate watchdog states after handling inbound/outbound traffic.
+        watchdog.adapt_to_rtt(latency.rto(), tunnel_heartbeat_interval);
         let wd_status = watchdog.evaluate(logger);
         if wd_status.serial_recovered {
             serial_watchdog_active = false;
@@ -772,6 +1051,7 @@ pub(super) fn run_render_loop(
         if wd_status.serial_expired && !serial_watchdog_active {
             serial_watchdog_active = true;
             logger.warn("watchdog: serial channel expired; forcing reconnect");
+            link_quality.record(LinkEvent::Timeout, current_time);
             if serial_connection.is_some() {
                 serial_connection = None;
                 backoff.mark_failure(current_time);
@@ -780,8 +1060,9 @@ pub(super) fn run_render_loop(
                 last_disconnect_reason = None;
             }
             if !offline_displayed {
-                render_offline_message(lcd, config.cols)?;
+                render_offline_all(displays)?;
                 offline_displayed = true;
+                backlight.wake(current_time);
             }
         }
         if wd_status.tunnel_expired && !tunnel_watchdog_active {
@@ -789,90 +1070,95 @@ pub(super) fn run_render_loop(
             logger.warn("watchdog: tunnel channel expired");
         }
 
-        // Rotate to the next queued frame after its page timeout.
-        if state.len() > 1 && current_time >= next_page {
-            if let Some(frame) = state.next_page() {
-                current_frame = Some(frame);
-                scroll_offsets = ScrollOffsets::zero();
-                if let Some(frame) = current_frame.as_ref() {
-                    next_page = current_time + Duration::from_millis(frame.page_timeout_ms);
-                    lcd.clear()?;
-                    backlight_state = frame.backlight_on;
-                    lcd.set_backlight(backlight_state)?;
-                    lcd.set_blink(frame.blink)?;
-                    next_blink = current_time + blink_interval;
-                    let palette = render_if_allowed(
-                        lcd,
-                        frame,
-                        &mut last_render,
-                        min_render_interval,
-                        (scroll_offsets.top, scroll_offsets.bottom),
-                        heartbeat_on,
-                        &mut icon_bank,
-                    )?;
-                    log_icon_fallbacks(logger, palette);
+        // Step back from a degraded link, then publish the status page.
+        if let Some(serial_ref) = serial_connection.as_mut() {
+            let options = AdaptOptions {
+                compression_available: !config.compression_enabled && !config.bus.is_enabled(),
+                chunk_bytes,
+                link_baud: serial_ref.get_ref().get_ref().baud(),
+                base_baud: config.baud,
+                max_baud: config.negotiation.max_baud,
+            };
+            if let Some((adaptation, quality)) = link_quality.check(current_time, &options) {
+                if adaptation.is_recovery() {
+                    logger.info(format!(
+                        "link quality recovered; {adaptation} ({})",
+                        quality.summary()
+                    ));
+                } else {
+                    logger.warn(format!(
+                        "link quality degraded; {adaptation} ({})",
+                        quality.summary()
+                    ));
                 }
-            }
-        }
-
-        if let Some(frame) = current_frame.as_ref() {
-            let width = lcd.cols() as usize;
-            let needs_scroll = match frame.bar_row {
-                Some(0) => frame.scroll_enabled && line_needs_scroll(&frame.line2, width),
-                Some(1) => frame.scroll_enabled && line_needs_scroll(&frame.line1, width),
-                _ => {
-                    frame.scroll_enabled
-                        && (line_needs_scroll(&frame.line1, width)
-                            || line_needs_scroll(&frame.line2, width))
+                match adaptation {
+                    Adaptation::EnableCompression | Adaptation::DisableCompression => {
+                        config.compression_enabled = adaptation == Adaptation::EnableCompression;
+                        displays.set_compression_policy(compression_policy_from_config(config));
+                        request_renegotiation(serial_ref, "link quality", logger);
+                        pending_renegotiation = Some(None);
+                    }
+                    Adaptation::ShrinkChunks(bytes) | Adaptation::RestoreChunks(bytes) => {
+                        chunk_bytes = bytes;
+                        command_executor.set_chunk_size(bytes);
+                        tunnel.set_chunk_size(bytes);
+                    }
+                    Adaptation::LowerBaud(baud) => {
+                        // Both peers restart at the configured rate and upgrade no further
+                        // than `baud`.
+                        config.negotiation.max_baud = Some(baud);
+                        serial_connection = None;
+                        reconnect_displayed = false;
+                        offline_displayed = false;
+                    }
+                    Adaptation::RestoreMaxBaud(cap) => {
+                        config.negotiation.max_baud = cap;
+                        serial_connection = None;
+                        reconnect_displayed = false;
+                        offline_displayed = false;
+                    }
                 }
-            };
-            // Scroll long lines forward when allowed by the frame.
-            if needs_scroll && current_time >= next_scroll {
-                scroll_offsets = scroll_offsets.update(
-                    advance_offset(&frame.line1, lcd.cols() as usize, scroll_offsets.top),
-                    advance_offset(&frame.line2, lcd.cols() as usize, scroll_offsets.bottom),
-                );
-                next_scroll = current_time + Duration::from_millis(frame.scroll_speed_ms);
-                let palette = render_if_allowed(
-                    lcd,
-                    frame,
-                    &mut last_render,
-                    min_render_interval,
-                    (scroll_offsets.top, scroll_offsets.bottom),
-                    heartbeat_on,
-                    &mut icon_bank,
-                )?;
-                log_icon_fallbacks(logger, palette);
             }
+        }
+        link_quality.write_status(
+            current_time,
+            &StatusContext {
+                link_baud: serial_connection
+                    .as_ref()
+                    .map(|link| link.get_ref().get_ref().baud()),
+                compression: config.compression_enabled,
+                chunk_bytes,
+                latency: latency.snapshot(),
+                watchdog_timeouts: watchdog.timeouts(),
+            },
+        );
 
-            if frame.blink {
-                // Drive periodic blink by toggling backlight.
-                if current_time >= next_blink {
-                    backlight_state = !backlight_state;
-                    lcd.set_backlight(backlight_state)?;
-                    next_blink = current_time + blink_interval;
-                }
-            } else if backlight_state != frame.backlight_on {
-                backlight_state = frame.backlight_on;
-                lcd.set_backlight(backlight_state)?;
-            }
+        // Rotate pages, scroll, and blink on every display independently, then apply the
+        // scheduled/idle backlight level.
+        let brightness = backlight.level(current_time);
+        for slot in displays.iter_mut() {
+            slot.tick(current_time, heartbeat_on, logger)?;
+            slot.lcd.set_brightness(brightness)?;
         }
 
-        let no_frames_available = state.is_empty();
         if let Some(polling_state) = polling.as_mut() {
+            let primary = displays.primary_mut();
+            let no_frames_available = primary.state.is_empty();
             maybe_render_polling_overlay(
                 polling_state,
-                lcd,
+                &mut primary.lcd,
                 config.cols,
                 serial_connection.is_some(),
-                current_frame.is_some(),
+                primary.current_frame.is_some(),
                 no_frames_available,
             )?;
         }
     }
 
-    // Leave the display in a clean shutdown state.
-    render_shutdown(lcd)?;
+    // Leave every display in a clean shutdown state.
+    for slot in displays.iter_mut() {
+        render_shutdown(&mut slot.lcd)?;
+    }
     logger.info(format!(
         "shutdown: frames accepted={} rejected={} checksum_failures={} duplicates={} reconnects={}",
         stats.frames_accepted,
@@ -881,10 +1167,53 @@ pub(super) fn run_render_loop(
         stats.duplicates,
         stats.reconnects
     ));
+    logger.info(format!(
+        "shutdown: link quality {}",
+        link_quality.snapshot(Instant::now()).summary()
+    ));
+    logger.info(format!(
+        "shutdown: latency {}",
+        latency.snapshot().summary()
+    ));
     logger.info("daemon exiting");
     Ok(())
 }
 
+fn render_reconnecting_all(displays: &mut Displays) -> Result<()> {
+    for slot in displays.iter_mut() {
+        let cols = slot.lcd.cols();
+        render_reconnecting(&mut slot.lcd, cols)?;
+    }
+    Ok(())
+}
+
+/// Introduce the peer on the primary display; its first payload replaces this page.
+fn show_peer_status(
+    displays: &mut Displays,
+    peer: Option<&PeerIdentity>,
+    role: Role,
+    alert: Option<&str>,
+) -> Result<()> {
+    let unknown = PeerIdentity::default();
+    let peer = peer.unwrap_or(&unknown);
+    let title = match alert {
+        Some(_) => "PEER MISMATCH".to_string(),
+        None => format!("PEER {}", peer.label()),
+    };
+    let detail = format!("{} {}", role.as_str(), peer.short_uuid());
+    let lcd = &mut displays.primary_mut().lcd;
+    let cols = lcd.cols();
+    render_peer_status(lcd, cols, &title, &detail, alert.is_some())
+}
+
+fn render_offline_all(displays: &mut Displays) -> Result<()> {
+    for slot in displays.iter_mut() {
+        let cols = slot.lcd.cols();
+        render_offline_message(&mut slot.lcd, cols)?;
+    }
+    Ok(())
+}
+
 fn looks_like_tunnel_frame(line: &str) -> bool {
     line.contains("\"msg\"") && line.contains("\"crc32\"")
 }
@@ -893,13 +1222,21 @@ fn looks_like_command_frame(line: &str) -> bool {
     line.contains("\"channel\":\"command\"") && line.contains("\"crc32\"")
 }
 
+/// Handshake frames that arrive after negotiation finished (e.g. the peer's last baud check).
+fn looks_like_control_frame(line: &str) -> bool {
+    line.starts_with("{\"type\"") && serde_json::from_str::<ControlFrame>(line).is_ok()
+}
+
 fn looks_like_payload_frame(line: &str) -> bool {
     let trimmed = line.trim();
     if trimmed.is_empty() {
         return false;
     }
-    // Never treat tunnel/command frames as display payloads.
-    if looks_like_tunnel_frame(trimmed) || looks_like_command_frame(trimmed) {
+    // Never treat tunnel/command/handshake frames as display payloads.
+    if looks_like_tunnel_frame(trimmed)
+        || looks_like_command_frame(trimmed)
+        || looks_like_control_frame(trimmed)
+    {
         return false;
     }
     // Display payloads are either JSON objects or `key=value` pairs.
@@ -1006,6 +1343,9 @@ mod tests {
         assert!(!looks_like_payload_frame(
             r#"{"channel":"command","schema_version":1,"message":{"type":"ack","request_id":1},"crc32":1}"#
         ));
+        assert!(!looks_like_payload_frame(
+            r#"{"type":"baud_check","baud":115200,"probe":"x","crc32":1}"#
+        ));
     }
 
     #[test]
@@ -1016,39 +1356,105 @@ mod tests {
         let p = preview_frame("abcdefghijk", 5);
         assert_eq!(p, "abcde…");
     }
+
+    #[test]
+    fn tunnel_flush_spends_at_most_its_byte_budget_per_iteration() {
+        let logger = Logger::new(LogLevel::Info, None).unwrap();
+        let mut tunnel = TunnelController::new(Vec::new()).unwrap();
+        let pull = TunnelMsgOwned::FilePull { path: "x".into() };
+        for _ in 0..20 {
+            tunnel.refuse_request(&pull, "queued reply");
+        }
+        let mut serial = crate::serial::fake::FakeSerialPort::default();
+        assert_eq!(tunnel_flush_budget(9_600), 96);
+        flush_tunnel_messages(
+            &mut serial,
+            &mut tunnel,
+            tunnel_flush_budget(9_600),
+            &logger,
+        );
+        let first = serial.writes().len();
+        assert!((1..40).contains(&first), "sent {first} of 40 frames");
+        flush_tunnel_messages(&mut serial, &mut tunnel, usize::MAX, &logger);
+        assert_eq!(serial.writes().len(), 40);
+    }
+
+    #[test]
+    fn forwarded_poll_snapshot_is_a_valid_payload() {
+        let snapshot = PollSnapshot {
+            cpu_percent: 12.4,
+            mem_used_kb: 512,
+            mem_total_kb: 1024,
+            disk_used_pct: 70.0,
+            disk_available_kb: Some(2048),
+            temperature_c: Some(45.2),
+        };
+        let payload = poll_snapshot_payload(&snapshot, 16, ProtocolVersion::default());
+        assert!(looks_like_payload_frame(&payload));
+        let frame = crate::payload::RenderFrame::from_payload_json(&payload).unwrap();
+        assert_eq!(frame.line1, "CPU 12% MEM 50%");
+        assert!(frame.line2.starts_with("DSK 70% TMP"));
+    }
 }
 
-fn flush_tunnel_messages(serial: &mut SerialPort, tunnel: &mut TunnelController, logger: &Logger) {
-    while let Some(msg) = tunnel.next_outgoing() {
-        send_tunnel_frame(serial, msg, logger);
+/// Bytes of tunnel frames one loop iteration may send at `baud`: about
+/// `TUNNEL_FLUSH_SLICE_MS` of line time (ten bit times per byte), so a long pull leaves room
+/// for LCD payloads, heartbeats and reads. At least one frame always goes out.
+fn tunnel_flush_budget(baud: u32) -> usize {
+    (u64::from(baud) / 10 * TUNNEL_FLUSH_SLICE_MS / 1_000) as usize
+}
+
+fn flush_tunnel_messages<IO: LineIo>(
+    serial: &mut IO,
+    tunnel: &mut TunnelController,
+    budget_bytes: usize,
+    logger: &Logger,
+) {
+    tunnel.poll_forwards();
+    let mut sent = 0;
+    while sent == 0 || sent < budget_bytes {
+        let Some(msg) = tunnel.next_outgoing() else {
+            break;
+        };
+        sent += send_tunnel_frame(serial, msg, logger).max(1);
     }
 }
 
-fn send_tunnel_frame(serial: &mut SerialPort, msg: TunnelMsgOwned, logger: &Logger) {
+/// Returns the encoded length, or 0 when nothing was sent.
+fn send_tunnel_frame<IO: LineIo>(serial: &mut IO, msg: TunnelMsgOwned, logger: &Logger) -> usize {
     match encode_tunnel_msg(&msg) {
         Ok(encoded) => {
             if let Err(err) = serial.send_command_line(&encoded) {
                 logger.warn(format!("tunnel send failed: {err}"));
+                return 0;
             }
+            encoded.len() + 1
         }
         Err(err) => {
             logger.warn(format!("tunnel encode failed: {err}"));
+            0
         }
     }
 }
 
-fn flush_command_messages(
-    serial: &mut SerialPort,
+fn flush_command_messages<IO: LineIo>(
+    serial: &mut IO,
     executor: &mut CommandExecutor,
+    schema_version: u8,
     logger: &Logger,
 ) {
     while let Some(msg) = executor.next_outgoing() {
-        send_command_frame(serial, msg, logger);
+        send_command_frame(serial, msg, schema_version, logger);
     }
 }
 
-fn send_command_frame(serial: &mut SerialPort, msg: CommandMessage, logger: &Logger) {
-    match encode_command_frame(&msg) {
+fn send_command_frame<IO: LineIo>(
+    serial: &mut IO,
+    msg: CommandMessage,
+    schema_version: u8,
+    logger: &Logger,
+) {
+    match encode_command_frame_with_schema(&msg, schema_version) {
         Ok(encoded) => {
             if let Err(err) = serial.send_command_line(&encoded) {
                 logger.warn(format!("command send failed: {err}"));
@@ -1060,6 +1466,48 @@ fn send_command_frame(serial: &mut SerialPort, msg: CommandMessage, logger: &Log
     }
 }
 
+fn request_renegotiation<IO: LineIo>(serial: &mut IO, reason: &str, logger: &Logger) {
+    let frame = ControlFrame::Renegotiate {
+        reason: Some(reason.to_string()),
+    };
+    match serde_json::to_string(&frame) {
+        Ok(encoded) => {
+            if let Err(err) = serial.send_command_line(&encoded) {
+                logger.warn(format!("renegotiate send failed: {err}"));
+            }
+        }
+        Err(err) => logger.warn(format!("renegotiate encode failed: {err}")),
+    }
+}
+
+/// Client role: send a poll snapshot to the server as a display payload.
+fn forward_poll_snapshot<IO: LineIo>(
+    serial: &mut IO,
+    snapshot: &PollSnapshot,
+    cols: u8,
+    protocol: ProtocolVersion,
+    logger: &Logger,
+) {
+    let payload = poll_snapshot_payload(snapshot, cols as usize, protocol);
+    if let Err(err) = serial.send_command_line(&payload) {
+        logger.warn(format!("poll snapshot forward failed: {err}"));
+    }
+}
+
+fn poll_snapshot_payload(
+    snapshot: &PollSnapshot,
+    width: usize,
+    protocol: ProtocolVersion,
+) -> String {
+    let (line1, line2) = format_polling_lines(snapshot, width, true);
+    serde_json::json!({
+        "schema_version": protocol.max_payload_schema(),
+        "line1": line1.trim_end(),
+        "line2": line2.trim_end(),
+    })
+    .to_string()
+}
+
 fn maybe_render_polling_overlay(
     polling: &mut PollingState,
     lcd: &mut Lcd,
@@ -1103,10 +1551,11 @@ fn render_polling_overlay(
     let (line1, line2) = format_polling_lines(snapshot, width, serial_active);
     lcd.clear()?;
     lcd.set_backlight(true)?;
+    lcd.set_backlight_color(Rgb::default())?;
     lcd.set_blink(false)?;
     lcd.write_line(0, &line1)?;
     lcd.write_line(1, &line2)?;
-    Ok(())
+    lcd.finish_frame()
 }
 
 fn format_polling_lines(
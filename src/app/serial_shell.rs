@@ -1,14 +1,41 @@
+use super::connection::lookup_device;
+use super::file_transfer::FILE_TRANSFER_MAX_BYTES;
+use super::line_editor::{LineEditor, RawMode};
 use crate::payload::{decode_tunnel_frame, encode_tunnel_msg};
 use crate::{
-    app::AppConfig, cli::RunOptions, config::Config, payload::TunnelMsgOwned, serial::SerialPort,
-    Result,
+    app::AppConfig,
+    cli::RunOptions,
+    config::{loader::config_dir, Config},
+    payload::TunnelMsgOwned,
+    serial::{discovery::DeviceSelector, SerialOptions, SerialPort},
+    Error, Result,
 };
-use std::io::{self, BufRead, Write};
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const PROMPT: &str = "serialsh> ";
+const HISTORY_FILE_NAME: &str = "serialsh_history";
+/// Entries kept in memory and on disk; older lines are trimmed when the file is loaded.
+pub const SHELL_HISTORY_MAX_ENTRIES: usize = 500;
+/// Pushed files are split into chunks small enough to stay under the tunnel frame cap.
+const PUSH_CHUNK_BYTES: usize = 512;
+const DEFAULT_AUDIT_ENTRIES: u32 = 10;
+const BUILTIN_NAMES: &str = ":status :stats :push :pull :reconnect :history :audit :quit";
 
 /// Abstraction over the serial port used by the serial shell loop.
 pub trait SerialShellTransport {
     fn send_command_line(&mut self, line: &str) -> Result<()>;
     fn read_message_line(&mut self, buf: &mut String) -> Result<usize>;
+
+    /// Re-open the underlying link (used by `:reconnect`).
+    fn reconnect(&mut self) -> Result<()> {
+        Err(Error::InvalidArgs(
+            "this transport cannot reconnect".to_string(),
+        ))
+    }
 }
 
 impl SerialShellTransport for SerialPort {
@@ -21,17 +48,262 @@ impl SerialShellTransport for SerialPort {
     }
 }
 
+/// Serial port plus the settings needed to re-open it from `:reconnect`.
+struct ShellPort {
+    port: SerialPort,
+    device: String,
+    device_match: Option<DeviceSelector>,
+    options: SerialOptions,
+}
+
+impl SerialShellTransport for ShellPort {
+    fn send_command_line(&mut self, line: &str) -> Result<()> {
+        self.port.send_command_line(line)
+    }
+
+    fn read_message_line(&mut self, buf: &mut String) -> Result<usize> {
+        self.port.read_message_line(buf)
+    }
+
+    fn reconnect(&mut self) -> Result<()> {
+        // A replugged adapter may come back under another name.
+        let device =
+            lookup_device(&self.device, self.device_match.as_ref()).map_err(Error::InvalidArgs)?;
+        self.port = SerialPort::connect(&device, self.options)?;
+        Ok(())
+    }
+}
+
+/// How remote output is written to the local stdout/stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShellOutput {
+    /// Forward remote stdout/stderr bytes verbatim.
+    #[default]
+    Raw,
+    /// Emit one JSON record per line on stdout (`stdout`, `stderr`, `exit`, `busy`).
+    Json,
+}
+
+/// Structured record printed by `--json` mode.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ShellRecord<'a> {
+    Stdout { command: &'a str, data: String },
+    Stderr { command: &'a str, data: String },
+    Exit { command: &'a str, code: i32 },
+    Busy { command: &'a str },
+}
+
+/// Command history, optionally persisted to `~/.serial_lcd/serialsh_history`.
+#[derive(Debug, Default)]
+pub struct ShellHistory {
+    entries: Vec<String>,
+    path: Option<PathBuf>,
+}
+
+impl ShellHistory {
+    /// History that lives only for the current session.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Load history from `path`, trimming the file to the newest entries when it has grown.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let mut entries: Vec<String> = fs::read_to_string(&path)
+            .map(|raw| {
+                raw.lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if entries.len() > SHELL_HISTORY_MAX_ENTRIES {
+            entries.drain(..entries.len() - SHELL_HISTORY_MAX_ENTRIES);
+            let mut body = entries.join("\n");
+            body.push('\n');
+            let _ = fs::write(&path, body);
+        }
+        Self {
+            entries,
+            path: Some(path),
+        }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Record a line, skipping blanks and immediate repeats. Persistence is best effort.
+    pub fn push(&mut self, line: &str) {
+        if line.is_empty() || self.entries.last().map(String::as_str) == Some(line) {
+            return;
+        }
+        self.entries.push(line.to_string());
+        if self.entries.len() > SHELL_HISTORY_MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+                let _ = writeln!(file, "{line}");
+            }
+        }
+    }
+}
+
+/// Counters reported by `:stats`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ShellStats {
+    pub commands: u64,
+    pub failures: u64,
+    pub busy: u64,
+    pub stdout_bytes: u64,
+    pub stderr_bytes: u64,
+    pub frames_sent: u64,
+    pub frames_received: u64,
+    pub bytes_pushed: u64,
+    pub bytes_pulled: u64,
+}
+
+/// Per-session state shared by the interactive and batch drivers.
+struct ShellSession {
+    output: ShellOutput,
+    target: String,
+    history: ShellHistory,
+    stats: ShellStats,
+    started: Instant,
+    last_exit: i32,
+    last_remote_exit: Option<i32>,
+}
+
+impl ShellSession {
+    fn new(output: ShellOutput, target: String, history: ShellHistory) -> Self {
+        Self {
+            output,
+            target,
+            history,
+            stats: ShellStats::default(),
+            started: Instant::now(),
+            last_exit: 0,
+            last_remote_exit: None,
+        }
+    }
+}
+
+/// Source of interactive command lines.
+trait ShellInput {
+    fn read_command<E: Write>(
+        &mut self,
+        history: &[String],
+        stderr: &mut E,
+    ) -> Result<Option<String>>;
+}
+
+/// Plain `BufRead` input (pipes, tests): prints the prompt and reads a whole line.
+struct PromptedLines<'a, I>(&'a mut I);
+
+impl<I: BufRead> ShellInput for PromptedLines<'_, I> {
+    fn read_command<E: Write>(
+        &mut self,
+        _history: &[String],
+        stderr: &mut E,
+    ) -> Result<Option<String>> {
+        write_prompt(stderr)?;
+        let mut buffer = String::new();
+        if self.0.read_line(&mut buffer)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(buffer.trim().to_string()))
+    }
+}
+
+/// Interactive terminal input with cursor movement, history recall, and Ctrl-R search.
+struct TerminalInput {
+    editor: LineEditor<io::StdinLock<'static>>,
+}
+
+impl ShellInput for TerminalInput {
+    fn read_command<E: Write>(
+        &mut self,
+        history: &[String],
+        stderr: &mut E,
+    ) -> Result<Option<String>> {
+        // Raw mode only while editing so remote output keeps normal newline handling.
+        let _raw = RawMode::enable(io::stdin())?;
+        let line = self.editor.read_line(PROMPT, history, stderr)?;
+        Ok(line.map(|line| line.trim().to_string()))
+    }
+}
+
 /// Run the serial shell with stdin/stdout/stderr connected to the current process.
+///
+/// `-c` and `--script` switch to batch mode: no prompt is shown and the process exit code
+/// mirrors the remote one.
 pub fn run_serial_shell(opts: RunOptions) -> Result<i32> {
     super::wizard::maybe_run(&opts)?;
+    let output = if opts.serialsh_json {
+        ShellOutput::Json
+    } else {
+        ShellOutput::Raw
+    };
+    let batch = match (&opts.serialsh_command, &opts.serialsh_script) {
+        (Some(command), _) => Some(vec![command.clone()]),
+        (None, Some(script)) => Some(load_script(script)?),
+        (None, None) => None,
+    };
     let cfg = Config::load_or_default()?;
     let merged = AppConfig::from_sources(cfg, opts);
-    let mut serial = SerialPort::connect(&merged.device, merged.serial_options())?;
-    let stdin = io::stdin();
-    let mut stdin_lock = stdin.lock();
+    let options = merged.serial_options();
+    let device = merged.resolved_device()?;
+    let mut serial = ShellPort {
+        port: SerialPort::connect(&device, options)?,
+        device: merged.device.clone(),
+        device_match: merged.device_match.clone(),
+        options,
+    };
+    let target = format!("{device} @ {} baud", merged.baud);
     let mut stdout = io::stdout();
     let mut stderr = io::stderr();
-    drive_serial_shell_loop(&mut serial, &mut stdin_lock, &mut stdout, &mut stderr)
+    if let Some(commands) = batch {
+        let mut session = ShellSession::new(output, target, ShellHistory::in_memory());
+        return batch_loop(
+            &mut serial,
+            &commands,
+            &mut session,
+            &mut stdout,
+            &mut stderr,
+        );
+    }
+    let history = match config_dir() {
+        Ok(dir) => ShellHistory::load(dir.join(HISTORY_FILE_NAME)),
+        Err(_) => ShellHistory::in_memory(),
+    };
+    let mut session = ShellSession::new(output, target, history);
+    if io::stdin().is_terminal() {
+        let mut input = TerminalInput {
+            editor: LineEditor::new(io::stdin().lock()),
+        };
+        interactive_loop(
+            &mut serial,
+            &mut input,
+            &mut session,
+            &mut stdout,
+            &mut stderr,
+        )
+    } else {
+        let stdin = io::stdin();
+        let mut stdin_lock = stdin.lock();
+        interactive_loop(
+            &mut serial,
+            &mut PromptedLines(&mut stdin_lock),
+            &mut session,
+            &mut stdout,
+            &mut stderr,
+        )
+    }
 }
 
 /// Core loop used by `run_serial_shell`. Accepts injectable transports + IO for easier testing.
@@ -47,46 +319,468 @@ where
     O: Write,
     E: Write,
 {
-    serial.send_command_line("INIT")?;
-    let mut buffer = String::new();
-    let mut last_exit = 0;
+    let mut session = ShellSession::new(
+        ShellOutput::Raw,
+        "serial".to_string(),
+        ShellHistory::in_memory(),
+    );
+    interactive_loop(
+        serial,
+        &mut PromptedLines(input),
+        &mut session,
+        stdout,
+        stderr,
+    )
+}
 
-    loop {
-        buffer.clear();
-        write_prompt(stderr)?;
-        let bytes = input.read_line(&mut buffer)?;
-        if bytes == 0 {
+/// Run `commands` in order without prompting, stopping at the first non-zero exit code.
+///
+/// Blank lines and `#` comments are skipped; `exit` ends the batch early.
+pub fn drive_serial_shell_batch<T, O, E>(
+    serial: &mut T,
+    commands: &[String],
+    output: ShellOutput,
+    stdout: &mut O,
+    stderr: &mut E,
+) -> Result<i32>
+where
+    T: SerialShellTransport,
+    O: Write,
+    E: Write,
+{
+    let mut session = ShellSession::new(output, "serial".to_string(), ShellHistory::in_memory());
+    batch_loop(serial, commands, &mut session, stdout, stderr)
+}
+
+fn batch_loop<T, O, E>(
+    serial: &mut T,
+    commands: &[String],
+    session: &mut ShellSession,
+    stdout: &mut O,
+    stderr: &mut E,
+) -> Result<i32>
+where
+    T: SerialShellTransport,
+    O: Write,
+    E: Write,
+{
+    serial.send_command_line("INIT")?;
+    for raw in commands {
+        let command = raw.trim();
+        if command.is_empty() || command.starts_with('#') {
+            continue;
+        }
+        if is_quit(command) {
             break;
         }
-        let command = buffer.trim();
+        session.last_exit = run_line(serial, command, session, stdout, stderr)?;
+        if session.last_exit != 0 {
+            break;
+        }
+    }
+    Ok(session.last_exit)
+}
+
+fn interactive_loop<T, I, O, E>(
+    serial: &mut T,
+    input: &mut I,
+    session: &mut ShellSession,
+    stdout: &mut O,
+    stderr: &mut E,
+) -> Result<i32>
+where
+    T: SerialShellTransport,
+    I: ShellInput,
+    O: Write,
+    E: Write,
+{
+    serial.send_command_line("INIT")?;
+    while let Some(command) = input.read_command(session.history.entries(), stderr)? {
         if command.is_empty() {
             continue;
         }
-        if command.eq_ignore_ascii_case("exit") {
+        session.history.push(&command);
+        if is_quit(&command) {
             break;
         }
-        send_serial_command(serial, command)?;
-        last_exit = wait_for_exit(serial, stdout, stderr)?;
+        session.last_exit = run_line(serial, &command, session, stdout, stderr)?;
+    }
+    Ok(session.last_exit)
+}
+
+fn is_quit(command: &str) -> bool {
+    command.eq_ignore_ascii_case("exit") || command == ":quit" || command == ":q"
+}
+
+/// Dispatch one shell line to a local built-in or the remote executor.
+fn run_line<T, O, E>(
+    serial: &mut T,
+    command: &str,
+    session: &mut ShellSession,
+    stdout: &mut O,
+    stderr: &mut E,
+) -> Result<i32>
+where
+    T: SerialShellTransport,
+    O: Write,
+    E: Write,
+{
+    if let Some(builtin) = command.strip_prefix(':') {
+        return run_builtin(serial, builtin, session, stdout, stderr);
+    }
+    session.stats.commands += 1;
+    send_frame(
+        serial,
+        &TunnelMsgOwned::CmdRequest {
+            cmd: command.to_string(),
+        },
+        &mut session.stats,
+    )?;
+    let code = wait_for_exit(
+        serial,
+        command,
+        session.output,
+        &mut session.stats,
+        stdout,
+        stderr,
+    )?;
+    if code != 0 {
+        session.stats.failures += 1;
+    }
+    session.last_remote_exit = Some(code);
+    Ok(code)
+}
+
+fn load_script(path: &str) -> Result<Vec<String>> {
+    let raw = if path == "-" {
+        let mut raw = String::new();
+        io::stdin().read_to_string(&mut raw)?;
+        raw
+    } else {
+        fs::read_to_string(path)
+            .map_err(|e| Error::InvalidArgs(format!("unable to read script {path}: {e}")))?
+    };
+    Ok(raw.lines().map(str::to_string).collect())
+}
+
+/// Handle `:`-prefixed commands locally instead of forwarding them to the remote executor.
+fn run_builtin<T, O, E>(
+    serial: &mut T,
+    builtin: &str,
+    session: &mut ShellSession,
+    stdout: &mut O,
+    stderr: &mut E,
+) -> Result<i32>
+where
+    T: SerialShellTransport,
+    O: Write,
+    E: Write,
+{
+    let mut parts = builtin.split_whitespace();
+    let name = parts.next().unwrap_or_default();
+    let args: Vec<&str> = parts.collect();
+    match (name, args.as_slice()) {
+        ("status", []) => {
+            writeln!(stdout, "link: {}", session.target)?;
+            writeln!(
+                stdout,
+                "session: {}s, last remote exit {}",
+                session.started.elapsed().as_secs(),
+                session
+                    .last_remote_exit
+                    .map(|code| code.to_string())
+                    .unwrap_or_else(|| "n/a".to_string())
+            )?;
+            writeln!(
+                stdout,
+                "output: {}",
+                match session.output {
+                    ShellOutput::Raw => "raw",
+                    ShellOutput::Json => "json",
+                }
+            )?;
+            Ok(0)
+        }
+        ("stats", []) => {
+            let stats = &session.stats;
+            writeln!(
+                stdout,
+                "commands: {} ({} failed, {} busy)",
+                stats.commands, stats.failures, stats.busy
+            )?;
+            writeln!(
+                stdout,
+                "output: {} stdout bytes, {} stderr bytes",
+                stats.stdout_bytes, stats.stderr_bytes
+            )?;
+            writeln!(
+                stdout,
+                "frames: {} sent, {} received",
+                stats.frames_sent, stats.frames_received
+            )?;
+            writeln!(
+                stdout,
+                "transfers: {} bytes pushed, {} bytes pulled",
+                stats.bytes_pushed, stats.bytes_pulled
+            )?;
+            Ok(0)
+        }
+        ("history", []) => {
+            for (idx, entry) in session.history.entries().iter().enumerate() {
+                writeln!(stdout, "{:>5}  {entry}", idx + 1)?;
+            }
+            Ok(0)
+        }
+        ("reconnect", []) => match serial.reconnect() {
+            Ok(()) => {
+                send_line(serial, "INIT", &mut session.stats)?;
+                writeln!(stderr, "reconnected to {}", session.target)?;
+                Ok(0)
+            }
+            Err(err) => {
+                writeln!(stderr, "reconnect failed: {err}")?;
+                Ok(1)
+            }
+        },
+        ("push", [local]) => push_file(serial, local, file_name(local), session, stdout, stderr),
+        ("push", [local, remote]) => push_file(serial, local, remote, session, stdout, stderr),
+        ("pull", [remote]) => pull_file(serial, remote, file_name(remote), session, stdout, stderr),
+        ("pull", [remote, local]) => pull_file(serial, remote, local, session, stdout, stderr),
+        ("push", _) | ("pull", _) => {
+            writeln!(
+                stderr,
+                "usage: :push <local> [remote] | :pull <remote> [local]"
+            )?;
+            Ok(1)
+        }
+        ("audit", _) => {
+            let limit = match args.first() {
+                Some(raw) => match raw.parse::<u32>() {
+                    Ok(limit) if limit > 0 => limit,
+                    _ => {
+                        writeln!(stderr, ":audit expects a positive entry count")?;
+                        return Ok(1);
+                    }
+                },
+                None => DEFAULT_AUDIT_ENTRIES,
+            };
+            send_frame(
+                serial,
+                &TunnelMsgOwned::AuditRequest { limit },
+                &mut session.stats,
+            )?;
+            wait_for_exit(
+                serial,
+                &format!(":audit {limit}"),
+                session.output,
+                &mut session.stats,
+                stdout,
+                stderr,
+            )
+        }
+        (other, _) => {
+            writeln!(
+                stderr,
+                "unknown built-in ':{other}' (available: {BUILTIN_NAMES})"
+            )?;
+            Ok(1)
+        }
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(path)
+}
+
+/// Upload a local file into the remote transfer directory, one acknowledged chunk at a time.
+fn push_file<T, O, E>(
+    serial: &mut T,
+    local: &str,
+    remote: &str,
+    session: &mut ShellSession,
+    stdout: &mut O,
+    stderr: &mut E,
+) -> Result<i32>
+where
+    T: SerialShellTransport,
+    O: Write,
+    E: Write,
+{
+    let body = match fs::metadata(local) {
+        Ok(meta) if meta.len() > FILE_TRANSFER_MAX_BYTES => {
+            writeln!(
+                stderr,
+                "{local} exceeds the {FILE_TRANSFER_MAX_BYTES}-byte transfer limit"
+            )?;
+            return Ok(1);
+        }
+        Ok(_) => fs::read(local)?,
+        Err(err) => {
+            writeln!(stderr, "cannot read {local}: {err}")?;
+            return Ok(1);
+        }
+    };
+    let command = format!(":push {local} {remote}");
+    let mut chunks: Vec<&[u8]> = body.chunks(PUSH_CHUNK_BYTES).collect();
+    if chunks.is_empty() {
+        chunks.push(&[]);
+    }
+    let mut offset = 0u64;
+    for chunk in chunks {
+        send_frame(
+            serial,
+            &TunnelMsgOwned::FilePush {
+                path: remote.to_string(),
+                offset,
+                chunk: chunk.to_vec(),
+            },
+            &mut session.stats,
+        )?;
+        let code = wait_for_exit(
+            serial,
+            &command,
+            ShellOutput::Raw,
+            &mut session.stats,
+            stdout,
+            stderr,
+        )?;
+        if code != 0 {
+            return finish_transfer(&command, code, session.output, stdout);
+        }
+        offset += chunk.len() as u64;
+    }
+    session.stats.bytes_pushed += offset;
+    if session.output == ShellOutput::Raw {
+        writeln!(stderr, "pushed {offset} bytes to {remote}")?;
+    }
+    finish_transfer(&command, 0, session.output, stdout)
+}
+
+/// Download a file from the remote transfer directory into `local`.
+fn pull_file<T, O, E>(
+    serial: &mut T,
+    remote: &str,
+    local: &str,
+    session: &mut ShellSession,
+    stdout: &mut O,
+    stderr: &mut E,
+) -> Result<i32>
+where
+    T: SerialShellTransport,
+    O: Write,
+    E: Write,
+{
+    let command = format!(":pull {remote} {local}");
+    // Stage next to `local` so an existing file survives a failed pull and the final rename
+    // stays on one filesystem.
+    let staging = staging_path(Path::new(local));
+    let mut file = match fs::File::create(&staging) {
+        Ok(file) => file,
+        Err(err) => {
+            writeln!(stderr, "cannot create {local}: {err}")?;
+            return Ok(1);
+        }
+    };
+    send_frame(
+        serial,
+        &TunnelMsgOwned::FilePull {
+            path: remote.to_string(),
+        },
+        &mut session.stats,
+    )?;
+    let before = session.stats.stdout_bytes;
+    let waited = wait_for_exit(
+        serial,
+        &command,
+        ShellOutput::Raw,
+        &mut session.stats,
+        &mut file,
+        stderr,
+    );
+    drop(file);
+    let received = session.stats.stdout_bytes - before;
+    // File contents are not command output; keep stdout_bytes about remote commands only.
+    session.stats.stdout_bytes = before;
+    let code = match waited {
+        Ok(code) => code,
+        Err(err) => {
+            let _ = fs::remove_file(&staging);
+            return Err(err);
+        }
+    };
+    if code != 0 {
+        let _ = fs::remove_file(&staging);
+        return finish_transfer(&command, code, session.output, stdout);
+    }
+    if let Err(err) = fs::rename(&staging, local) {
+        let _ = fs::remove_file(&staging);
+        writeln!(stderr, "cannot write {local}: {err}")?;
+        return finish_transfer(&command, 1, session.output, stdout);
     }
+    session.stats.bytes_pulled += received;
+    if session.output == ShellOutput::Raw {
+        writeln!(stderr, "pulled {received} bytes into {local}")?;
+    }
+    finish_transfer(&command, 0, session.output, stdout)
+}
 
-    Ok(last_exit)
+/// Hidden `.<name>.part` sibling of `local`, where a pull is written until it succeeds.
+fn staging_path(local: &Path) -> PathBuf {
+    let name = local
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "pull".into());
+    local.with_file_name(format!(".{name}.part"))
+}
+
+fn finish_transfer<O: Write>(
+    command: &str,
+    code: i32,
+    output: ShellOutput,
+    stdout: &mut O,
+) -> Result<i32> {
+    if output == ShellOutput::Json {
+        write_record(&ShellRecord::Exit { command, code }, stdout)?;
+    }
+    Ok(code)
 }
 
 fn write_prompt<W: Write>(stderr: &mut W) -> Result<()> {
-    stderr.write_all(b"serialsh> ")?;
+    stderr.write_all(PROMPT.as_bytes())?;
     stderr.flush()?;
     Ok(())
 }
 
-fn send_serial_command<T: SerialShellTransport>(serial: &mut T, command: &str) -> Result<()> {
-    let msg = TunnelMsgOwned::CmdRequest {
-        cmd: command.to_string(),
-    };
-    let encoded = encode_tunnel_msg(&msg)?;
-    serial.send_command_line(&encoded)
+fn send_frame<T: SerialShellTransport>(
+    serial: &mut T,
+    msg: &TunnelMsgOwned,
+    stats: &mut ShellStats,
+) -> Result<()> {
+    let encoded = encode_tunnel_msg(msg)?;
+    send_line(serial, &encoded, stats)
+}
+
+fn send_line<T: SerialShellTransport>(
+    serial: &mut T,
+    line: &str,
+    stats: &mut ShellStats,
+) -> Result<()> {
+    serial.send_command_line(line)?;
+    stats.frames_sent += 1;
+    Ok(())
 }
 
-fn wait_for_exit<T, O, E>(serial: &mut T, stdout: &mut O, stderr: &mut E) -> Result<i32>
+fn wait_for_exit<T, O, E>(
+    serial: &mut T,
+    command: &str,
+    output: ShellOutput,
+    stats: &mut ShellStats,
+    stdout: &mut O,
+    stderr: &mut E,
+) -> Result<i32>
 where
     T: SerialShellTransport,
     O: Write,
@@ -102,16 +796,47 @@ where
         if trimmed.is_empty() || !is_tunnel_line(trimmed) {
             continue;
         }
-        match decode_tunnel_frame(trimmed)? {
+        let msg = decode_tunnel_frame(trimmed)?;
+        stats.frames_received += 1;
+        match msg {
             TunnelMsgOwned::Stdout { chunk } => {
-                write_chunk(&chunk, stdout)?;
+                stats.stdout_bytes += chunk.len() as u64;
+                match output {
+                    ShellOutput::Raw => write_chunk(&chunk, stdout)?,
+                    ShellOutput::Json => write_record(
+                        &ShellRecord::Stdout {
+                            command,
+                            data: String::from_utf8_lossy(&chunk).into_owned(),
+                        },
+                        stdout,
+                    )?,
+                }
             }
             TunnelMsgOwned::Stderr { chunk } => {
-                write_chunk(&chunk, stderr)?;
+                stats.stderr_bytes += chunk.len() as u64;
+                match output {
+                    ShellOutput::Raw => write_chunk(&chunk, stderr)?,
+                    ShellOutput::Json => write_record(
+                        &ShellRecord::Stderr {
+                            command,
+                            data: String::from_utf8_lossy(&chunk).into_owned(),
+                        },
+                        stdout,
+                    )?,
+                }
+            }
+            TunnelMsgOwned::Exit { code } => {
+                if output == ShellOutput::Json {
+                    write_record(&ShellRecord::Exit { command, code }, stdout)?;
+                }
+                return Ok(code);
             }
-            TunnelMsgOwned::Exit { code } => return Ok(code),
             TunnelMsgOwned::Busy => {
-                writeln!(stderr, "remote busy")?;
+                stats.busy += 1;
+                match output {
+                    ShellOutput::Raw => writeln!(stderr, "remote busy")?,
+                    ShellOutput::Json => write_record(&ShellRecord::Busy { command }, stdout)?,
+                }
                 return Ok(1);
             }
             TunnelMsgOwned::Heartbeat => {}
@@ -126,6 +851,13 @@ fn write_chunk<W: Write>(chunk: &[u8], target: &mut W) -> Result<()> {
     Ok(())
 }
 
+fn write_record<W: Write>(record: &ShellRecord<'_>, target: &mut W) -> Result<()> {
+    let line = serde_json::to_string(record).map_err(|e| Error::Parse(e.to_string()))?;
+    writeln!(target, "{line}")?;
+    target.flush()?;
+    Ok(())
+}
+
 fn is_tunnel_line(line: &str) -> bool {
     line.contains("\"msg\"") && line.contains("\"crc32\"")
 }
@@ -198,6 +930,33 @@ mod tests {
         assert!(err_text.contains("serialsh> "));
     }
 
+    #[test]
+    fn audit_builtin_requests_remote_audit_tail() {
+        let mut serial = FakeSerialPort::new(vec![
+            Ok(encoded(TunnelMsgOwned::Stdout {
+                chunk: b"{\"request_id\":1}\n".to_vec(),
+            })),
+            Ok(encoded(TunnelMsgOwned::Exit { code: 0 })),
+        ]);
+        let mut input = Cursor::new(":audit 3\n:bogus\nexit\n");
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        let exit_code = drive_serial_shell_loop(&mut serial, &mut input, &mut stdout, &mut stderr)
+            .expect("loop failed");
+
+        assert_eq!(exit_code, 1);
+        assert!(String::from_utf8_lossy(&stdout).contains("request_id"));
+        assert!(String::from_utf8_lossy(&stderr).contains("unknown built-in ':bogus'"));
+        assert_eq!(
+            serial.writes(),
+            &[
+                "INIT".to_string(),
+                encoded(TunnelMsgOwned::AuditRequest { limit: 3 }),
+            ]
+        );
+    }
+
     #[test]
     fn heartbeat_frames_are_ignored_between_stdout_and_exit() {
         let mut serial = FakeSerialPort::new(vec![
@@ -228,4 +987,201 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn batch_mode_stops_at_first_failure_and_skips_comments() {
+        let mut serial = FakeSerialPort::new(vec![
+            Ok(encoded(TunnelMsgOwned::Exit { code: 0 })),
+            Ok(encoded(TunnelMsgOwned::Exit { code: 3 })),
+        ]);
+        let commands: Vec<String> = vec![
+            "# warm up".into(),
+            "uptime".into(),
+            "".into(),
+            "false".into(),
+            "never".into(),
+        ];
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        let exit_code = drive_serial_shell_batch(
+            &mut serial,
+            &commands,
+            ShellOutput::Raw,
+            &mut stdout,
+            &mut stderr,
+        )
+        .expect("batch failed");
+
+        assert_eq!(exit_code, 3);
+        assert!(!String::from_utf8_lossy(&stderr).contains("serialsh> "));
+        assert_eq!(
+            serial.writes(),
+            &[
+                "INIT".to_string(),
+                encoded(TunnelMsgOwned::CmdRequest {
+                    cmd: "uptime".into()
+                }),
+                encoded(TunnelMsgOwned::CmdRequest {
+                    cmd: "false".into()
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn json_mode_emits_structured_records() {
+        let mut serial = FakeSerialPort::new(vec![
+            Ok(encoded(TunnelMsgOwned::Stdout {
+                chunk: b"up 3 days\n".to_vec(),
+            })),
+            Ok(encoded(TunnelMsgOwned::Stderr {
+                chunk: b"warn".to_vec(),
+            })),
+            Ok(encoded(TunnelMsgOwned::Exit { code: 0 })),
+        ]);
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        let exit_code = drive_serial_shell_batch(
+            &mut serial,
+            &["uptime".to_string()],
+            ShellOutput::Json,
+            &mut stdout,
+            &mut stderr,
+        )
+        .expect("batch failed");
+
+        assert_eq!(exit_code, 0);
+        assert!(stderr.is_empty());
+        let records: Vec<serde_json::Value> = String::from_utf8(stdout)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0]["type"], "stdout");
+        assert_eq!(records[0]["data"], "up 3 days\n");
+        assert_eq!(records[1]["type"], "stderr");
+        assert_eq!(records[2]["type"], "exit");
+        assert_eq!(records[2]["command"], "uptime");
+        assert_eq!(records[2]["code"], 0);
+    }
+
+    #[test]
+    fn local_builtins_report_history_and_stats_without_remote_traffic() {
+        let mut serial = FakeSerialPort::new(vec![
+            Ok(encoded(TunnelMsgOwned::Stdout {
+                chunk: b"up".to_vec(),
+            })),
+            Ok(encoded(TunnelMsgOwned::Exit { code: 2 })),
+        ]);
+        let mut input =
+            Cursor::new("uptime\n:history\n:stats\n:status\n:reconnect\n:quit\nnever\n");
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        let exit_code = drive_serial_shell_loop(&mut serial, &mut input, &mut stdout, &mut stderr)
+            .expect("loop failed");
+
+        // `:reconnect` fails on the fake transport, which becomes the last exit code.
+        assert_eq!(exit_code, 1);
+        let out = String::from_utf8_lossy(&stdout);
+        assert!(out.contains("    1  uptime"), "{out}");
+        assert!(out.contains("    2  :history"), "{out}");
+        assert!(out.contains("commands: 1 (1 failed, 0 busy)"), "{out}");
+        assert!(out.contains("output: 2 stdout bytes"), "{out}");
+        assert!(out.contains("last remote exit 2"), "{out}");
+        assert!(String::from_utf8_lossy(&stderr).contains("reconnect failed"));
+        assert_eq!(serial.writes().len(), 2, "built-ins must stay local");
+    }
+
+    #[test]
+    fn push_and_pull_transfer_files_through_tunnel_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join("report.txt");
+        fs::write(&local, vec![b'x'; PUSH_CHUNK_BYTES + 10]).unwrap();
+        let pulled = dir.path().join("copy.txt");
+        let mut serial = FakeSerialPort::new(vec![
+            Ok(encoded(TunnelMsgOwned::Exit { code: 0 })),
+            Ok(encoded(TunnelMsgOwned::Exit { code: 0 })),
+            Ok(encoded(TunnelMsgOwned::Stdout {
+                chunk: b"remote bytes".to_vec(),
+            })),
+            Ok(encoded(TunnelMsgOwned::Exit { code: 0 })),
+        ]);
+        let script = format!(
+            ":push {}\n:pull logs/remote.txt {}\nexit\n",
+            local.display(),
+            pulled.display()
+        );
+        let mut input = Cursor::new(script);
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        let exit_code = drive_serial_shell_loop(&mut serial, &mut input, &mut stdout, &mut stderr)
+            .expect("loop failed");
+
+        assert_eq!(exit_code, 0);
+        assert_eq!(fs::read(&pulled).unwrap(), b"remote bytes");
+        assert!(stdout.is_empty());
+        let writes = serial.writes();
+        assert_eq!(writes.len(), 4);
+        assert_eq!(
+            writes[2],
+            encoded(TunnelMsgOwned::FilePush {
+                path: "report.txt".into(),
+                offset: PUSH_CHUNK_BYTES as u64,
+                chunk: vec![b'x'; 10],
+            })
+        );
+        assert_eq!(
+            writes[3],
+            encoded(TunnelMsgOwned::FilePull {
+                path: "logs/remote.txt".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn failed_pull_keeps_existing_local_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join("keep.txt");
+        fs::write(&local, b"original").unwrap();
+        let mut serial = FakeSerialPort::new(vec![
+            Ok(encoded(TunnelMsgOwned::Stdout {
+                chunk: b"partial".to_vec(),
+            })),
+            Ok(encoded(TunnelMsgOwned::Stderr {
+                chunk: b"pull missing.txt: file not found\n".to_vec(),
+            })),
+            Ok(encoded(TunnelMsgOwned::Exit { code: 1 })),
+        ]);
+        let mut input = Cursor::new(format!(":pull missing.txt {}\nexit\n", local.display()));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        let exit_code = drive_serial_shell_loop(&mut serial, &mut input, &mut stdout, &mut stderr)
+            .expect("loop failed");
+
+        assert_eq!(exit_code, 1);
+        assert_eq!(fs::read(&local).unwrap(), b"original");
+        assert!(!staging_path(&local).exists());
+    }
+
+    #[test]
+    fn history_persists_and_skips_repeats() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("serialsh_history");
+        let mut history = ShellHistory::load(&path);
+        history.push("uptime");
+        history.push("uptime");
+        history.push(":stats");
+
+        let reloaded = ShellHistory::load(&path);
+        assert_eq!(
+            reloaded.entries(),
+            &["uptime".to_string(), ":stats".to_string()]
+        );
+    }
 }
@@ -1,23 +1,49 @@
+use super::audit::{now_ms, AuditDecision, AuditEntry, AuditLog};
+use super::file_transfer::FileTransferManager;
+use super::forward::ForwardMux;
 use super::Logger;
 use crate::app::events::{CommandEvent, CommandExecutor};
 use crate::{
     payload::{CommandMessage, CommandStream, TunnelMsgOwned},
     Result, CACHE_DIR,
 };
-use std::fs::{create_dir_all, OpenOptions};
-use std::io::{ErrorKind, Write};
+use std::collections::VecDeque;
+use std::fs::{create_dir_all, File, OpenOptions};
+use std::io::{ErrorKind, Read, Write};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU32, Ordering};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Audit and file-pull replies are split into stdout chunks small enough to stay under the frame cap.
+const REPLY_CHUNK_BYTES: usize = 512;
+
+/// A queued reply frame, or a pulled file still being read.
+enum Outgoing {
+    Frame(TunnelMsgOwned),
+    /// Send `remaining` more bytes of `file` as stdout chunks, then exit 0.
+    File {
+        file: File,
+        remaining: u64,
+    },
+}
 
 pub struct TunnelController {
     executor: CommandExecutor,
     request_counter: AtomicU32,
     tunnel_dir: PathBuf,
+    audit: AuditLog,
+    transfers: FileTransferManager,
+    forwards: ForwardMux,
+    pending: VecDeque<Outgoing>,
+    reply_chunk_bytes: usize,
 }
 
 impl TunnelController {
     pub fn new(allowlist: Vec<String>) -> Result<Self> {
+        Self::with_audit_log(allowlist, AuditLog::new())
+    }
+
+    pub fn with_audit_log(allowlist: Vec<String>, audit: AuditLog) -> Result<Self> {
         let tunnel_dir = PathBuf::from(CACHE_DIR).join("tunnel");
         match create_dir_all(&tunnel_dir) {
             Ok(_) => {}
@@ -32,12 +58,35 @@ impl TunnelController {
             Err(err) => return Err(err.into()),
         }
         Ok(Self {
-            executor: CommandExecutor::new(allowlist),
+            executor: CommandExecutor::with_audit(allowlist, audit.clone(), "tunnel"),
             request_counter: AtomicU32::new(1),
             tunnel_dir,
+            audit,
+            transfers: FileTransferManager::new(CACHE_DIR),
+            forwards: ForwardMux::server(Vec::new()),
+            pending: VecDeque::new(),
+            reply_chunk_bytes: REPLY_CHUNK_BYTES,
         })
     }
 
+    /// Accept `ForwardOpen` requests for these `host:port` targets.
+    pub fn with_forward_allowlist(mut self, allowlist: Vec<String>) -> Self {
+        self.forwards = ForwardMux::server(allowlist);
+        self
+    }
+
+    /// Shrink command output and reply chunks, e.g. when the link quality monitor sees errors.
+    pub fn set_chunk_size(&mut self, bytes: usize) {
+        self.reply_chunk_bytes = bytes.clamp(1, REPLY_CHUNK_BYTES);
+        self.executor.set_chunk_size(bytes);
+    }
+
+    #[cfg(test)]
+    fn with_transfer_dir(mut self, cache_dir: &str) -> Self {
+        self.transfers = FileTransferManager::new(cache_dir);
+        self
+    }
+
     pub fn handle_msg(&mut self, msg: TunnelMsgOwned, logger: &Logger) -> Option<TunnelMsgOwned> {
         match msg {
             TunnelMsgOwned::CmdRequest { cmd } => {
@@ -55,17 +104,200 @@ impl TunnelController {
                 }
                 None
             }
+            TunnelMsgOwned::AuditRequest { limit } => {
+                self.queue_audit_reply(limit as usize, logger);
+                None
+            }
+            TunnelMsgOwned::FilePush {
+                path,
+                offset,
+                chunk,
+            } => {
+                let started = Instant::now();
+                let result = self.transfers.receive_chunk(&path, offset, &chunk);
+                let argv = vec![path.clone(), offset.to_string()];
+                self.audit_transfer("file_push", argv, started, 0, result.as_ref().err(), logger);
+                match result {
+                    Ok(()) => Some(TunnelMsgOwned::Exit { code: 0 }),
+                    Err(err) => {
+                        logger.warn(format!("file push to {path} failed: {err}"));
+                        self.queue_failure(format!("push {path}: {err}"));
+                        None
+                    }
+                }
+            }
+            TunnelMsgOwned::FilePull { path } => {
+                let started = Instant::now();
+                let result = self.transfers.prepare_send(&path);
+                let sent = result.as_ref().map_or(0, |(_, len)| *len);
+                let argv = vec![path.clone()];
+                self.audit_transfer(
+                    "file_pull",
+                    argv,
+                    started,
+                    sent,
+                    result.as_ref().err(),
+                    logger,
+                );
+                match result {
+                    Ok((file, remaining)) => {
+                        self.pending.push_back(Outgoing::File { file, remaining })
+                    }
+                    Err(err) => {
+                        logger.warn(format!("file pull of {path} failed: {err}"));
+                        self.queue_failure(format!("pull {path}: {err}"));
+                    }
+                }
+                None
+            }
+            msg @ (TunnelMsgOwned::ForwardOpen { .. }
+            | TunnelMsgOwned::ForwardData { .. }
+            | TunnelMsgOwned::ForwardWindow { .. }
+            | TunnelMsgOwned::ForwardClose { .. }) => {
+                self.forwards.handle(msg);
+                None
+            }
             _ => None,
         }
     }
 
+    /// Answer a request this node must not serve (a client never executes tunnel work) with
+    /// `reason`. Returns `false` for replies and stream traffic, which are handled as usual.
+    pub fn refuse_request(&mut self, msg: &TunnelMsgOwned, reason: &str) -> bool {
+        match msg {
+            TunnelMsgOwned::CmdRequest { .. }
+            | TunnelMsgOwned::AuditRequest { .. }
+            | TunnelMsgOwned::FilePush { .. }
+            | TunnelMsgOwned::FilePull { .. } => self.queue_failure(reason.to_string()),
+            TunnelMsgOwned::ForwardOpen { stream_id, .. } => {
+                self.pending
+                    .push_back(Outgoing::Frame(TunnelMsgOwned::ForwardClose {
+                        stream_id: *stream_id,
+                        reason: Some(reason.to_string()),
+                    }))
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Service forwarded TCP streams once; call once per loop iteration so the per-poll
+    /// frame budget keeps LCD traffic flowing.
+    pub fn poll_forwards(&mut self) {
+        self.forwards.poll();
+    }
+
     pub fn next_outgoing(&mut self) -> Option<TunnelMsgOwned> {
+        if let Some(msg) = self.next_pending() {
+            return Some(msg);
+        }
         while let Some(msg) = self.executor.next_outgoing() {
             if let Some(frame) = command_message_to_tunnel(msg) {
                 return Some(frame);
             }
         }
-        None
+        self.forwards.next_outgoing()
+    }
+
+    /// Answer an `AuditRequest` with the newest audit lines as stdout followed by an exit code.
+    fn queue_audit_reply(&mut self, limit: usize, logger: &Logger) {
+        match self.audit.tail(limit) {
+            Ok(lines) => {
+                let mut body = Vec::new();
+                for line in lines {
+                    body.extend_from_slice(line.as_bytes());
+                    body.push(b'\n');
+                }
+                self.queue_stdout_reply(&body);
+            }
+            Err(err) => {
+                logger.warn(format!("audit log read failed: {err}"));
+                self.queue_failure(format!("audit log unavailable: {err}"));
+            }
+        }
+    }
+
+    /// Record a push chunk or pull in the audit log next to remote commands; `program` names
+    /// the operation and `argv` carries the path (and offset for pushes).
+    fn audit_transfer(
+        &self,
+        program: &str,
+        argv: Vec<String>,
+        started: Instant,
+        stdout_bytes: u64,
+        error: Option<&crate::Error>,
+        logger: &Logger,
+    ) {
+        let decision = match error {
+            Some(crate::Error::InvalidArgs(_)) => AuditDecision::Invalid,
+            _ => AuditDecision::Allowed,
+        };
+        let entry = AuditEntry {
+            timestamp_ms: now_ms(),
+            channel: "tunnel".into(),
+            request_id: self.request_counter.fetch_add(1, Ordering::SeqCst),
+            program: Some(program.to_string()),
+            argv,
+            decision,
+            exit_code: Some(if error.is_some() { 1 } else { 0 }),
+            duration_ms: started.elapsed().as_millis() as u64,
+            stdout_bytes,
+            stderr_bytes: 0,
+            error: error.map(|err| err.to_string()),
+        };
+        if let Err(err) = self.audit.append(&entry) {
+            logger.debug(format!("audit log write failed: {err}"));
+        }
+    }
+
+    fn queue_stdout_reply(&mut self, body: &[u8]) {
+        for chunk in body.chunks(self.reply_chunk_bytes) {
+            self.pending
+                .push_back(Outgoing::Frame(TunnelMsgOwned::Stdout {
+                    chunk: chunk.to_vec(),
+                }));
+        }
+        self.pending
+            .push_back(Outgoing::Frame(TunnelMsgOwned::Exit { code: 0 }));
+    }
+
+    fn queue_failure(&mut self, message: String) {
+        self.pending
+            .push_back(Outgoing::Frame(TunnelMsgOwned::Stderr {
+                chunk: format!("{message}\n").into_bytes(),
+            }));
+        self.pending
+            .push_back(Outgoing::Frame(TunnelMsgOwned::Exit { code: 1 }));
+    }
+
+    /// Pulled files are read one chunk per call, so a transfer only holds the frame being sent.
+    fn next_pending(&mut self) -> Option<TunnelMsgOwned> {
+        let Outgoing::File { file, remaining } = self.pending.front_mut()? else {
+            return match self.pending.pop_front() {
+                Some(Outgoing::Frame(msg)) => Some(msg),
+                _ => None,
+            };
+        };
+        if *remaining == 0 {
+            self.pending.pop_front();
+            return Some(TunnelMsgOwned::Exit { code: 0 });
+        }
+        let mut chunk = vec![0u8; self.reply_chunk_bytes.min(*remaining as usize)];
+        let failure = match file.read(&mut chunk) {
+            Ok(0) => "file shrank during the transfer".to_string(),
+            Ok(read) => {
+                chunk.truncate(read);
+                *remaining -= read as u64;
+                return Some(TunnelMsgOwned::Stdout { chunk });
+            }
+            Err(err) => err.to_string(),
+        };
+        self.pending.pop_front();
+        self.pending
+            .push_front(Outgoing::Frame(TunnelMsgOwned::Exit { code: 1 }));
+        Some(TunnelMsgOwned::Stderr {
+            chunk: format!("pull failed: {failure}\n").into_bytes(),
+        })
     }
 
     pub fn log_frame_error(&self, detail: &str, raw: &str) {
@@ -160,6 +392,175 @@ mod tests {
         assert!(matches!(final_exit, TunnelMsgOwned::Exit { code: 0 }));
     }
 
+    #[test]
+    fn refused_requests_reply_without_running() {
+        let mut controller = TunnelController::new(vec!["true".into()]).unwrap();
+        assert!(controller.refuse_request(
+            &TunnelMsgOwned::CmdRequest { cmd: "true".into() },
+            "client role"
+        ));
+        assert!(matches!(
+            controller.next_outgoing(),
+            Some(TunnelMsgOwned::Stderr { chunk }) if chunk == b"client role\n"
+        ));
+        assert!(matches!(
+            controller.next_outgoing(),
+            Some(TunnelMsgOwned::Exit { code: 1 })
+        ));
+
+        let open = TunnelMsgOwned::ForwardOpen {
+            stream_id: 7,
+            target: "127.0.0.1:22".into(),
+        };
+        assert!(controller.refuse_request(&open, "client role"));
+        assert!(matches!(
+            controller.next_outgoing(),
+            Some(TunnelMsgOwned::ForwardClose {
+                stream_id: 7,
+                reason: Some(_)
+            })
+        ));
+        assert!(!controller.refuse_request(&TunnelMsgOwned::Exit { code: 0 }, "client role"));
+    }
+
+    #[test]
+    fn audit_request_replays_recent_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit = AuditLog::with_path(dir.path().join("audit.jsonl"), 64 * 1024);
+        let mut controller =
+            TunnelController::with_audit_log(vec!["true".into()], audit.clone()).unwrap();
+        let logger = Logger::new(crate::app::logger::LogLevel::Info, None).unwrap();
+
+        assert!(controller
+            .handle_msg(TunnelMsgOwned::CmdRequest { cmd: "nope".into() }, &logger)
+            .is_some());
+        while controller.next_outgoing().is_some() {}
+
+        assert!(controller
+            .handle_msg(TunnelMsgOwned::AuditRequest { limit: 5 }, &logger)
+            .is_none());
+        let mut stdout = Vec::new();
+        let mut exit = None;
+        while let Some(msg) = controller.next_outgoing() {
+            match msg {
+                TunnelMsgOwned::Stdout { chunk } => stdout.extend(chunk),
+                TunnelMsgOwned::Exit { code } => exit = Some(code),
+                _ => {}
+            }
+        }
+        assert_eq!(exit, Some(0));
+        let text = String::from_utf8(stdout).unwrap();
+        assert!(text.contains("\"program\":\"nope\""), "{text}");
+        assert!(text.contains("\"decision\":\"denied\""), "{text}");
+    }
+
+    #[test]
+    fn file_push_then_pull_round_trips_through_transfer_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit = AuditLog::with_path(dir.path().join("audit.jsonl"), 64 * 1024);
+        let mut controller = TunnelController::with_audit_log(Vec::new(), audit.clone())
+            .unwrap()
+            .with_transfer_dir(dir.path().to_str().unwrap());
+        let logger = Logger::new(crate::app::logger::LogLevel::Info, None).unwrap();
+
+        let ack = controller.handle_msg(
+            TunnelMsgOwned::FilePush {
+                path: "notes.txt".into(),
+                offset: 0,
+                chunk: b"hi there".to_vec(),
+            },
+            &logger,
+        );
+        assert_eq!(ack, Some(TunnelMsgOwned::Exit { code: 0 }));
+
+        assert!(controller
+            .handle_msg(
+                TunnelMsgOwned::FilePull {
+                    path: "notes.txt".into(),
+                },
+                &logger,
+            )
+            .is_none());
+        assert_eq!(
+            controller.next_outgoing(),
+            Some(TunnelMsgOwned::Stdout {
+                chunk: b"hi there".to_vec(),
+            })
+        );
+        assert_eq!(
+            controller.next_outgoing(),
+            Some(TunnelMsgOwned::Exit { code: 0 })
+        );
+
+        assert!(controller
+            .handle_msg(
+                TunnelMsgOwned::FilePull {
+                    path: "../etc/passwd".into(),
+                },
+                &logger,
+            )
+            .is_none());
+        assert!(matches!(
+            controller.next_outgoing(),
+            Some(TunnelMsgOwned::Stderr { .. })
+        ));
+        assert_eq!(
+            controller.next_outgoing(),
+            Some(TunnelMsgOwned::Exit { code: 1 })
+        );
+
+        let entries: Vec<AuditEntry> = audit
+            .tail(10)
+            .unwrap()
+            .iter()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].program.as_deref(), Some("file_push"));
+        assert_eq!(entries[0].argv, vec!["notes.txt", "0"]);
+        assert_eq!(entries[1].program.as_deref(), Some("file_pull"));
+        assert_eq!(entries[1].stdout_bytes, 8);
+        assert_eq!(entries[2].decision, AuditDecision::Invalid);
+        assert_eq!(entries[2].exit_code, Some(1));
+    }
+
+    #[test]
+    fn file_pull_streams_the_body_in_chunks_after_queued_replies() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit = AuditLog::with_path(dir.path().join("audit.jsonl"), 64 * 1024);
+        let mut controller = TunnelController::with_audit_log(Vec::new(), audit)
+            .unwrap()
+            .with_transfer_dir(dir.path().to_str().unwrap());
+        controller.set_chunk_size(4);
+        let logger = Logger::new(crate::app::logger::LogLevel::Info, None).unwrap();
+        let jail = dir.path().join("transfers");
+        std::fs::create_dir_all(&jail).unwrap();
+        std::fs::write(jail.join("big.bin"), b"0123456789").unwrap();
+
+        controller.refuse_request(&TunnelMsgOwned::FilePull { path: "x".into() }, "busy");
+        controller.handle_msg(
+            TunnelMsgOwned::FilePull {
+                path: "big.bin".into(),
+            },
+            &logger,
+        );
+        let frames: Vec<TunnelMsgOwned> =
+            std::iter::from_fn(|| controller.next_outgoing()).collect();
+        let stdout = |chunk: &[u8]| TunnelMsgOwned::Stdout {
+            chunk: chunk.to_vec(),
+        };
+        assert_eq!(
+            frames[2..],
+            [
+                stdout(b"0123"),
+                stdout(b"4567"),
+                stdout(b"89"),
+                TunnelMsgOwned::Exit { code: 0 },
+            ]
+        );
+        assert_eq!(frames[1], TunnelMsgOwned::Exit { code: 1 });
+    }
+
     #[cfg(unix)]
     #[test]
     fn streams_stdout_chunks_before_exit() {
@@ -8,6 +8,13 @@ use std::time::{Duration, Instant};
 
 const WATCHDOG_DIR: &str = "watchdog";
 const HOOK_NAME: &str = "offline_hook.sh";
+/// Once RTT is measured, a channel expires after this many ping intervals without traffic
+/// plus `RTT_TIMEOUT_MULTIPLE` retransmission timeouts (`srtt + 4 * rttvar`).
+const RTT_MISSED_PINGS: u32 = 3;
+const RTT_TIMEOUT_MULTIPLE: u32 = 4;
+const RTT_TIMEOUT_FLOOR_MS: u64 = 1_000;
+/// Slow links may stretch the configured timeout up to this factor.
+const RTT_TIMEOUT_STRETCH: u32 = 2;
 
 /// Tracks last-seen timestamps for watchdog channels.
 #[derive(Debug, Clone)]
@@ -29,8 +36,25 @@ impl Watchdog {
     }
 
     pub fn is_expired_at(&self, now: Instant) -> bool {
-        now.duration_since(self.last_seen) > self.timeout
+        self.idle_at(now) > self.timeout
     }
+
+    /// Time since the channel was last touched.
+    pub fn idle_at(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_seen)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+/// Expiry derived from the measured retransmission timeout instead of the configured value,
+/// kept between a one-second floor and twice the configured timeout.
+fn rtt_timeout(rto: Duration, ping_interval: Duration, configured: Duration) -> Duration {
+    let derived = ping_interval * RTT_MISSED_PINGS + rto * RTT_TIMEOUT_MULTIPLE;
+    let floor = Duration::from_millis(RTT_TIMEOUT_FLOOR_MS).min(configured);
+    derived.clamp(floor, configured * RTT_TIMEOUT_STRETCH)
 }
 
 /// Describes state transitions for watchdog channels.
@@ -75,6 +99,8 @@ impl WatchdogLog {
 pub struct WatchdogMonitor {
     serial: Watchdog,
     tunnel: Watchdog,
+    serial_configured: Duration,
+    tunnel_configured: Duration,
     serial_expired: bool,
     tunnel_expired: bool,
     hook_invoked: bool,
@@ -86,6 +112,8 @@ impl WatchdogMonitor {
         Self {
             serial: Watchdog::new(serial_timeout_ms),
             tunnel: Watchdog::new(tunnel_timeout_ms),
+            serial_configured: Duration::from_millis(serial_timeout_ms),
+            tunnel_configured: Duration::from_millis(tunnel_timeout_ms),
             serial_expired: false,
             tunnel_expired: false,
             hook_invoked: false,
@@ -101,6 +129,27 @@ impl WatchdogMonitor {
         self.tunnel.touch();
     }
 
+    /// Time since serial traffic was last seen.
+    pub fn serial_idle(&self, now: Instant) -> Duration {
+        self.serial.idle_at(now)
+    }
+
+    /// Scale both expiries to the measured `rto` when pings go out every `ping_interval`;
+    /// `None` (no samples yet, or a fresh link) restores the configured timeouts.
+    pub fn adapt_to_rtt(&mut self, rto: Option<Duration>, ping_interval: Duration) {
+        self.serial.timeout = rto.map_or(self.serial_configured, |rto| {
+            rtt_timeout(rto, ping_interval, self.serial_configured)
+        });
+        self.tunnel.timeout = rto.map_or(self.tunnel_configured, |rto| {
+            rtt_timeout(rto, ping_interval, self.tunnel_configured)
+        });
+    }
+
+    /// Current serial and tunnel expiries.
+    pub fn timeouts(&self) -> (Duration, Duration) {
+        (self.serial.timeout(), self.tunnel.timeout())
+    }
+
     /// Evaluate watchdogs and emit transition status.
     pub fn evaluate(&mut self, logger: &Logger) -> WatchdogStatus {
         let now = Instant::now();
@@ -188,6 +237,7 @@ mod tests {
         let mut w = Watchdog::new(5);
         sleep(Duration::from_millis(10));
         assert!(w.is_expired_at(Instant::now()));
+        assert!(w.idle_at(Instant::now()) >= Duration::from_millis(10));
         w.touch();
         assert!(!w.is_expired_at(Instant::now()));
     }
@@ -207,4 +257,29 @@ mod tests {
         assert!(recovered.serial_recovered);
         assert!(recovered.tunnel_recovered);
     }
+
+    #[test]
+    fn rtt_scales_expiry_within_bounds() {
+        let mut monitor = WatchdogMonitor::new(12_000, 5_000);
+        let ping = Duration::from_millis(1_000);
+
+        monitor.adapt_to_rtt(Some(Duration::from_millis(50)), ping);
+        assert_eq!(
+            monitor.timeouts(),
+            (Duration::from_millis(3_200), Duration::from_millis(3_200))
+        );
+
+        // A slow link stretches, but never past twice the configured value.
+        monitor.adapt_to_rtt(Some(Duration::from_millis(2_000)), ping);
+        assert_eq!(
+            monitor.timeouts(),
+            (Duration::from_millis(11_000), Duration::from_millis(10_000))
+        );
+
+        monitor.adapt_to_rtt(None, ping);
+        assert_eq!(
+            monitor.timeouts(),
+            (Duration::from_millis(12_000), Duration::from_millis(5_000))
+        );
+    }
 }
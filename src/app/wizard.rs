@@ -307,6 +307,7 @@ impl FirstRunWizard {
                 parity: self.defaults.parity,
                 stop_bits: self.defaults.stop_bits,
                 dtr: self.defaults.dtr_on_open,
+                rts_driver_enable: false,
             };
             let (chosen, attempts) = run_link_speed_rehearsal(
                 &device,
@@ -623,6 +624,7 @@ fn rehearsal_handshake<IO: crate::serial::LineIo>(
                     peer_caps: crate::negotiation::ControlCaps {
                         bits: negotiator.local_caps().bits(),
                     },
+                    proto_version: None,
                 };
                 let ack_payload = serde_json::to_string(&ack)
                     .map_err(|e| crate::Error::Parse(format!("json: {e}")))?;
@@ -630,8 +632,14 @@ fn rehearsal_handshake<IO: crate::serial::LineIo>(
                 continue;
             }
             Ok(crate::negotiation::ControlFrame::HelloAck { .. }) => return Ok(()),
-            Ok(crate::negotiation::ControlFrame::LegacyFallback) => {
-                return Err(crate::Error::Parse("peer requested legacy fallback".into()))
+            Ok(crate::negotiation::ControlFrame::BaudCheck { .. })
+            | Ok(crate::negotiation::ControlFrame::BaudCommit { .. })
+            | Ok(crate::negotiation::ControlFrame::Renegotiate { .. }) => continue,
+            Ok(crate::negotiation::ControlFrame::LegacyFallback { reason }) => {
+                return Err(crate::Error::Parse(format!(
+                    "peer requested legacy fallback{}",
+                    reason.map(|r| format!(": {r}")).unwrap_or_default()
+                )))
             }
             Err(_) => {
                 return Err(crate::Error::Parse(
@@ -950,17 +958,12 @@ struct WizardDisplay {
 impl WizardDisplay {
     fn new(defaults: &Config, attempt_lcd: bool) -> Self {
         let lcd = if attempt_lcd {
-            Lcd::new(
-                defaults.cols,
-                defaults.rows,
-                defaults.pcf8574_addr.clone(),
-                defaults.display_driver,
-            )
-            .map_err(|err| {
-                eprintln!("lifelinetty wizard: LCD unavailable ({err})");
-                err
-            })
-            .ok()
+            Lcd::new(defaults.cols, defaults.rows, &defaults.lcd_wiring())
+                .map_err(|err| {
+                    eprintln!("lifelinetty wizard: LCD unavailable ({err})");
+                    err
+                })
+                .ok()
         } else {
             None
         };
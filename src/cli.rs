@@ -13,6 +13,62 @@ pub enum RunMode {
     Daemon,
     /// P7: CLI integration groundwork for the serial shell preview gate.
     SerialShell,
+    /// Listen on local TCP ports and carry connections through the tunnel (`--forward`).
+    Forward,
+}
+
+/// Where frames are drawn (`--display`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayBackend {
+    /// The configured LCD hardware (or the silent stub when `lcd_present = false`).
+    #[default]
+    Hardware,
+    /// A simulated panel drawn in the terminal for developing payloads without hardware.
+    Virtual,
+    /// No output at all; pair with `--screenshot`/`--record` for CI.
+    Headless,
+}
+
+impl std::str::FromStr for DisplayBackend {
+    type Err = String;
+
+    fn from_str(raw: &str) -> std::result::Result<Self, Self::Err> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "hardware" | "lcd" => Ok(DisplayBackend::Hardware),
+            "virtual" | "tui" => Ok(DisplayBackend::Virtual),
+            "headless" | "stub" => Ok(DisplayBackend::Headless),
+            other => Err(format!(
+                "display must be 'hardware', 'virtual', or 'headless', got '{other}'"
+            )),
+        }
+    }
+}
+
+/// One `--forward <local_port>:<host>:<port>` listener, in the spirit of `ssh -L`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForwardSpec {
+    pub local_port: u16,
+    /// `host:port` the far side connects to; must be on its `forward_allowlist`.
+    pub target: String,
+}
+
+impl std::str::FromStr for ForwardSpec {
+    type Err = String;
+
+    fn from_str(raw: &str) -> std::result::Result<Self, Self::Err> {
+        let usage = || format!("forward spec must be <local_port>:<host>:<port> (got '{raw}')");
+        let (local, target) = raw.split_once(':').ok_or_else(usage)?;
+        let local_port = local
+            .parse::<u16>()
+            .ok()
+            .filter(|port| *port > 0)
+            .ok_or_else(usage)?;
+        crate::config::parse_host_port(target).ok_or_else(usage)?;
+        Ok(Self {
+            local_port,
+            target: target.to_string(),
+        })
+    }
 }
 
 /// Options for the `run` command; values are `None` when not provided on CLI.
@@ -38,9 +94,26 @@ pub struct RunOptions {
     pub compression_enabled: Option<bool>,
     pub compression_codec: Option<CompressionCodec>,
     pub demo: bool,
+    pub display_backend: Option<DisplayBackend>,
+    /// Write every completed frame to this `.cast` (asciicast v2) or JSON-lines file.
+    pub record_path: Option<String>,
+    /// Keep a PNG of the current screen at this path.
+    pub screenshot_path: Option<String>,
     pub polling_enabled: Option<bool>,
     pub poll_interval_ms: Option<u64>,
     pub wizard: bool,
+    /// Detect baud, parity and stop bits from the peer's traffic before connecting.
+    pub autobaud: bool,
+    /// Also write the detected line settings to `~/.serial_lcd/config.toml` (implies `autobaud`).
+    pub autobaud_save: bool,
+    /// Run a single remote command and exit with its code (`--serialsh -c`).
+    pub serialsh_command: Option<String>,
+    /// Run each line of a script file (or `-` for stdin) without prompting.
+    pub serialsh_script: Option<String>,
+    /// Emit structured JSON records instead of raw remote output.
+    pub serialsh_json: bool,
+    /// Local listeners requested with `--forward` (repeatable).
+    pub forwards: Vec<ForwardSpec>,
 }
 
 /// Parsed command-line intent.
@@ -83,17 +156,21 @@ impl Command {
         );
 
         help.push_str(
-            "  --config-file <path>           Load config from the provided TOML instead of ~/.serial_lcd/config.toml (env overrides still apply)\n  --polling                      Enable hardware polling (default: config)\n  --no-polling                   Disable hardware polling even if config enables it\n  --poll-interval-ms <number>    Polling interval in milliseconds (default: 5000)\n  --compressed                   Enable schema compression (applies to schema_v1 payloads)\n  --no-compressed                Disable compression even if config enables it\n  --codec <lz4|zstd>             Codec to use when compression is enabled (default: lz4)\n  --demo                         Run built-in demo pages on the LCD (no serial input)\n",
+            "  --config-file <path>           Load config from the provided TOML instead of ~/.serial_lcd/config.toml (env overrides still apply)\n  --polling                      Enable hardware polling (default: config)\n  --no-polling                   Disable hardware polling even if config enables it\n  --poll-interval-ms <number>    Polling interval in milliseconds (default: 5000)\n  --compressed                   Enable schema compression (applies to schema_v1 payloads)\n  --no-compressed                Disable compression even if config enables it\n  --codec <lz4|zstd>             Codec to use when compression is enabled (default: lz4)\n  --demo                         Run built-in demo pages on the LCD (no serial input)\n  --display <hardware|virtual|headless>  Draw on the LCD hardware, on a simulated panel in this terminal, or nowhere (default: hardware)\n  --record <path>                Record every frame shown to an asciicast (.cast) or JSON-lines file\n  --screenshot <path>            Keep a PNG of the current screen at <path> (rewritten on every change)\n",
         );
 
         help.push_str(
-            "  --serialsh                   Enable the optional serial shell that runs commands over the tunnel and streams remote stdout/stderr + exit codes\n",
+            "  --serialsh                   Enable the optional serial shell that runs commands over the tunnel and streams remote stdout/stderr + exit codes\n  -c <command>                 With --serialsh: run one remote command and exit with its exit code\n  --script <path|->            With --serialsh: run each line of a file (or stdin) and stop at the first non-zero exit\n  --json                       With --serialsh: print stdout/stderr/exit as JSON lines instead of raw output\n  --forward <lport:host:port>  Forward a local TCP port through the tunnel to an allowlisted host:port on the far side (repeatable)\n",
         );
 
         help.push_str(
             "  --wizard                    Run the guided first-run setup wizard even if a config already exists\n",
         );
 
+        help.push_str(
+            "  --autobaud                  Detect baud, parity and stop bits from the peer's traffic before connecting\n  --autobaud-save             Like --autobaud, then write the detected settings to ~/.serial_lcd/config.toml\n",
+        );
+
         help.push_str("  -h, --help        Show this help\n  -V, --version     Show version\n");
         help
     }
@@ -212,6 +289,17 @@ fn parse_run_options(iter: &mut std::slice::Iter<String>) -> Result<RunOptions>
             "--demo" => {
                 opts.demo = true;
             }
+            "--display" => {
+                let raw = take_value(flag, iter)?;
+                opts.display_backend =
+                    Some(raw.parse().map_err(|e: String| Error::InvalidArgs(e))?);
+            }
+            "--record" => {
+                opts.record_path = Some(take_value(flag, iter)?);
+            }
+            "--screenshot" => {
+                opts.screenshot_path = Some(take_value(flag, iter)?);
+            }
             "--serialsh" => {
                 // Milestone G: run the CLI serial shell through the command tunnel.
                 opts.mode = RunMode::SerialShell;
@@ -219,6 +307,30 @@ fn parse_run_options(iter: &mut std::slice::Iter<String>) -> Result<RunOptions>
             "--wizard" => {
                 opts.wizard = true;
             }
+            "--autobaud" => {
+                opts.autobaud = true;
+            }
+            "--autobaud-save" => {
+                opts.autobaud = true;
+                opts.autobaud_save = true;
+            }
+            "-c" => {
+                opts.serialsh_command = Some(take_value(flag, iter)?);
+            }
+            "--script" => {
+                opts.serialsh_script = Some(take_value(flag, iter)?);
+            }
+            "--json" => {
+                opts.serialsh_json = true;
+            }
+            "--forward" => {
+                let raw = take_value(flag, iter)?;
+                opts.forwards
+                    .push(raw.parse().map_err(|e: String| Error::InvalidArgs(e))?);
+                if opts.mode == RunMode::Daemon {
+                    opts.mode = RunMode::Forward;
+                }
+            }
             other => {
                 return Err(Error::InvalidArgs(format!(
                     "unknown flag '{other}', try --help"
@@ -228,6 +340,8 @@ fn parse_run_options(iter: &mut std::slice::Iter<String>) -> Result<RunOptions>
     }
 
     validate_serialsh_options(&opts)?;
+    validate_forward_options(&opts)?;
+    validate_autobaud_options(&opts)?;
     Ok(opts)
 }
 
@@ -243,6 +357,40 @@ fn validate_serialsh_options(opts: &RunOptions) -> Result<()> {
             "--serialsh cannot be combined with --demo or --payload-file".to_string(),
         ));
     }
+    let batch_flags =
+        opts.serialsh_command.is_some() || opts.serialsh_script.is_some() || opts.serialsh_json;
+    if batch_flags && !matches!(opts.mode, RunMode::SerialShell) {
+        return Err(Error::InvalidArgs(
+            "-c, --script, and --json require --serialsh".to_string(),
+        ));
+    }
+    if opts.serialsh_command.is_some() && opts.serialsh_script.is_some() {
+        return Err(Error::InvalidArgs(
+            "-c and --script cannot be combined".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_forward_options(opts: &RunOptions) -> Result<()> {
+    if !opts.forwards.is_empty()
+        && (opts.mode != RunMode::Forward || opts.payload_file.is_some() || opts.demo)
+    {
+        return Err(Error::InvalidArgs(
+            "--forward cannot be combined with --serialsh, --demo, or --payload-file".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Detection runs in the daemon's connect path; the other modes open the port themselves.
+fn validate_autobaud_options(opts: &RunOptions) -> Result<()> {
+    if opts.autobaud && (opts.mode != RunMode::Daemon || opts.payload_file.is_some() || opts.demo) {
+        return Err(Error::InvalidArgs(
+            "--autobaud cannot be combined with --serialsh, --forward, --demo, or --payload-file"
+                .to_string(),
+        ));
+    }
     Ok(())
 }
 
@@ -315,7 +463,16 @@ mod tests {
             polling_enabled: None,
             poll_interval_ms: None,
             demo: true,
+            display_backend: None,
+            record_path: None,
+            screenshot_path: None,
             wizard: false,
+            autobaud: false,
+            autobaud_save: false,
+            serialsh_command: None,
+            serialsh_script: None,
+            serialsh_json: false,
+            forwards: Vec::new(),
         };
         let cmd = Command::parse(&args).unwrap();
         assert_eq!(cmd, Command::Run(Box::new(expected)));
@@ -352,7 +509,16 @@ mod tests {
             polling_enabled: None,
             poll_interval_ms: None,
             demo: false,
+            display_backend: None,
+            record_path: None,
+            screenshot_path: None,
             wizard: false,
+            autobaud: false,
+            autobaud_save: false,
+            serialsh_command: None,
+            serialsh_script: None,
+            serialsh_json: false,
+            forwards: Vec::new(),
         };
         let cmd = Command::parse(&args).unwrap();
         assert_eq!(cmd, Command::Run(Box::new(expected)));
@@ -408,6 +574,49 @@ mod tests {
         assert_eq!(cmd, Command::Run(Box::new(expected)));
     }
 
+    #[test]
+    fn parse_display_backend_flag() {
+        let args = vec!["--display".into(), "virtual".into()];
+        let expected = RunOptions {
+            display_backend: Some(DisplayBackend::Virtual),
+            ..Default::default()
+        };
+        let cmd = Command::parse(&args).unwrap();
+        assert_eq!(cmd, Command::Run(Box::new(expected)));
+        let args = vec!["--display".into(), "oled".into()];
+        assert!(Command::parse(&args).is_err());
+
+        let args = vec![
+            "--display".into(),
+            "headless".into(),
+            "--record".into(),
+            "frames.cast".into(),
+            "--screenshot".into(),
+            "lcd.png".into(),
+        ];
+        let expected = RunOptions {
+            display_backend: Some(DisplayBackend::Headless),
+            record_path: Some("frames.cast".into()),
+            screenshot_path: Some("lcd.png".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            Command::parse(&args).unwrap(),
+            Command::Run(Box::new(expected))
+        );
+    }
+
+    #[test]
+    fn parse_autobaud_flags() {
+        let cmd = Command::parse(&["--autobaud-save".into()]).unwrap();
+        match cmd {
+            Command::Run(opts) => assert!(opts.autobaud && opts.autobaud_save),
+            other => panic!("expected Run variant, got {other:?}"),
+        }
+        let err = Command::parse(&["--autobaud".into(), "--demo".into()]).unwrap_err();
+        assert!(format!("{err}").contains("--autobaud cannot be combined"));
+    }
+
     #[test]
     fn parse_help() {
         let args = vec!["--help".into()];
@@ -468,4 +677,85 @@ mod tests {
         let err = Command::parse(&args).unwrap_err();
         assert!(format!("{err}").contains("serialsh"));
     }
+
+    #[test]
+    fn parse_serialsh_batch_flags() {
+        let args = vec![
+            "--serialsh".into(),
+            "-c".into(),
+            "uptime".into(),
+            "--json".into(),
+        ];
+        let expected = RunOptions {
+            mode: RunMode::SerialShell,
+            serialsh_command: Some("uptime".into()),
+            serialsh_json: true,
+            ..Default::default()
+        };
+        let cmd = Command::parse(&args).unwrap();
+        assert_eq!(cmd, Command::Run(Box::new(expected)));
+    }
+
+    #[test]
+    fn serialsh_batch_flags_require_serialsh_and_are_exclusive() {
+        let args = vec!["--script".into(), "jobs.txt".into()];
+        let err = Command::parse(&args).unwrap_err();
+        assert!(format!("{err}").contains("require --serialsh"));
+
+        let args = vec![
+            "--serialsh".into(),
+            "-c".into(),
+            "uptime".into(),
+            "--script".into(),
+            "jobs.txt".into(),
+        ];
+        let err = Command::parse(&args).unwrap_err();
+        assert!(format!("{err}").contains("cannot be combined"));
+    }
+
+    #[test]
+    fn parse_forward_flags_select_forward_mode() {
+        let args = vec![
+            "--forward".into(),
+            "8080:127.0.0.1:80".into(),
+            "--forward".into(),
+            "2222:localhost:22".into(),
+        ];
+        let cmd = Command::parse(&args).unwrap();
+        match cmd {
+            Command::Run(opts) => {
+                assert_eq!(opts.mode, RunMode::Forward);
+                assert_eq!(
+                    opts.forwards,
+                    vec![
+                        ForwardSpec {
+                            local_port: 8080,
+                            target: "127.0.0.1:80".into(),
+                        },
+                        ForwardSpec {
+                            local_port: 2222,
+                            target: "localhost:22".into(),
+                        },
+                    ]
+                );
+            }
+            other => panic!("expected Run variant, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn forward_rejects_bad_specs_and_serialsh() {
+        for bad in ["8080", "0:host:22", "8080:host", "8080:host:0"] {
+            let args = vec!["--forward".into(), bad.into()];
+            let err = Command::parse(&args).unwrap_err();
+            assert!(format!("{err}").contains("forward spec"), "{bad}: {err}");
+        }
+        let args = vec![
+            "--serialsh".into(),
+            "--forward".into(),
+            "8080:127.0.0.1:80".into(),
+        ];
+        let err = Command::parse(&args).unwrap_err();
+        assert!(format!("{err}").contains("--forward cannot be combined"));
+    }
 }
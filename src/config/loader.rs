@@ -6,10 +6,11 @@ use std::{
 
 use crate::{compression::CompressionCodec, Error, Result};
 
-use super::{Config, CONFIG_DIR_NAME, CONFIG_FILE_NAME};
+use super::{Config, DisplayConfig, CONFIG_DIR_NAME, CONFIG_FILE_NAME, DISPLAY_SECTION_PREFIX};
 
 const REQUIRED_KEYS: &[&str] = &[
     "device",
+    "device_match",
     "baud",
     "flow_control",
     "parity",
@@ -25,16 +26,43 @@ const REQUIRED_KEYS: &[&str] = &[
     "poll_interval_ms",
     "button_gpio_pin",
     "pcf8574_addr",
+    "i2c_bus",
     "display_driver",
+    "gpio_pins",
+    "lcd_backpack",
+    "lcd_pin_map",
+    "backlight_rgb",
+    "character_rom",
+    "replacement_char",
     "backoff_initial_ms",
     "backoff_max_ms",
     "watchdog.serial_timeout_ms",
     "watchdog.tunnel_timeout_ms",
+    "backlight.brightness",
+    "backlight.idle_timeout_ms",
+    "backlight.schedule",
     "negotiation.node_id",
     "negotiation.preference",
     "negotiation.timeout_ms",
+    "negotiation.reliable_delivery",
+    "negotiation.max_baud",
+    "negotiation.node_name",
+    "negotiation.peer_uuid",
+    "negotiation.peer_mismatch",
     "protocol.schema_version",
+    "bus.mode",
+    "bus.address",
+    "bus.master_address",
+    "bus.nodes",
+    "bus.rts_driver_enable",
+    "bus.turn_timeout_ms",
+    "link_quality.adapt",
+    "link_quality.window_ms",
+    "link_quality.max_error_rate_pct",
+    "link_quality.max_rtt_ms",
+    "link_quality.recovery_windows",
     "command_allowlist",
+    "forward_allowlist",
 ];
 
 pub fn load_or_default() -> Result<Config> {
@@ -109,6 +137,7 @@ pub fn save_to_path(config: &Config, path: &Path) -> Result<()> {
     let contents = format!(
         "# lifelinetty config\n\
 device = \"{}\"\n\
+device_match = {}\n\
 baud = {}\n\
 flow_control = \"{}\"\n\
 parity = \"{}\"\n\
@@ -124,20 +153,54 @@ page_timeout_ms = {}\n\
     poll_interval_ms = {}\n\
     button_gpio_pin = {}\n\
 pcf8574_addr = {}\n\
+i2c_bus = {}\n\
 display_driver = {}\n\
+gpio_pins = {}\n\
+lcd_backpack = \"{}\"\n\
+lcd_pin_map = {}\n\
+backlight_rgb = {}\n\
+character_rom = \"{}\"\n\
+replacement_char = \"{}\"\n\
 backoff_initial_ms = {}\n\
 backoff_max_ms = {}\n\
 [watchdog]\n\
 serial_timeout_ms = {}\n\
 tunnel_timeout_ms = {}\n\
+[backlight]\n\
+brightness = {}\n\
+idle_timeout_ms = {}\n\
+schedule = {}\n\
 [protocol]\n\
 schema_version = {}\n\
 compression = {{ enabled = {}, codec = \"{}\" }}\n\
 [negotiation]\n\
 node_id = {}\n\
 preference = \"{}\"\n\
-timeout_ms = {}\n",
+timeout_ms = {}\n\
+reliable_delivery = {}\n\
+max_baud = {}\n\
+node_name = {}\n\
+peer_uuid = {}\n\
+peer_mismatch = \"{}\"\n\
+[bus]\n\
+mode = \"{}\"\n\
+address = {}\n\
+master_address = {}\n\
+nodes = [{}]\n\
+rts_driver_enable = {}\n\
+turn_timeout_ms = {}\n\
+[link_quality]\n\
+adapt = {}\n\
+window_ms = {}\n\
+max_error_rate_pct = {}\n\
+max_rtt_ms = {}\n\
+recovery_windows = {}\n",
         config.device,
+        config
+            .device_match
+            .as_ref()
+            .map(|selector| format!("\"{selector}\""))
+            .unwrap_or_else(|| "null".into()),
         config.baud,
         config.flow_control,
         config.parity,
@@ -156,23 +219,197 @@ timeout_ms = {}\n",
             .map(|p| p.to_string())
             .unwrap_or_else(|| "null".into()),
         super::format_pcf_addr(&config.pcf8574_addr),
+        format_optional(config.i2c_bus),
         super::format_display_driver(&config.display_driver),
+        config
+            .gpio_pins
+            .as_ref()
+            .map(|pins| format!("\"{pins}\""))
+            .unwrap_or_else(|| "null".into()),
+        config.lcd_backpack,
+        config
+            .lcd_pin_map
+            .map(|pins| format!("\"{pins}\""))
+            .unwrap_or_else(|| "null".into()),
+        config
+            .backlight_rgb
+            .as_ref()
+            .map(|rgb| format!("\"{rgb}\""))
+            .unwrap_or_else(|| "null".into()),
+        config.character_rom,
+        config.replacement_char,
         config.backoff_initial_ms,
         config.backoff_max_ms,
         config.watchdog.serial_timeout_ms,
         config.watchdog.tunnel_timeout_ms,
+        config.backlight.brightness,
+        config.backlight.idle_timeout_ms,
+        if config.backlight.schedule.is_empty() {
+            "null".to_string()
+        } else {
+            format!("\"{}\"", config.backlight.schedule)
+        },
         config.protocol.schema_version,
         config.protocol.compression_enabled,
         config.protocol.compression_codec.as_str(),
         config.negotiation.node_id,
         config.negotiation.preference,
         config.negotiation.timeout_ms,
+        config.negotiation.reliable_delivery,
+        config
+            .negotiation
+            .max_baud
+            .map(|baud| baud.to_string())
+            .unwrap_or_else(|| "null".into()),
+        config
+            .negotiation
+            .node_name
+            .as_ref()
+            .map(|name| format!("\"{name}\""))
+            .unwrap_or_else(|| "null".into()),
+        config
+            .negotiation
+            .peer_uuid
+            .as_ref()
+            .map(|uuid| format!("\"{uuid}\""))
+            .unwrap_or_else(|| "null".into()),
+        config.negotiation.peer_mismatch,
+        config.bus.mode,
+        config.bus.address,
+        config.bus.master_address,
+        config
+            .bus
+            .nodes
+            .iter()
+            .map(|node| node.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        config.bus.rts_driver_enable,
+        config.bus.turn_timeout_ms,
+        config.link_quality.adapt,
+        config.link_quality.window_ms,
+        config.link_quality.max_error_rate_pct,
+        config
+            .link_quality
+            .max_rtt_ms
+            .map(|rtt| rtt.to_string())
+            .unwrap_or_else(|| "null".into()),
+        config.link_quality.recovery_windows,
     );
-    let contents = format!("{contents}\ncommand_allowlist = {allowlist}\n");
+    let forward_allowlist = format_string_array(&config.forward_allowlist);
+    let mut contents = format!(
+        "{contents}\ncommand_allowlist = {allowlist}\nforward_allowlist = {forward_allowlist}\n"
+    );
+    for display in &config.displays {
+        contents.push_str(&format_display_section(display));
+    }
     fs::write(path, contents)?;
     Ok(())
 }
 
+/// A `[display.<name>]` section; a blank line first so the parser leaves the previous section.
+fn format_display_section(display: &DisplayConfig) -> String {
+    let quoted = |value: Option<String>| value.map(|v| format!("\"{v}\"")).unwrap_or("null".into());
+    format!(
+        "\n[{DISPLAY_SECTION_PREFIX}{}]\n\
+cols = {}\n\
+rows = {}\n\
+i2c_bus = {}\n\
+pcf8574_addr = {}\n\
+display_driver = {}\n\
+gpio_pins = {}\n\
+lcd_backpack = \"{}\"\n\
+lcd_pin_map = {}\n\
+backlight_rgb = {}\n",
+        display.name,
+        display.cols,
+        display.rows,
+        format_optional(display.i2c_bus),
+        super::format_pcf_addr(&display.pcf8574_addr),
+        super::format_display_driver(&display.display_driver),
+        quoted(display.gpio_pins.as_ref().map(ToString::to_string)),
+        display.lcd_backpack,
+        quoted(display.lcd_pin_map.map(|pins| pins.to_string())),
+        quoted(display.backlight_rgb.as_ref().map(ToString::to_string)),
+    )
+}
+
+fn format_optional(value: Option<u8>) -> String {
+    value
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "null".into())
+}
+
+/// Apply one `display.<name>.<key>` line, creating the display on first sight.
+fn parse_display_key(
+    displays: &mut Vec<DisplayConfig>,
+    full_key: &str,
+    value: &str,
+    line: usize,
+) -> Result<()> {
+    let rest = &full_key[DISPLAY_SECTION_PREFIX.len()..];
+    let Some((name, key)) = rest.rsplit_once('.') else {
+        return Err(Error::InvalidArgs(format!(
+            "unknown config key '{full_key}' on line {line}"
+        )));
+    };
+    let invalid = |e: String| Error::InvalidArgs(format!("invalid {full_key} on line {line}: {e}"));
+    let optional = |value: &str| (value != "null").then(|| value.to_string());
+    let idx = match displays.iter().position(|d| d.name == name) {
+        Some(idx) => idx,
+        None => {
+            displays.push(DisplayConfig::new(name));
+            displays.len() - 1
+        }
+    };
+    let display = &mut displays[idx];
+    match key {
+        "cols" => {
+            display.cols = value
+                .parse()
+                .map_err(|_| invalid("expected a number".into()))?
+        }
+        "rows" => {
+            display.rows = value
+                .parse()
+                .map_err(|_| invalid("expected a number".into()))?
+        }
+        "i2c_bus" => {
+            display.i2c_bus = optional(value)
+                .map(|v| v.parse())
+                .transpose()
+                .map_err(|_| invalid("expected a bus number or null".into()))?;
+        }
+        "pcf8574_addr" => display.pcf8574_addr = super::parse_pcf_addr(value).map_err(invalid)?,
+        "display_driver" => display.display_driver = value.parse().map_err(invalid)?,
+        "gpio_pins" => {
+            display.gpio_pins = optional(value)
+                .map(|v| v.parse())
+                .transpose()
+                .map_err(invalid)?;
+        }
+        "lcd_backpack" => display.lcd_backpack = value.parse().map_err(invalid)?,
+        "lcd_pin_map" => {
+            display.lcd_pin_map = optional(value)
+                .map(|v| v.parse())
+                .transpose()
+                .map_err(invalid)?;
+        }
+        "backlight_rgb" => {
+            display.backlight_rgb = optional(value)
+                .map(|v| v.parse())
+                .transpose()
+                .map_err(invalid)?;
+        }
+        _ => {
+            return Err(Error::InvalidArgs(format!(
+                "unknown config key '{full_key}' on line {line}"
+            )));
+        }
+    }
+    Ok(())
+}
+
 pub fn parse(raw: &str) -> Result<Config> {
     parse_with_seen(raw).map(|(cfg, _)| cfg)
 }
@@ -210,6 +447,15 @@ fn parse_with_seen(raw: &str) -> Result<(Config, HashSet<String>)> {
         seen_keys.insert(full_key.clone());
         match full_key.as_str() {
             "device" => cfg.device = value.to_string(),
+            "device_match" => {
+                cfg.device_match = if value == "null" {
+                    None
+                } else {
+                    Some(value.parse().map_err(|e: String| {
+                        Error::InvalidArgs(format!("invalid device_match on line {}: {e}", idx + 1))
+                    })?)
+                };
+            }
             "baud" => {
                 cfg.baud = value.parse().map_err(|_| {
                     Error::InvalidArgs(format!("invalid baud value on line {}", idx + 1))
@@ -280,6 +526,24 @@ fn parse_with_seen(raw: &str) -> Result<(Config, HashSet<String>)> {
                     Error::InvalidArgs(format!("invalid pcf8574_addr on line {}: {e}", idx + 1))
                 })?;
             }
+            "character_rom" => {
+                cfg.character_rom = value.parse().map_err(|e: String| {
+                    Error::InvalidArgs(format!("invalid character_rom on line {}: {e}", idx + 1))
+                })?;
+            }
+            "replacement_char" => {
+                let mut chars = value.chars();
+                cfg.replacement_char = match (chars.next(), chars.next()) {
+                    (Some(ch), None) => Some(ch),
+                    _ => None,
+                }
+                .ok_or_else(|| {
+                    Error::InvalidArgs(format!(
+                        "invalid replacement_char on line {}: expected one quoted character",
+                        idx + 1
+                    ))
+                })?;
+            }
             "display_driver" => {
                 cfg.display_driver = value.parse().map_err(|e: String| {
                     Error::InvalidArgs(format!("invalid display_driver on line {}: {e}", idx + 1))
@@ -311,6 +575,35 @@ fn parse_with_seen(raw: &str) -> Result<(Config, HashSet<String>)> {
                     ))
                 })?;
             }
+            "backlight.brightness" => {
+                cfg.backlight.brightness = crate::display::backlight::parse_brightness(value)
+                    .map_err(|e| {
+                        Error::InvalidArgs(format!(
+                            "invalid backlight.brightness on line {}: {e}",
+                            idx + 1
+                        ))
+                    })?;
+            }
+            "backlight.idle_timeout_ms" => {
+                cfg.backlight.idle_timeout_ms = value.parse().map_err(|_| {
+                    Error::InvalidArgs(format!(
+                        "invalid backlight.idle_timeout_ms on line {}",
+                        idx + 1
+                    ))
+                })?;
+            }
+            "backlight.schedule" => {
+                cfg.backlight.schedule = if value == "null" {
+                    Default::default()
+                } else {
+                    value.parse().map_err(|e: String| {
+                        Error::InvalidArgs(format!(
+                            "invalid backlight.schedule on line {}: {e}",
+                            idx + 1
+                        ))
+                    })?
+                };
+            }
             "negotiation.node_id" => {
                 cfg.negotiation.node_id = value.parse().map_err(|_| {
                     Error::InvalidArgs(format!("invalid negotiation.node_id on line {}", idx + 1))
@@ -332,6 +625,166 @@ fn parse_with_seen(raw: &str) -> Result<(Config, HashSet<String>)> {
                     ))
                 })?;
             }
+            "negotiation.reliable_delivery" => {
+                cfg.negotiation.reliable_delivery = value.parse().map_err(|_| {
+                    Error::InvalidArgs(format!(
+                        "invalid negotiation.reliable_delivery on line {}",
+                        idx + 1
+                    ))
+                })?;
+            }
+            "negotiation.max_baud" => {
+                cfg.negotiation.max_baud = if value == "null" {
+                    None
+                } else {
+                    Some(value.parse().map_err(|_| {
+                        Error::InvalidArgs(format!(
+                            "invalid negotiation.max_baud on line {}",
+                            idx + 1
+                        ))
+                    })?)
+                };
+            }
+            "negotiation.node_name" => {
+                cfg.negotiation.node_name = (value != "null").then(|| value.to_string());
+            }
+            "negotiation.peer_uuid" => {
+                cfg.negotiation.peer_uuid = (value != "null").then(|| value.to_ascii_lowercase());
+            }
+            "negotiation.peer_mismatch" => {
+                cfg.negotiation.peer_mismatch = value.parse().map_err(|e: String| {
+                    Error::InvalidArgs(format!(
+                        "invalid negotiation.peer_mismatch on line {}: {e}",
+                        idx + 1
+                    ))
+                })?;
+            }
+            "bus.mode" => {
+                cfg.bus.mode = value.parse().map_err(|e: String| {
+                    Error::InvalidArgs(format!("invalid bus.mode on line {}: {e}", idx + 1))
+                })?;
+            }
+            "bus.address" => {
+                cfg.bus.address = value.parse().map_err(|_| {
+                    Error::InvalidArgs(format!("invalid bus.address on line {}", idx + 1))
+                })?;
+            }
+            "bus.master_address" => {
+                cfg.bus.master_address = value.parse().map_err(|_| {
+                    Error::InvalidArgs(format!("invalid bus.master_address on line {}", idx + 1))
+                })?;
+            }
+            "bus.nodes" => {
+                cfg.bus.nodes = parse_string_array(value)
+                    .and_then(|entries| {
+                        entries
+                            .iter()
+                            .map(|entry| {
+                                entry
+                                    .parse::<u8>()
+                                    .map_err(|_| format!("'{entry}' is not a node address"))
+                            })
+                            .collect()
+                    })
+                    .map_err(|e| {
+                        Error::InvalidArgs(format!("invalid bus.nodes on line {}: {e}", idx + 1))
+                    })?;
+            }
+            "bus.rts_driver_enable" => {
+                cfg.bus.rts_driver_enable = value.parse().map_err(|_| {
+                    Error::InvalidArgs(format!("invalid bus.rts_driver_enable on line {}", idx + 1))
+                })?;
+            }
+            "bus.turn_timeout_ms" => {
+                cfg.bus.turn_timeout_ms = value.parse().map_err(|_| {
+                    Error::InvalidArgs(format!("invalid bus.turn_timeout_ms on line {}", idx + 1))
+                })?;
+            }
+            "link_quality.adapt" => {
+                cfg.link_quality.adapt = value.parse().map_err(|_| {
+                    Error::InvalidArgs(format!("invalid link_quality.adapt on line {}", idx + 1))
+                })?;
+            }
+            "link_quality.window_ms" => {
+                cfg.link_quality.window_ms = value.parse().map_err(|_| {
+                    Error::InvalidArgs(format!(
+                        "invalid link_quality.window_ms on line {}",
+                        idx + 1
+                    ))
+                })?;
+            }
+            "link_quality.max_error_rate_pct" => {
+                cfg.link_quality.max_error_rate_pct = value.parse().map_err(|_| {
+                    Error::InvalidArgs(format!(
+                        "invalid link_quality.max_error_rate_pct on line {}",
+                        idx + 1
+                    ))
+                })?;
+            }
+            "link_quality.max_rtt_ms" => {
+                cfg.link_quality.max_rtt_ms = if value == "null" {
+                    None
+                } else {
+                    Some(value.parse().map_err(|_| {
+                        Error::InvalidArgs(format!(
+                            "invalid link_quality.max_rtt_ms on line {}",
+                            idx + 1
+                        ))
+                    })?)
+                };
+            }
+            "link_quality.recovery_windows" => {
+                cfg.link_quality.recovery_windows = value.parse().map_err(|_| {
+                    Error::InvalidArgs(format!(
+                        "invalid link_quality.recovery_windows on line {}",
+                        idx + 1
+                    ))
+                })?;
+            }
+            "lcd_backpack" => {
+                cfg.lcd_backpack = value.parse().map_err(|e: String| {
+                    Error::InvalidArgs(format!("invalid lcd_backpack on line {}: {e}", idx + 1))
+                })?;
+            }
+            "lcd_pin_map" => {
+                if value == "null" {
+                    cfg.lcd_pin_map = None;
+                } else {
+                    cfg.lcd_pin_map = Some(value.parse().map_err(|e: String| {
+                        Error::InvalidArgs(format!("invalid lcd_pin_map on line {}: {e}", idx + 1))
+                    })?);
+                }
+            }
+            "backlight_rgb" => {
+                if value == "null" {
+                    cfg.backlight_rgb = None;
+                } else {
+                    cfg.backlight_rgb = Some(value.parse().map_err(|e: String| {
+                        Error::InvalidArgs(format!(
+                            "invalid backlight_rgb on line {}: {e}",
+                            idx + 1
+                        ))
+                    })?);
+                }
+            }
+            "gpio_pins" => {
+                if value == "null" {
+                    cfg.gpio_pins = None;
+                } else {
+                    cfg.gpio_pins = Some(value.parse().map_err(|e: String| {
+                        Error::InvalidArgs(format!("invalid gpio_pins on line {}: {e}", idx + 1))
+                    })?);
+                }
+            }
+            "i2c_bus" => {
+                if value == "null" {
+                    cfg.i2c_bus = None;
+                } else {
+                    cfg.i2c_bus = Some(value.parse().map_err(|_| {
+                        Error::InvalidArgs(format!("invalid i2c_bus on line {}", idx + 1))
+                    })?);
+                }
+            }
             "button_gpio_pin" => {
                 if value == "null" {
                     cfg.button_gpio_pin = None;
@@ -349,6 +802,14 @@ fn parse_with_seen(raw: &str) -> Result<(Config, HashSet<String>)> {
                     ))
                 })?;
             }
+            "forward_allowlist" => {
+                cfg.forward_allowlist = parse_string_array(value).map_err(|e| {
+                    Error::InvalidArgs(format!(
+                        "invalid forward_allowlist on line {}: {e}",
+                        idx + 1
+                    ))
+                })?;
+            }
             "protocol.schema_version" => {
                 cfg.protocol.schema_version = value.parse().map_err(|_| {
                     Error::InvalidArgs(format!(
@@ -384,6 +845,9 @@ fn parse_with_seen(raw: &str) -> Result<(Config, HashSet<String>)> {
                 cfg.protocol.compression_enabled = enabled;
                 cfg.protocol.compression_codec = codec;
             }
+            other if other.starts_with(DISPLAY_SECTION_PREFIX) => {
+                parse_display_key(&mut cfg.displays, other, value, idx + 1)?;
+            }
             other => {
                 return Err(Error::InvalidArgs(format!(
                     "unknown config key '{}' on line {}",
@@ -398,11 +862,16 @@ fn parse_with_seen(raw: &str) -> Result<(Config, HashSet<String>)> {
     Ok((cfg, seen_keys))
 }
 
-fn config_path() -> Result<PathBuf> {
+/// `~/.serial_lcd`, home to the config file and other small persistent state.
+pub fn config_dir() -> Result<PathBuf> {
     let home = std::env::var_os("HOME")
         .map(PathBuf::from)
         .ok_or_else(|| Error::InvalidArgs("HOME not set; cannot locate config directory".into()))?;
-    Ok(home.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+    Ok(home.join(CONFIG_DIR_NAME))
+}
+
+fn config_path() -> Result<PathBuf> {
+    Ok(config_dir()?.join(CONFIG_FILE_NAME))
 }
 
 fn missing_required_keys(seen_keys: &HashSet<String>) -> bool {
@@ -655,6 +1124,144 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[test]
+    fn parses_and_validates_forward_allowlist() {
+        let path = temp_path("forward_allowlist");
+        fs::write(
+            &path,
+            "forward_allowlist = [\"127.0.0.1:22\", \"localhost:8080\"]",
+        )
+        .unwrap();
+        let cfg = load_from_path(&path).unwrap();
+        assert_eq!(
+            cfg.forward_allowlist,
+            vec!["127.0.0.1:22", "localhost:8080"]
+        );
+
+        fs::write(&path, "forward_allowlist = [\"localhost\"]").unwrap();
+        let err = load_from_path(&path).unwrap_err();
+        assert!(format!("{err}").contains("host:port"));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn parses_character_rom_and_replacement() {
+        let path = temp_path("charset");
+        fs::write(&path, "character_rom = \"a02\"\nreplacement_char = \"#\"").unwrap();
+        let cfg = load_from_path(&path).unwrap();
+        assert_eq!(cfg.character_rom, crate::display::charset::CharRom::A02);
+        assert_eq!(cfg.replacement_char, '#');
+
+        fs::write(&path, "replacement_char = \"ab\"").unwrap();
+        assert!(load_from_path(&path).is_err());
+        fs::write(&path, "replacement_char = \"é\"").unwrap();
+        let err = load_from_path(&path).unwrap_err();
+        assert!(format!("{err}").contains("printable ASCII"));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn gpio_display_driver_requires_free_pins() {
+        let path = temp_path("gpio_pins");
+        fs::write(
+            &path,
+            "display_driver = \"gpio\"\ngpio_pins = \"rs=26,e=19,d4=13,d5=6,d6=5,d7=11\"",
+        )
+        .unwrap();
+        let cfg = load_from_path(&path).unwrap();
+        assert_eq!(cfg.display_driver, DisplayDriver::Gpio);
+        assert_eq!(cfg.gpio_pins.unwrap().data, vec![13, 6, 5, 11]);
+
+        fs::write(&path, "display_driver = \"gpio\"").unwrap();
+        let err = load_from_path(&path).unwrap_err();
+        assert!(format!("{err}").contains("requires gpio_pins"));
+
+        fs::write(
+            &path,
+            "display_driver = \"gpio\"\nbutton_gpio_pin = 19\ngpio_pins = \"rs=26,e=19,d4=13,d5=6,d6=5,d7=11\"",
+        )
+        .unwrap();
+        let err = load_from_path(&path).unwrap_err();
+        assert!(format!("{err}").contains("already used"));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn parses_extra_display_sections() {
+        let path = temp_path("displays");
+        fs::write(
+            &path,
+            "i2c_bus = 1\npcf8574_addr = 0x27\n\n[display.alerts]\ncols = 20\nrows = 4\ni2c_bus = 1\npcf8574_addr = 0x3f\n\n[display.door]\ndisplay_driver = \"gpio\"\ngpio_pins = \"rs=26,e=19,d4=13,d5=6,d6=5,d7=11\"\n",
+        )
+        .unwrap();
+        let cfg = load_from_path(&path).unwrap();
+        assert_eq!(cfg.i2c_bus, Some(1));
+        let names: Vec<&str> = cfg.displays.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["alerts", "door"]);
+        assert_eq!(cfg.displays[0].rows, 4);
+        assert_eq!(cfg.displays[0].pcf8574_addr, Pcf8574Addr::Addr(0x3f));
+        assert_eq!(cfg.displays[1].display_driver, DisplayDriver::Gpio);
+
+        for (raw, needle) in [
+            ("[display.main]\ncols = 16", "used more than once"),
+            ("[display.a b]\ncols = 16", "display names"),
+            ("[display.x]\ncolour = 1", "unknown config key"),
+            ("[display.x]\nrows = many", "invalid display.x.rows"),
+            (
+                "pcf8574_addr = 0x27\n\n[display.x]\npcf8574_addr = 0x27",
+                "already used by another display",
+            ),
+            (
+                "display_driver = \"gpio\"\ngpio_pins = \"rs=26,e=19,d4=13,d5=6,d6=5,d7=11\"\n\n[display.x]\ndisplay_driver = \"gpio\"\ngpio_pins = \"rs=2,e=3,d4=13,d5=14,d6=15,d7=18\"",
+                "gpio pin 13 is already used",
+            ),
+        ] {
+            fs::write(&path, raw).unwrap();
+            let err = load_from_path(&path).unwrap_err();
+            assert!(format!("{err}").contains(needle), "{raw}: {err}");
+        }
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn parses_lcd_backpack_and_pin_map() {
+        use crate::lcd_driver::backpack::Backpack;
+        let path = temp_path("backpack");
+        fs::write(
+            &path,
+            "lcd_backpack = \"mcp23008\"\nlcd_pin_map = \"rs=1,e=2,d4=3,d5=4,d6=5,d7=6\"",
+        )
+        .unwrap();
+        let cfg = load_from_path(&path).unwrap();
+        assert_eq!(cfg.lcd_backpack, Backpack::Mcp23008);
+        assert_eq!(cfg.lcd_pin_map.unwrap().backlight, None);
+
+        fs::write(
+            &path,
+            "lcd_backpack = \"st7032\"\nlcd_pin_map = \"rs=1,e=2,d4=3,d5=4,d6=5,d7=6\"",
+        )
+        .unwrap();
+        let err = load_from_path(&path).unwrap_err();
+        assert!(format!("{err}").contains("does not apply"));
+
+        fs::write(
+            &path,
+            "lcd_backpack = \"aip31068\"\ndisplay_driver = \"hd44780-driver\"",
+        )
+        .unwrap();
+        let err = load_from_path(&path).unwrap_err();
+        assert!(format!("{err}").contains("only supports"));
+
+        let dual = "lcd_pin_map = \"rs=0,e=2,e2=1,d4=4,d5=5,d6=6,d7=7,bl=3\"";
+        fs::write(&path, format!("cols = 40\nrows = 4\n{dual}")).unwrap();
+        let cfg = load_from_path(&path).unwrap();
+        assert!(cfg.lcd_wiring().is_dual_controller());
+        fs::write(&path, format!("cols = 40\nrows = 2\n{dual}")).unwrap();
+        let err = load_from_path(&path).unwrap_err();
+        assert!(format!("{err}").contains("4-row panels"));
+        let _ = fs::remove_file(path);
+    }
+
     #[test]
     fn rejects_unknown_key() {
         let path = temp_path("unknown");
@@ -669,6 +1276,7 @@ mod tests {
         let path = temp_path("roundtrip");
         let cfg = Config {
             device: "/dev/ttyS1".into(),
+            device_match: Some("usb=0403:6001,by-id=*FTDI*".parse().unwrap()),
             baud: 57_600,
             flow_control: FlowControlMode::Hardware,
             parity: ParityMode::Even,
@@ -683,18 +1291,69 @@ mod tests {
             poll_interval_ms: 2000,
             button_gpio_pin: Some(22),
             pcf8574_addr: Pcf8574Addr::Auto,
+            i2c_bus: Some(1),
             display_driver: DisplayDriver::Hd44780Driver,
+            gpio_pins: Some("rs=26,e=19,d4=13,d5=6,d6=5,d7=11,bl=12".parse().unwrap()),
+            lcd_backpack: crate::lcd_driver::backpack::Backpack::Pcf8574,
+            lcd_pin_map: Some("rs=0,e=2,d4=4,d5=5,d6=6,d7=7".parse().unwrap()),
+            backlight_rgb: Some("gpio:r=16,g=20,b=21".parse().unwrap()),
+            character_rom: crate::display::charset::CharRom::A02,
+            replacement_char: ' ',
             lcd_present: crate::config::DEFAULT_LCD_PRESENT,
             backoff_initial_ms: DEFAULT_BACKOFF_INITIAL_MS,
             backoff_max_ms: DEFAULT_BACKOFF_MAX_MS,
-            negotiation: crate::config::NegotiationConfig::default(),
+            negotiation: crate::config::NegotiationConfig {
+                reliable_delivery: true,
+                max_baud: Some(115_200),
+                node_name: Some("rack-pi".into()),
+                peer_uuid: Some("0f8e2c1a-5b7d-4e3f-9a10-c2d4e6f80123".into()),
+                peer_mismatch: crate::config::PeerMismatchAction::Alert,
+                ..crate::config::NegotiationConfig::default()
+            },
             command_allowlist: Vec::new(),
+            forward_allowlist: vec!["127.0.0.1:22".into()],
             protocol: crate::config::ProtocolConfig {
                 schema_version: 1,
                 compression_enabled: true,
                 compression_codec: CompressionCodec::Lz4,
             },
             watchdog: crate::config::WatchdogConfig::default(),
+            backlight: crate::config::BacklightConfig {
+                brightness: 0,
+                idle_timeout_ms: 0,
+                schedule: "18:00-22:00=40,22:00-07:30=off".parse().unwrap(),
+            },
+            bus: crate::config::BusConfig {
+                // Off, since the pinned peer above is rejected in bus mode.
+                mode: crate::config::BusMode::Off,
+                address: 1,
+                master_address: 1,
+                nodes: vec![2, 5, 9],
+                rts_driver_enable: true,
+                turn_timeout_ms: 150,
+            },
+            link_quality: crate::config::LinkQualityConfig {
+                adapt: true,
+                window_ms: 30_000,
+                max_error_rate_pct: 12,
+                max_rtt_ms: Some(400),
+                recovery_windows: 5,
+            },
+            displays: vec![
+                DisplayConfig {
+                    i2c_bus: Some(3),
+                    pcf8574_addr: Pcf8574Addr::Addr(0x3f),
+                    backlight_rgb: Some("pca9633@0x60".parse().unwrap()),
+                    ..DisplayConfig::new("alerts")
+                },
+                DisplayConfig {
+                    cols: 40,
+                    rows: 4,
+                    display_driver: DisplayDriver::Gpio,
+                    gpio_pins: Some("rs=5,e=6,e2=7,d4=8,d5=9,d6=10,d7=11".parse().unwrap()),
+                    ..DisplayConfig::new("wall")
+                },
+            ],
         };
         save_to_path(&cfg, &path).unwrap();
         let loaded = load_from_path(&path).unwrap();
@@ -731,6 +1390,34 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[test]
+    fn rejects_inconsistent_bus_settings() {
+        let path = temp_path("bus_invalid");
+        fs::write(&path, "[bus]\nmode = \"master\"\nnodes = []\n").unwrap();
+        let err = load_from_path(&path).unwrap_err();
+        assert!(format!("{err}").contains("bus.nodes must list"));
+
+        fs::write(&path, "[bus]\nmode = \"master\"\nnodes = [2, 1]\n").unwrap();
+        let err = load_from_path(&path).unwrap_err();
+        assert!(format!("{err}").contains("other than bus.address"));
+
+        fs::write(
+            &path,
+            "[bus]\nmode = \"node\"\naddress = 4\nnodes = [2, x]\n",
+        )
+        .unwrap();
+        let err = load_from_path(&path).unwrap_err();
+        assert!(format!("{err}").contains("invalid bus.nodes"));
+
+        fs::write(&path, "[bus]\nmode = \"node\"\naddress = 4\n").unwrap();
+        let cfg = load_from_path(&path).unwrap();
+        assert_eq!(
+            cfg.bus.station(),
+            Some(crate::serial::bus::BusStation::Node { master: 1 })
+        );
+        let _ = fs::remove_file(path);
+    }
+
     #[test]
     fn rejects_rows_outside_range() {
         let path = temp_path("rows_out_of_range");
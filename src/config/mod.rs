@@ -1,7 +1,20 @@
 use crate::{
     compression::CompressionCodec,
+    display::{
+        backlight::{BacklightSchedule, RgbBacklightConfig},
+        charset::CharRom,
+    },
+    lcd::LcdWiring,
+    lcd_driver::{
+        backpack::{Backpack, PinMap},
+        parallel::ParallelPins,
+    },
     negotiation::RolePreference,
-    serial::{DtrBehavior, FlowControlMode, ParityMode, StopBitsMode},
+    serial::{
+        bus::{BusStation, BUS_BROADCAST, BUS_DEFAULT_TURN_TIMEOUT_MS, BUS_MAX_ADDRESS},
+        discovery::DeviceSelector,
+        DtrBehavior, FlowControlMode, ParityMode, StopBitsMode,
+    },
     Error, Result,
 };
 use std::path::Path;
@@ -29,6 +42,8 @@ pub const MIN_POLL_INTERVAL_MS: u64 = 1000;
 pub const MAX_POLL_INTERVAL_MS: u64 = 60000;
 pub const DEFAULT_PCF8574_ADDR: Pcf8574Addr = Pcf8574Addr::Auto;
 pub const DEFAULT_DISPLAY_DRIVER: DisplayDriver = DisplayDriver::Auto;
+pub const DEFAULT_CHARACTER_ROM: CharRom = CharRom::A00;
+pub const DEFAULT_REPLACEMENT_CHAR: char = '?';
 pub const DEFAULT_BACKOFF_INITIAL_MS: u64 = 500;
 pub const DEFAULT_BACKOFF_MAX_MS: u64 = 10_000;
 pub const DEFAULT_SERIAL_TIMEOUT_MS: u64 = 500;
@@ -38,14 +53,33 @@ pub const DEFAULT_WATCHDOG_SERIAL_TIMEOUT_MS: u64 = 12_000;
 pub const DEFAULT_WATCHDOG_TUNNEL_TIMEOUT_MS: u64 = 5_000;
 pub const MIN_WATCHDOG_TIMEOUT_MS: u64 = 1_000;
 pub const MAX_WATCHDOG_TIMEOUT_MS: u64 = 120_000;
+pub const DEFAULT_BACKLIGHT_BRIGHTNESS: u8 = 100;
+/// Shortest non-zero backlight idle timeout; anything lower would flicker between frames.
+pub const MIN_BACKLIGHT_IDLE_TIMEOUT_MS: u64 = 5_000;
 pub const DEFAULT_NEGOTIATION_NODE_ID: u32 = 42;
 pub const DEFAULT_NEGOTIATION_TIMEOUT_MS: u64 = 1_000;
+pub const DEFAULT_NEGOTIATION_RELIABLE_DELIVERY: bool = false;
 pub const MIN_NEGOTIATION_TIMEOUT_MS: u64 = 250;
 pub const MAX_NEGOTIATION_TIMEOUT_MS: u64 = 5_000;
 pub const NEGOTIATION_SECTION_NAME: &str = "negotiation";
+pub const MAX_NODE_NAME_CHARS: usize = 32;
+pub const DEFAULT_BUS_MASTER_ADDRESS: u8 = 1;
+pub const MIN_BUS_TURN_TIMEOUT_MS: u64 = 20;
+pub const MAX_BUS_TURN_TIMEOUT_MS: u64 = 10_000;
+pub const DEFAULT_LINK_QUALITY_WINDOW_MS: u64 = 60_000;
+pub const MIN_LINK_QUALITY_WINDOW_MS: u64 = 5_000;
+pub const MAX_LINK_QUALITY_WINDOW_MS: u64 = 600_000;
+pub const DEFAULT_LINK_QUALITY_MAX_ERROR_RATE_PCT: u8 = 5;
+pub const MIN_LINK_QUALITY_MAX_RTT_MS: u64 = 10;
+pub const MAX_LINK_QUALITY_MAX_RTT_MS: u64 = 60_000;
+pub const DEFAULT_LINK_QUALITY_RECOVERY_WINDOWS: u32 = 3;
+pub const MAX_LINK_QUALITY_RECOVERY_WINDOWS: u32 = 100;
 pub const DEFAULT_PROTOCOL_SCHEMA_VERSION: u8 = 1;
 pub const DEFAULT_PROTOCOL_COMPRESSION_ENABLED: bool = false;
 pub const DEFAULT_PROTOCOL_COMPRESSION_CODEC: CompressionCodec = CompressionCodec::Lz4;
+/// Name payloads use (or omit) to address the display configured by the top-level keys.
+pub const PRIMARY_DISPLAY_NAME: &str = "main";
+pub const DISPLAY_SECTION_PREFIX: &str = "display.";
 const CONFIG_DIR_NAME: &str = ".serial_lcd";
 const CONFIG_FILE_NAME: &str = "config.toml";
 
@@ -72,6 +106,47 @@ pub struct NegotiationConfig {
     pub node_id: u32,
     pub preference: RolePreference,
     pub timeout_ms: u64,
+    /// Advertise sequenced/ACKed delivery; used only when the peer advertises it too.
+    pub reliable_delivery: bool,
+    /// Fastest rate to offer for a post-handshake baud upgrade; `None` stays at `baud`.
+    pub max_baud: Option<u32>,
+    /// Human-readable name sent in `hello`; `None` uses the hostname.
+    pub node_name: Option<String>,
+    /// UUID of the only peer this node should talk to; `None` accepts any peer.
+    pub peer_uuid: Option<String>,
+    /// What to do when the pinned peer is not the one on the cable.
+    pub peer_mismatch: PeerMismatchAction,
+}
+
+/// Reaction to a peer whose UUID differs from `negotiation.peer_uuid`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PeerMismatchAction {
+    /// Close the port and keep retrying until the pinned peer answers.
+    #[default]
+    Refuse,
+    /// Keep the session but show a warning on the LCD.
+    Alert,
+}
+
+impl std::str::FromStr for PeerMismatchAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "refuse" => Ok(PeerMismatchAction::Refuse),
+            "alert" => Ok(PeerMismatchAction::Alert),
+            other => Err(format!("expected 'refuse' or 'alert', got '{other}'")),
+        }
+    }
+}
+
+impl std::fmt::Display for PeerMismatchAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PeerMismatchAction::Refuse => "refuse",
+            PeerMismatchAction::Alert => "alert",
+        })
+    }
 }
 
 impl Default for NegotiationConfig {
@@ -80,6 +155,11 @@ impl Default for NegotiationConfig {
             node_id: DEFAULT_NEGOTIATION_NODE_ID,
             preference: RolePreference::default(),
             timeout_ms: DEFAULT_NEGOTIATION_TIMEOUT_MS,
+            reliable_delivery: DEFAULT_NEGOTIATION_RELIABLE_DELIVERY,
+            max_baud: None,
+            node_name: None,
+            peer_uuid: None,
+            peer_mismatch: PeerMismatchAction::default(),
         }
     }
 }
@@ -104,6 +184,8 @@ pub enum DisplayDriver {
     Auto,
     InTree,
     Hd44780Driver,
+    /// HD44780 wired directly to GPIO; pins come from `gpio_pins`.
+    Gpio,
 }
 
 impl std::str::FromStr for DisplayDriver {
@@ -114,8 +196,9 @@ impl std::str::FromStr for DisplayDriver {
             "auto" => Ok(DisplayDriver::Auto),
             "in-tree" | "intree" => Ok(DisplayDriver::InTree),
             "hd44780-driver" | "hd44780" => Ok(DisplayDriver::Hd44780Driver),
+            "gpio" | "parallel" => Ok(DisplayDriver::Gpio),
             other => Err(format!(
-                "expected 'auto', 'in-tree', or 'hd44780-driver', got '{other}'"
+                "expected 'auto', 'in-tree', 'hd44780-driver', or 'gpio', got '{other}'"
             )),
         }
     }
@@ -127,6 +210,7 @@ impl std::fmt::Display for DisplayDriver {
             DisplayDriver::Auto => "auto",
             DisplayDriver::InTree => "in-tree",
             DisplayDriver::Hd44780Driver => "hd44780-driver",
+            DisplayDriver::Gpio => "gpio",
         })
     }
 }
@@ -147,9 +231,186 @@ impl Default for WatchdogConfig {
     }
 }
 
+/// How this node uses a shared RS-485 line, from the `[bus]` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BusMode {
+    /// Point-to-point link with the usual negotiation.
+    #[default]
+    Off,
+    /// Polls `bus.nodes` and broadcasts its own frames.
+    Master,
+    /// Answers to `bus.address` and speaks only when polled.
+    Node,
+}
+
+impl std::str::FromStr for BusMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "off" => Ok(BusMode::Off),
+            "master" => Ok(BusMode::Master),
+            "node" => Ok(BusMode::Node),
+            other => Err(format!("expected 'off', 'master' or 'node', got '{other}'")),
+        }
+    }
+}
+
+impl std::fmt::Display for BusMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BusMode::Off => "off",
+            BusMode::Master => "master",
+            BusMode::Node => "node",
+        })
+    }
+}
+
+/// Multi-drop settings; only read when `mode` is not `off`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BusConfig {
+    pub mode: BusMode,
+    /// This node's address (1-247); 0 is the broadcast address.
+    pub address: u8,
+    /// Address nodes answer to.
+    pub master_address: u8,
+    /// Addresses the master polls, in order.
+    pub nodes: Vec<u8>,
+    /// Raise RTS while transmitting to drive a half-duplex transceiver's DE pin.
+    pub rts_driver_enable: bool,
+    /// How long the master waits for a polled node to hand the bus back.
+    pub turn_timeout_ms: u64,
+}
+
+impl BusConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.mode != BusMode::Off
+    }
+
+    pub fn station(&self) -> Option<BusStation> {
+        match self.mode {
+            BusMode::Off => None,
+            BusMode::Master => Some(BusStation::Master {
+                nodes: self.nodes.clone(),
+            }),
+            BusMode::Node => Some(BusStation::Node {
+                master: self.master_address,
+            }),
+        }
+    }
+}
+
+impl Default for BusConfig {
+    fn default() -> Self {
+        Self {
+            mode: BusMode::default(),
+            address: DEFAULT_BUS_MASTER_ADDRESS,
+            master_address: DEFAULT_BUS_MASTER_ADDRESS,
+            nodes: Vec::new(),
+            rts_driver_enable: false,
+            turn_timeout_ms: BUS_DEFAULT_TURN_TIMEOUT_MS,
+        }
+    }
+}
+
+/// Rolling link-quality window and the thresholds that trigger adaptation, from the
+/// `[link_quality]` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkQualityConfig {
+    /// Act on a degraded link (compression, smaller chunks, lower baud); otherwise only report.
+    pub adapt: bool,
+    /// Span of the rolling window; also the minimum gap between two adaptations.
+    pub window_ms: u64,
+    /// Errors, CRC failures and timeouts as a percentage of all frames in the window.
+    pub max_error_rate_pct: u8,
+    /// Mean round-trip time that counts as degraded; `None` ignores latency.
+    pub max_rtt_ms: Option<u64>,
+    /// Healthy windows in a row before the most recent adaptation is undone; 0 keeps them.
+    pub recovery_windows: u32,
+}
+
+impl Default for LinkQualityConfig {
+    fn default() -> Self {
+        Self {
+            adapt: false,
+            window_ms: DEFAULT_LINK_QUALITY_WINDOW_MS,
+            max_error_rate_pct: DEFAULT_LINK_QUALITY_MAX_ERROR_RATE_PCT,
+            max_rtt_ms: None,
+            recovery_windows: DEFAULT_LINK_QUALITY_RECOVERY_WINDOWS,
+        }
+    }
+}
+
+/// Backlight brightness, idle timeout, and time-of-day schedule from the `[backlight]` section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BacklightConfig {
+    /// Percent (0-100); PWM/RGB backlights dim, plain backpacks treat anything above 0 as on.
+    pub brightness: u8,
+    /// Turn the backlight off when no payload arrives for this long; 0 disables.
+    pub idle_timeout_ms: u64,
+    pub schedule: BacklightSchedule,
+}
+
+impl Default for BacklightConfig {
+    fn default() -> Self {
+        Self {
+            brightness: DEFAULT_BACKLIGHT_BRIGHTNESS,
+            idle_timeout_ms: 0,
+            schedule: BacklightSchedule::default(),
+        }
+    }
+}
+
+/// An extra LCD driven alongside the primary one, configured by a `[display.<name>]` section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayConfig {
+    pub name: String,
+    pub cols: u8,
+    pub rows: u8,
+    /// I2C bus number (`/dev/i2c-N`); `None` probes the usual buses.
+    pub i2c_bus: Option<u8>,
+    pub pcf8574_addr: Pcf8574Addr,
+    pub display_driver: DisplayDriver,
+    pub gpio_pins: Option<ParallelPins>,
+    pub lcd_backpack: Backpack,
+    pub lcd_pin_map: Option<PinMap>,
+    pub backlight_rgb: Option<RgbBacklightConfig>,
+}
+
+impl DisplayConfig {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            cols: DEFAULT_COLS,
+            rows: DEFAULT_ROWS,
+            i2c_bus: None,
+            pcf8574_addr: DEFAULT_PCF8574_ADDR,
+            display_driver: DEFAULT_DISPLAY_DRIVER,
+            gpio_pins: None,
+            lcd_backpack: Backpack::default(),
+            lcd_pin_map: None,
+            backlight_rgb: None,
+        }
+    }
+
+    pub fn lcd_wiring(&self) -> LcdWiring {
+        LcdWiring {
+            pcf8574_addr: self.pcf8574_addr.clone(),
+            display_driver: self.display_driver,
+            backpack: self.lcd_backpack,
+            pin_map: self.lcd_pin_map,
+            gpio_pins: self.gpio_pins.clone(),
+            rgb_backlight: self.backlight_rgb.clone(),
+            i2c_bus: self.i2c_bus,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Config {
     pub device: String,
+    /// Pick the port by USB ID, serial number or by-id name; `device` is ignored when set.
+    pub device_match: Option<DeviceSelector>,
     pub baud: u32,
     pub flow_control: FlowControlMode,
     pub parity: ParityMode,
@@ -164,20 +425,42 @@ pub struct Config {
     pub poll_interval_ms: u64,
     pub button_gpio_pin: Option<u8>,
     pub pcf8574_addr: Pcf8574Addr,
+    /// I2C bus number (`/dev/i2c-N`) for the primary display; `None` probes the usual buses.
+    pub i2c_bus: Option<u8>,
     pub display_driver: DisplayDriver,
+    /// RS/E/data (and optional backlight) pins for `display_driver = "gpio"`.
+    pub gpio_pins: Option<ParallelPins>,
+    /// I2C expander or controller between the bus and the HD44780.
+    pub lcd_backpack: Backpack,
+    /// Expander bit wiring when it differs from the backpack's stock board.
+    pub lcd_pin_map: Option<PinMap>,
+    /// Separate RGB backlight controller (`pca9633[@addr]` or `gpio:r=,g=,b=`).
+    pub backlight_rgb: Option<RgbBacklightConfig>,
+    /// Character ROM fitted to the panel (`a00` Japanese or `a02` European).
+    pub character_rom: CharRom,
+    /// Shown for characters neither the ROM nor a CGRAM glyph can render.
+    pub replacement_char: char,
     pub lcd_present: bool,
     pub backoff_initial_ms: u64,
     pub backoff_max_ms: u64,
     pub negotiation: NegotiationConfig,
     pub command_allowlist: Vec<String>,
+    /// `host:port` pairs the tunnel may open TCP connections to (`--forward`).
+    pub forward_allowlist: Vec<String>,
     pub protocol: ProtocolConfig,
     pub watchdog: WatchdogConfig,
+    pub backlight: BacklightConfig,
+    pub bus: BusConfig,
+    pub link_quality: LinkQualityConfig,
+    /// Additional displays beyond the primary one.
+    pub displays: Vec<DisplayConfig>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             device: DEFAULT_DEVICE.to_string(),
+            device_match: None,
             baud: DEFAULT_BAUD,
             flow_control: FlowControlMode::default(),
             parity: ParityMode::default(),
@@ -192,14 +475,26 @@ impl Default for Config {
             poll_interval_ms: DEFAULT_POLL_INTERVAL_MS,
             button_gpio_pin: None,
             pcf8574_addr: DEFAULT_PCF8574_ADDR,
+            i2c_bus: None,
             display_driver: DEFAULT_DISPLAY_DRIVER,
+            gpio_pins: None,
+            lcd_backpack: Backpack::default(),
+            lcd_pin_map: None,
+            backlight_rgb: None,
+            character_rom: DEFAULT_CHARACTER_ROM,
+            replacement_char: DEFAULT_REPLACEMENT_CHAR,
             lcd_present: DEFAULT_LCD_PRESENT,
             backoff_initial_ms: DEFAULT_BACKOFF_INITIAL_MS,
             backoff_max_ms: DEFAULT_BACKOFF_MAX_MS,
             negotiation: NegotiationConfig::default(),
             command_allowlist: Vec::new(),
+            forward_allowlist: Vec::new(),
             protocol: ProtocolConfig::default(),
             watchdog: WatchdogConfig::default(),
+            backlight: BacklightConfig::default(),
+            bus: BusConfig::default(),
+            link_quality: LinkQualityConfig::default(),
+            displays: Vec::new(),
         }
     }
 }
@@ -221,6 +516,18 @@ impl Config {
         loader::save_to_path(self, path)
     }
 
+    pub fn lcd_wiring(&self) -> LcdWiring {
+        LcdWiring {
+            pcf8574_addr: self.pcf8574_addr.clone(),
+            display_driver: self.display_driver,
+            backpack: self.lcd_backpack,
+            pin_map: self.lcd_pin_map,
+            gpio_pins: self.gpio_pins.clone(),
+            rgb_backlight: self.backlight_rgb.clone(),
+            i2c_bus: self.i2c_bus,
+        }
+    }
+
     #[allow(dead_code)]
     fn parse(raw: &str) -> Result<Self> {
         loader::parse(raw)
@@ -238,6 +545,152 @@ fn parse_pcf_addr(raw: &str) -> std::result::Result<Pcf8574Addr, String> {
     Ok(Pcf8574Addr::Addr(value))
 }
 
+/// Split `host:port` (the port being the text after the last colon) into its parts.
+pub fn parse_host_port(raw: &str) -> Option<(&str, u16)> {
+    let (host, port) = raw.rsplit_once(':')?;
+    let port = port.parse::<u16>().ok().filter(|port| *port > 0)?;
+    (!host.trim().is_empty()).then_some((host, port))
+}
+
+/// Checks shared by the primary display and every `[display.<name>]` section; `prefix`
+/// names the section in error messages.
+fn validate_display_wiring(
+    prefix: &str,
+    wiring: &LcdWiring,
+    rows: u8,
+    button_gpio_pin: Option<u8>,
+) -> Result<()> {
+    if wiring.display_driver == DisplayDriver::Gpio {
+        let Some(pins) = &wiring.gpio_pins else {
+            return Err(Error::InvalidArgs(format!(
+                "{prefix}display_driver = \"gpio\" requires gpio_pins"
+            )));
+        };
+        if let Some(button) = button_gpio_pin.filter(|pin| pins.all().contains(pin)) {
+            return Err(Error::InvalidArgs(format!(
+                "button_gpio_pin {button} is already used by {prefix}gpio_pins"
+            )));
+        }
+    }
+    if wiring.display_driver == DisplayDriver::Hd44780Driver && wiring.backpack != Backpack::Pcf8574
+    {
+        return Err(Error::InvalidArgs(format!(
+            "{prefix}display_driver = \"hd44780-driver\" only supports lcd_backpack = \"pcf8574\" (got {})",
+            wiring.backpack
+        )));
+    }
+    if let Some(RgbBacklightConfig::GpioPwm { red, green, blue }) = &wiring.rgb_backlight {
+        let mut taken: Vec<u8> = button_gpio_pin.into_iter().collect();
+        if wiring.display_driver == DisplayDriver::Gpio {
+            taken.extend(wiring.gpio_pins.iter().flat_map(|pins| pins.all()));
+        }
+        if let Some(pin) = [red, green, blue]
+            .into_iter()
+            .find(|pin| taken.contains(pin))
+        {
+            return Err(Error::InvalidArgs(format!(
+                "{prefix}backlight_rgb pin {pin} is already used by the display or button"
+            )));
+        }
+    }
+    if wiring.is_dual_controller() {
+        if rows != 4 {
+            return Err(Error::InvalidArgs(format!(
+                "{prefix}a second enable line (e2) is only used by 4-row panels, but rows = {rows}"
+            )));
+        }
+        if wiring.display_driver == DisplayDriver::Hd44780Driver {
+            return Err(Error::InvalidArgs(format!(
+                "{prefix}display_driver = \"hd44780-driver\" cannot drive a second enable line (e2)"
+            )));
+        }
+    }
+    if wiring.pin_map.is_some() && !wiring.backpack.is_expander() {
+        return Err(Error::InvalidArgs(format!(
+            "{prefix}lcd_pin_map does not apply to the {} controller",
+            wiring.backpack
+        )));
+    }
+    Ok(())
+}
+
+/// GPIO pins a display claims outright (parallel wiring and PWM backlight channels).
+fn claimed_gpio_pins(wiring: &LcdWiring) -> Vec<u8> {
+    let mut pins = Vec::new();
+    if wiring.display_driver == DisplayDriver::Gpio {
+        pins.extend(wiring.gpio_pins.iter().flat_map(|pins| pins.all()));
+    }
+    if let Some(RgbBacklightConfig::GpioPwm { red, green, blue }) = wiring.rgb_backlight {
+        pins.extend([red, green, blue]);
+    }
+    pins
+}
+
+fn validate_extra_displays(cfg: &Config) -> Result<()> {
+    let mut names = vec![PRIMARY_DISPLAY_NAME];
+    let mut claimed = claimed_gpio_pins(&cfg.lcd_wiring());
+    let mut i2c_slots: Vec<(Option<u8>, u8)> = i2c_slot(&cfg.lcd_wiring()).into_iter().collect();
+    for display in &cfg.displays {
+        let prefix = format!("{DISPLAY_SECTION_PREFIX}{}: ", display.name);
+        if display.name.is_empty()
+            || !display
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(Error::InvalidArgs(format!(
+                "display names may only use letters, digits, '-' and '_' (got '{}')",
+                display.name
+            )));
+        }
+        if names.contains(&display.name.as_str()) {
+            return Err(Error::InvalidArgs(format!(
+                "display name '{}' is used more than once",
+                display.name
+            )));
+        }
+        names.push(&display.name);
+        if display.cols < MIN_COLS || display.cols > MAX_COLS {
+            return Err(Error::InvalidArgs(format!(
+                "{prefix}cols must be between {MIN_COLS} and {MAX_COLS}"
+            )));
+        }
+        if display.rows < MIN_ROWS || display.rows > MAX_ROWS {
+            return Err(Error::InvalidArgs(format!(
+                "{prefix}rows must be between {MIN_ROWS} and {MAX_ROWS}"
+            )));
+        }
+        let wiring = display.lcd_wiring();
+        validate_display_wiring(&prefix, &wiring, display.rows, cfg.button_gpio_pin)?;
+        for pin in claimed_gpio_pins(&wiring) {
+            if claimed.contains(&pin) {
+                return Err(Error::InvalidArgs(format!(
+                    "{prefix}gpio pin {pin} is already used by another display"
+                )));
+            }
+            claimed.push(pin);
+        }
+        if let Some(slot) = i2c_slot(&wiring) {
+            if i2c_slots.contains(&slot) {
+                return Err(Error::InvalidArgs(format!(
+                    "{prefix}i2c address {:#04x} is already used by another display on that bus",
+                    slot.1
+                )));
+            }
+            i2c_slots.push(slot);
+        }
+    }
+    Ok(())
+}
+
+/// Bus and explicit address of an I2C-attached display; `auto` addresses are not compared.
+fn i2c_slot(wiring: &LcdWiring) -> Option<(Option<u8>, u8)> {
+    match (&wiring.display_driver, &wiring.pcf8574_addr) {
+        (DisplayDriver::Gpio, _) | (_, Pcf8574Addr::Auto) => None,
+        (_, Pcf8574Addr::Addr(addr)) => Some((wiring.i2c_bus, *addr)),
+    }
+}
+
 pub(crate) fn validate(cfg: &Config) -> Result<()> {
     validate_baud(cfg.baud)?;
     if cfg.cols < MIN_COLS || cfg.cols > MAX_COLS {
@@ -265,6 +718,13 @@ pub(crate) fn validate(cfg: &Config) -> Result<()> {
             "poll_interval_ms must be between {MIN_POLL_INTERVAL_MS} and {MAX_POLL_INTERVAL_MS}"
         )));
     }
+    validate_display_wiring("", &cfg.lcd_wiring(), cfg.rows, cfg.button_gpio_pin)?;
+    validate_extra_displays(cfg)?;
+    if !cfg.replacement_char.is_ascii_graphic() && cfg.replacement_char != ' ' {
+        return Err(Error::InvalidArgs(
+            "replacement_char must be a single printable ASCII character".to_string(),
+        ));
+    }
     for entry in &cfg.command_allowlist {
         if entry.trim().is_empty() {
             return Err(Error::InvalidArgs(
@@ -272,6 +732,13 @@ pub(crate) fn validate(cfg: &Config) -> Result<()> {
             ));
         }
     }
+    for entry in &cfg.forward_allowlist {
+        if parse_host_port(entry).is_none() {
+            return Err(Error::InvalidArgs(format!(
+                "forward_allowlist entries must be host:port (got '{entry}')"
+            )));
+        }
+    }
     if cfg.protocol.schema_version != DEFAULT_PROTOCOL_SCHEMA_VERSION {
         return Err(Error::InvalidArgs(format!(
             "protocol.schema_version must be {DEFAULT_PROTOCOL_SCHEMA_VERSION}"
@@ -291,6 +758,27 @@ pub(crate) fn validate(cfg: &Config) -> Result<()> {
             "negotiation.timeout_ms must be between {MIN_NEGOTIATION_TIMEOUT_MS} and {MAX_NEGOTIATION_TIMEOUT_MS}"
         )));
     }
+    if let Some(max_baud) = cfg.negotiation.max_baud {
+        validate_baud(max_baud).map_err(|_| {
+            Error::InvalidArgs(format!(
+                "negotiation.max_baud must be at least {MIN_BAUD} (or null to disable upgrades)"
+            ))
+        })?;
+    }
+    if let Some(name) = &cfg.negotiation.node_name {
+        if name.trim().is_empty() || name.chars().count() > MAX_NODE_NAME_CHARS {
+            return Err(Error::InvalidArgs(format!(
+                "negotiation.node_name must be 1-{MAX_NODE_NAME_CHARS} characters"
+            )));
+        }
+    }
+    if let Some(uuid) = &cfg.negotiation.peer_uuid {
+        if !is_uuid(uuid) {
+            return Err(Error::InvalidArgs(format!(
+                "negotiation.peer_uuid must look like xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx, got '{uuid}'"
+            )));
+        }
+    }
     if cfg.watchdog.serial_timeout_ms < MIN_WATCHDOG_TIMEOUT_MS
         || cfg.watchdog.serial_timeout_ms > MAX_WATCHDOG_TIMEOUT_MS
     {
@@ -305,9 +793,113 @@ pub(crate) fn validate(cfg: &Config) -> Result<()> {
             "watchdog.tunnel_timeout_ms must be between {MIN_WATCHDOG_TIMEOUT_MS} and {MAX_WATCHDOG_TIMEOUT_MS}"
         )));
     }
+    validate_bus(cfg)?;
+    validate_link_quality(&cfg.link_quality)?;
+    if cfg.backlight.brightness > 100 {
+        return Err(Error::InvalidArgs(
+            "backlight.brightness must be between 0 and 100".into(),
+        ));
+    }
+    if cfg.backlight.idle_timeout_ms != 0
+        && cfg.backlight.idle_timeout_ms < MIN_BACKLIGHT_IDLE_TIMEOUT_MS
+    {
+        return Err(Error::InvalidArgs(format!(
+            "backlight.idle_timeout_ms must be 0 (disabled) or at least {MIN_BACKLIGHT_IDLE_TIMEOUT_MS}"
+        )));
+    }
     Ok(())
 }
 
+fn validate_link_quality(link: &LinkQualityConfig) -> Result<()> {
+    if !(MIN_LINK_QUALITY_WINDOW_MS..=MAX_LINK_QUALITY_WINDOW_MS).contains(&link.window_ms) {
+        return Err(Error::InvalidArgs(format!(
+            "link_quality.window_ms must be between {MIN_LINK_QUALITY_WINDOW_MS} and {MAX_LINK_QUALITY_WINDOW_MS}"
+        )));
+    }
+    if !(1..=100).contains(&link.max_error_rate_pct) {
+        return Err(Error::InvalidArgs(
+            "link_quality.max_error_rate_pct must be between 1 and 100".into(),
+        ));
+    }
+    if let Some(rtt) = link.max_rtt_ms {
+        if !(MIN_LINK_QUALITY_MAX_RTT_MS..=MAX_LINK_QUALITY_MAX_RTT_MS).contains(&rtt) {
+            return Err(Error::InvalidArgs(format!(
+                "link_quality.max_rtt_ms must be between {MIN_LINK_QUALITY_MAX_RTT_MS} and {MAX_LINK_QUALITY_MAX_RTT_MS} (or null)"
+            )));
+        }
+    }
+    if link.recovery_windows > MAX_LINK_QUALITY_RECOVERY_WINDOWS {
+        return Err(Error::InvalidArgs(format!(
+            "link_quality.recovery_windows must be between 0 and {MAX_LINK_QUALITY_RECOVERY_WINDOWS}"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_bus(cfg: &Config) -> Result<()> {
+    let bus = &cfg.bus;
+    if !bus.is_enabled() {
+        return Ok(());
+    }
+    let in_range = |address: u8| (1..=BUS_MAX_ADDRESS).contains(&address);
+    if !in_range(bus.address) || !in_range(bus.master_address) {
+        return Err(Error::InvalidArgs(format!(
+            "bus.address and bus.master_address must be between 1 and {BUS_MAX_ADDRESS} ({BUS_BROADCAST} is broadcast)"
+        )));
+    }
+    match bus.mode {
+        BusMode::Master => {
+            if bus.nodes.is_empty() {
+                return Err(Error::InvalidArgs(
+                    "bus.nodes must list at least one node address in master mode".into(),
+                ));
+            }
+            let mut seen = std::collections::HashSet::new();
+            for &node in &bus.nodes {
+                if !in_range(node) || node == bus.address || !seen.insert(node) {
+                    return Err(Error::InvalidArgs(format!(
+                        "bus.nodes entries must be unique addresses between 1 and {BUS_MAX_ADDRESS}, other than bus.address (got {node})"
+                    )));
+                }
+            }
+        }
+        BusMode::Node if bus.address == bus.master_address => {
+            return Err(Error::InvalidArgs(
+                "bus.address must differ from bus.master_address in node mode".into(),
+            ));
+        }
+        _ => {}
+    }
+    if bus.turn_timeout_ms < MIN_BUS_TURN_TIMEOUT_MS
+        || bus.turn_timeout_ms > MAX_BUS_TURN_TIMEOUT_MS
+    {
+        return Err(Error::InvalidArgs(format!(
+            "bus.turn_timeout_ms must be between {MIN_BUS_TURN_TIMEOUT_MS} and {MAX_BUS_TURN_TIMEOUT_MS}"
+        )));
+    }
+    if cfg.negotiation.peer_uuid.is_some() {
+        return Err(Error::InvalidArgs(
+            "negotiation.peer_uuid cannot be used in bus mode; a bus has no single peer".into(),
+        ));
+    }
+    if bus.rts_driver_enable && cfg.flow_control == FlowControlMode::Hardware {
+        return Err(Error::InvalidArgs(
+            "bus.rts_driver_enable drives RTS itself; set flow_control = \"none\"".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Canonical 8-4-4-4-12 hex UUID text.
+pub fn is_uuid(value: &str) -> bool {
+    let groups: Vec<&str> = value.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
 pub fn validate_baud(baud: u32) -> Result<()> {
     if baud < MIN_BAUD {
         return Err(Error::InvalidArgs(format!(
@@ -324,7 +916,7 @@ fn format_pcf_addr(addr: &Pcf8574Addr) -> String {
     }
 }
 
-fn format_display_driver(driver: &DisplayDriver) -> String {
+pub(crate) fn format_display_driver(driver: &DisplayDriver) -> String {
     format!("\"{driver}\"")
 }
 
@@ -424,6 +1016,7 @@ mod tests {
         let path = temp_path("roundtrip");
         let cfg = Config {
             device: "/dev/ttyS1".into(),
+            device_match: None,
             baud: 57_600,
             flow_control: FlowControlMode::Hardware,
             parity: ParityMode::Even,
@@ -438,14 +1031,30 @@ mod tests {
             poll_interval_ms: 2000,
             button_gpio_pin: Some(22),
             pcf8574_addr: Pcf8574Addr::Auto,
+            i2c_bus: None,
             display_driver: DisplayDriver::InTree,
+            gpio_pins: None,
+            lcd_backpack: Backpack::default(),
+            lcd_pin_map: None,
+            backlight_rgb: None,
+            character_rom: DEFAULT_CHARACTER_ROM,
+            replacement_char: DEFAULT_REPLACEMENT_CHAR,
             backoff_initial_ms: DEFAULT_BACKOFF_INITIAL_MS,
             backoff_max_ms: DEFAULT_BACKOFF_MAX_MS,
             negotiation: NegotiationConfig::default(),
             command_allowlist: Vec::new(),
+            forward_allowlist: Vec::new(),
             protocol: ProtocolConfig::default(),
             lcd_present: DEFAULT_LCD_PRESENT,
             watchdog: WatchdogConfig::default(),
+            backlight: BacklightConfig {
+                brightness: 60,
+                idle_timeout_ms: 600_000,
+                schedule: "22:00-06:00=off".parse().unwrap(),
+            },
+            bus: BusConfig::default(),
+            link_quality: LinkQualityConfig::default(),
+            displays: Vec::new(),
         };
         cfg.save_to_path(&path).unwrap();
         let loaded = Config::load_from_path(&path).unwrap();
@@ -0,0 +1,379 @@
+//! Backlight colours for RGB-backlit panels, the severity palette used for alerts, and
+//! time-of-day brightness schedules.
+
+use std::{fmt, str::FromStr};
+
+/// 8-bit-per-channel backlight colour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const OFF: Rgb = Rgb::new(0, 0, 0);
+    pub const WHITE: Rgb = Rgb::new(255, 255, 255);
+    pub const RED: Rgb = Rgb::new(255, 0, 0);
+    pub const GREEN: Rgb = Rgb::new(0, 255, 0);
+    pub const BLUE: Rgb = Rgb::new(0, 0, 255);
+    /// LED amber; a true 255/191/0 reads as yellow on most RGB backlights.
+    pub const AMBER: Rgb = Rgb::new(255, 120, 0);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// This colour at `percent` brightness (0-100), for PWM-driven backlights.
+    pub fn scaled(self, percent: u8) -> Self {
+        let percent = u16::from(percent.min(100));
+        let scale = |channel: u8| ((u16::from(channel) * percent + 50) / 100) as u8;
+        Rgb::new(scale(self.r), scale(self.g), scale(self.b))
+    }
+}
+
+impl Default for Rgb {
+    /// Plain white, matching a single-colour panel.
+    fn default() -> Self {
+        Rgb::WHITE
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = String;
+
+    /// Accept `#rrggbb`, `rrggbb`, or a colour name (`red`, `amber`, `off`, ...).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let raw = s.trim().to_ascii_lowercase();
+        let named = match raw.as_str() {
+            "off" | "black" => Some(Rgb::OFF),
+            "white" => Some(Rgb::WHITE),
+            "red" => Some(Rgb::RED),
+            "green" => Some(Rgb::GREEN),
+            "blue" => Some(Rgb::BLUE),
+            "amber" | "orange" => Some(Rgb::AMBER),
+            "yellow" => Some(Rgb::new(255, 255, 0)),
+            _ => None,
+        };
+        if let Some(color) = named {
+            return Ok(color);
+        }
+        let hex = raw.strip_prefix('#').unwrap_or(&raw);
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!(
+                "expected #rrggbb or a colour name (red, green, amber, ...), got '{}'",
+                s.trim()
+            ));
+        }
+        let channel = |idx: usize| u8::from_str_radix(&hex[idx..idx + 2], 16).unwrap_or(0);
+        Ok(Rgb::new(channel(0), channel(2), channel(4)))
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// How urgent a frame or daemon alert is; drives the backlight colour when no explicit
+/// `backlight_color` is given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Ok,
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn color(self) -> Rgb {
+        match self {
+            Severity::Ok => Rgb::GREEN,
+            Severity::Info => Rgb::WHITE,
+            Severity::Warning => Rgb::AMBER,
+            Severity::Critical => Rgb::RED,
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ok" | "normal" => Ok(Severity::Ok),
+            "info" => Ok(Severity::Info),
+            "warning" | "warn" => Ok(Severity::Warning),
+            "critical" | "crit" | "error" => Ok(Severity::Critical),
+            other => Err(format!(
+                "expected 'ok', 'info', 'warning', or 'critical', got '{other}'"
+            )),
+        }
+    }
+}
+
+/// Hardware that drives the RGB backlight, separate from the text controller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RgbBacklightConfig {
+    /// PCA9633 (or pin-compatible PCA9632) I2C LED controller; Grove modules use 0x62.
+    Pca9633 { addr: u8 },
+    /// One software-PWM GPIO pin (BCM numbering) per channel.
+    GpioPwm { red: u8, green: u8, blue: u8 },
+}
+
+pub const DEFAULT_PCA9633_ADDR: u8 = 0x62;
+
+impl FromStr for RgbBacklightConfig {
+    type Err = String;
+
+    /// Parse `pca9633`, `pca9633@0x62`, or `gpio:r=17,g=27,b=22`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let raw = s.trim().to_ascii_lowercase();
+        if let Some(rest) = raw.strip_prefix("pca9633") {
+            let addr = match rest.strip_prefix('@') {
+                None if rest.is_empty() => DEFAULT_PCA9633_ADDR,
+                Some(addr) => u8::from_str_radix(addr.trim_start_matches("0x"), 16)
+                    .map_err(|_| format!("invalid pca9633 address '{addr}'"))?,
+                None => return Err(format!("unknown rgb backlight '{}'", s.trim())),
+            };
+            return Ok(RgbBacklightConfig::Pca9633 { addr });
+        }
+        if let Some(pins) = raw.strip_prefix("gpio:") {
+            let mut channels = [None; 3];
+            for part in pins.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+                let (name, pin) = part
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected r=/g=/b=<pin>, got '{part}'"))?;
+                let idx = match name.trim() {
+                    "r" | "red" => 0,
+                    "g" | "green" => 1,
+                    "b" | "blue" => 2,
+                    other => return Err(format!("unknown rgb channel '{other}'")),
+                };
+                channels[idx] = Some(
+                    pin.trim()
+                        .parse::<u8>()
+                        .map_err(|_| format!("invalid pin for {name}: '{}'", pin.trim()))?,
+                );
+            }
+            let [Some(red), Some(green), Some(blue)] = channels else {
+                return Err("gpio rgb backlight needs r=, g=, and b= pins".into());
+            };
+            if red == green || green == blue || red == blue {
+                return Err("rgb backlight pins must be distinct".into());
+            }
+            return Ok(RgbBacklightConfig::GpioPwm { red, green, blue });
+        }
+        Err(format!(
+            "expected 'pca9633[@addr]' or 'gpio:r=<pin>,g=<pin>,b=<pin>', got '{}'",
+            s.trim()
+        ))
+    }
+}
+
+impl fmt::Display for RgbBacklightConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RgbBacklightConfig::Pca9633 { addr } => write!(f, "pca9633@{addr:#04x}"),
+            RgbBacklightConfig::GpioPwm { red, green, blue } => {
+                write!(f, "gpio:r={red},g={green},b={blue}")
+            }
+        }
+    }
+}
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// One `HH:MM-HH:MM=<level>` entry; windows that end before they start wrap past midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleWindow {
+    /// Minutes after local midnight.
+    pub start: u16,
+    pub end: u16,
+    /// Brightness percent while the window is active; 0 turns the backlight off.
+    pub brightness: u8,
+}
+
+impl ScheduleWindow {
+    fn contains(&self, minute: u16) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+}
+
+/// Time-of-day backlight levels, e.g. `22:00-06:00=off,18:00-22:00=40`. The first matching
+/// window wins; outside every window the configured brightness applies.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BacklightSchedule {
+    pub windows: Vec<ScheduleWindow>,
+}
+
+impl BacklightSchedule {
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// Brightness the schedule asks for at `minute` past local midnight, if any window covers it.
+    pub fn brightness_at(&self, minute: u16) -> Option<u8> {
+        self.windows
+            .iter()
+            .find(|window| window.contains(minute))
+            .map(|window| window.brightness)
+    }
+}
+
+impl FromStr for BacklightSchedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut windows = Vec::new();
+        for entry in s.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (span, level) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected HH:MM-HH:MM=<level>, got '{entry}'"))?;
+            let (start, end) = span
+                .split_once('-')
+                .ok_or_else(|| format!("expected HH:MM-HH:MM, got '{}'", span.trim()))?;
+            let start = parse_time_of_day(start)?;
+            let end = parse_time_of_day(end)?;
+            if start == end {
+                return Err(format!("schedule window '{}' is empty", span.trim()));
+            }
+            windows.push(ScheduleWindow {
+                start,
+                end,
+                brightness: parse_brightness(level)?,
+            });
+        }
+        Ok(BacklightSchedule { windows })
+    }
+}
+
+impl fmt::Display for BacklightSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, window) in self.windows.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(",")?;
+            }
+            write!(
+                f,
+                "{:02}:{:02}-{:02}:{:02}=",
+                window.start / 60,
+                window.start % 60,
+                window.end / 60,
+                window.end % 60
+            )?;
+            match window.brightness {
+                0 => f.write_str("off")?,
+                level => write!(f, "{level}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_time_of_day(raw: &str) -> Result<u16, String> {
+    let raw = raw.trim();
+    let invalid = || format!("expected HH:MM, got '{raw}'");
+    let (hours, minutes) = raw.split_once(':').ok_or_else(invalid)?;
+    let hours: u16 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u16 = minutes.parse().map_err(|_| invalid())?;
+    // 24:00 is accepted as the end of the day.
+    if minutes >= 60 || hours * 60 + minutes > MINUTES_PER_DAY {
+        return Err(invalid());
+    }
+    Ok((hours * 60 + minutes) % MINUTES_PER_DAY)
+}
+
+/// `off`, `on`, or a percentage (`40` or `40%`).
+pub fn parse_brightness(raw: &str) -> Result<u8, String> {
+    let raw = raw.trim().to_ascii_lowercase();
+    match raw.as_str() {
+        "off" => Ok(0),
+        "on" | "full" => Ok(100),
+        _ => raw
+            .trim_end_matches('%')
+            .parse::<u8>()
+            .ok()
+            .filter(|level| *level <= 100)
+            .ok_or_else(|| format!("expected off, on, or a percentage 0-100, got '{raw}'")),
+    }
+}
+
+/// Minutes since local midnight in the system time zone (`TZ`, else `/etc/localtime`),
+/// falling back to UTC when no zone can be loaded.
+pub fn local_minute_of_day() -> u16 {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let local = secs + super::local_time::utc_offset_at(secs).unwrap_or(0);
+    (local.div_euclid(60) % i64::from(MINUTES_PER_DAY)) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_and_named_colours() {
+        assert_eq!("#ff7800".parse::<Rgb>().unwrap(), Rgb::AMBER);
+        assert_eq!("00FF00".parse::<Rgb>().unwrap(), Rgb::GREEN);
+        assert_eq!("Red".parse::<Rgb>().unwrap(), Rgb::RED);
+        assert_eq!(Rgb::AMBER.to_string(), "#ff7800");
+        assert!("#12345".parse::<Rgb>().is_err());
+        assert!("purple-ish".parse::<Rgb>().is_err());
+    }
+
+    #[test]
+    fn severity_maps_to_traffic_light_colours() {
+        assert_eq!("ok".parse::<Severity>().unwrap().color(), Rgb::GREEN);
+        assert_eq!("warn".parse::<Severity>().unwrap().color(), Rgb::AMBER);
+        assert_eq!("critical".parse::<Severity>().unwrap().color(), Rgb::RED);
+        assert!("panic".parse::<Severity>().is_err());
+    }
+
+    #[test]
+    fn rgb_backlight_config_round_trips() {
+        for raw in ["pca9633@0x62", "pca9633@0x60", "gpio:r=17,g=27,b=22"] {
+            let cfg: RgbBacklightConfig = raw.parse().unwrap();
+            assert_eq!(cfg.to_string(), raw);
+        }
+        assert_eq!(
+            "pca9633".parse::<RgbBacklightConfig>().unwrap(),
+            RgbBacklightConfig::Pca9633 {
+                addr: DEFAULT_PCA9633_ADDR
+            }
+        );
+        assert!("gpio:r=1,g=1,b=2".parse::<RgbBacklightConfig>().is_err());
+        assert!("gpio:r=1,g=2".parse::<RgbBacklightConfig>().is_err());
+        assert!("ws2812".parse::<RgbBacklightConfig>().is_err());
+    }
+
+    #[test]
+    fn schedule_windows_wrap_midnight_and_round_trip() {
+        let schedule: BacklightSchedule = "22:00-06:00=off, 18:00-22:00=40%".parse().unwrap();
+        assert_eq!(schedule.to_string(), "22:00-06:00=off,18:00-22:00=40");
+        assert_eq!(schedule.brightness_at(23 * 60), Some(0));
+        assert_eq!(schedule.brightness_at(3 * 60), Some(0));
+        assert_eq!(schedule.brightness_at(6 * 60), None);
+        assert_eq!(schedule.brightness_at(19 * 60 + 30), Some(40));
+        assert_eq!(schedule.brightness_at(12 * 60), None);
+        assert!("".parse::<BacklightSchedule>().unwrap().is_empty());
+        assert!("22:00-06:00".parse::<BacklightSchedule>().is_err());
+        assert!("25:00-06:00=off".parse::<BacklightSchedule>().is_err());
+        assert!("08:00-08:00=on".parse::<BacklightSchedule>().is_err());
+        assert!("08:00-09:00=150".parse::<BacklightSchedule>().is_err());
+    }
+
+    #[test]
+    fn scales_colour_by_brightness() {
+        assert_eq!(Rgb::AMBER.scaled(100), Rgb::AMBER);
+        assert_eq!(Rgb::AMBER.scaled(50), Rgb::new(128, 60, 0));
+        assert_eq!(Rgb::WHITE.scaled(0), Rgb::OFF);
+    }
+}
@@ -0,0 +1,614 @@
+//! Headless capture of what the LCD shows: PNG screenshots rasterised from an embedded
+//! HD44780 font plus the loaded CGRAM bitmaps, and a timestamped frame recorder writing
+//! asciicast v2 (`.cast`) or JSON lines.
+
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::{
+    display::{
+        backlight::Rgb,
+        virtual_lcd::{self, ScreenView},
+    },
+    Result,
+};
+
+/// Everything visible on one panel at a point in time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LcdSnapshot {
+    pub cols: u8,
+    /// Text per row as written, with CGRAM slots as characters 0-7.
+    pub lines: Vec<String>,
+    /// The same rows translated to character ROM codes (one `char` per byte).
+    pub rom_lines: Vec<String>,
+    pub glyphs: [[u8; 8]; 8],
+    pub backlight_on: bool,
+    pub backlight_color: Rgb,
+    pub blink_on: bool,
+}
+
+impl LcdSnapshot {
+    pub(crate) fn view(&self) -> ScreenView<'_> {
+        ScreenView {
+            cols: self.cols,
+            rows: &self.lines,
+            backlight_on: self.backlight_on,
+            backlight_color: self.backlight_color,
+            blink_on: self.blink_on,
+            glyphs: &self.glyphs,
+        }
+    }
+}
+
+const GLYPH_W: usize = 5;
+const GLYPH_H: usize = 8;
+/// Pixels of glass between cells, and around the whole panel, before scaling.
+const CELL_GAP: usize = 1;
+const BORDER: usize = 3;
+/// Each LCD dot becomes a SCALE×SCALE block.
+const SCALE: usize = 4;
+
+/// HD44780U A00 ROM codes 0x20-0x7F, five column bytes per character with bit 0 at the top.
+/// 0x5C is `¥`, 0x7E `→` and 0x7F `←` as on the real ROM.
+#[rustfmt::skip]
+const FONT_A00: [[u8; 5]; 96] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], [0x00, 0x00, 0x5f, 0x00, 0x00], [0x00, 0x07, 0x00, 0x07, 0x00], [0x14, 0x7f, 0x14, 0x7f, 0x14],
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12], [0x23, 0x13, 0x08, 0x64, 0x62], [0x36, 0x49, 0x55, 0x22, 0x50], [0x00, 0x05, 0x03, 0x00, 0x00],
+    [0x00, 0x1c, 0x22, 0x41, 0x00], [0x00, 0x41, 0x22, 0x1c, 0x00], [0x08, 0x2a, 0x1c, 0x2a, 0x08], [0x08, 0x08, 0x3e, 0x08, 0x08],
+    [0x00, 0x50, 0x30, 0x00, 0x00], [0x08, 0x08, 0x08, 0x08, 0x08], [0x00, 0x60, 0x60, 0x00, 0x00], [0x20, 0x10, 0x08, 0x04, 0x02],
+    [0x3e, 0x51, 0x49, 0x45, 0x3e], [0x00, 0x42, 0x7f, 0x40, 0x00], [0x42, 0x61, 0x51, 0x49, 0x46], [0x21, 0x41, 0x45, 0x4b, 0x31],
+    [0x18, 0x14, 0x12, 0x7f, 0x10], [0x27, 0x45, 0x45, 0x45, 0x39], [0x3c, 0x4a, 0x49, 0x49, 0x30], [0x01, 0x71, 0x09, 0x05, 0x03],
+    [0x36, 0x49, 0x49, 0x49, 0x36], [0x06, 0x49, 0x49, 0x29, 0x1e], [0x00, 0x36, 0x36, 0x00, 0x00], [0x00, 0x56, 0x36, 0x00, 0x00],
+    [0x08, 0x14, 0x22, 0x41, 0x00], [0x14, 0x14, 0x14, 0x14, 0x14], [0x00, 0x41, 0x22, 0x14, 0x08], [0x02, 0x01, 0x51, 0x09, 0x06],
+    [0x32, 0x49, 0x79, 0x41, 0x3e], [0x7e, 0x11, 0x11, 0x11, 0x7e], [0x7f, 0x49, 0x49, 0x49, 0x36], [0x3e, 0x41, 0x41, 0x41, 0x22],
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], [0x7f, 0x49, 0x49, 0x49, 0x41], [0x7f, 0x09, 0x09, 0x09, 0x01], [0x3e, 0x41, 0x49, 0x49, 0x7a],
+    [0x7f, 0x08, 0x08, 0x08, 0x7f], [0x00, 0x41, 0x7f, 0x41, 0x00], [0x20, 0x40, 0x41, 0x3f, 0x01], [0x7f, 0x08, 0x14, 0x22, 0x41],
+    [0x7f, 0x40, 0x40, 0x40, 0x40], [0x7f, 0x02, 0x0c, 0x02, 0x7f], [0x7f, 0x04, 0x08, 0x10, 0x7f], [0x3e, 0x41, 0x41, 0x41, 0x3e],
+    [0x7f, 0x09, 0x09, 0x09, 0x06], [0x3e, 0x41, 0x51, 0x21, 0x5e], [0x7f, 0x09, 0x19, 0x29, 0x46], [0x46, 0x49, 0x49, 0x49, 0x31],
+    [0x01, 0x01, 0x7f, 0x01, 0x01], [0x3f, 0x40, 0x40, 0x40, 0x3f], [0x1f, 0x20, 0x40, 0x20, 0x1f], [0x3f, 0x40, 0x38, 0x40, 0x3f],
+    [0x63, 0x14, 0x08, 0x14, 0x63], [0x07, 0x08, 0x70, 0x08, 0x07], [0x61, 0x51, 0x49, 0x45, 0x43], [0x00, 0x7f, 0x41, 0x41, 0x00],
+    [0x15, 0x16, 0x7c, 0x16, 0x15], [0x00, 0x41, 0x41, 0x7f, 0x00], [0x04, 0x02, 0x01, 0x02, 0x04], [0x40, 0x40, 0x40, 0x40, 0x40],
+    [0x00, 0x01, 0x02, 0x04, 0x00], [0x20, 0x54, 0x54, 0x54, 0x78], [0x7f, 0x48, 0x44, 0x44, 0x38], [0x38, 0x44, 0x44, 0x44, 0x20],
+    [0x38, 0x44, 0x44, 0x48, 0x7f], [0x38, 0x54, 0x54, 0x54, 0x18], [0x08, 0x7e, 0x09, 0x01, 0x02], [0x0c, 0x52, 0x52, 0x52, 0x3e],
+    [0x7f, 0x08, 0x04, 0x04, 0x78], [0x00, 0x44, 0x7d, 0x40, 0x00], [0x20, 0x40, 0x44, 0x3d, 0x00], [0x7f, 0x10, 0x28, 0x44, 0x00],
+    [0x00, 0x41, 0x7f, 0x40, 0x00], [0x7c, 0x04, 0x18, 0x04, 0x78], [0x7c, 0x08, 0x04, 0x04, 0x78], [0x38, 0x44, 0x44, 0x44, 0x38],
+    [0x7c, 0x14, 0x14, 0x14, 0x08], [0x08, 0x14, 0x14, 0x18, 0x7c], [0x7c, 0x08, 0x04, 0x04, 0x08], [0x48, 0x54, 0x54, 0x54, 0x20],
+    [0x04, 0x3f, 0x44, 0x40, 0x20], [0x3c, 0x40, 0x40, 0x20, 0x7c], [0x1c, 0x20, 0x40, 0x20, 0x1c], [0x3c, 0x40, 0x30, 0x40, 0x3c],
+    [0x44, 0x28, 0x10, 0x28, 0x44], [0x0c, 0x50, 0x50, 0x50, 0x3c], [0x44, 0x64, 0x54, 0x4c, 0x44], [0x00, 0x08, 0x36, 0x41, 0x00],
+    [0x00, 0x00, 0x7f, 0x00, 0x00], [0x00, 0x41, 0x36, 0x08, 0x00], [0x08, 0x08, 0x2a, 0x1c, 0x08], [0x08, 0x1c, 0x2a, 0x08, 0x08],
+];
+
+/// The few upper-half ROM codes the charset maps common symbols to.
+const FONT_A00_EXTRA: [(u8, [u8; 5]); 3] = [
+    (0xdf, [0x00, 0x07, 0x05, 0x07, 0x00]), // °
+    (0xe4, [0x7c, 0x20, 0x20, 0x10, 0x3c]), // µ
+    (0xff, [0x7f, 0x7f, 0x7f, 0x7f, 0x7f]), // full block
+];
+
+/// Hollow box for ROM codes the embedded font does not cover.
+const MISSING_GLYPH: [u8; 5] = [0x7f, 0x41, 0x41, 0x41, 0x7f];
+
+/// 5x8 bitmap (one row per byte, bit 4 leftmost) for a ROM code.
+fn rom_bitmap(code: u8, glyphs: &[[u8; 8]; 8]) -> [u8; 8] {
+    // Codes 0x00-0x0F address the eight CGRAM slots twice over.
+    if code < 0x10 {
+        return glyphs[(code & 0x07) as usize];
+    }
+    let columns = match code {
+        0x20..=0x7f => FONT_A00[(code - 0x20) as usize],
+        _ => FONT_A00_EXTRA
+            .iter()
+            .find(|(extra, _)| *extra == code)
+            .map(|(_, columns)| *columns)
+            .unwrap_or(MISSING_GLYPH),
+    };
+    let mut rows = [0u8; 8];
+    for (x, column) in columns.iter().enumerate() {
+        for (y, row) in rows.iter_mut().enumerate() {
+            if column & (1 << y) != 0 {
+                *row |= 1 << (GLYPH_W - 1 - x);
+            }
+        }
+    }
+    rows
+}
+
+/// RGB8 raster of the panel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Raster {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Raster {
+    fn fill(&mut self, x: usize, y: usize, w: usize, h: usize, color: Rgb) {
+        for row in y..y + h {
+            for col in x..x + w {
+                let idx = (row * self.width + col) * 3;
+                self.pixels[idx..idx + 3].copy_from_slice(&[color.r, color.g, color.b]);
+            }
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Rgb {
+        let idx = (y * self.width + x) * 3;
+        Rgb::new(self.pixels[idx], self.pixels[idx + 1], self.pixels[idx + 2])
+    }
+}
+
+fn scale_color(color: Rgb, num: u16, den: u16) -> Rgb {
+    let channel = |c: u8| (u16::from(c) * num / den) as u8;
+    Rgb::new(channel(color.r), channel(color.g), channel(color.b))
+}
+
+/// Glass, unlit dot, and lit dot colours for the current backlight.
+fn palette(snapshot: &LcdSnapshot) -> (Rgb, Rgb, Rgb) {
+    if snapshot.backlight_on {
+        let glass = scale_color(snapshot.backlight_color, 3, 4);
+        (glass, scale_color(glass, 7, 8), scale_color(glass, 1, 6))
+    } else {
+        let glass = Rgb::new(0x22, 0x26, 0x22);
+        (
+            glass,
+            Rgb::new(0x1e, 0x22, 0x1e),
+            Rgb::new(0x08, 0x08, 0x08),
+        )
+    }
+}
+
+/// Rasterise the panel: every cell is a 5x8 dot matrix, one dot of glass between cells.
+pub fn rasterize(snapshot: &LcdSnapshot) -> Raster {
+    let cols = snapshot.cols as usize;
+    let rows = snapshot.rom_lines.len();
+    let dots_w = 2 * BORDER + cols * (GLYPH_W + CELL_GAP) - CELL_GAP;
+    let dots_h = 2 * BORDER + rows * (GLYPH_H + CELL_GAP) - CELL_GAP;
+    let (glass, dot_off, dot_on) = palette(snapshot);
+    let mut raster = Raster {
+        width: dots_w * SCALE,
+        height: dots_h * SCALE,
+        pixels: Vec::new(),
+    };
+    raster.pixels = [glass.r, glass.g, glass.b].repeat(raster.width * raster.height);
+    for (row, line) in snapshot.rom_lines.iter().enumerate() {
+        let codes = line
+            .chars()
+            .map(|ch| ch as u32 as u8)
+            .chain(std::iter::repeat(b' '));
+        for (col, code) in codes.take(cols).enumerate() {
+            let bitmap = rom_bitmap(code, &snapshot.glyphs);
+            let origin_x = BORDER + col * (GLYPH_W + CELL_GAP);
+            let origin_y = BORDER + row * (GLYPH_H + CELL_GAP);
+            for (y, bits) in bitmap.iter().enumerate() {
+                for x in 0..GLYPH_W {
+                    let lit = bits & (1 << (GLYPH_W - 1 - x)) != 0;
+                    raster.fill(
+                        (origin_x + x) * SCALE,
+                        (origin_y + y) * SCALE,
+                        SCALE,
+                        SCALE,
+                        if lit { dot_on } else { dot_off },
+                    );
+                }
+            }
+        }
+    }
+    raster
+}
+
+/// Encode an RGB8 raster as PNG using stored (uncompressed) deflate blocks, so output is
+/// byte-for-byte stable across builds and golden images diff cleanly.
+pub fn encode_png(raster: &Raster) -> Vec<u8> {
+    let mut scanlines = Vec::with_capacity((raster.width * 3 + 1) * raster.height);
+    for row in raster.pixels.chunks(raster.width * 3) {
+        scanlines.push(0); // filter: none
+        scanlines.extend_from_slice(row);
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = scanlines.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[0x01, 0x00, 0x00, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        zlib.push(u8::from(blocks.peek().is_none()));
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&scanlines).to_be_bytes());
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&(raster.width as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(raster.height as u32).to_be_bytes());
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]); // 8-bit RGB, no interlace
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    write_chunk(&mut png, b"IHDR", &ihdr);
+    write_chunk(&mut png, b"IDAT", &zlib);
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(kind);
+    hasher.update(data);
+    out.extend_from_slice(&hasher.finalize().to_be_bytes());
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65_521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + u32::from(*byte)) % MOD;
+        b = (b + a) % MOD;
+    }
+    (b << 16) | a
+}
+
+/// Write a PNG screenshot of `snapshot` to `path`.
+pub fn save_png(snapshot: &LcdSnapshot, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, encode_png(&rasterize(snapshot)))?;
+    Ok(())
+}
+
+/// Output format of a [`FrameRecorder`], picked from the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// asciicast v2 (`.cast`), playable with `asciinema play`.
+    Asciicast,
+    /// One JSON object per frame (any other extension).
+    Jsonl,
+}
+
+impl RecordFormat {
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("cast") => RecordFormat::Asciicast,
+            _ => RecordFormat::Jsonl,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonlFrame<'a> {
+    t: f64,
+    lines: &'a [String],
+    backlight: bool,
+    backlight_color: String,
+    blink: bool,
+}
+
+/// Appends every completed frame with its offset from the start of the recording;
+/// consecutive identical frames are written once.
+pub struct FrameRecorder {
+    out: Box<dyn Write>,
+    format: RecordFormat,
+    started: Instant,
+    last: Option<LcdSnapshot>,
+}
+
+impl FrameRecorder {
+    pub fn create(path: &Path, cols: u8, rows: u8) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let file = BufWriter::new(File::create(path)?);
+        Self::with_writer(Box::new(file), RecordFormat::for_path(path), cols, rows)
+    }
+
+    pub fn with_writer(
+        mut out: Box<dyn Write>,
+        format: RecordFormat,
+        cols: u8,
+        rows: u8,
+    ) -> Result<Self> {
+        if format == RecordFormat::Asciicast {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            // Box, status line, and up to nine lines of glyph art below it.
+            let header = serde_json::json!({
+                "version": 2,
+                "width": (cols as usize + 2).max(48),
+                "height": rows as usize + 3 + 9,
+                "timestamp": timestamp,
+            });
+            writeln!(out, "{header}")?;
+        }
+        Ok(Self {
+            out,
+            format,
+            started: Instant::now(),
+            last: None,
+        })
+    }
+
+    pub fn record(&mut self, snapshot: &LcdSnapshot) -> Result<()> {
+        if self.last.as_ref() == Some(snapshot) {
+            return Ok(());
+        }
+        let t = self.started.elapsed().as_secs_f64();
+        let line = match self.format {
+            RecordFormat::Asciicast => {
+                let screen = virtual_lcd::render(&snapshot.view(), true).replace('\n', "\r\n");
+                serde_json::json!([t, "o", format!("\x1b[H\x1b[2J{screen}")]).to_string()
+            }
+            RecordFormat::Jsonl => serde_json::to_string(&JsonlFrame {
+                t,
+                lines: &snapshot.lines,
+                backlight: snapshot.backlight_on,
+                backlight_color: snapshot.backlight_color.to_string(),
+                blink: snapshot.blink_on,
+            })
+            .map_err(|e| crate::Error::Parse(format!("json: {e}")))?,
+        };
+        writeln!(self.out, "{line}")?;
+        self.out.flush()?;
+        self.last = Some(snapshot.clone());
+        Ok(())
+    }
+}
+
+/// Shortest gap between two screenshot rewrites; scrolling and blinking change the
+/// panel several times a second.
+const SCREENSHOT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where a display's completed frames go: a recording, a PNG rewritten as the panel
+/// changes, or both. Capture is best effort: a sink that fails is reported once and
+/// switched off, and rendering carries on.
+pub struct FrameCapture {
+    recorder: Option<FrameRecorder>,
+    screenshot: Option<PathBuf>,
+    last_screenshot: Option<LcdSnapshot>,
+    screenshot_at: Option<Instant>,
+    /// Newest frame held back by the throttle.
+    pending_screenshot: Option<LcdSnapshot>,
+}
+
+impl FrameCapture {
+    pub fn new(recorder: Option<FrameRecorder>, screenshot: Option<PathBuf>) -> Self {
+        Self {
+            recorder,
+            screenshot,
+            last_screenshot: None,
+            screenshot_at: None,
+            pending_screenshot: None,
+        }
+    }
+
+    pub fn capture(&mut self, snapshot: &LcdSnapshot) {
+        self.capture_at(snapshot, Instant::now());
+    }
+
+    fn capture_at(&mut self, snapshot: &LcdSnapshot, now: Instant) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            if let Err(e) = recorder.record(snapshot) {
+                eprintln!("warning: frame recording failed ({e}); recording disabled");
+                self.recorder = None;
+            }
+        }
+        if self.screenshot.is_none() || self.last_screenshot.as_ref() == Some(snapshot) {
+            self.pending_screenshot = None;
+            return;
+        }
+        let due = self
+            .screenshot_at
+            .is_none_or(|at| now.saturating_duration_since(at) >= SCREENSHOT_INTERVAL);
+        if due {
+            self.write_screenshot(snapshot.clone(), now);
+        } else {
+            self.pending_screenshot = Some(snapshot.clone());
+        }
+    }
+
+    fn write_screenshot(&mut self, snapshot: LcdSnapshot, now: Instant) {
+        let Some(path) = self.screenshot.as_ref() else {
+            return;
+        };
+        self.pending_screenshot = None;
+        self.screenshot_at = Some(now);
+        match save_png(&snapshot, path) {
+            Ok(()) => self.last_screenshot = Some(snapshot),
+            Err(e) => {
+                eprintln!(
+                    "warning: screenshot {} failed ({e}); screenshots disabled",
+                    path.display()
+                );
+                self.screenshot = None;
+            }
+        }
+    }
+}
+
+impl Drop for FrameCapture {
+    /// Leave the screenshot showing the last frame even if the throttle held it back.
+    fn drop(&mut self) {
+        if let Some(snapshot) = self.pending_screenshot.take() {
+            self.write_screenshot(snapshot, Instant::now());
+        }
+    }
+}
+
+/// Path for an extra display's capture: `frames.cast` becomes `frames-alerts.cast`.
+pub fn path_for_display(path: &Path, display: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{stem}-{display}.{}", ext.to_string_lossy()),
+        None => format!("{stem}-{display}"),
+    };
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    fn snapshot(lines: &[&str]) -> LcdSnapshot {
+        LcdSnapshot {
+            cols: 4,
+            lines: lines.iter().map(|l| l.to_string()).collect(),
+            rom_lines: lines.iter().map(|l| l.to_string()).collect(),
+            glyphs: [[0x1f; 8]; 8],
+            backlight_on: true,
+            backlight_color: Rgb::WHITE,
+            blink_on: false,
+        }
+    }
+
+    #[test]
+    fn font_rows_come_out_left_to_right() {
+        let glyphs = [[0u8; 8]; 8];
+        // 'A': .###. / #...# / #...# / #...# / #####
+        assert_eq!(
+            rom_bitmap(b'A', &glyphs)[..5],
+            [0x0e, 0x11, 0x11, 0x11, 0x1f]
+        );
+        assert_eq!(rom_bitmap(b'A', &glyphs)[7], 0, "cursor row stays blank");
+        assert_eq!(rom_bitmap(0x09, &[[0x15; 8]; 8]), [0x15; 8], "CGRAM mirror");
+        assert_eq!(
+            rom_bitmap(0xa7, &glyphs)[0],
+            0x1f,
+            "unknown codes draw a box"
+        );
+    }
+
+    #[test]
+    fn rasterises_lit_and_unlit_dots() {
+        let raster = rasterize(&snapshot(&["\u{0}", ""]));
+        let dots_w = 2 * BORDER + 4 * (GLYPH_W + CELL_GAP) - CELL_GAP;
+        assert_eq!(raster.width, dots_w * SCALE);
+        let (glass, dot_off, dot_on) = palette(&snapshot(&[]));
+        assert_eq!(raster.pixel(0, 0), glass);
+        assert_eq!(raster.pixel(BORDER * SCALE, BORDER * SCALE), dot_on);
+        let second_cell = (BORDER + GLYPH_W + CELL_GAP) * SCALE;
+        assert_eq!(raster.pixel(second_cell, BORDER * SCALE), dot_off);
+    }
+
+    #[test]
+    fn png_has_valid_structure() {
+        let png = encode_png(&rasterize(&snapshot(&["Hi", "42"])));
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+        // IHDR CRC covers the chunk type and data.
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&png[12..29]);
+        assert_eq!(png[29..33], hasher.finalize().to_be_bytes());
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[derive(Clone, Default)]
+    struct Sink(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn recorder_writes_jsonl_and_skips_repeats() {
+        let sink = Sink::default();
+        let mut recorder =
+            FrameRecorder::with_writer(Box::new(sink.clone()), RecordFormat::Jsonl, 4, 2).unwrap();
+        recorder.record(&snapshot(&["A", "B"])).unwrap();
+        recorder.record(&snapshot(&["A", "B"])).unwrap();
+        recorder.record(&snapshot(&["C", "D"])).unwrap();
+        let text = String::from_utf8(sink.0.borrow().clone()).unwrap();
+        let frames: Vec<serde_json::Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1]["lines"], serde_json::json!(["C", "D"]));
+        assert_eq!(frames[0]["backlight_color"], "#ffffff");
+    }
+
+    #[test]
+    fn screenshots_are_throttled_and_failures_disable_capture() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("panel.png");
+        let start = Instant::now();
+        let mut capture = FrameCapture::new(None, Some(path.clone()));
+        capture.capture_at(&snapshot(&["A", ""]), start);
+        let first = fs::read(&path).unwrap();
+        capture.capture_at(&snapshot(&["B", ""]), start + Duration::from_millis(100));
+        assert_eq!(fs::read(&path).unwrap(), first, "rewrite held back");
+        drop(capture);
+        assert_ne!(fs::read(&path).unwrap(), first, "pending frame flushed");
+
+        // A screenshot path that cannot be written turns screenshots off.
+        let blocked = dir.path().join("panel.png").join("x.png");
+        let mut capture = FrameCapture::new(None, Some(blocked));
+        capture.capture_at(&snapshot(&["A", ""]), start);
+        assert!(capture.screenshot.is_none());
+        capture.capture_at(&snapshot(&["B", ""]), start + SCREENSHOT_INTERVAL);
+    }
+
+    #[test]
+    fn recorder_errors_disable_recording() {
+        struct Broken;
+        impl Write for Broken {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("disk full"))
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let recorder =
+            FrameRecorder::with_writer(Box::new(Broken), RecordFormat::Jsonl, 4, 2).unwrap();
+        let mut capture = FrameCapture::new(Some(recorder), None);
+        capture.capture(&snapshot(&["A", "B"]));
+        assert!(capture.recorder.is_none());
+    }
+
+    #[test]
+    fn recorder_writes_asciicast_header_and_events() {
+        let sink = Sink::default();
+        let mut recorder =
+            FrameRecorder::with_writer(Box::new(sink.clone()), RecordFormat::Asciicast, 16, 2)
+                .unwrap();
+        recorder.record(&snapshot(&["Hi", ""])).unwrap();
+        let text = String::from_utf8(sink.0.borrow().clone()).unwrap();
+        let mut lines = text.lines();
+        let header: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(header["version"], 2);
+        let event: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(event[1], "o");
+        assert!(event[2].as_str().unwrap().contains("Hi"));
+        assert_eq!(
+            RecordFormat::for_path(Path::new("/tmp/x.cast")),
+            RecordFormat::Asciicast
+        );
+        assert_eq!(
+            path_for_display(Path::new("/tmp/frames.cast"), "alerts"),
+            PathBuf::from("/tmp/frames-alerts.cast")
+        );
+    }
+}
@@ -0,0 +1,426 @@
+use std::{fmt, str::FromStr, sync::OnceLock};
+
+/// Character ROM mask programmed into the HD44780 (printed as `A00`/`A02` on datasheets).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CharRom {
+    /// Japanese ROM: ASCII (with `¥` at 0x5C, arrows at 0x7E/0x7F), half-width katakana,
+    /// and a handful of Greek/math symbols. Nearly every PCF8574 backpack ships with this.
+    #[default]
+    A00,
+    /// European ROM: full ASCII, Cyrillic/Greek in 0x80–0x9F, Latin-1 in 0xA0–0xFF.
+    A02,
+}
+
+impl FromStr for CharRom {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "a00" => Ok(Self::A00),
+            "a02" => Ok(Self::A02),
+            other => Err(format!("invalid character_rom '{other}', expected a00|a02")),
+        }
+    }
+}
+
+impl fmt::Display for CharRom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CharRom::A00 => write!(f, "a00"),
+            CharRom::A02 => write!(f, "a02"),
+        }
+    }
+}
+
+/// Translates Unicode text into the byte stream the configured ROM understands.
+///
+/// Lookup order per character: the ROM itself (including visual lookalikes such as Cyrillic
+/// `А` → `A`), a CGRAM glyph already placed by the [`IconBank`](super::icon_bank::IconBank),
+/// an accent-stripped ASCII fold, and finally the replacement character.
+/// Characters `\u{0}`–`\u{7}` are CGRAM slots and pass through untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Charset {
+    rom: CharRom,
+    replacement: char,
+}
+
+impl Default for Charset {
+    fn default() -> Self {
+        Self::new(CharRom::default(), '?')
+    }
+}
+
+impl Charset {
+    pub fn new(rom: CharRom, replacement: char) -> Self {
+        Self { rom, replacement }
+    }
+
+    pub fn rom(&self) -> CharRom {
+        self.rom
+    }
+
+    /// ROM code for `ch`, if the character ROM can show it without help.
+    pub fn rom_code(&self, ch: char) -> Option<u8> {
+        let direct = match self.rom {
+            CharRom::A00 => a00_code(ch),
+            CharRom::A02 => a02_code(ch),
+        };
+        direct.or_else(|| {
+            let upper = cyrillic_upper(ch)?;
+            match self.rom {
+                CharRom::A00 => a00_code(upper),
+                CharRom::A02 => a02_code(upper),
+            }
+        })
+    }
+
+    /// True when `ch` is missing from the ROM but a CGRAM glyph can stand in for it.
+    pub fn needs_cgram(&self, ch: char) -> bool {
+        self.rom_code(ch).is_none() && glyph_bitmap(ch).is_some()
+    }
+
+    /// Encode `text` for the driver; every output `char` is a single ROM byte.
+    pub fn encode(&self, text: &str) -> String {
+        text.chars().map(|ch| self.encode_char(ch)).collect()
+    }
+
+    fn encode_char(&self, ch: char) -> char {
+        if (ch as u32) < 8 {
+            return ch;
+        }
+        if let Some(code) = self.rom_code(ch) {
+            return char::from(code);
+        }
+        if let Some(code) = fold(ch).and_then(|folded| self.rom_code(folded)) {
+            return char::from(code);
+        }
+        self.rom_code(self.replacement)
+            .map(char::from)
+            .unwrap_or('?')
+    }
+
+    /// Turn [`Charset::encode`] output back into the Unicode characters the panel shows,
+    /// so a terminal draws `¥` where the A00 ROM does. CGRAM slots stay `\u{0}`–`\u{7}`.
+    pub fn decode(&self, rom_text: &str) -> String {
+        let table = rom_table(self.rom);
+        rom_text
+            .chars()
+            .map(|ch| table.get(ch as usize).copied().unwrap_or(MISSING_CHAR))
+            .collect()
+    }
+}
+
+/// Stand-in for ROM codes no Unicode character maps to.
+const MISSING_CHAR: char = '□';
+
+/// Lowest Unicode character for each ROM code, built once per ROM.
+fn rom_table(rom: CharRom) -> &'static [char; 256] {
+    static A00: OnceLock<[char; 256]> = OnceLock::new();
+    static A02: OnceLock<[char; 256]> = OnceLock::new();
+    let (table, rom_code): (_, fn(char) -> Option<u8>) = match rom {
+        CharRom::A00 => (&A00, a00_code),
+        CharRom::A02 => (&A02, a02_code),
+    };
+    table.get_or_init(|| {
+        let mut chars = [None; 256];
+        // Codes 0x00-0x0F address the eight CGRAM slots twice over.
+        for (code, slot) in chars.iter_mut().enumerate().take(0x10) {
+            *slot = Some(char::from(code as u8 & 0x07));
+        }
+        for ch in ' '..='\u{ffff}' {
+            if let Some(code) = rom_code(ch) {
+                chars[code as usize].get_or_insert(ch);
+            }
+        }
+        chars.map(|ch| ch.unwrap_or(MISSING_CHAR))
+    })
+}
+
+/// 5x8 CGRAM bitmap for characters neither ROM covers well.
+pub fn glyph_bitmap(ch: char) -> Option<[u8; 8]> {
+    let ch = cyrillic_upper(ch).unwrap_or(ch);
+    let rows = match ch {
+        'é' => [
+            0b00010, 0b00100, 0b01110, 0b10001, 0b11111, 0b10000, 0b01110, 0,
+        ],
+        'è' => [
+            0b01000, 0b00100, 0b01110, 0b10001, 0b11111, 0b10000, 0b01110, 0,
+        ],
+        'à' => [
+            0b01000, 0b00100, 0b01110, 0b00001, 0b01111, 0b10001, 0b01111, 0,
+        ],
+        'ç' => [
+            0, 0b01110, 0b10000, 0b10000, 0b10001, 0b01110, 0b00100, 0b01100,
+        ],
+        '\\' => [0, 0b10000, 0b01000, 0b00100, 0b00010, 0b00001, 0, 0],
+        '~' => [0, 0, 0b01000, 0b10101, 0b00010, 0, 0, 0],
+        '€' => [
+            0b00110, 0b01001, 0b11110, 0b01000, 0b11110, 0b01001, 0b00110, 0,
+        ],
+        'Б' => [
+            0b11111, 0b10000, 0b10000, 0b11110, 0b10001, 0b10001, 0b11110, 0,
+        ],
+        'Г' => [
+            0b11111, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0,
+        ],
+        'Д' => [
+            0b00110, 0b01010, 0b01010, 0b01010, 0b01010, 0b11111, 0b10001, 0,
+        ],
+        'Ж' => [
+            0b10101, 0b10101, 0b10101, 0b01110, 0b10101, 0b10101, 0b10101, 0,
+        ],
+        'И' => [
+            0b10001, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b10001, 0,
+        ],
+        'Л' => [
+            0b00111, 0b01001, 0b01001, 0b01001, 0b01001, 0b01001, 0b10001, 0,
+        ],
+        'П' => [
+            0b11111, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0,
+        ],
+        'Ф' => [
+            0b00100, 0b01110, 0b10101, 0b10101, 0b10101, 0b01110, 0b00100, 0,
+        ],
+        'Ц' => [
+            0b10010, 0b10010, 0b10010, 0b10010, 0b10010, 0b10010, 0b11111, 0b00001,
+        ],
+        'Ч' => [
+            0b10001, 0b10001, 0b10001, 0b01111, 0b00001, 0b00001, 0b00001, 0,
+        ],
+        'Ш' => [
+            0b10101, 0b10101, 0b10101, 0b10101, 0b10101, 0b10101, 0b11111, 0,
+        ],
+        'Ю' => [
+            0b10010, 0b10101, 0b10101, 0b11101, 0b10101, 0b10101, 0b10010, 0,
+        ],
+        'Я' => [
+            0b01111, 0b10001, 0b10001, 0b01111, 0b00101, 0b01001, 0b10001, 0,
+        ],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+fn a00_code(ch: char) -> Option<u8> {
+    let code = ch as u32;
+    match ch {
+        '\\' | '~' => None,
+        ' '..='}' => Some(code as u8),
+        '¥' => Some(0x5c),
+        '→' => Some(0x7e),
+        '←' => Some(0x7f),
+        // Half-width katakana and punctuation follow JIS X 0201 one-to-one.
+        '\u{ff61}'..='\u{ff9f}' => Some((code - 0xff61 + 0xa1) as u8),
+        '・' | '·' | '•' => Some(0xa5),
+        '°' => Some(0xdf),
+        'α' => Some(0xe0),
+        'ä' => Some(0xe1),
+        'β' | 'ß' => Some(0xe2),
+        'ε' => Some(0xe3),
+        'μ' | 'µ' => Some(0xe4),
+        'σ' => Some(0xe5),
+        'ρ' => Some(0xe6),
+        '√' => Some(0xe8),
+        '¢' => Some(0xec),
+        'ñ' => Some(0xee),
+        'ö' => Some(0xef),
+        'θ' => Some(0xf2),
+        '∞' => Some(0xf3),
+        'Ω' | '\u{2126}' => Some(0xf4),
+        'ü' => Some(0xf5),
+        'Σ' | '∑' => Some(0xf6),
+        'π' => Some(0xf7),
+        '千' => Some(0xfa),
+        '万' => Some(0xfb),
+        '円' => Some(0xfc),
+        '÷' => Some(0xfd),
+        '█' => Some(0xff),
+        _ => cyrillic_lookalike(ch).and_then(a00_code),
+    }
+}
+
+fn a02_code(ch: char) -> Option<u8> {
+    let code = ch as u32;
+    match ch {
+        ' '..='~' => Some(code as u8),
+        '►' | '▶' => Some(0x10),
+        '◄' | '◀' => Some(0x11),
+        '↑' => Some(0x18),
+        '↓' => Some(0x19),
+        '→' => Some(0x1a),
+        '←' => Some(0x1b),
+        '≤' => Some(0x1c),
+        '≥' => Some(0x1d),
+        '▲' => Some(0x1e),
+        '▼' => Some(0x1f),
+        'Б' => Some(0x80),
+        'Д' => Some(0x81),
+        'Ж' => Some(0x82),
+        'З' => Some(0x83),
+        'И' => Some(0x84),
+        'Й' => Some(0x85),
+        'Л' => Some(0x86),
+        'П' => Some(0x87),
+        'У' => Some(0x88),
+        'Ц' => Some(0x89),
+        'Ч' => Some(0x8a),
+        'Ш' => Some(0x8b),
+        'Щ' => Some(0x8c),
+        'Ъ' => Some(0x8d),
+        'Ы' => Some(0x8e),
+        'Э' => Some(0x8f),
+        'α' => Some(0x90),
+        'Γ' | 'Г' => Some(0x92),
+        'π' => Some(0x93),
+        'Σ' | '∑' => Some(0x94),
+        'σ' => Some(0x95),
+        'τ' => Some(0x97),
+        'Θ' => Some(0x99),
+        'Ω' | '\u{2126}' => Some(0x9a),
+        'δ' => Some(0x9b),
+        '∞' => Some(0x9c),
+        'ε' => Some(0x9e),
+        '•' => Some(0xb7),
+        // Latin-1 supplement sits at its ISO-8859-1 code points.
+        '\u{a1}'..='\u{ff}' => Some(code as u8),
+        _ => cyrillic_lookalike(ch).and_then(a02_code),
+    }
+}
+
+/// Cyrillic letters that are drawn identically to a Latin letter or digit.
+fn cyrillic_lookalike(ch: char) -> Option<char> {
+    let mapped = match ch {
+        'А' => 'A',
+        'В' => 'B',
+        'Е' => 'E',
+        'З' => '3',
+        'К' => 'K',
+        'М' => 'M',
+        'Н' => 'H',
+        'О' => 'O',
+        'Р' => 'P',
+        'С' => 'C',
+        'Т' => 'T',
+        'У' => 'Y',
+        'Х' => 'X',
+        'а' => 'a',
+        'е' => 'e',
+        'о' => 'o',
+        'р' => 'p',
+        'с' => 'c',
+        'у' => 'y',
+        'х' => 'x',
+        _ => return None,
+    };
+    Some(mapped)
+}
+
+/// Uppercase form of a lowercase Cyrillic letter; the ROMs only carry capitals.
+fn cyrillic_upper(ch: char) -> Option<char> {
+    if ('а'..='я').contains(&ch) || ('ѐ'..='џ').contains(&ch) {
+        ch.to_uppercase().next()
+    } else {
+        None
+    }
+}
+
+/// Lossy single-character ASCII fold used when neither ROM nor CGRAM can help.
+fn fold(ch: char) -> Option<char> {
+    let folded = match ch {
+        'À'..='Å' | 'Ą' | 'Ā' => 'A',
+        'à'..='å' | 'ą' | 'ā' => 'a',
+        'Ç' | 'Ć' | 'Č' => 'C',
+        'ç' | 'ć' | 'č' => 'c',
+        'Ď' => 'D',
+        'ď' => 'd',
+        'È'..='Ë' | 'Ę' | 'Ě' | 'Ē' | 'Ё' => 'E',
+        'è'..='ë' | 'ę' | 'ě' | 'ē' | 'ё' => 'e',
+        'Ì'..='Ï' => 'I',
+        'ì'..='ï' | 'ı' => 'i',
+        'Ł' => 'L',
+        'ł' => 'l',
+        'Ñ' | 'Ń' | 'Ň' => 'N',
+        'ñ' | 'ń' | 'ň' => 'n',
+        'Ò'..='Ö' | 'Ø' | 'Ő' => 'O',
+        'ò'..='ö' | 'ø' | 'ő' => 'o',
+        'Ř' => 'R',
+        'ř' => 'r',
+        'Ś' | 'Š' => 'S',
+        'ś' | 'š' | 'ß' => 's',
+        'Ť' => 'T',
+        'ť' => 't',
+        'Ù'..='Ü' | 'Ů' | 'Ű' => 'U',
+        'ù'..='ü' | 'ů' | 'ű' => 'u',
+        'Ý' | 'Ÿ' => 'Y',
+        'ý' | 'ÿ' => 'y',
+        'Ź' | 'Ż' | 'Ž' => 'Z',
+        'ź' | 'ż' | 'ž' => 'z',
+        '‘' | '’' | '′' => '\'',
+        '“' | '”' | '″' => '"',
+        '–' | '—' | '−' => '-',
+        '…' => '.',
+        '\u{a0}' => ' ',
+        '×' => 'x',
+        '•' | '·' => '*',
+        _ => return None,
+    };
+    Some(folded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(charset: &Charset, text: &str) -> Vec<u8> {
+        charset.encode(text).chars().map(|ch| ch as u8).collect()
+    }
+
+    #[test]
+    fn a00_maps_symbols_and_katakana() {
+        let charset = Charset::new(CharRom::A00, '?');
+        assert_eq!(bytes(&charset, "25°C"), vec![b'2', b'5', 0xdf, b'C']);
+        assert_eq!(bytes(&charset, "µ→ü"), vec![0xe4, 0x7e, 0xf5]);
+        assert_eq!(bytes(&charset, "ｱｲ"), vec![0xb1, 0xb2]);
+        assert_eq!(bytes(&charset, "\\"), vec![b'?'], "0x5c is ¥ on A00");
+    }
+
+    #[test]
+    fn a02_maps_latin1_and_cyrillic() {
+        let charset = Charset::new(CharRom::A02, '?');
+        assert_eq!(bytes(&charset, "café"), vec![b'c', b'a', b'f', 0xe9]);
+        assert_eq!(bytes(&charset, "ЖУк"), vec![0x82, 0x88, b'K']);
+        assert_eq!(bytes(&charset, "→~"), vec![0x1a, b'~']);
+    }
+
+    #[test]
+    fn falls_back_to_fold_then_replacement() {
+        let charset = Charset::new(CharRom::A00, '#');
+        assert_eq!(charset.encode("Łódź"), "Lodz");
+        assert_eq!(charset.encode("✓"), "#");
+    }
+
+    #[test]
+    fn cgram_slots_pass_through_and_gaps_request_glyphs() {
+        let charset = Charset::new(CharRom::A00, '?');
+        assert_eq!(charset.encode("\u{3}ok"), "\u{3}ok");
+        assert!(charset.needs_cgram('é'));
+        assert!(charset.needs_cgram('я'));
+        assert!(!charset.needs_cgram('ü'), "ü is in the A00 ROM");
+        assert!(!Charset::new(CharRom::A02, '?').needs_cgram('é'));
+    }
+
+    #[test]
+    fn decode_shows_what_the_rom_draws() {
+        let a00 = Charset::new(CharRom::A00, '?');
+        assert_eq!(a00.decode(&a00.encode("¥5 naïve\\ 25°C")), "¥5 naive? 25°C");
+        assert_eq!(a00.decode("\u{3}\u{5c}\u{7e}"), "\u{3}¥→");
+        let a02 = Charset::new(CharRom::A02, '?');
+        assert_eq!(a02.decode(&a02.encode("café→")), "café→");
+    }
+
+    #[test]
+    fn parses_rom_names() {
+        assert_eq!("A02".parse::<CharRom>().unwrap(), CharRom::A02);
+        assert!("a01".parse::<CharRom>().is_err());
+    }
+}
@@ -1,6 +1,10 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::{display::lcd::Lcd, payload::Icon, Result};
+use crate::{
+    display::{charset, lcd::Lcd},
+    payload::Icon,
+    Result,
+};
 
 pub trait GlyphWriter {
     fn write_glyph(&mut self, slot: u8, bitmap: &[u8; 8]) -> Result<()>;
@@ -33,6 +37,7 @@ enum GlyphKind {
     Bar(u8),
     Heartbeat,
     Icon(Icon),
+    Char(char),
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -45,6 +50,7 @@ pub struct IconPalette {
     bar_chars: [Option<char>; BAR_LEVEL_COUNT],
     heartbeat_char: Option<char>,
     icon_chars: HashMap<Icon, char>,
+    text_chars: HashMap<char, char>,
     pub missing_icons: Vec<Icon>,
 }
 
@@ -54,6 +60,7 @@ impl IconPalette {
             bar_chars: [None; BAR_LEVEL_COUNT],
             heartbeat_char: None,
             icon_chars: HashMap::new(),
+            text_chars: HashMap::new(),
             missing_icons: Vec::new(),
         }
     }
@@ -69,6 +76,9 @@ impl IconPalette {
             GlyphKind::Icon(icon) => {
                 self.icon_chars.insert(icon, ch);
             }
+            GlyphKind::Char(text) => {
+                self.text_chars.insert(text, ch);
+            }
         }
     }
 
@@ -93,6 +103,11 @@ impl IconPalette {
     pub fn icon_char(&self, icon: Icon) -> Option<char> {
         self.icon_chars.get(&icon).copied()
     }
+
+    /// CGRAM slot standing in for a text character the ROM cannot show.
+    pub fn text_char(&self, ch: char) -> Option<char> {
+        self.text_chars.get(&ch).copied()
+    }
 }
 
 pub struct IconBank {
@@ -140,6 +155,14 @@ impl IconBank {
             }
         }
 
+        // Text glyphs come last so bars, heartbeat, and icons keep priority over CGRAM slots.
+        for ch in request.chars {
+            let kind = GlyphKind::Char(*ch);
+            if charset::glyph_bitmap(*ch).is_some() && !required.contains(&kind) {
+                required.push(kind);
+            }
+        }
+
         let required_set: HashSet<GlyphKind> = required.iter().copied().collect();
         for kind in required {
             match self.ensure_glyph(kind, &required_set, writer)? {
@@ -241,6 +264,7 @@ fn bitmap_for(kind: GlyphKind) -> Option<[u8; 8]> {
         GlyphKind::Bar(level) => BAR_BITMAPS.get(level as usize).copied(),
         GlyphKind::Heartbeat => Icon::Heart.bitmap(),
         GlyphKind::Icon(icon) => icon.bitmap(),
+        GlyphKind::Char(ch) => charset::glyph_bitmap(ch),
     }
 }
 
@@ -253,6 +277,8 @@ pub struct PaletteRequest<'a> {
     pub bar_required: bool,
     pub heartbeat: bool,
     pub icons: &'a [Icon],
+    /// Text characters missing from the character ROM that should borrow a CGRAM slot.
+    pub chars: &'a [char],
 }
 
 impl Default for IconPalette {
@@ -286,6 +312,7 @@ mod tests {
             bar_required: false,
             heartbeat: false,
             icons: &icon_list,
+            chars: &[],
         };
 
         let palette = bank.build_palette(&mut writer, request).unwrap();
@@ -322,6 +349,7 @@ mod tests {
                     bar_required: true,
                     heartbeat: true,
                     icons: &icons,
+                    chars: &[],
                 },
             )
             .unwrap();
@@ -334,4 +362,33 @@ mod tests {
             .iter()
             .all(|icon| icons[1..].contains(icon)));
     }
+
+    #[test]
+    fn text_glyphs_fill_remaining_slots_after_icons() {
+        let mut bank = IconBank::new();
+        let mut writer = TestWriter::default();
+        let palette = bank
+            .build_palette(
+                &mut writer,
+                PaletteRequest {
+                    bar_required: true,
+                    heartbeat: false,
+                    icons: &[Icon::Battery],
+                    chars: &['é', 'Ж', 'x'],
+                },
+            )
+            .unwrap();
+
+        assert!(palette.icon_char(Icon::Battery).is_some());
+        assert!(palette.text_char('é').is_some());
+        assert!(
+            palette.text_char('Ж').is_none(),
+            "no slot left for a ninth glyph"
+        );
+        assert!(
+            palette.text_char('x').is_none(),
+            "no bitmap for plain ASCII"
+        );
+        assert!(palette.missing_icons.is_empty());
+    }
 }
@@ -1,5 +1,15 @@
 use crate::{
-    config::{DisplayDriver, Pcf8574Addr},
+    config::{DisplayDriver, Pcf8574Addr, DEFAULT_DISPLAY_DRIVER, DEFAULT_PCF8574_ADDR},
+    display::{
+        backlight::{Rgb, RgbBacklightConfig},
+        capture::{FrameCapture, LcdSnapshot},
+        charset::Charset,
+        virtual_lcd::{ScreenView, VirtualScreen},
+    },
+    lcd_driver::{
+        backpack::{Backpack, PinMap},
+        parallel::ParallelPins,
+    },
     Error, Result,
 };
 
@@ -7,7 +17,9 @@ use crate::{
 use crate::lcd_driver::{
     self,
     external::ExternalHd44780,
+    parallel::{ParallelHd44780, RppalGpioBus},
     pcf8574::{I2cdevBus, RppalBus},
+    rgb::{GpioPwmRgb, Pca9633, RppalPwmBus, GROVE_CHANNELS},
 };
 #[cfg(target_os = "linux")]
 use linux_embedded_hal::I2cdev;
@@ -24,7 +36,6 @@ pub const BATTERY_CHAR: char = '\u{7}';
 pub const CGRAM_FREE_CHAR: char = BATTERY_CHAR;
 pub const WIFI_CHAR: char = 'w';
 
-#[cfg(target_os = "linux")]
 const BAR_GLYPHS: [[&str; 8]; 8] = [
     [
         "00000", "00000", "00000", "00000", "00000", "00000", "00000", "00000",
@@ -52,9 +63,6 @@ const BAR_GLYPHS: [[&str; 8]; 8] = [
     ],
 ];
 
-#[cfg(target_os = "linux")]
-const PCF8574_ADDR_CANDIDATES: [u8; 8] = [0x27, 0x26, 0x25, 0x24, 0x23, 0x22, 0x21, 0x20];
-
 #[cfg(target_os = "linux")]
 const I2CDEV_PATHS: [&str; 2] = ["/dev/i2c-1", "/dev/i2c-0"];
 
@@ -96,28 +104,49 @@ fn discover_i2cdev_paths(dev_dir: &std::path::Path) -> Vec<PathBuf> {
     out
 }
 
+/// `BAR_GLYPHS` as CGRAM bitmaps, so the stub's CGRAM matches a freshly initialised panel.
+fn bar_glyph_bitmaps() -> [[u8; 8]; 8] {
+    let mut bitmaps = [[0u8; 8]; 8];
+    for (bitmap, glyph) in bitmaps.iter_mut().zip(BAR_GLYPHS.iter()) {
+        for (row, bits) in bitmap.iter_mut().zip(glyph.iter()) {
+            *row = crate::lcd_driver::parse_bitmap_row(bits).unwrap_or(0);
+        }
+    }
+    bitmaps
+}
+
+/// Last state pushed to the panel; every backend mirrors into it so frames can be
+/// captured and simulated without hardware.
 struct StubState {
     last_lines: (String, String),
+    /// Every row, for the virtual display; `last_lines` mirrors the first two.
+    rows: Vec<String>,
     backlight_on: bool,
+    backlight_color: Rgb,
+    brightness: u8,
     blink_on: bool,
     clears: usize,
     custom_chars: [[u8; 8]; 8],
 }
 
 impl StubState {
-    fn new() -> Self {
+    fn new(rows: u8) -> Self {
         Self {
             last_lines: (String::new(), String::new()),
+            rows: vec![String::new(); rows as usize],
             backlight_on: true,
+            backlight_color: Rgb::default(),
+            brightness: 100,
             blink_on: false,
             clears: 0,
-            custom_chars: [[0u8; 8]; 8],
+            custom_chars: bar_glyph_bitmaps(),
         }
     }
 
     fn clear(&mut self) -> Result<()> {
         self.clears = self.clears.saturating_add(1);
         self.last_lines = (String::new(), String::new());
+        self.rows.iter_mut().for_each(String::clear);
         Ok(())
     }
 
@@ -137,6 +166,9 @@ impl StubState {
             1 => self.last_lines.1 = line.to_string(),
             _ => (),
         }
+        if let Some(slot) = self.rows.get_mut(row as usize) {
+            *slot = line.to_string();
+        }
         Ok(())
     }
 
@@ -147,6 +179,117 @@ impl StubState {
     }
 }
 
+/// Unchanged cells between two dirty runs that are cheaper to rewrite than to skip with a
+/// second DDRAM address command.
+const RUN_MERGE_GAP: usize = 1;
+
+/// Mirror of the controller's DDRAM so redraws only touch cells that changed.
+///
+/// `None` marks a cell whose contents are unknown (before the first write, or after a failed
+/// bus transaction); such cells are always rewritten.
+struct DdramShadow {
+    cells: Vec<Vec<Option<char>>>,
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+impl DdramShadow {
+    fn new(cols: u8, rows: u8) -> Self {
+        Self {
+            cells: vec![vec![None; cols as usize]; rows as usize],
+        }
+    }
+
+    /// The controller was cleared: every cell now holds a space.
+    fn blank(&mut self) {
+        for row in &mut self.cells {
+            row.fill(Some(' '));
+        }
+    }
+
+    fn invalidate_row(&mut self, row: u8) {
+        if let Some(cells) = self.cells.get_mut(row as usize) {
+            cells.fill(None);
+        }
+    }
+
+    /// Record `text` (padded with spaces) as the new row contents and return the
+    /// `(column, run)` writes needed to get there.
+    fn diff_row(&mut self, row: u8, text: &str) -> Vec<(u8, String)> {
+        let Some(cells) = self.cells.get_mut(row as usize) else {
+            return Vec::new();
+        };
+        let mut wanted: Vec<char> = text.chars().take(cells.len()).collect();
+        wanted.resize(cells.len(), ' ');
+
+        let mut spans: Vec<(usize, usize)> = Vec::new();
+        for (col, ch) in wanted.iter().enumerate() {
+            if cells[col] == Some(*ch) {
+                continue;
+            }
+            match spans.last_mut() {
+                Some((_, end)) if col - *end <= RUN_MERGE_GAP + 1 => *end = col,
+                _ => spans.push((col, col)),
+            }
+        }
+        for (cell, ch) in cells.iter_mut().zip(&wanted) {
+            *cell = Some(*ch);
+        }
+        spans
+            .into_iter()
+            .map(|(start, end)| (start as u8, wanted[start..=end].iter().collect()))
+            .collect()
+    }
+}
+
+/// How a panel is attached: driver, I2C backpack or controller, and wiring.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LcdWiring {
+    /// I2C address, or `auto` to probe the backpack's usual range.
+    pub pcf8574_addr: Pcf8574Addr,
+    pub display_driver: DisplayDriver,
+    pub backpack: Backpack,
+    /// Expander bit assignments; `None` uses the backpack's stock wiring.
+    pub pin_map: Option<PinMap>,
+    /// GPIO pins for `DisplayDriver::Gpio`.
+    pub gpio_pins: Option<ParallelPins>,
+    /// Separate RGB backlight controller, if the panel has one.
+    pub rgb_backlight: Option<RgbBacklightConfig>,
+    /// I2C bus number (`/dev/i2c-N`); `None` probes the usual buses.
+    pub i2c_bus: Option<u8>,
+}
+
+impl Default for LcdWiring {
+    fn default() -> Self {
+        Self {
+            pcf8574_addr: DEFAULT_PCF8574_ADDR,
+            display_driver: DEFAULT_DISPLAY_DRIVER,
+            backpack: Backpack::default(),
+            pin_map: None,
+            gpio_pins: None,
+            rgb_backlight: None,
+            i2c_bus: None,
+        }
+    }
+}
+
+impl LcdWiring {
+    pub fn expander_pins(&self) -> PinMap {
+        self.pin_map
+            .unwrap_or_else(|| self.backpack.default_pin_map())
+    }
+
+    /// True for 40x4 modules with a second enable line; the driver sends rows 2-3 there.
+    pub fn is_dual_controller(&self) -> bool {
+        match self.display_driver {
+            DisplayDriver::Gpio => self
+                .gpio_pins
+                .as_ref()
+                .is_some_and(|pins| pins.e2.is_some()),
+            _ => self.backpack.is_expander() && self.expander_pins().e2.is_some(),
+        }
+    }
+}
+
 #[cfg(target_os = "linux")]
 pub enum LcdBus {
     Rppal(RppalI2c),
@@ -160,8 +303,17 @@ pub struct Lcd {
     rows: u8,
     stub: StubState,
     observe_stub: bool,
+    virtual_screen: Option<VirtualScreen>,
+    capture: Option<FrameCapture>,
+    charset: Charset,
+    shadow: DdramShadow,
+    backlight_lit: bool,
+    backlight_color: Rgb,
+    brightness: u8,
     #[cfg(target_os = "linux")]
     driver: Option<DriverBackend>,
+    #[cfg(target_os = "linux")]
+    rgb: Option<RgbBackend>,
 }
 
 fn observe_lcd_stub_enabled() -> bool {
@@ -179,32 +331,64 @@ impl Lcd {
         Self {
             cols,
             rows,
-            stub: StubState::new(),
+            stub: StubState::new(rows),
             observe_stub: observe_lcd_stub_enabled(),
+            virtual_screen: None,
+            capture: None,
+            charset: Charset::default(),
+            shadow: DdramShadow::new(cols, rows),
+            backlight_lit: true,
+            backlight_color: Rgb::default(),
+            brightness: 100,
             #[cfg(target_os = "linux")]
             driver: None,
+            #[cfg(target_os = "linux")]
+            rgb: None,
         }
     }
 
-    pub fn new(
-        cols: u8,
-        rows: u8,
-        pcf_addr: Pcf8574Addr,
-        display_driver: DisplayDriver,
-    ) -> Result<Self> {
+    pub fn new(cols: u8, rows: u8, wiring: &LcdWiring) -> Result<Self> {
         #[cfg(target_os = "linux")]
         {
-            let stub = StubState::new();
-            match DriverBackend::new(cols, rows, pcf_addr, display_driver) {
-                Ok((mut driver, addr)) => {
-                    eprintln!("pcf8574 addr: 0x{addr:02x}");
+            let stub = StubState::new(rows);
+            let backend = match (wiring.display_driver, &wiring.gpio_pins) {
+                (DisplayDriver::Gpio, Some(pins)) => {
+                    eprintln!("lcd gpio pins: {pins}");
+                    DriverBackend::new_parallel(cols, rows, pins.clone())
+                }
+                (DisplayDriver::Gpio, None) => Err(Error::InvalidArgs(
+                    "display_driver = \"gpio\" requires gpio_pins".into(),
+                )),
+                _ => DriverBackend::new(cols, rows, wiring).map(|(driver, addr)| {
+                    eprintln!("{} addr: 0x{addr:02x}", wiring.backpack);
+                    driver
+                }),
+            };
+            match backend {
+                Ok(mut driver) => {
                     driver.load_bar_glyphs()?;
+                    let mut rgb = wiring
+                        .rgb_backlight
+                        .as_ref()
+                        .map(|rgb| RgbBackend::open(rgb, wiring.i2c_bus))
+                        .transpose()?;
+                    if let Some(rgb) = rgb.as_mut() {
+                        rgb.set_color(Rgb::default())?;
+                    }
                     Ok(Self {
                         cols,
                         rows,
                         stub,
                         observe_stub: observe_lcd_stub_enabled(),
+                        virtual_screen: None,
+                        capture: None,
+                        charset: Charset::default(),
+                        shadow: DdramShadow::new(cols, rows),
+                        backlight_lit: true,
+                        backlight_color: Rgb::default(),
+                        brightness: 100,
                         driver: Some(driver),
+                        rgb,
                     })
                 }
                 Err(err) => {
@@ -217,67 +401,180 @@ impl Lcd {
 
         #[cfg(not(target_os = "linux"))]
         {
-            let _ = (pcf_addr, display_driver);
+            let _ = wiring;
             Ok(Self {
                 cols,
                 rows,
-                stub: StubState::new(),
+                stub: StubState::new(rows),
                 observe_stub: observe_lcd_stub_enabled(),
+                virtual_screen: None,
+                capture: None,
+                charset: Charset::default(),
+                shadow: DdramShadow::new(cols, rows),
+                backlight_lit: true,
+                backlight_color: Rgb::default(),
+                brightness: 100,
             })
         }
     }
 
-    fn observe_stub_snapshot(&self) {
-        if !self.observe_stub {
-            return;
+    /// Terminal simulator instead of hardware (`--display virtual`).
+    pub fn new_virtual(cols: u8, rows: u8) -> Result<Self> {
+        let mut lcd = Self::new_stub(cols, rows);
+        lcd.virtual_screen = Some(VirtualScreen::stdout());
+        lcd.finish_frame()?;
+        Ok(lcd)
+    }
+
+    /// Record or screenshot every completed frame from now on.
+    pub fn set_capture(&mut self, capture: FrameCapture) {
+        self.capture = Some(capture);
+    }
+
+    /// Mark the end of a complete frame (all rows written) so capture sees no half-drawn
+    /// screens.
+    pub fn finish_frame(&mut self) -> Result<()> {
+        if self.capture.is_none() && self.virtual_screen.is_none() {
+            return Ok(());
         }
-        eprintln!(
-            "LIFELINETTY_LCD {:?} | {:?}",
-            self.stub.last_lines.0, self.stub.last_lines.1
-        );
+        let snapshot = self.snapshot();
+        if let Some(capture) = self.capture.as_mut() {
+            capture.capture(&snapshot);
+        }
+        if let Some(screen) = self.virtual_screen.as_mut() {
+            // Draw the ROM codes the panel receives, not the Unicode that was written.
+            let rows: Vec<String> = snapshot
+                .rom_lines
+                .iter()
+                .map(|row| self.charset.decode(row))
+                .collect();
+            screen.present(&ScreenView {
+                rows: &rows,
+                ..snapshot.view()
+            })?;
+        }
+        Ok(())
+    }
+
+    /// What the panel currently shows, including CGRAM contents and backlight state.
+    pub fn snapshot(&self) -> LcdSnapshot {
+        LcdSnapshot {
+            cols: self.cols,
+            lines: self.stub.rows.clone(),
+            rom_lines: self
+                .stub
+                .rows
+                .iter()
+                .map(|row| self.charset.encode(row))
+                .collect(),
+            glyphs: self.stub.custom_chars,
+            backlight_on: self.stub.backlight_on && self.stub.brightness > 0,
+            backlight_color: self.stub.backlight_color.scaled(self.stub.brightness),
+            blink_on: self.stub.blink_on,
+        }
+    }
+
+    /// Echo stub state for `LIFELINETTY_LCD_OBSERVE`; the virtual display redraws from
+    /// [`Lcd::finish_frame`].
+    fn stub_changed(&mut self) -> Result<()> {
+        if self.observe_stub {
+            eprintln!(
+                "LIFELINETTY_LCD {:?} | {:?}",
+                self.stub.last_lines.0, self.stub.last_lines.1
+            );
+        }
+        Ok(())
     }
 
     pub fn render_boot_message(&mut self) -> Result<()> {
         self.clear()?;
-        self.write_line(0, "LifelineTTY ready")
+        self.write_line(0, "LifelineTTY ready")?;
+        self.finish_frame()
     }
 
     pub fn clear(&mut self) -> Result<()> {
         #[cfg(target_os = "linux")]
         {
             if let Some(driver) = &mut self.driver {
-                return driver.clear();
+                driver.clear()?;
+                self.shadow.blank();
             }
         }
-        let out = self.stub.clear();
-        self.observe_stub_snapshot();
-        out
+        self.stub.clear()?;
+        self.stub_changed()
     }
 
     pub fn set_backlight(&mut self, on: bool) -> Result<()> {
+        self.backlight_lit = on;
+        #[cfg(target_os = "linux")]
+        {
+            if let Some(driver) = &mut self.driver {
+                driver.set_backlight(on && self.brightness > 0)?;
+                self.apply_backlight_color()?;
+            }
+        }
+        self.stub.set_backlight(on)?;
+        self.stub_changed()
+    }
+
+    /// Colour shown while the backlight is on; ignored by panels without an RGB backlight.
+    pub fn set_backlight_color(&mut self, color: Rgb) -> Result<()> {
+        if self.backlight_color == color {
+            return Ok(());
+        }
+        self.backlight_color = color;
+        self.stub.backlight_color = color;
+        #[cfg(target_os = "linux")]
+        {
+            self.apply_backlight_color()?;
+        }
+        self.stub_changed()
+    }
+
+    /// Backlight level in percent. RGB/PWM backlights dim to it; single-transistor
+    /// backlights can only switch, so any level above 0 is fully on.
+    pub fn set_brightness(&mut self, percent: u8) -> Result<()> {
+        let percent = percent.min(100);
+        if self.brightness == percent {
+            return Ok(());
+        }
+        self.brightness = percent;
+        self.stub.brightness = percent;
         #[cfg(target_os = "linux")]
         {
             if let Some(driver) = &mut self.driver {
-                return driver.set_backlight(on);
+                driver.set_backlight(self.backlight_lit && percent > 0)?;
             }
+            self.apply_backlight_color()?;
         }
-        let out = self.stub.set_backlight(on);
-        self.observe_stub_snapshot();
-        out
+        self.stub_changed()
+    }
+
+    #[cfg(target_os = "linux")]
+    fn apply_backlight_color(&mut self) -> Result<()> {
+        let Some(rgb) = self.rgb.as_mut() else {
+            return Ok(());
+        };
+        rgb.set_color(if self.backlight_lit {
+            self.backlight_color.scaled(self.brightness)
+        } else {
+            Rgb::OFF
+        })
     }
 
     pub fn set_blink(&mut self, on: bool) -> Result<()> {
         #[cfg(target_os = "linux")]
         {
             if let Some(driver) = &mut self.driver {
-                return driver.set_blink(on);
+                driver.set_blink(on)?;
             }
         }
-        let out = self.stub.set_blink(on);
-        self.observe_stub_snapshot();
-        out
+        self.stub.set_blink(on)?;
+        self.stub_changed()
     }
 
+    /// Show `content` on `row`, padded with spaces; only cells that differ from what the
+    /// panel already shows are sent over the bus.
     pub fn write_line(&mut self, row: u8, content: &str) -> Result<()> {
         if row >= self.rows {
             return Err(Error::InvalidArgs(format!(
@@ -291,12 +588,17 @@ impl Lcd {
         #[cfg(target_os = "linux")]
         {
             if let Some(driver) = &mut self.driver {
-                return driver.write_line(row, &trimmed);
+                let encoded = self.charset.encode(&trimmed);
+                for (col, run) in self.shadow.diff_row(row, &encoded) {
+                    if let Err(err) = driver.write_at(col, row, &run) {
+                        self.shadow.invalidate_row(row);
+                        return Err(err);
+                    }
+                }
             }
         }
-        let out = self.stub.write_line(row, &trimmed);
-        self.observe_stub_snapshot();
-        out
+        self.stub.write_line(row, &trimmed)?;
+        self.stub_changed()
     }
 
     /// Convenience to write both lines back-to-back to reduce flicker.
@@ -309,12 +611,20 @@ impl Lcd {
         #[cfg(target_os = "linux")]
         {
             if let Some(driver) = &mut self.driver {
-                return driver.custom_char(slot, bitmap);
+                driver.custom_char(slot, bitmap)?;
             }
         }
-        let out = self.stub.custom_char(slot, bitmap);
-        self.observe_stub_snapshot();
-        out
+        self.stub.custom_char(slot, bitmap)?;
+        self.stub_changed()
+    }
+
+    /// Select the character ROM and replacement used when text reaches the glass.
+    pub fn set_charset(&mut self, charset: Charset) {
+        self.charset = charset;
+    }
+
+    pub fn charset(&self) -> Charset {
+        self.charset
     }
 
     pub fn cols(&self) -> u8 {
@@ -330,32 +640,36 @@ impl Lcd {
         cols: u8,
         rows: u8,
         addr: u8,
-        display_driver: DisplayDriver,
+        wiring: &LcdWiring,
         bus: LcdBus,
     ) -> Result<Self> {
         let mut driver = match bus {
-            LcdBus::Rppal(raw) => DriverBackend::from_rppal_bus(
-                RppalBus::from_inner(raw),
-                addr,
-                cols,
-                rows,
-                display_driver,
-            )?,
+            LcdBus::Rppal(raw) => {
+                DriverBackend::from_rppal_bus(RppalBus::from_inner(raw), addr, cols, rows, wiring)?
+            }
             LcdBus::I2cdev(dev) => DriverBackend::from_i2cdev_bus(
                 I2cdevBus::from_inner(dev),
                 addr,
                 cols,
                 rows,
-                display_driver,
+                wiring,
             )?,
         };
         driver.load_bar_glyphs()?;
         Ok(Self {
             cols,
             rows,
-            stub: StubState::new(),
+            stub: StubState::new(rows),
             observe_stub: observe_lcd_stub_enabled(),
+            virtual_screen: None,
+            capture: None,
+            charset: Charset::default(),
+            shadow: DdramShadow::new(cols, rows),
+            backlight_lit: true,
+            backlight_color: Rgb::default(),
+            brightness: 100,
             driver: Some(driver),
+            rgb: None,
         })
     }
 
@@ -367,6 +681,14 @@ impl Lcd {
         self.stub.backlight_on
     }
 
+    pub fn last_backlight_color(&self) -> Rgb {
+        self.stub.backlight_color
+    }
+
+    pub fn last_brightness(&self) -> u8 {
+        self.stub.brightness
+    }
+
     pub fn last_blink(&self) -> bool {
         self.stub.blink_on
     }
@@ -388,10 +710,70 @@ fn load_bar_glyphs_external(driver: &mut ExternalHd44780) -> Result<()> {
     driver.load_custom_bitmaps(&BAR_GLYPHS)
 }
 
+#[cfg(target_os = "linux")]
+enum RgbBackend {
+    Pca9633Rppal(Pca9633<RppalBus>),
+    Pca9633I2cdev(Pca9633<I2cdevBus>),
+    Pwm(GpioPwmRgb<RppalPwmBus>),
+}
+
+#[cfg(target_os = "linux")]
+impl RgbBackend {
+    fn open(config: &RgbBacklightConfig, i2c_bus: Option<u8>) -> Result<Self> {
+        match *config {
+            RgbBacklightConfig::Pca9633 { addr } => match DriverBackend::open_rppal_bus(i2c_bus) {
+                Ok(bus) => Ok(Self::Pca9633Rppal(Pca9633::new(bus, addr, GROVE_CHANNELS)?)),
+                Err(_) => {
+                    let bus = DriverBackend::open_i2cdev_bus(i2c_bus)?;
+                    Ok(Self::Pca9633I2cdev(Pca9633::new(
+                        bus,
+                        addr,
+                        GROVE_CHANNELS,
+                    )?))
+                }
+            },
+            RgbBacklightConfig::GpioPwm { red, green, blue } => {
+                let bus = RppalPwmBus::new(&[red, green, blue])?;
+                Ok(Self::Pwm(GpioPwmRgb::new(bus, red, green, blue)))
+            }
+        }
+    }
+
+    fn set_color(&mut self, color: Rgb) -> Result<()> {
+        match self {
+            RgbBackend::Pca9633Rppal(led) => led.set_rgb(color.r, color.g, color.b),
+            RgbBackend::Pca9633I2cdev(led) => led.set_rgb(color.r, color.g, color.b),
+            RgbBackend::Pwm(led) => led.set_rgb(color.r, color.g, color.b),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn require_pcf8574_for_external(wiring: &LcdWiring) -> Result<()> {
+    if wiring.backpack != Backpack::Pcf8574 {
+        return Err(Error::InvalidArgs(format!(
+            "display_driver = \"hd44780-driver\" only supports the pcf8574 backpack, not {}",
+            wiring.backpack
+        )));
+    }
+    if wiring.is_dual_controller() {
+        return Err(Error::InvalidArgs(
+            "display_driver = \"hd44780-driver\" cannot drive a second enable line (e2)".into(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn gpio_on_i2c_error() -> Error {
+    Error::InvalidArgs("display_driver = \"gpio\" drives GPIO pins, not an I2C bus".into())
+}
+
 #[cfg(target_os = "linux")]
 enum DriverBackend {
     Internal(InternalDriver),
     External(ExternalHd44780),
+    Parallel(ParallelHd44780<RppalGpioBus>),
 }
 
 #[cfg(target_os = "linux")]
@@ -402,13 +784,33 @@ enum InternalDriver {
 
 #[cfg(target_os = "linux")]
 impl InternalDriver {
-    fn from_rppal(bus: RppalBus, addr: u8, cols: u8, rows: u8) -> Result<Self> {
-        let driver = lcd_driver::Hd44780::new(bus, addr, cols, rows)?;
+    fn from_rppal(bus: RppalBus, addr: u8, cols: u8, rows: u8, wiring: &LcdWiring) -> Result<Self> {
+        let driver = lcd_driver::Hd44780::with_backpack(
+            bus,
+            addr,
+            cols,
+            rows,
+            wiring.backpack,
+            wiring.expander_pins(),
+        )?;
         Ok(Self::Rppal(driver))
     }
 
-    fn from_i2cdev(bus: I2cdevBus, addr: u8, cols: u8, rows: u8) -> Result<Self> {
-        let driver = lcd_driver::Hd44780::new(bus, addr, cols, rows)?;
+    fn from_i2cdev(
+        bus: I2cdevBus,
+        addr: u8,
+        cols: u8,
+        rows: u8,
+        wiring: &LcdWiring,
+    ) -> Result<Self> {
+        let driver = lcd_driver::Hd44780::with_backpack(
+            bus,
+            addr,
+            cols,
+            rows,
+            wiring.backpack,
+            wiring.expander_pins(),
+        )?;
         Ok(Self::I2cdev(driver))
     }
 
@@ -457,10 +859,10 @@ impl InternalDriver {
         }
     }
 
-    fn write_line(&mut self, row: u8, text: &str) -> Result<()> {
+    fn write_at(&mut self, col: u8, row: u8, text: &str) -> Result<()> {
         match self {
-            InternalDriver::Rppal(driver) => driver.write_line(row, text),
-            InternalDriver::I2cdev(driver) => driver.write_line(row, text),
+            InternalDriver::Rppal(driver) => driver.write_at(col, row, text),
+            InternalDriver::I2cdev(driver) => driver.write_at(col, row, text),
         }
     }
 
@@ -481,19 +883,14 @@ impl InternalDriver {
 
 #[cfg(target_os = "linux")]
 impl DriverBackend {
-    fn new(
-        cols: u8,
-        rows: u8,
-        pcf_addr: Pcf8574Addr,
-        preference: DisplayDriver,
-    ) -> Result<(Self, u8)> {
-        match Self::new_with_rppal(cols, rows, pcf_addr.clone(), preference) {
+    fn new(cols: u8, rows: u8, wiring: &LcdWiring) -> Result<(Self, u8)> {
+        match Self::new_with_rppal(cols, rows, wiring) {
             Ok(tuple) => Ok(tuple),
             Err(primary_err) => {
                 eprintln!(
                     "warning: rppal I2C init failed ({primary_err}); trying linux-embedded-hal"
                 );
-                match Self::new_with_i2cdev(cols, rows, pcf_addr, preference) {
+                match Self::new_with_i2cdev(cols, rows, wiring) {
                     Ok(tuple) => Ok(tuple),
                     Err(fallback_err) => Err(Error::Io(std::io::Error::other(format!(
                         "lcd init failed: {primary_err}; fallback: {fallback_err}"
@@ -503,23 +900,32 @@ impl DriverBackend {
         }
     }
 
+    fn new_parallel(cols: u8, rows: u8, pins: ParallelPins) -> Result<Self> {
+        let bus = RppalGpioBus::new(&pins)?;
+        Ok(DriverBackend::Parallel(ParallelHd44780::new(
+            bus, pins, cols, rows,
+        )?))
+    }
+
     fn from_rppal_bus(
         bus: RppalBus,
         addr: u8,
         cols: u8,
         rows: u8,
-        preference: DisplayDriver,
+        wiring: &LcdWiring,
     ) -> Result<Self> {
-        match preference {
+        match wiring.display_driver {
             DisplayDriver::Hd44780Driver => {
+                require_pcf8574_for_external(wiring)?;
                 let raw = bus.into_inner();
                 let external = ExternalHd44780::new_from_rppal(raw, addr, cols, rows)?;
                 Ok(DriverBackend::External(external))
             }
             DisplayDriver::Auto | DisplayDriver::InTree => {
-                let internal = InternalDriver::from_rppal(bus, addr, cols, rows)?;
+                let internal = InternalDriver::from_rppal(bus, addr, cols, rows, wiring)?;
                 Ok(DriverBackend::Internal(internal))
             }
+            DisplayDriver::Gpio => Err(gpio_on_i2c_error()),
         }
     }
 
@@ -528,52 +934,60 @@ impl DriverBackend {
         addr: u8,
         cols: u8,
         rows: u8,
-        preference: DisplayDriver,
+        wiring: &LcdWiring,
     ) -> Result<Self> {
-        match preference {
+        match wiring.display_driver {
             DisplayDriver::Hd44780Driver => {
+                require_pcf8574_for_external(wiring)?;
                 let raw = bus.into_inner();
                 let external = ExternalHd44780::new_from_i2cdev(raw, addr, cols, rows)?;
                 Ok(DriverBackend::External(external))
             }
             DisplayDriver::Auto | DisplayDriver::InTree => {
-                let internal = InternalDriver::from_i2cdev(bus, addr, cols, rows)?;
+                let internal = InternalDriver::from_i2cdev(bus, addr, cols, rows, wiring)?;
                 Ok(DriverBackend::Internal(internal))
             }
+            DisplayDriver::Gpio => Err(gpio_on_i2c_error()),
         }
     }
 
-    fn new_with_rppal(
-        cols: u8,
-        rows: u8,
-        pcf_addr: Pcf8574Addr,
-        preference: DisplayDriver,
-    ) -> Result<(Self, u8)> {
-        let mut bus = RppalBus::new_default()?;
-        let addr = match pcf_addr {
-            Pcf8574Addr::Auto => bus.detect_address(&PCF8574_ADDR_CANDIDATES, 0x27),
+    fn new_with_rppal(cols: u8, rows: u8, wiring: &LcdWiring) -> Result<(Self, u8)> {
+        let mut bus = Self::open_rppal_bus(wiring.i2c_bus)?;
+        let addr = match wiring.pcf8574_addr {
+            Pcf8574Addr::Auto => bus.detect_address(
+                wiring.backpack.address_candidates(),
+                wiring.backpack.default_address(),
+            ),
             Pcf8574Addr::Addr(addr) => addr,
         };
-        let backend = Self::from_rppal_bus(bus, addr, cols, rows, preference)?;
+        let backend = Self::from_rppal_bus(bus, addr, cols, rows, wiring)?;
         Ok((backend, addr))
     }
 
-    fn new_with_i2cdev(
-        cols: u8,
-        rows: u8,
-        pcf_addr: Pcf8574Addr,
-        preference: DisplayDriver,
-    ) -> Result<(Self, u8)> {
-        let mut bus = Self::open_i2cdev_bus()?;
-        let addr = match pcf_addr {
-            Pcf8574Addr::Auto => bus.detect_address(&PCF8574_ADDR_CANDIDATES, 0x27),
+    fn new_with_i2cdev(cols: u8, rows: u8, wiring: &LcdWiring) -> Result<(Self, u8)> {
+        let mut bus = Self::open_i2cdev_bus(wiring.i2c_bus)?;
+        let addr = match wiring.pcf8574_addr {
+            Pcf8574Addr::Auto => bus.detect_address(
+                wiring.backpack.address_candidates(),
+                wiring.backpack.default_address(),
+            ),
             Pcf8574Addr::Addr(addr) => addr,
         };
-        let backend = Self::from_i2cdev_bus(bus, addr, cols, rows, preference)?;
+        let backend = Self::from_i2cdev_bus(bus, addr, cols, rows, wiring)?;
         Ok((backend, addr))
     }
 
-    fn open_i2cdev_bus() -> Result<I2cdevBus> {
+    fn open_rppal_bus(i2c_bus: Option<u8>) -> Result<RppalBus> {
+        match i2c_bus {
+            Some(bus) => RppalBus::new_with_bus(bus),
+            None => RppalBus::new_default(),
+        }
+    }
+
+    fn open_i2cdev_bus(i2c_bus: Option<u8>) -> Result<I2cdevBus> {
+        if let Some(bus) = i2c_bus {
+            return I2cdevBus::from_path(PathBuf::from(format!("/dev/i2c-{bus}")));
+        }
         let mut failures: Vec<String> = Vec::new();
         let candidates = discover_i2cdev_paths(std::path::Path::new("/dev"));
 
@@ -599,6 +1013,7 @@ impl DriverBackend {
         match self {
             DriverBackend::Internal(driver) => driver.clear(),
             DriverBackend::External(driver) => driver.clear(),
+            DriverBackend::Parallel(driver) => driver.clear(),
         }
     }
 
@@ -607,6 +1022,8 @@ impl DriverBackend {
             (DriverBackend::Internal(driver), _) => driver.set_backlight(on),
             (DriverBackend::External(driver), true) => driver.backlight_on(),
             (DriverBackend::External(driver), false) => driver.backlight_off(),
+            (DriverBackend::Parallel(driver), true) => driver.backlight_on(),
+            (DriverBackend::Parallel(driver), false) => driver.backlight_off(),
         }
     }
 
@@ -615,13 +1032,16 @@ impl DriverBackend {
             (DriverBackend::Internal(driver), _) => driver.set_blink(on),
             (DriverBackend::External(driver), true) => driver.blink_cursor_on(),
             (DriverBackend::External(driver), false) => driver.blink_cursor_off(),
+            (DriverBackend::Parallel(driver), true) => driver.blink_cursor_on(),
+            (DriverBackend::Parallel(driver), false) => driver.blink_cursor_off(),
         }
     }
 
-    fn write_line(&mut self, row: u8, text: &str) -> Result<()> {
+    fn write_at(&mut self, col: u8, row: u8, text: &str) -> Result<()> {
         match self {
-            DriverBackend::Internal(driver) => driver.write_line(row, text),
-            DriverBackend::External(driver) => driver.write_line(row, text),
+            DriverBackend::Internal(driver) => driver.write_at(col, row, text),
+            DriverBackend::External(driver) => driver.write_at(col, row, text),
+            DriverBackend::Parallel(driver) => driver.write_at(col, row, text),
         }
     }
 
@@ -629,6 +1049,7 @@ impl DriverBackend {
         match self {
             DriverBackend::Internal(driver) => driver.load_bar_glyphs(),
             DriverBackend::External(driver) => load_bar_glyphs_external(driver),
+            DriverBackend::Parallel(driver) => driver.load_custom_bitmaps(&BAR_GLYPHS),
         }
     }
 
@@ -636,6 +1057,7 @@ impl DriverBackend {
         match self {
             DriverBackend::Internal(driver) => driver.custom_char(slot, bitmap),
             DriverBackend::External(driver) => driver.custom_char(slot, bitmap),
+            DriverBackend::Parallel(driver) => driver.custom_char(slot, bitmap),
         }
     }
 }
@@ -694,13 +1116,7 @@ mod tests {
     #[test]
     #[ignore]
     fn rejects_out_of_bounds_row() {
-        let mut lcd = Lcd::new(
-            16,
-            2,
-            crate::config::DEFAULT_PCF8574_ADDR,
-            crate::config::DEFAULT_DISPLAY_DRIVER,
-        )
-        .unwrap();
+        let mut lcd = Lcd::new(16, 2, &LcdWiring::default()).unwrap();
         let err = lcd.write_line(2, "oops").unwrap_err();
         assert!(format!("{err}").contains("out of bounds"));
     }
@@ -708,13 +1124,68 @@ mod tests {
     #[test]
     #[ignore]
     fn accepts_in_bounds_row() {
-        let mut lcd = Lcd::new(
-            16,
-            2,
-            crate::config::DEFAULT_PCF8574_ADDR,
-            crate::config::DEFAULT_DISPLAY_DRIVER,
-        )
-        .unwrap();
+        let mut lcd = Lcd::new(16, 2, &LcdWiring::default()).unwrap();
         lcd.write_line(1, "ok").unwrap();
     }
+
+    #[test]
+    fn shadow_first_write_covers_the_whole_row() {
+        let mut shadow = DdramShadow::new(8, 2);
+        assert_eq!(shadow.diff_row(0, "hi"), vec![(0, "hi      ".to_string())]);
+        assert!(shadow.diff_row(0, "hi").is_empty(), "unchanged row is free");
+    }
+
+    #[test]
+    fn shadow_emits_short_runs_for_changed_cells() {
+        let mut shadow = DdramShadow::new(16, 2);
+        shadow.blank();
+        shadow.diff_row(1, "CPU 12%  T 40C");
+        assert_eq!(
+            shadow.diff_row(1, "CPU 13%  T 41C"),
+            vec![(5, "3".to_string()), (12, "1".to_string())]
+        );
+        // A one-cell gap is cheaper to rewrite than to skip with another address command.
+        assert_eq!(
+            shadow.diff_row(1, "CPU 24%  T 41C"),
+            vec![(4, "24".to_string())]
+        );
+        assert_eq!(
+            shadow.diff_row(1, "CPU 24%  T 41\u{6}"),
+            vec![(13, "\u{6}".to_string())],
+            "heartbeat blink only touches its own cell"
+        );
+    }
+
+    #[test]
+    fn shadow_rewrites_invalidated_rows() {
+        let mut shadow = DdramShadow::new(4, 1);
+        shadow.blank();
+        shadow.diff_row(0, "ab");
+        shadow.invalidate_row(0);
+        assert_eq!(shadow.diff_row(0, "ab"), vec![(0, "ab  ".to_string())]);
+    }
+
+    #[test]
+    fn stub_records_backlight_color() {
+        let mut lcd = Lcd::new_stub(16, 2);
+        assert_eq!(lcd.last_backlight_color(), Rgb::WHITE);
+        lcd.set_backlight_color(Rgb::RED).unwrap();
+        lcd.set_backlight(false).unwrap();
+        assert_eq!(lcd.last_backlight_color(), Rgb::RED);
+        assert!(!lcd.last_backlight());
+    }
+
+    #[test]
+    fn brightness_dims_snapshot_colour() {
+        let mut lcd = Lcd::new_stub(16, 2);
+        lcd.set_backlight_color(Rgb::AMBER).unwrap();
+        lcd.set_brightness(50).unwrap();
+        assert_eq!(lcd.last_brightness(), 50);
+        assert_eq!(lcd.last_backlight_color(), Rgb::AMBER);
+        let snapshot = lcd.snapshot();
+        assert!(snapshot.backlight_on);
+        assert_eq!(snapshot.backlight_color, Rgb::AMBER.scaled(50));
+        lcd.set_brightness(0).unwrap();
+        assert!(!lcd.snapshot().backlight_on);
+    }
 }
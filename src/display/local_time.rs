@@ -0,0 +1,514 @@
+//! Local UTC offset for the backlight schedule, read from `TZ` or `/etc/localtime` (TZif,
+//! RFC 8536) with std only. Zones are cached and reloaded once a minute so edits to the
+//! system zone are picked up without a restart.
+
+use std::{
+    fs,
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+const LOCALTIME_PATH: &str = "/etc/localtime";
+const ZONEINFO_DIR: &str = "/usr/share/zoneinfo";
+const ZONE_RELOAD: Duration = Duration::from_secs(60);
+const SECS_PER_DAY: i64 = 86_400;
+/// POSIX default for the DST switch time and the DST shift.
+const DEFAULT_RULE_TIME: i64 = 2 * 3600;
+const DEFAULT_DST_SHIFT: i64 = 3600;
+
+/// Seconds to add to UTC for local time at `unix`, or `None` when no zone can be loaded.
+pub fn utc_offset_at(unix: i64) -> Option<i64> {
+    static CACHE: Mutex<Option<(Instant, Option<Zone>)>> = Mutex::new(None);
+    let mut cache = CACHE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let now = Instant::now();
+    if cache
+        .as_ref()
+        .is_none_or(|(loaded, _)| now.duration_since(*loaded) >= ZONE_RELOAD)
+    {
+        *cache = Some((now, load_system_zone()));
+    }
+    cache
+        .as_ref()
+        .and_then(|(_, zone)| zone.as_ref())
+        .map(|zone| zone.offset_at(unix))
+}
+
+/// `TZ` as POSIX describes it (`:Area/City`, a file path, or a rule such as
+/// `CET-1CEST,M3.5.0,M10.5.0/3`), else `/etc/localtime`.
+fn load_system_zone() -> Option<Zone> {
+    let Ok(tz) = std::env::var("TZ") else {
+        return read_tzif(Path::new(LOCALTIME_PATH));
+    };
+    let spec = tz.strip_prefix(':').unwrap_or(&tz);
+    if spec.is_empty() {
+        return Some(Zone::Rule(PosixTz::utc()));
+    }
+    if spec.starts_with('/') {
+        return read_tzif(Path::new(spec));
+    }
+    if !spec.split('/').any(|part| part == "..") {
+        if let Some(zone) = read_tzif(&Path::new(ZONEINFO_DIR).join(spec)) {
+            return Some(zone);
+        }
+    }
+    PosixTz::parse(spec).map(Zone::Rule)
+}
+
+fn read_tzif(path: &Path) -> Option<Zone> {
+    parse_tzif(&fs::read(path).ok()?)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Zone {
+    Tzif {
+        /// `(utc_seconds, offset)` in ascending order.
+        transitions: Vec<(i64, i64)>,
+        /// Offset before the first transition.
+        initial: i64,
+        /// Rule for instants after the last transition (TZif v2+ footer).
+        footer: Option<PosixTz>,
+    },
+    Rule(PosixTz),
+}
+
+impl Zone {
+    fn offset_at(&self, unix: i64) -> i64 {
+        match self {
+            Zone::Rule(rule) => rule.offset_at(unix),
+            Zone::Tzif {
+                transitions,
+                initial,
+                footer,
+            } => {
+                let passed = transitions.partition_point(|(at, _)| *at <= unix);
+                match (passed, footer) {
+                    (0, _) => *initial,
+                    (n, Some(rule)) if n == transitions.len() => rule.offset_at(unix),
+                    (n, _) => transitions[n - 1].1,
+                }
+            }
+        }
+    }
+}
+
+/// Field counts from a TZif header, in file order after the version byte.
+struct TzifCounts {
+    isutcnt: usize,
+    isstdcnt: usize,
+    leapcnt: usize,
+    timecnt: usize,
+    typecnt: usize,
+    charcnt: usize,
+}
+
+const TZIF_HEADER_LEN: usize = 44;
+
+impl TzifCounts {
+    fn parse(data: &[u8]) -> Option<(u8, Self)> {
+        let header = data.get(..TZIF_HEADER_LEN)?;
+        if &header[..4] != b"TZif" {
+            return None;
+        }
+        let count = |index: usize| {
+            let at = 20 + index * 4;
+            u32::from_be_bytes(header[at..at + 4].try_into().unwrap()) as usize
+        };
+        let counts = Self {
+            isutcnt: count(0),
+            isstdcnt: count(1),
+            leapcnt: count(2),
+            timecnt: count(3),
+            typecnt: count(4),
+            charcnt: count(5),
+        };
+        Some((header[4], counts))
+    }
+
+    /// Bytes of the data block that follows the header, for `time_size`-byte timestamps.
+    fn block_len(&self, time_size: usize) -> usize {
+        self.timecnt * (time_size + 1)
+            + self.typecnt * 6
+            + self.charcnt
+            + self.leapcnt * (time_size + 4)
+            + self.isstdcnt
+            + self.isutcnt
+    }
+}
+
+fn parse_tzif(data: &[u8]) -> Option<Zone> {
+    let (version, counts) = TzifCounts::parse(data)?;
+    if version == 0 {
+        let block = data.get(TZIF_HEADER_LEN..)?;
+        return parse_tzif_block(block, &counts, 4, None);
+    }
+    // v2+ repeats the header with 64-bit times after the v1 block, then a POSIX footer.
+    let rest = data.get(TZIF_HEADER_LEN + counts.block_len(4)..)?;
+    let (_, counts) = TzifCounts::parse(rest)?;
+    let block = rest.get(TZIF_HEADER_LEN..)?;
+    let footer = block
+        .get(counts.block_len(8)..)
+        .and_then(|tail| std::str::from_utf8(tail).ok())
+        .and_then(|tail| tail.strip_prefix('\n'))
+        .and_then(|tail| tail.split('\n').next())
+        .and_then(PosixTz::parse);
+    parse_tzif_block(block, &counts, 8, footer)
+}
+
+fn parse_tzif_block(
+    block: &[u8],
+    counts: &TzifCounts,
+    time_size: usize,
+    footer: Option<PosixTz>,
+) -> Option<Zone> {
+    if counts.typecnt == 0 || block.len() < counts.block_len(time_size) {
+        return None;
+    }
+    let (times, rest) = block.split_at(counts.timecnt * time_size);
+    let (indices, rest) = rest.split_at(counts.timecnt);
+    let offsets: Vec<i64> = rest[..counts.typecnt * 6]
+        .chunks_exact(6)
+        .map(|info| i64::from(i32::from_be_bytes(info[..4].try_into().unwrap())))
+        .collect();
+    let transitions = times
+        .chunks_exact(time_size)
+        .zip(indices)
+        .map(|(time, index)| {
+            let at = match time_size {
+                4 => i64::from(i32::from_be_bytes(time.try_into().unwrap())),
+                _ => i64::from_be_bytes(time.try_into().unwrap()),
+            };
+            Some((at, *offsets.get(*index as usize)?))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(Zone::Tzif {
+        transitions,
+        initial: offsets[0],
+        footer,
+    })
+}
+
+/// A POSIX `TZ` rule: a standard offset and an optional DST period.
+#[derive(Debug, Clone, PartialEq)]
+struct PosixTz {
+    /// Seconds to add to UTC outside DST.
+    std_offset: i64,
+    dst: Option<DstRule>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct DstRule {
+    offset: i64,
+    start: Transition,
+    end: Transition,
+}
+
+/// When DST starts or ends: a day of the year plus seconds after local midnight.
+#[derive(Debug, Clone, PartialEq)]
+struct Transition {
+    day: DayRule,
+    time: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum DayRule {
+    /// `Jn`: day 1-365, never counting February 29.
+    JulianNoLeap(i64),
+    /// `n`: day 0-365, counting February 29.
+    Julian(i64),
+    /// `Mm.w.d`: weekday `d` (0 = Sunday) of week `w` (5 = last) in month `m`.
+    MonthWeekDay { month: i64, week: i64, weekday: i64 },
+}
+
+impl PosixTz {
+    fn utc() -> Self {
+        Self {
+            std_offset: 0,
+            dst: None,
+        }
+    }
+
+    fn parse(spec: &str) -> Option<Self> {
+        let mut rest = spec;
+        skip_name(&mut rest)?;
+        // POSIX offsets count hours west of Greenwich; flip them to "add to UTC".
+        let std_offset = -parse_clock(&mut rest)?;
+        if rest.is_empty() {
+            return Some(Self {
+                std_offset,
+                dst: None,
+            });
+        }
+        skip_name(&mut rest)?;
+        let offset = if rest.is_empty() || rest.starts_with(',') {
+            std_offset + DEFAULT_DST_SHIFT
+        } else {
+            -parse_clock(&mut rest)?
+        };
+        // A DST name without rules means the US defaults.
+        let (start, end) = match rest.strip_prefix(',') {
+            Some(rules) => {
+                let (start, end) = rules.split_once(',')?;
+                (parse_transition(start)?, parse_transition(end)?)
+            }
+            None if rest.is_empty() => (parse_transition("M3.2.0")?, parse_transition("M11.1.0")?),
+            None => return None,
+        };
+        Some(Self {
+            std_offset,
+            dst: Some(DstRule { offset, start, end }),
+        })
+    }
+
+    fn offset_at(&self, unix: i64) -> i64 {
+        let Some(dst) = &self.dst else {
+            return self.std_offset;
+        };
+        let (year, _, _) = civil_from_days((unix + self.std_offset).div_euclid(SECS_PER_DAY));
+        // Start is given in standard time and end in daylight time.
+        let start = dst.start.local_secs(year) - self.std_offset;
+        let end = dst.end.local_secs(year) - dst.offset;
+        let in_dst = if start < end {
+            start <= unix && unix < end
+        } else {
+            // Southern hemisphere: DST spans the new year.
+            !(end <= unix && unix < start)
+        };
+        if in_dst {
+            dst.offset
+        } else {
+            self.std_offset
+        }
+    }
+}
+
+impl Transition {
+    /// Local seconds since the epoch at which this transition happens in `year`.
+    fn local_secs(&self, year: i64) -> i64 {
+        let jan1 = days_from_civil(year, 1, 1);
+        let day = match self.day {
+            DayRule::Julian(n) => jan1 + n,
+            DayRule::JulianNoLeap(n) => jan1 + n - 1 + i64::from(is_leap(year) && n >= 60),
+            DayRule::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            } => {
+                let first = days_from_civil(year, month, 1);
+                let next_month = match month {
+                    12 => days_from_civil(year + 1, 1, 1),
+                    _ => days_from_civil(year, month + 1, 1),
+                };
+                let first_match = first + (weekday - weekday_of(first)).rem_euclid(7);
+                let mut day = first_match + (week - 1) * 7;
+                while day >= next_month {
+                    day -= 7;
+                }
+                day
+            }
+        };
+        day * SECS_PER_DAY + self.time
+    }
+}
+
+/// Skip a zone abbreviation: letters, or anything quoted in `<...>`.
+fn skip_name(rest: &mut &str) -> Option<()> {
+    let len = if let Some(quoted) = rest.strip_prefix('<') {
+        quoted.find('>')? + 2
+    } else {
+        rest.find(|ch: char| !ch.is_ascii_alphabetic())
+            .unwrap_or(rest.len())
+    };
+    if len < 3 {
+        return None;
+    }
+    *rest = &rest[len..];
+    Some(())
+}
+
+/// `[+-]hh[:mm[:ss]]` as signed seconds.
+fn parse_clock(rest: &mut &str) -> Option<i64> {
+    let end = rest
+        .find(|ch: char| !(ch.is_ascii_digit() || matches!(ch, ':' | '+' | '-')))
+        .unwrap_or(rest.len());
+    let (clock, tail) = rest.split_at(end);
+    let (sign, digits) = match clock.as_bytes().first()? {
+        b'-' => (-1, &clock[1..]),
+        b'+' => (1, &clock[1..]),
+        _ => (1, clock),
+    };
+    let mut secs = 0;
+    let mut parts = 0;
+    for (part, scale) in digits.split(':').zip([3600, 60, 1]) {
+        secs += part.parse::<i64>().ok()? * scale;
+        parts += 1;
+    }
+    if parts == 0 || digits.split(':').count() > 3 {
+        return None;
+    }
+    *rest = tail;
+    Some(sign * secs)
+}
+
+fn parse_transition(raw: &str) -> Option<Transition> {
+    let (day, time) = match raw.split_once('/') {
+        Some((day, mut time)) => {
+            let secs = parse_clock(&mut time)?;
+            if !time.is_empty() {
+                return None;
+            }
+            (day, secs)
+        }
+        None => (raw, DEFAULT_RULE_TIME),
+    };
+    let day = if let Some(n) = day.strip_prefix('J') {
+        DayRule::JulianNoLeap(n.parse().ok().filter(|n| (1..=365).contains(n))?)
+    } else if let Some(mwd) = day.strip_prefix('M') {
+        let mut fields = mwd.split('.').map(|field| field.parse::<i64>().ok());
+        let (month, week, weekday) = (fields.next()??, fields.next()??, fields.next()??);
+        if fields.next().is_some()
+            || !(1..=12).contains(&month)
+            || !(1..=5).contains(&week)
+            || !(0..=6).contains(&weekday)
+        {
+            return None;
+        }
+        DayRule::MonthWeekDay {
+            month,
+            week,
+            weekday,
+        }
+    } else {
+        DayRule::Julian(day.parse().ok().filter(|n| (0..=365).contains(n))?)
+    };
+    Some(Transition { day, time })
+}
+
+fn is_leap(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// 0 = Sunday; 1970-01-01 was a Thursday.
+fn weekday_of(days: i64) -> i64 {
+    (days + 4).rem_euclid(7)
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i64, month: i64, day: i64, hour: i64, minute: i64) -> i64 {
+        days_from_civil(year, month, day) * SECS_PER_DAY + hour * 3600 + minute * 60
+    }
+
+    #[test]
+    fn posix_rules_switch_at_the_documented_instants() {
+        let us = PosixTz::parse("EST5EDT,M3.2.0,M11.1.0").unwrap();
+        assert_eq!(us.offset_at(at(2024, 1, 15, 12, 0)), -5 * 3600);
+        assert_eq!(us.offset_at(at(2024, 7, 1, 12, 0)), -4 * 3600);
+        // 2024-03-10 02:00 EST is 07:00 UTC.
+        assert_eq!(us.offset_at(at(2024, 3, 10, 6, 59)), -5 * 3600);
+        assert_eq!(us.offset_at(at(2024, 3, 10, 7, 0)), -4 * 3600);
+        // 2024-11-03 02:00 EDT is 06:00 UTC.
+        assert_eq!(us.offset_at(at(2024, 11, 3, 5, 59)), -4 * 3600);
+        assert_eq!(us.offset_at(at(2024, 11, 3, 6, 0)), -5 * 3600);
+
+        let sydney = PosixTz::parse("<+10>-10<+11>,M10.1.0,M4.1.0/3").unwrap();
+        assert_eq!(sydney.offset_at(at(2024, 1, 15, 0, 0)), 11 * 3600);
+        assert_eq!(sydney.offset_at(at(2024, 7, 1, 0, 0)), 10 * 3600);
+
+        assert_eq!(PosixTz::parse("IST-5:30").unwrap().offset_at(0), 19_800);
+        assert!(PosixTz::parse("E5").is_none());
+        assert!(PosixTz::parse("CET-1CEST,M13.5.0,M10.5.0").is_none());
+    }
+
+    fn tzif(version: u8, transitions: &[(i64, u8)], offsets: &[i32], footer: &str) -> Vec<u8> {
+        fn block(
+            out: &mut Vec<u8>,
+            version: u8,
+            transitions: &[(i64, u8)],
+            offsets: &[i32],
+            wide: bool,
+        ) {
+            out.extend_from_slice(b"TZif");
+            out.push(version);
+            out.extend_from_slice(&[0; 15]);
+            for count in [0, 0, 0, transitions.len(), offsets.len(), 1] {
+                out.extend_from_slice(&(count as u32).to_be_bytes());
+            }
+            for (time, _) in transitions {
+                if wide {
+                    out.extend_from_slice(&time.to_be_bytes());
+                } else {
+                    out.extend_from_slice(&(*time as i32).to_be_bytes());
+                }
+            }
+            out.extend(transitions.iter().map(|(_, index)| index));
+            for offset in offsets {
+                out.extend_from_slice(&offset.to_be_bytes());
+                out.extend_from_slice(&[0, 0]);
+            }
+            out.push(0);
+        }
+        let mut out = Vec::new();
+        block(&mut out, version, transitions, offsets, false);
+        if version != 0 {
+            block(&mut out, version, transitions, offsets, true);
+            out.extend_from_slice(format!("\n{footer}\n").as_bytes());
+        }
+        out
+    }
+
+    #[test]
+    fn tzif_uses_transitions_then_the_footer_rule() {
+        let switch = at(2000, 1, 1, 0, 0);
+        let v1 = parse_tzif(&tzif(0, &[(switch, 1)], &[3600, 7200], "")).unwrap();
+        assert_eq!(v1.offset_at(switch - 1), 3600);
+        assert_eq!(v1.offset_at(switch), 7200);
+
+        let v2 = parse_tzif(&tzif(
+            b'2',
+            &[(switch, 0)],
+            &[-5 * 3600],
+            "EST5EDT,M3.2.0,M11.1.0",
+        ))
+        .unwrap();
+        assert_eq!(v2.offset_at(at(2024, 7, 1, 12, 0)), -4 * 3600);
+        assert_eq!(v2.offset_at(at(2024, 1, 1, 12, 0)), -5 * 3600);
+        assert!(parse_tzif(b"TZif2 truncated").is_none());
+    }
+
+    #[test]
+    fn civil_day_conversions_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+        assert_eq!(weekday_of(days_from_civil(2024, 3, 10)), 0);
+    }
+}
@@ -1,3 +1,8 @@
+pub mod backlight;
+pub mod capture;
+pub mod charset;
 pub mod icon_bank;
 pub mod lcd;
+pub mod local_time;
 pub mod overlays;
+pub mod virtual_lcd;
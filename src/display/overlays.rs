@@ -3,6 +3,7 @@ use std::time::Instant;
 
 use crate::{
     display::{
+        backlight::Severity,
         icon_bank::{IconBank, IconPalette, PaletteRequest},
         lcd::Lcd,
     },
@@ -33,24 +34,34 @@ pub fn render_frame_with_scroll(
     }
 
     let width = lcd.cols() as usize;
+    let bar_row = frame.bar_row;
+    let text1 = view_line(&frame.line1, width, offsets.0, frame.scroll_enabled);
+    let text2 = view_line(&frame.line2, width, offsets.1, frame.scroll_enabled);
+    let charset = lcd.charset();
+    let mut glyph_chars: Vec<char> = Vec::new();
+    for ch in text1.chars().chain(text2.chars()) {
+        if charset.needs_cgram(ch) && !glyph_chars.contains(&ch) {
+            glyph_chars.push(ch);
+        }
+    }
     let palette = icon_bank.build_palette(
         lcd,
         PaletteRequest {
             bar_required: frame.bar_percent.is_some(),
             heartbeat: heartbeat_on,
             icons: &frame.icons,
+            chars: &glyph_chars,
         },
     )?;
-    let bar_row = frame.bar_row;
     let mut line1 = if bar_row == Some(0) && frame.bar_percent.is_some() {
         render_bar(frame.bar_percent.unwrap(), width, &palette)
     } else {
-        view_line(&frame.line1, width, offsets.0, frame.scroll_enabled)
+        substitute_glyphs(&text1, &palette)
     };
     let mut line2 = if bar_row == Some(1) && frame.bar_percent.is_some() {
         render_bar(frame.bar_percent.unwrap(), width, &palette)
     } else {
-        view_line(&frame.line2, width, offsets.1, frame.scroll_enabled)
+        substitute_glyphs(&text2, &palette)
     };
 
     if heartbeat_on && width > 0 {
@@ -82,6 +93,7 @@ pub fn render_frame_with_scroll(
     };
 
     lcd.write_lines(out1, out2)?;
+    lcd.finish_frame()?;
     Ok(palette)
 }
 
@@ -122,10 +134,11 @@ pub fn render_parse_error(lcd: &mut Lcd, cols: u8, err: &Error) -> Result<()> {
     let width = cols as usize;
     let msg = truncate_with_ellipsis(&format!("{err}"), width);
     lcd.set_backlight(true)?;
+    lcd.set_backlight_color(Severity::Critical.color())?;
     lcd.set_blink(true)?;
     lcd.write_line(0, "ERR PARSE")?;
     lcd.write_line(1, &msg)?;
-    Ok(())
+    lcd.finish_frame()
 }
 
 pub fn render_reconnecting(lcd: &mut Lcd, cols: u8) -> Result<()> {
@@ -134,10 +147,33 @@ pub fn render_reconnecting(lcd: &mut Lcd, cols: u8) -> Result<()> {
     let detail = truncate_to_width("retrying...", width);
     lcd.clear()?;
     lcd.set_backlight(true)?;
+    lcd.set_backlight_color(Severity::Warning.color())?;
     lcd.set_blink(false)?;
     lcd.write_line(0, &title)?;
     lcd.write_line(1, &detail)?;
-    Ok(())
+    lcd.finish_frame()
+}
+
+/// Name the negotiated peer; `alert` marks a peer that is not the pinned one.
+pub fn render_peer_status(
+    lcd: &mut Lcd,
+    cols: u8,
+    title: &str,
+    detail: &str,
+    alert: bool,
+) -> Result<()> {
+    let width = cols as usize;
+    lcd.clear()?;
+    lcd.set_backlight(true)?;
+    lcd.set_backlight_color(if alert {
+        Severity::Critical.color()
+    } else {
+        Severity::Ok.color()
+    })?;
+    lcd.set_blink(alert)?;
+    lcd.write_line(0, &truncate_to_width(title, width))?;
+    lcd.write_line(1, &truncate_to_width(detail, width))?;
+    lcd.finish_frame()
 }
 
 pub fn render_offline_message(lcd: &mut Lcd, cols: u8) -> Result<()> {
@@ -146,10 +182,11 @@ pub fn render_offline_message(lcd: &mut Lcd, cols: u8) -> Result<()> {
     let detail = truncate_to_width("will retry...", width);
     lcd.clear()?;
     lcd.set_backlight(true)?;
+    lcd.set_backlight_color(Severity::Warning.color())?;
     lcd.set_blink(true)?;
     lcd.write_line(0, &title)?;
     lcd.write_line(1, &detail)?;
-    Ok(())
+    lcd.finish_frame()
 }
 
 fn render_bar(percent: u8, width: usize, palette: &IconPalette) -> String {
@@ -169,6 +206,13 @@ fn render_bar(percent: u8, width: usize, palette: &IconPalette) -> String {
     s
 }
 
+/// Swap characters the ROM lacks for the CGRAM slots the palette loaded for them.
+fn substitute_glyphs(text: &str, palette: &IconPalette) -> String {
+    text.chars()
+        .map(|ch| palette.text_char(ch).unwrap_or(ch))
+        .collect()
+}
+
 fn view_with_scroll(text: &str, width: usize, offset: usize) -> String {
     let chars: Vec<char> = text.chars().collect();
     if chars.len() <= width {
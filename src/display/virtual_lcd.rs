@@ -0,0 +1,214 @@
+//! Terminal simulator for the LCD (`--display virtual`): draws the panel as a box of
+//! cols×rows cells with backlight/blink status and the CGRAM glyphs in use as 5x8 block art.
+
+use std::io::{self, IsTerminal, Write};
+
+use crate::{display::backlight::Rgb, Result};
+
+/// CGRAM slots the HD44780 exposes as characters 0-7.
+const CGRAM_SLOTS: usize = 8;
+const GLYPH_ROWS: usize = 8;
+const GLYPH_COLS: u32 = 5;
+
+/// What the simulator needs to draw one frame.
+pub struct ScreenView<'a> {
+    pub cols: u8,
+    pub rows: &'a [String],
+    pub backlight_on: bool,
+    pub backlight_color: Rgb,
+    pub blink_on: bool,
+    pub glyphs: &'a [[u8; 8]; 8],
+}
+
+/// Draws frames to a terminal, skipping redraws when nothing visible changed.
+pub struct VirtualScreen {
+    out: Box<dyn Write>,
+    ansi: bool,
+    last_frame: String,
+}
+
+impl VirtualScreen {
+    /// Draw on stdout, with colour and screen clearing only when stdout is a terminal.
+    pub fn stdout() -> Self {
+        let ansi = io::stdout().is_terminal();
+        Self::with_writer(Box::new(io::stdout()), ansi)
+    }
+
+    pub fn with_writer(out: Box<dyn Write>, ansi: bool) -> Self {
+        Self {
+            out,
+            ansi,
+            last_frame: String::new(),
+        }
+    }
+
+    pub fn present(&mut self, view: &ScreenView<'_>) -> Result<()> {
+        let frame = render(view, self.ansi);
+        if frame == self.last_frame {
+            return Ok(());
+        }
+        if self.ansi {
+            // Home the cursor and clear so the panel redraws in place.
+            self.out.write_all(b"\x1b[H\x1b[2J")?;
+        }
+        self.out.write_all(frame.as_bytes())?;
+        self.out.flush()?;
+        self.last_frame = frame;
+        Ok(())
+    }
+}
+
+/// Render one frame: the framed panel, a status line, and any CGRAM glyphs on screen.
+pub fn render(view: &ScreenView<'_>, ansi: bool) -> String {
+    let width = view.cols as usize;
+    let border = "─".repeat(width);
+    let mut out = format!("┌{border}┐\n");
+    let mut used_slots = [false; CGRAM_SLOTS];
+    for row in view.rows {
+        let mut cells = String::with_capacity(width);
+        for ch in row.chars().chain(std::iter::repeat(' ')).take(width) {
+            match cgram_slot(ch) {
+                Some(slot) => {
+                    used_slots[slot] = true;
+                    cells.push(glyph_shade(&view.glyphs[slot]));
+                }
+                None => cells.push(ch),
+            }
+        }
+        out.push('│');
+        if ansi {
+            out.push_str(&backlight_style(view));
+            out.push_str(&cells);
+            out.push_str("\x1b[0m");
+        } else {
+            out.push_str(&cells);
+        }
+        out.push_str("│\n");
+    }
+    out.push_str(&format!("└{border}┘\n"));
+    out.push_str(&format!(
+        "backlight {} {}  blink {}\n",
+        if view.backlight_on { "on" } else { "off" },
+        view.backlight_color,
+        if view.blink_on { "on" } else { "off" }
+    ));
+
+    let slots: Vec<usize> = (0..CGRAM_SLOTS).filter(|slot| used_slots[*slot]).collect();
+    if !slots.is_empty() {
+        let header: Vec<String> = slots.iter().map(|slot| format!("cg{slot}  ")).collect();
+        out.push_str(&header.join(" "));
+        out.push('\n');
+        for line in 0..GLYPH_ROWS {
+            let art: Vec<String> = slots
+                .iter()
+                .map(|slot| glyph_row(view.glyphs[*slot][line]))
+                .collect();
+            out.push_str(&art.join(" "));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn cgram_slot(ch: char) -> Option<usize> {
+    let code = ch as usize;
+    (code < CGRAM_SLOTS).then_some(code)
+}
+
+/// One 5-pixel glyph row as block art.
+fn glyph_row(bits: u8) -> String {
+    (0..GLYPH_COLS)
+        .map(|col| {
+            if bits & (1 << (GLYPH_COLS - 1 - col)) != 0 {
+                '█'
+            } else {
+                '·'
+            }
+        })
+        .collect()
+}
+
+/// Single-cell stand-in for a glyph inside the panel, shaded by how many pixels are lit.
+fn glyph_shade(bitmap: &[u8; 8]) -> char {
+    let lit: u32 = bitmap.iter().map(|row| (row & 0x1f).count_ones()).sum();
+    match lit * 4 / (GLYPH_COLS * GLYPH_ROWS as u32) {
+        _ if lit == 0 => ' ',
+        0 => '░',
+        1 => '▒',
+        2 | 3 => '▓',
+        _ => '█',
+    }
+}
+
+/// Dark text on the backlight colour when lit, dim text when off.
+fn backlight_style(view: &ScreenView<'_>) -> String {
+    if !view.backlight_on {
+        return "\x1b[2m".into();
+    }
+    let Rgb { r, g, b } = view.backlight_color;
+    format!("\x1b[48;2;{r};{g};{b}m\x1b[38;2;0;0;0m")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_box_status_and_glyph_art() {
+        let mut glyphs = [[0u8; 8]; 8];
+        glyphs[5] = [0x1f; 8];
+        glyphs[6] = [0x0a, 0x1f, 0x1f, 0x1f, 0x0e, 0x04, 0x00, 0x00];
+        let rows = vec!["CPU \u{5}\u{5}".to_string(), "\u{6} ok".to_string()];
+        let view = ScreenView {
+            cols: 8,
+            rows: &rows,
+            backlight_on: false,
+            backlight_color: Rgb::AMBER,
+            blink_on: true,
+            glyphs: &glyphs,
+        };
+        let frame = render(&view, false);
+        let lines: Vec<&str> = frame.lines().collect();
+        assert_eq!(lines[0], "┌────────┐");
+        assert_eq!(lines[1], "│CPU ██  │");
+        assert_eq!(lines[2], "│▓ ok    │");
+        assert_eq!(lines[3], "└────────┘");
+        assert_eq!(lines[4], "backlight off #ff7800  blink on");
+        assert_eq!(lines[5], "cg5   cg6  ");
+        assert_eq!(lines[6], "█████ ·█·█·");
+        assert_eq!(lines[10], "█████ ·███·");
+        assert_eq!(lines.len(), 14);
+    }
+
+    #[test]
+    fn skips_redraw_when_frame_is_unchanged() {
+        #[derive(Clone, Default)]
+        struct Sink(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+        impl Write for Sink {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.borrow_mut().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let sink = Sink::default();
+        let mut screen = VirtualScreen::with_writer(Box::new(sink.clone()), false);
+        let glyphs = [[0u8; 8]; 8];
+        let rows = vec!["hi".to_string()];
+        let view = ScreenView {
+            cols: 4,
+            rows: &rows,
+            backlight_on: true,
+            backlight_color: Rgb::WHITE,
+            blink_on: false,
+            glyphs: &glyphs,
+        };
+        screen.present(&view).unwrap();
+        let first = sink.0.borrow().len();
+        screen.present(&view).unwrap();
+        assert_eq!(sink.0.borrow().len(), first);
+    }
+}
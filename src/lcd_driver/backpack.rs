@@ -0,0 +1,284 @@
+//! I2C backpacks and native-I2C controllers the in-tree driver can talk to.
+//!
+//! Port expanders (PCF8574/PCF8574A, MCP23008) run the HD44780 in 4-bit mode and need a
+//! pin map from expander bits to RS/E/D4-D7/backlight. Native controllers (AIP31068,
+//! ST7032) take whole command/data bytes behind a control byte and have no pin map.
+
+use std::{fmt, str::FromStr};
+
+use super::{MASK_E, MASK_RS, SHIFT_BACKLIGHT, SHIFT_DATA};
+
+/// PCF8574 (0x20-0x27) first, then PCF8574A (0x38-0x3F); same bit layout, different base.
+const PCF8574_ADDRS: [u8; 16] = [
+    0x27, 0x26, 0x25, 0x24, 0x23, 0x22, 0x21, 0x20, 0x3f, 0x3e, 0x3d, 0x3c, 0x3b, 0x3a, 0x39, 0x38,
+];
+const MCP23008_ADDRS: [u8; 8] = [0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27];
+/// AIP31068 and ST7032 both answer at a fixed 0x3E.
+const NATIVE_ADDRS: [u8; 1] = [0x3e];
+
+/// MCP23008 register map (IOCON.BANK is irrelevant on the 8-bit part).
+pub(super) const MCP23008_IODIR: u8 = 0x00;
+pub(super) const MCP23008_GPIO: u8 = 0x09;
+
+/// Control bytes for native-I2C controllers: Co=0 followed by RS.
+pub(super) const NATIVE_CONTROL_COMMAND: u8 = 0x00;
+pub(super) const NATIVE_CONTROL_DATA: u8 = 0x40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backpack {
+    /// PCF8574 or PCF8574A remote 8-bit I/O expander (the common blue backpack).
+    #[default]
+    Pcf8574,
+    /// MCP23008 expander, e.g. the Adafruit I2C/SPI backpack.
+    Mcp23008,
+    /// AIP31068 controller with a native I2C interface (Grove LCD/RGB modules).
+    Aip31068,
+    /// ST7032 controller with a native I2C interface and software contrast.
+    St7032,
+}
+
+impl Backpack {
+    /// Addresses probed when the address is `auto`, most common first.
+    pub fn address_candidates(self) -> &'static [u8] {
+        match self {
+            Backpack::Pcf8574 => &PCF8574_ADDRS,
+            Backpack::Mcp23008 => &MCP23008_ADDRS,
+            Backpack::Aip31068 | Backpack::St7032 => &NATIVE_ADDRS,
+        }
+    }
+
+    /// Address assumed when probing finds nothing.
+    pub fn default_address(self) -> u8 {
+        self.address_candidates()[0]
+    }
+
+    /// True for port expanders that bit-bang the 4-bit bus through a pin map.
+    pub fn is_expander(self) -> bool {
+        matches!(self, Backpack::Pcf8574 | Backpack::Mcp23008)
+    }
+
+    /// Wiring used by the stock board for this expander.
+    pub fn default_pin_map(self) -> PinMap {
+        match self {
+            Backpack::Mcp23008 => PinMap {
+                rs: 1,
+                e: 2,
+                e2: None,
+                data: [3, 4, 5, 6],
+                backlight: Some(7),
+            },
+            _ => PinMap::default(),
+        }
+    }
+}
+
+impl FromStr for Backpack {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "pcf8574" | "pcf8574a" => Ok(Backpack::Pcf8574),
+            "mcp23008" => Ok(Backpack::Mcp23008),
+            "aip31068" => Ok(Backpack::Aip31068),
+            "st7032" => Ok(Backpack::St7032),
+            other => Err(format!(
+                "expected 'pcf8574', 'mcp23008', 'aip31068', or 'st7032', got '{other}'"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Backpack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Backpack::Pcf8574 => "pcf8574",
+            Backpack::Mcp23008 => "mcp23008",
+            Backpack::Aip31068 => "aip31068",
+            Backpack::St7032 => "st7032",
+        })
+    }
+}
+
+/// Expander bit (0-7) wired to each HD44780 line. RW is assumed tied low or held low.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinMap {
+    pub rs: u8,
+    pub e: u8,
+    /// Enable line of the second controller on dual-controller 40x4 modules.
+    pub e2: Option<u8>,
+    /// Bits for D4, D5, D6, D7.
+    pub data: [u8; 4],
+    pub backlight: Option<u8>,
+}
+
+impl Default for PinMap {
+    /// The PCF8574 layout used by python_lcd and nearly every blue backpack.
+    fn default() -> Self {
+        Self {
+            rs: MASK_RS.trailing_zeros() as u8,
+            e: MASK_E.trailing_zeros() as u8,
+            e2: None,
+            data: [SHIFT_DATA, SHIFT_DATA + 1, SHIFT_DATA + 2, SHIFT_DATA + 3],
+            backlight: Some(SHIFT_BACKLIGHT),
+        }
+    }
+}
+
+impl PinMap {
+    pub(super) fn rs_mask(&self) -> u8 {
+        1 << self.rs
+    }
+
+    /// Enable bit for `controller` (0, or 1 on dual-controller modules).
+    pub(super) fn e_mask(&self, controller: usize) -> u8 {
+        match (controller, self.e2) {
+            (1, Some(bit)) => 1 << bit,
+            _ => 1 << self.e,
+        }
+    }
+
+    pub(super) fn backlight_mask(&self) -> u8 {
+        self.backlight.map(|bit| 1 << bit).unwrap_or(0)
+    }
+
+    /// Spread the low nibble of `nibble` across the mapped data bits.
+    pub(super) fn data_bits(&self, nibble: u8) -> u8 {
+        self.data
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| nibble & (1 << idx) != 0)
+            .fold(0, |acc, (_, bit)| acc | (1 << bit))
+    }
+}
+
+impl FromStr for PinMap {
+    type Err = String;
+
+    /// Parse `rs=0,e=2,d4=4,d5=5,d6=6,d7=7,bl=3`; omit `bl` when the backlight is not switchable
+    /// and add `e2` for the second enable line of a 40x4 module.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut rs = None;
+        let mut e = None;
+        let mut e2 = None;
+        let mut data: [Option<u8>; 4] = [None; 4];
+        let mut backlight = None;
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, bit) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected name=bit, got '{part}'"))?;
+            let name = name.trim().to_ascii_lowercase();
+            let bit: u8 = bit
+                .trim()
+                .parse()
+                .ok()
+                .filter(|bit| *bit < 8)
+                .ok_or_else(|| format!("{name} must be an expander bit 0-7"))?;
+            let slot = match name.as_str() {
+                "rs" => &mut rs,
+                "e" | "en" => &mut e,
+                "e2" | "en2" => &mut e2,
+                "bl" | "backlight" => &mut backlight,
+                "d4" => &mut data[0],
+                "d5" => &mut data[1],
+                "d6" => &mut data[2],
+                "d7" => &mut data[3],
+                other => return Err(format!("unknown pin name '{other}'")),
+            };
+            if slot.replace(bit).is_some() {
+                return Err(format!("pin {name} given more than once"));
+            }
+        }
+        let mut pins = [0u8; 4];
+        for (idx, bit) in data.iter().enumerate() {
+            pins[idx] = bit.ok_or(format!("missing d{} bit", idx + 4))?;
+        }
+        let map = PinMap {
+            rs: rs.ok_or("missing rs bit")?,
+            e: e.ok_or("missing e bit")?,
+            e2,
+            data: pins,
+            backlight,
+        };
+        let mut all = vec![map.rs, map.e];
+        all.extend(map.e2);
+        all.extend(map.data);
+        all.extend(map.backlight);
+        all.sort_unstable();
+        if all.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err("each expander bit may only be used once".into());
+        }
+        Ok(map)
+    }
+}
+
+impl fmt::Display for PinMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rs={},e={}", self.rs, self.e)?;
+        if let Some(bit) = self.e2 {
+            write!(f, ",e2={bit}")?;
+        }
+        write!(
+            f,
+            ",d4={},d5={},d6={},d7={}",
+            self.data[0], self.data[1], self.data[2], self.data[3]
+        )?;
+        if let Some(bit) = self.backlight {
+            write!(f, ",bl={bit}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_pin_map_matches_pcf8574_masks() {
+        let map = PinMap::default();
+        assert_eq!(map.rs_mask(), MASK_RS);
+        assert_eq!(map.e_mask(0), MASK_E);
+        assert_eq!(map.e_mask(1), MASK_E, "single controller ignores the index");
+        assert_eq!(map.backlight_mask(), 1 << SHIFT_BACKLIGHT);
+        assert_eq!(map.data_bits(0x0a), 0x0a << SHIFT_DATA);
+    }
+
+    #[test]
+    fn pin_map_round_trips_and_scatters_bits() {
+        let map: PinMap = "rs=1,e=2,d4=3,d5=4,d6=5,d7=6,bl=7".parse().unwrap();
+        assert_eq!(map, Backpack::Mcp23008.default_pin_map());
+        assert_eq!(map.to_string().parse::<PinMap>().unwrap(), map);
+        assert_eq!(map.data_bits(0b1001), (1 << 3) | (1 << 6));
+
+        let dual: PinMap = "rs=0,e=2,e2=1,d4=4,d5=5,d6=6,d7=7".parse().unwrap();
+        assert_eq!(dual.e_mask(1), 0x02);
+        assert_eq!(dual.to_string().parse::<PinMap>().unwrap(), dual);
+
+        let reversed: PinMap = "rs=7,e=6,d4=3,d5=2,d6=1,d7=0".parse().unwrap();
+        assert_eq!(reversed.backlight_mask(), 0);
+        assert_eq!(reversed.data_bits(0b0001), 1 << 3);
+    }
+
+    #[test]
+    fn pin_map_rejects_bad_input() {
+        for (raw, needle) in [
+            ("rs=0,e=2,d4=4,d5=5,d6=6", "missing d7"),
+            ("rs=0,e=2,d4=4,d5=5,d6=6,d7=8", "bit 0-7"),
+            ("rs=0,e=0,d4=4,d5=5,d6=6,d7=7", "only be used once"),
+            ("rs=0,rw=1,e=2,d4=4,d5=5,d6=6,d7=7", "unknown pin"),
+        ] {
+            let err = raw.parse::<PinMap>().unwrap_err();
+            assert!(err.contains(needle), "{raw}: {err}");
+        }
+    }
+
+    #[test]
+    fn pcf8574_probe_covers_the_a_variant() {
+        let candidates = Backpack::Pcf8574.address_candidates();
+        assert_eq!(candidates[0], 0x27);
+        assert!(candidates.contains(&0x3f) && candidates.contains(&0x38));
+        assert_eq!("pcf8574a".parse::<Backpack>().unwrap(), Backpack::Pcf8574);
+        assert_eq!(Backpack::St7032.default_address(), 0x3e);
+        assert!(!Backpack::Aip31068.is_expander());
+    }
+}
@@ -104,6 +104,12 @@ impl ExternalHd44780 {
         self.putstr(text)
     }
 
+    /// Write a run of cells starting at (`col`, `row`) without touching the rest of the row.
+    pub fn write_at(&mut self, col: u8, row: u8, text: &str) -> Result<()> {
+        self.move_to(col, row)?;
+        self.putstr(text)
+    }
+
     pub fn load_custom_bitmap(&mut self, location: u8, rows: [&str; 8]) -> Result<()> {
         let mut pattern = [0u8; 8];
         for (idx, row) in rows.iter().enumerate() {
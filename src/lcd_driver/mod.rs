@@ -1,12 +1,20 @@
 //! HD44780 over PCF8574 driver translated from dhylands/python_lcd.
-//! This keeps the HAL split and init sequence from the reference Python code.
+//! This keeps the HAL split and init sequence from the reference Python code; other
+//! backpacks and native-I2C controllers plug in through [`backpack::Backpack`].
+//! 40x4 modules carry two controllers on separate enable lines; see [`controller_for_row`].
 
 use std::time::Duration;
 
 use crate::{Error, Result};
+use backpack::{
+    Backpack, PinMap, MCP23008_GPIO, MCP23008_IODIR, NATIVE_CONTROL_COMMAND, NATIVE_CONTROL_DATA,
+};
 
+pub mod backpack;
 pub mod external;
+pub mod parallel;
 pub mod pcf8574;
+pub mod rgb;
 
 /// Backlight state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -18,14 +26,21 @@ pub enum Backlight {
 /// Minimal trait to allow swapping the I2C backend (for tests or rppal).
 pub trait I2cBus {
     fn write_byte(&mut self, addr: u8, byte: u8) -> Result<()>;
+    /// Write several bytes in one transaction (register writes, control-byte framing).
+    fn write_bytes(&mut self, addr: u8, bytes: &[u8]) -> Result<()>;
 }
 
-/// HD44780 driver that targets a PCF8574 backpack in 4-bit mode.
+/// HD44780 driver that targets a PCF8574 backpack in 4-bit mode, or any other
+/// [`Backpack`] the caller selects.
 pub struct Hd44780<B: I2cBus> {
     bus: B,
     addr: u8,
+    backpack: Backpack,
+    pins: PinMap,
     cols: u8,
     rows: u8,
+    /// Controller whose enable line is pulsed; always 0 unless the pin map has `e2`.
+    controller: usize,
     cursor_x: u8,
     cursor_y: u8,
     implied_newline: bool,
@@ -52,98 +67,206 @@ const LCD_ON_BLINK: u8 = 0x01;
 const LCD_FUNCTION: u8 = 0x20;
 const LCD_FUNCTION_2LINES: u8 = 0x08;
 const LCD_FUNCTION_RESET: u8 = 0x30;
+const LCD_FUNCTION_8BIT: u8 = 0x10;
+// ST7032 extended instruction set (IS=1), see the ST7032 datasheet instruction table.
+const ST7032_FUNCTION_IS: u8 = 0x01;
+const ST7032_OSC_FREQ: u8 = 0x14;
+const ST7032_CONTRAST_LOW: u8 = 0x70;
+const ST7032_POWER_ICON_CONTRAST: u8 = 0x5c;
+const ST7032_FOLLOWER: u8 = 0x6c;
+const ST7032_DEFAULT_CONTRAST: u8 = 0x20;
 pub(super) const LCD_DDRAM: u8 = 0x80;
 pub(super) const LCD_CGRAM: u8 = 0x40;
 
 pub const DEFAULT_I2C_ADDR: u8 = 0x27;
 
 impl<B: I2cBus> Hd44780<B> {
-    /// Create and initialize the display. Defaults backlight to on.
+    /// Create and initialize a display on a PCF8574 backpack. Defaults backlight to on.
     pub fn new(bus: B, addr: u8, cols: u8, rows: u8) -> Result<Self> {
+        Self::with_backpack(bus, addr, cols, rows, Backpack::Pcf8574, PinMap::default())
+    }
+
+    /// Create and initialize a display behind `backpack`; `pins` is ignored for
+    /// native-I2C controllers.
+    pub fn with_backpack(
+        bus: B,
+        addr: u8,
+        cols: u8,
+        rows: u8,
+        backpack: Backpack,
+        pins: PinMap,
+    ) -> Result<Self> {
         let mut driver = Hd44780 {
             bus,
             addr,
+            backpack,
+            pins,
             cols: cols.min(40),
             rows: rows.min(4),
+            controller: 0,
             cursor_x: 0,
             cursor_y: 0,
             implied_newline: false,
             backlight: Backlight::On,
         };
 
-        driver.bus.write_byte(driver.addr, 0)?;
+        if !backpack.is_expander() {
+            driver.init_native(rows)?;
+            return Ok(driver);
+        }
+        if backpack == Backpack::Mcp23008 {
+            // Every GPx pin drives the LCD, so make them all outputs.
+            driver
+                .bus
+                .write_bytes(driver.addr, &[MCP23008_IODIR, 0x00])?;
+        }
+        driver.write_port(0)?;
         // Power-on wait.
         // The HD44780 spec requires an initial delay after VCC rises before the first
         // Function Set sequence; this mirrors the reference python_lcd implementation.
         // See `docs/HD44780_specs.pdf` (initialization/timing guidance; AC characteristics tables
         // show sub-microsecond bus timing minima, so millisecond-scale sleeps are conservative).
         sleep_ms(20);
+        let two_lines = rows > 1;
+        for controller in 0..driver.controllers() {
+            driver.controller = controller;
+            driver.init_expander_controller(two_lines)?;
+        }
+        driver.controller = 0;
+        Ok(driver)
+    }
+
+    /// Reset-by-instruction and display setup for the currently selected controller.
+    fn init_expander_controller(&mut self, two_lines: bool) -> Result<()> {
         // Reset sequence: 3x reset nibble, then function nibble.
-        driver.write_init_nibble(LCD_FUNCTION_RESET)?;
+        self.write_init_nibble(LCD_FUNCTION_RESET)?;
         sleep_ms(5);
-        driver.write_init_nibble(LCD_FUNCTION_RESET)?;
+        self.write_init_nibble(LCD_FUNCTION_RESET)?;
         sleep_ms(1);
-        driver.write_init_nibble(LCD_FUNCTION_RESET)?;
+        self.write_init_nibble(LCD_FUNCTION_RESET)?;
         sleep_ms(1);
-        driver.write_init_nibble(LCD_FUNCTION)?;
+        self.write_init_nibble(LCD_FUNCTION)?;
         sleep_ms(1);
 
         // Function set.
         let mut cmd = LCD_FUNCTION;
-        if rows > 1 {
+        if two_lines {
             cmd |= LCD_FUNCTION_2LINES;
         }
-        driver.write_command(cmd)?;
+        self.write_command(cmd)?;
 
         // Mirror python_lcd init: display off, clear/home, entry mode, display on.
-        driver.write_command(LCD_ON_CTRL)?; // display off
-        driver.clear()?;
-        driver.write_command(LCD_ENTRY_MODE | LCD_ENTRY_INC)?;
-        driver.display_on()?;
-        Ok(driver)
+        self.write_command(LCD_ON_CTRL)?; // display off
+        self.write_command(LCD_CLR)?;
+        self.write_command(LCD_HOME)?;
+        self.write_command(LCD_ENTRY_MODE | LCD_ENTRY_INC)?;
+        self.write_command(LCD_ON_CTRL | LCD_ON_DISPLAY)
+    }
+
+    /// Number of HD44780 controllers on the module: two when the pin map has `e2`.
+    pub fn controllers(&self) -> usize {
+        if self.backpack.is_expander() && self.pins.e2.is_some() {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// AIP31068/ST7032 power-up: 8-bit function set (plus the ST7032 booster and
+    /// contrast setup in the extended instruction set), then the usual display init.
+    fn init_native(&mut self, rows: u8) -> Result<()> {
+        // Both datasheets ask for >40 ms after VDD rises before the first instruction.
+        sleep_ms(50);
+        let mut function = LCD_FUNCTION | LCD_FUNCTION_8BIT;
+        if rows > 1 {
+            function |= LCD_FUNCTION_2LINES;
+        }
+        if self.backpack == Backpack::St7032 {
+            self.write_command(function)?;
+            self.write_command(function | ST7032_FUNCTION_IS)?;
+            self.write_command(ST7032_OSC_FREQ)?;
+            self.write_command(ST7032_CONTRAST_LOW | (ST7032_DEFAULT_CONTRAST & 0x0f))?;
+            self.write_command(
+                ST7032_POWER_ICON_CONTRAST | ((ST7032_DEFAULT_CONTRAST >> 4) & 0x03),
+            )?;
+            self.write_command(ST7032_FOLLOWER)?;
+            // Follower circuit settle time (datasheet: >200 ms).
+            sleep_ms(200);
+        }
+        self.write_command(function)?;
+        sleep_ms(5);
+        self.write_command(function)?;
+        self.write_command(LCD_ON_CTRL)?; // display off
+        self.clear()?;
+        self.write_command(LCD_ENTRY_MODE | LCD_ENTRY_INC)?;
+        self.display_on()
     }
 
     /// Clear display and home cursor. Requires the longer delay.
     pub fn clear(&mut self) -> Result<()> {
-        self.write_command(LCD_CLR)?;
-        self.write_command(LCD_HOME)?;
+        for controller in 0..self.controllers() {
+            self.controller = controller;
+            self.write_command(LCD_CLR)?;
+            self.write_command(LCD_HOME)?;
+        }
+        self.controller = 0;
         self.cursor_x = 0;
         self.cursor_y = 0;
         Ok(())
     }
 
     pub fn display_on(&mut self) -> Result<()> {
-        self.write_command(LCD_ON_CTRL | LCD_ON_DISPLAY)
+        self.write_display_control(LCD_ON_DISPLAY, 0)
     }
 
     pub fn display_off(&mut self) -> Result<()> {
-        self.write_command(LCD_ON_CTRL)
+        self.write_display_control(0, 0)
     }
 
     pub fn show_cursor(&mut self) -> Result<()> {
-        self.write_command(LCD_ON_CTRL | LCD_ON_DISPLAY | LCD_ON_CURSOR)
+        self.write_display_control(LCD_ON_DISPLAY, LCD_ON_CURSOR)
     }
 
     pub fn hide_cursor(&mut self) -> Result<()> {
-        self.write_command(LCD_ON_CTRL | LCD_ON_DISPLAY)
+        self.write_display_control(LCD_ON_DISPLAY, 0)
     }
 
     pub fn blink_cursor_on(&mut self) -> Result<()> {
-        self.write_command(LCD_ON_CTRL | LCD_ON_DISPLAY | LCD_ON_CURSOR | LCD_ON_BLINK)
+        self.write_display_control(LCD_ON_DISPLAY, LCD_ON_CURSOR | LCD_ON_BLINK)
+    }
+
+    /// Send display on/off control to every controller; `cursor` bits only go to the one
+    /// holding the cursor so a dual-controller module never shows two cursors.
+    fn write_display_control(&mut self, display: u8, cursor: u8) -> Result<()> {
+        let active = self.controller;
+        for controller in 0..self.controllers() {
+            self.controller = controller;
+            let cursor = if controller == active { cursor } else { 0 };
+            self.write_command(LCD_ON_CTRL | display | cursor)?;
+        }
+        self.controller = active;
+        Ok(())
     }
 
     pub fn blink_cursor_off(&mut self) -> Result<()> {
         self.hide_cursor()
     }
 
+    /// Native-I2C controllers have no backlight line; the state is only remembered.
     pub fn backlight_on(&mut self) -> Result<()> {
         self.backlight = Backlight::On;
-        self.bus.write_byte(self.addr, 1 << SHIFT_BACKLIGHT)
+        if !self.backpack.is_expander() {
+            return Ok(());
+        }
+        self.write_port(self.pins.backlight_mask())
     }
 
     pub fn backlight_off(&mut self) -> Result<()> {
         self.backlight = Backlight::Off;
-        self.bus.write_byte(self.addr, 0)
+        if !self.backpack.is_expander() {
+            return Ok(());
+        }
+        self.write_port(0)
     }
 
     /// Position cursor and write a line (wraps using putchar logic).
@@ -152,22 +275,18 @@ impl<B: I2cBus> Hd44780<B> {
         self.putstr(text)
     }
 
+    /// Write a run of cells starting at (`col`, `row`) without touching the rest of the row.
+    pub fn write_at(&mut self, col: u8, row: u8, text: &str) -> Result<()> {
+        self.move_to(col, row)?;
+        self.putstr(text)
+    }
+
     pub fn move_to(&mut self, cursor_x: u8, cursor_y: u8) -> Result<()> {
         self.cursor_x = cursor_x;
         self.cursor_y = cursor_y % self.rows.max(1);
-
-        // HD44780 DDRAM row mapping:
-        // - Primary 16×2: row0 offset 0x00, row1 offset 0x40.
-        // - Common 4-line modules map rows 2/3 to +cols (non-linear DDRAM layout).
-        // This standard formula matches typical 16×2/20×4/16×4 glass.
-        let mut addr = cursor_x & 0x3f;
-        if self.cursor_y & 1 == 1 {
-            addr += 0x40;
-        }
-        if self.cursor_y & 2 == 2 {
-            addr += self.cols;
-        }
-        self.write_command(LCD_DDRAM | addr)
+        let (controller, row) = controller_for_row(self.cursor_y, self.controllers());
+        self.controller = controller;
+        self.write_command(LCD_DDRAM | ddram_address(cursor_x, row, self.cols))
     }
 
     pub fn putchar(&mut self, ch: char) -> Result<()> {
@@ -226,17 +345,20 @@ impl<B: I2cBus> Hd44780<B> {
         Ok(())
     }
 
-    /// Write a custom character pattern into CGRAM (location 0-7).
+    /// Write a custom character pattern into CGRAM (location 0-7) of every controller.
     pub fn custom_char(&mut self, location: u8, pattern: &[u8; 8]) -> Result<()> {
         let loc = location & 0x7;
-        self.write_command(LCD_CGRAM | (loc << 3))?;
-        // Table 6 lists most instruction execution times as 37 µs max at the reference oscillator
-        // frequency (and notes the value scales with controller clock). Sleeping ~40 µs is a
-        // conservative, spec-aligned fixed delay for CGRAM writes.
-        sleep_us(40);
-        for byte in pattern {
-            self.write_data(*byte)?;
+        for controller in 0..self.controllers() {
+            self.controller = controller;
+            self.write_command(LCD_CGRAM | (loc << 3))?;
+            // Table 6 lists most instruction execution times as 37 µs max at the reference
+            // oscillator frequency (and notes the value scales with controller clock). Sleeping
+            // ~40 µs is a conservative, spec-aligned fixed delay for CGRAM writes.
             sleep_us(40);
+            for byte in pattern {
+                self.write_data(*byte)?;
+                sleep_us(40);
+            }
         }
         self.move_to(self.cursor_x, self.cursor_y)?;
         Ok(())
@@ -260,15 +382,20 @@ impl<B: I2cBus> Hd44780<B> {
     }
 
     fn write_init_nibble(&mut self, nibble: u8) -> Result<()> {
-        let byte = ((nibble >> 4) & 0x0f) << SHIFT_DATA;
-        self.bus.write_byte(self.addr, byte | MASK_E)?;
-        self.bus.write_byte(self.addr, byte)?;
+        let byte = self.pins.data_bits((nibble >> 4) & 0x0f);
+        self.write_port(byte | self.e_mask())?;
+        self.write_port(byte)?;
         Ok(())
     }
 
     fn write_command(&mut self, cmd: u8) -> Result<()> {
-        self.write_nibble(cmd, false)?;
-        self.write_nibble(cmd << 4, false)?;
+        if self.backpack.is_expander() {
+            self.write_nibble(cmd, false)?;
+            self.write_nibble(cmd << 4, false)?;
+        } else {
+            self.bus
+                .write_bytes(self.addr, &[NATIVE_CONTROL_COMMAND, cmd])?;
+        }
         if cmd <= 3 {
             // HOME/CLEAR need extra delay.
             // We do not read the busy flag (RW is not wired on most PCF8574 backpacks),
@@ -282,6 +409,11 @@ impl<B: I2cBus> Hd44780<B> {
     }
 
     fn write_data(&mut self, data: u8) -> Result<()> {
+        if !self.backpack.is_expander() {
+            return self
+                .bus
+                .write_bytes(self.addr, &[NATIVE_CONTROL_DATA, data]);
+        }
         self.write_nibble(data, true)?;
         self.write_nibble(data << 4, true)?;
         Ok(())
@@ -290,28 +422,66 @@ impl<B: I2cBus> Hd44780<B> {
     fn write_nibble(&mut self, nibble: u8, is_data: bool) -> Result<()> {
         let mut byte = self.backlight_mask();
         if is_data {
-            byte |= MASK_RS;
+            byte |= self.pins.rs_mask();
         }
-        byte |= (nibble >> 4) << SHIFT_DATA;
+        byte |= self.pins.data_bits(nibble >> 4);
 
-        self.bus.write_byte(self.addr, byte | MASK_E)?;
-        self.bus.write_byte(self.addr, byte)?;
+        self.write_port(byte | self.e_mask())?;
+        self.write_port(byte)?;
         Ok(())
     }
 
+    /// Drive the expander's output port to `byte`.
+    fn write_port(&mut self, byte: u8) -> Result<()> {
+        match self.backpack {
+            Backpack::Mcp23008 => self.bus.write_bytes(self.addr, &[MCP23008_GPIO, byte]),
+            _ => self.bus.write_byte(self.addr, byte),
+        }
+    }
+
+    fn e_mask(&self) -> u8 {
+        self.pins.e_mask(self.controller)
+    }
+
     fn backlight_mask(&self) -> u8 {
         match self.backlight {
-            Backlight::On => 1 << SHIFT_BACKLIGHT,
+            Backlight::On => self.pins.backlight_mask(),
             Backlight::Off => 0,
         }
     }
 }
 
-fn sleep_ms(ms: u64) {
+/// Controller index and its local row for panel `row`. A dual-controller 40x4 module is two
+/// stacked two-line controllers: rows 0-1 sit behind the first enable line, rows 2-3 the second.
+pub(super) fn controller_for_row(row: u8, controllers: usize) -> (usize, u8) {
+    if controllers > 1 {
+        ((row / 2) as usize, row % 2)
+    } else {
+        (0, row)
+    }
+}
+
+/// DDRAM address of (`col`, `row`) on a panel `cols` wide.
+pub(super) fn ddram_address(col: u8, row: u8, cols: u8) -> u8 {
+    // HD44780 DDRAM row mapping:
+    // - Primary 16×2: row0 offset 0x00, row1 offset 0x40.
+    // - Common 4-line modules map rows 2/3 to +cols (non-linear DDRAM layout).
+    // This standard formula matches typical 16×2/20×4/16×4 glass.
+    let mut addr = col & 0x3f;
+    if row & 1 == 1 {
+        addr += 0x40;
+    }
+    if row & 2 == 2 {
+        addr += cols;
+    }
+    addr
+}
+
+pub(super) fn sleep_ms(ms: u64) {
     std::thread::sleep(Duration::from_millis(ms));
 }
 
-fn sleep_us(us: u64) {
+pub(super) fn sleep_us(us: u64) {
     std::thread::sleep(Duration::from_micros(us));
 }
 
@@ -324,7 +494,7 @@ pub(super) fn from_hex(byte: u8) -> Option<u8> {
     }
 }
 
-pub(super) fn parse_bitmap_row(row: &str) -> Result<u8> {
+pub(crate) fn parse_bitmap_row(row: &str) -> Result<u8> {
     if row.len() > 5 {
         return Err(Error::InvalidArgs(
             "bitmap rows must be at most 5 characters".into(),
@@ -347,6 +517,7 @@ mod tests {
     #[derive(Debug, Default)]
     struct MockBus {
         writes: Vec<(u8, u8)>,
+        blocks: Vec<(u8, Vec<u8>)>,
         decoded: Vec<DecodedByte>,
         pending_enable: Option<(bool, u8)>,
         partial_byte: Option<(bool, u8)>,
@@ -369,6 +540,11 @@ mod tests {
             self.writes.push((addr, byte));
             Ok(())
         }
+
+        fn write_bytes(&mut self, addr: u8, bytes: &[u8]) -> Result<()> {
+            self.blocks.push((addr, bytes.to_vec()));
+            Ok(())
+        }
     }
 
     impl MockBus {
@@ -499,4 +675,136 @@ mod tests {
         assert_eq!(driver.cursor_x, 3);
         assert_eq!(driver.cursor_y, 1);
     }
+
+    #[test]
+    fn write_at_emits_one_move_and_only_the_run() {
+        let mut driver = Hd44780::new(MockBus::default(), 0x27, 16, 2).unwrap();
+        driver.bus.decoded.clear();
+        driver.write_at(5, 1, "ab").unwrap();
+        let data: Vec<u8> = driver
+            .bus
+            .decoded
+            .iter()
+            .filter(|d| d.rs)
+            .map(|d| d.value)
+            .collect();
+        assert_eq!(data, b"ab");
+        assert_eq!(driver.bus.take_decoded_commands(), vec![LCD_DDRAM | 0x45]);
+    }
+
+    #[test]
+    fn mcp23008_sets_outputs_then_writes_the_gpio_register() {
+        let driver = Hd44780::with_backpack(
+            MockBus::default(),
+            0x20,
+            16,
+            2,
+            Backpack::Mcp23008,
+            Backpack::Mcp23008.default_pin_map(),
+        )
+        .unwrap();
+        assert!(driver.bus.writes.is_empty(), "no raw PCF8574-style writes");
+        assert_eq!(driver.bus.blocks[0], (0x20, vec![MCP23008_IODIR, 0x00]));
+        assert!(driver.bus.blocks[1..]
+            .iter()
+            .all(|(_, bytes)| bytes.len() == 2 && bytes[0] == MCP23008_GPIO));
+        // First reset nibble 0x3 lands on GP3/GP4 with E on GP2.
+        assert_eq!(driver.bus.blocks[2].1[1], 0b0001_1100);
+    }
+
+    #[test]
+    fn custom_pin_map_moves_the_backlight_bit() {
+        let pins: PinMap = "rs=7,e=6,d4=0,d5=1,d6=2,d7=3,bl=5".parse().unwrap();
+        let mut driver =
+            Hd44780::with_backpack(MockBus::default(), 0x3f, 16, 2, Backpack::Pcf8574, pins)
+                .unwrap();
+        driver.backlight_on().unwrap();
+        assert_eq!(driver.bus.writes.last(), Some(&(0x3f, 1 << 5)));
+    }
+
+    #[test]
+    fn native_controller_frames_commands_and_data() {
+        let mut driver = Hd44780::with_backpack(
+            MockBus::default(),
+            0x3e,
+            16,
+            2,
+            Backpack::Aip31068,
+            PinMap::default(),
+        )
+        .unwrap();
+        assert!(driver.bus.writes.is_empty());
+        assert_eq!(
+            driver.bus.blocks[0].1,
+            vec![
+                NATIVE_CONTROL_COMMAND,
+                LCD_FUNCTION | LCD_FUNCTION_8BIT | LCD_FUNCTION_2LINES
+            ]
+        );
+        driver.bus.blocks.clear();
+        driver.write_at(5, 1, "ab").unwrap();
+        let sent: Vec<Vec<u8>> = driver.bus.blocks.iter().map(|(_, b)| b.clone()).collect();
+        assert_eq!(
+            sent,
+            vec![
+                vec![NATIVE_CONTROL_COMMAND, LCD_DDRAM | 0x45],
+                vec![NATIVE_CONTROL_DATA, b'a'],
+                vec![NATIVE_CONTROL_DATA, b'b'],
+            ]
+        );
+    }
+
+    #[test]
+    fn st7032_runs_the_extended_instruction_setup() {
+        let driver = Hd44780::with_backpack(
+            MockBus::default(),
+            0x3e,
+            16,
+            2,
+            Backpack::St7032,
+            PinMap::default(),
+        )
+        .unwrap();
+        let commands: Vec<u8> = driver.bus.blocks.iter().map(|(_, b)| b[1]).collect();
+        assert_eq!(&commands[1..3], &[0x39, ST7032_OSC_FREQ]);
+        assert!(commands.contains(&ST7032_FOLLOWER));
+        assert_eq!(commands.last(), Some(&(LCD_ON_CTRL | LCD_ON_DISPLAY)));
+    }
+
+    #[test]
+    fn dual_enable_routes_rows_and_loads_cgram_twice() {
+        let pins: PinMap = "rs=0,e=2,e2=1,d4=4,d5=5,d6=6,d7=7,bl=3".parse().unwrap();
+        let mut driver =
+            Hd44780::with_backpack(MockBus::default(), 0x27, 40, 4, Backpack::Pcf8574, pins)
+                .unwrap();
+        assert_eq!(driver.controllers(), 2);
+        let e2_pulses = |writes: &[(u8, u8)]| writes.iter().filter(|(_, b)| b & 0x02 != 0).count();
+        assert!(
+            e2_pulses(&driver.bus.writes) > 0,
+            "second controller initialised"
+        );
+
+        driver.bus.writes.clear();
+        driver.write_at(3, 1, "a").unwrap();
+        assert_eq!(e2_pulses(&driver.bus.writes), 0, "rows 0-1 stay on E1");
+
+        driver.bus.writes.clear();
+        driver.write_at(3, 3, "b").unwrap();
+        assert!(driver.bus.writes.iter().all(|(_, b)| b & MASK_E == 0));
+        assert_eq!(driver.controller, 1);
+
+        driver.bus.writes.clear();
+        driver.custom_char(0, &[0x1f; 8]).unwrap();
+        let e1 = driver
+            .bus
+            .writes
+            .iter()
+            .filter(|(_, b)| b & MASK_E != 0)
+            .count();
+        assert_eq!(
+            e1,
+            e2_pulses(&driver.bus.writes) - 2,
+            "CGRAM loaded on both, then re-seek on E2"
+        );
+    }
 }
@@ -0,0 +1,601 @@
+//! HD44780 wired straight to GPIO (no I2C backpack) in 4-bit or 8-bit mode.
+//! Command set and DDRAM layout are shared with the PCF8574 driver in `super`.
+
+use std::{fmt, str::FromStr};
+
+use super::{
+    controller_for_row, ddram_address, sleep_ms, sleep_us, Backlight, LCD_CGRAM, LCD_CLR,
+    LCD_DDRAM, LCD_ENTRY_INC, LCD_ENTRY_MODE, LCD_FUNCTION, LCD_FUNCTION_2LINES, LCD_FUNCTION_8BIT,
+    LCD_FUNCTION_RESET, LCD_HOME, LCD_ON_BLINK, LCD_ON_CTRL, LCD_ON_CURSOR, LCD_ON_DISPLAY,
+};
+use crate::{Error, Result};
+
+/// Minimal trait to allow swapping the GPIO backend (for tests or rppal).
+pub trait GpioBus {
+    fn write_pin(&mut self, pin: u8, high: bool) -> Result<()>;
+}
+
+/// BCM pin numbers for a directly wired panel.
+///
+/// `data` holds D4-D7 for 4-bit wiring or D0-D7 for 8-bit wiring, lowest bit first.
+/// RW is expected to be tied to ground; `backlight` drives an optional transistor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParallelPins {
+    pub rs: u8,
+    pub e: u8,
+    /// Enable line of the second controller on dual-controller 40x4 modules.
+    pub e2: Option<u8>,
+    pub data: Vec<u8>,
+    pub backlight: Option<u8>,
+}
+
+impl ParallelPins {
+    pub fn is_eight_bit(&self) -> bool {
+        self.data.len() == 8
+    }
+
+    /// Every pin this wiring claims, for conflict checks against other GPIO users.
+    pub fn all(&self) -> Vec<u8> {
+        let mut pins = vec![self.rs, self.e];
+        pins.extend(self.e2);
+        pins.extend(&self.data);
+        pins.extend(self.backlight);
+        pins
+    }
+}
+
+impl FromStr for ParallelPins {
+    type Err = String;
+
+    /// Parse `rs=26,e=19,d4=13,d5=6,d6=5,d7=11` (optionally `d0`-`d3`, `e2`, and `bl`).
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut rs = None;
+        let mut e = None;
+        let mut e2 = None;
+        let mut data: [Option<u8>; 8] = [None; 8];
+        let mut backlight = None;
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, pin) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected name=pin, got '{part}'"))?;
+            let name = name.trim().to_ascii_lowercase();
+            let pin: u8 = pin
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid pin number for {name}: '{}'", pin.trim()))?;
+            let slot = match name.as_str() {
+                "rs" => &mut rs,
+                "e" | "en" => &mut e,
+                "e2" | "en2" => &mut e2,
+                "bl" | "backlight" => &mut backlight,
+                other => match other
+                    .strip_prefix('d')
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .filter(|n| *n < 8)
+                {
+                    Some(n) => &mut data[n],
+                    None => return Err(format!("unknown pin name '{other}'")),
+                },
+            };
+            if slot.replace(pin).is_some() {
+                return Err(format!("pin {name} given more than once"));
+            }
+        }
+
+        let rs = rs.ok_or("missing rs pin")?;
+        let e = e.ok_or("missing e pin")?;
+        let high: Vec<u8> = data[4..]
+            .iter()
+            .enumerate()
+            .map(|(i, pin)| pin.ok_or(format!("missing d{} pin", i + 4)))
+            .collect::<std::result::Result<_, _>>()?;
+        let data = match data[..4].iter().filter(|pin| pin.is_some()).count() {
+            0 => high,
+            4 => data.iter().flatten().copied().collect(),
+            _ => return Err("d0-d3 must be given together for 8-bit wiring".into()),
+        };
+        let pins = ParallelPins {
+            rs,
+            e,
+            e2,
+            data,
+            backlight,
+        };
+        let mut all = pins.all();
+        all.sort_unstable();
+        if all.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err("each pin may only be used once".into());
+        }
+        Ok(pins)
+    }
+}
+
+impl fmt::Display for ParallelPins {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rs={},e={}", self.rs, self.e)?;
+        if let Some(pin) = self.e2 {
+            write!(f, ",e2={pin}")?;
+        }
+        let first = 8 - self.data.len();
+        for (i, pin) in self.data.iter().enumerate() {
+            write!(f, ",d{}={pin}", first + i)?;
+        }
+        if let Some(pin) = self.backlight {
+            write!(f, ",bl={pin}")?;
+        }
+        Ok(())
+    }
+}
+
+/// HD44780 driver that toggles RS/E/data lines directly.
+pub struct ParallelHd44780<G: GpioBus> {
+    bus: G,
+    pins: ParallelPins,
+    cols: u8,
+    rows: u8,
+    /// Controller whose enable line is pulsed; always 0 unless `pins.e2` is set.
+    controller: usize,
+    cursor_x: u8,
+    cursor_y: u8,
+    backlight: Backlight,
+}
+
+impl<G: GpioBus> ParallelHd44780<G> {
+    /// Create and initialize the display. Defaults backlight to on.
+    pub fn new(bus: G, pins: ParallelPins, cols: u8, rows: u8) -> Result<Self> {
+        if pins.data.len() != 4 && pins.data.len() != 8 {
+            return Err(Error::InvalidArgs(
+                "parallel LCD needs 4 (D4-D7) or 8 (D0-D7) data pins".into(),
+            ));
+        }
+        let mut driver = Self {
+            bus,
+            pins,
+            cols: cols.min(40),
+            rows: rows.min(4),
+            controller: 0,
+            cursor_x: 0,
+            cursor_y: 0,
+            backlight: Backlight::On,
+        };
+
+        driver.bus.write_pin(driver.pins.rs, false)?;
+        driver.bus.write_pin(driver.pins.e, false)?;
+        if let Some(e2) = driver.pins.e2 {
+            driver.bus.write_pin(e2, false)?;
+        }
+        driver.apply_backlight()?;
+        // Same power-on wait and reset-by-instruction sequence as the backpack driver
+        // (`docs/HD44780_specs.pdf`, figures 23/24).
+        sleep_ms(20);
+        for controller in 0..driver.controllers() {
+            driver.controller = controller;
+            driver.init_controller(rows > 1)?;
+        }
+        driver.controller = 0;
+        Ok(driver)
+    }
+
+    /// Reset-by-instruction and display setup for the currently selected controller. The
+    /// reset nibble doubles as the 8-bit reset byte because D0-D3 are "don't care" there.
+    fn init_controller(&mut self, two_lines: bool) -> Result<()> {
+        self.write_init(LCD_FUNCTION_RESET)?;
+        sleep_ms(5);
+        self.write_init(LCD_FUNCTION_RESET)?;
+        sleep_ms(1);
+        self.write_init(LCD_FUNCTION_RESET)?;
+        sleep_ms(1);
+
+        let mut cmd = LCD_FUNCTION;
+        if self.pins.is_eight_bit() {
+            cmd |= LCD_FUNCTION_8BIT;
+        } else {
+            self.write_init(LCD_FUNCTION)?;
+            sleep_ms(1);
+        }
+        if two_lines {
+            cmd |= LCD_FUNCTION_2LINES;
+        }
+        self.write_command(cmd)?;
+
+        self.write_command(LCD_ON_CTRL)?; // display off
+        self.write_command(LCD_CLR)?;
+        self.write_command(LCD_HOME)?;
+        self.write_command(LCD_ENTRY_MODE | LCD_ENTRY_INC)?;
+        self.write_command(LCD_ON_CTRL | LCD_ON_DISPLAY)
+    }
+
+    /// Number of HD44780 controllers on the module: two when `e2` is wired.
+    pub fn controllers(&self) -> usize {
+        if self.pins.e2.is_some() {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Clear display and home cursor. Requires the longer delay.
+    pub fn clear(&mut self) -> Result<()> {
+        for controller in 0..self.controllers() {
+            self.controller = controller;
+            self.write_command(LCD_CLR)?;
+            self.write_command(LCD_HOME)?;
+        }
+        self.controller = 0;
+        self.cursor_x = 0;
+        self.cursor_y = 0;
+        Ok(())
+    }
+
+    pub fn display_on(&mut self) -> Result<()> {
+        self.write_display_control(LCD_ON_DISPLAY, 0)
+    }
+
+    pub fn display_off(&mut self) -> Result<()> {
+        self.write_display_control(0, 0)
+    }
+
+    pub fn blink_cursor_on(&mut self) -> Result<()> {
+        self.write_display_control(LCD_ON_DISPLAY, LCD_ON_CURSOR | LCD_ON_BLINK)
+    }
+
+    pub fn blink_cursor_off(&mut self) -> Result<()> {
+        self.write_display_control(LCD_ON_DISPLAY, 0)
+    }
+
+    /// Display control for every controller; only the one holding the cursor gets `cursor`.
+    fn write_display_control(&mut self, display: u8, cursor: u8) -> Result<()> {
+        let active = self.controller;
+        for controller in 0..self.controllers() {
+            self.controller = controller;
+            let cursor = if controller == active { cursor } else { 0 };
+            self.write_command(LCD_ON_CTRL | display | cursor)?;
+        }
+        self.controller = active;
+        Ok(())
+    }
+
+    /// No-op when the wiring has no backlight pin.
+    pub fn backlight_on(&mut self) -> Result<()> {
+        self.backlight = Backlight::On;
+        self.apply_backlight()
+    }
+
+    pub fn backlight_off(&mut self) -> Result<()> {
+        self.backlight = Backlight::Off;
+        self.apply_backlight()
+    }
+
+    /// Write a run of cells starting at (`col`, `row`); text past the last column is dropped.
+    pub fn write_at(&mut self, col: u8, row: u8, text: &str) -> Result<()> {
+        self.move_to(col, row)?;
+        for ch in text.chars() {
+            if self.cursor_x >= self.cols {
+                break;
+            }
+            self.write_data(ch as u8)?;
+            self.cursor_x += 1;
+        }
+        Ok(())
+    }
+
+    pub fn write_line(&mut self, row: u8, text: &str) -> Result<()> {
+        self.write_at(0, row, text)
+    }
+
+    pub fn move_to(&mut self, cursor_x: u8, cursor_y: u8) -> Result<()> {
+        self.cursor_x = cursor_x;
+        self.cursor_y = cursor_y % self.rows.max(1);
+        let (controller, row) = controller_for_row(self.cursor_y, self.controllers());
+        self.controller = controller;
+        self.write_command(LCD_DDRAM | ddram_address(cursor_x, row, self.cols))
+    }
+
+    /// Write a custom character pattern into CGRAM (location 0-7) of every controller.
+    pub fn custom_char(&mut self, location: u8, pattern: &[u8; 8]) -> Result<()> {
+        for controller in 0..self.controllers() {
+            self.controller = controller;
+            self.write_command(LCD_CGRAM | ((location & 0x7) << 3))?;
+            for byte in pattern {
+                self.write_data(*byte)?;
+            }
+        }
+        self.move_to(self.cursor_x, self.cursor_y)
+    }
+
+    /// Load multiple bitmaps sequentially starting at CGRAM address 0.
+    pub fn load_custom_bitmaps(&mut self, bitmaps: &[[&str; 8]]) -> Result<()> {
+        for (idx, rows) in bitmaps.iter().enumerate().take(8) {
+            let mut pattern = [0u8; 8];
+            for (i, row) in rows.iter().enumerate() {
+                pattern[i] = super::parse_bitmap_row(row)?;
+            }
+            self.custom_char(idx as u8, &pattern)?;
+        }
+        Ok(())
+    }
+
+    /// Latch the upper nibble of `value` (or the whole byte in 8-bit mode) with RS low.
+    fn write_init(&mut self, value: u8) -> Result<()> {
+        self.bus.write_pin(self.pins.rs, false)?;
+        if self.pins.is_eight_bit() {
+            self.latch(value)
+        } else {
+            self.latch(value >> 4)
+        }
+    }
+
+    fn write_command(&mut self, cmd: u8) -> Result<()> {
+        self.write_byte(cmd, false)?;
+        if cmd <= 3 {
+            // HOME/CLEAR: 1.52 ms max per Table 6; RW is tied low so we cannot poll BF.
+            sleep_ms(2);
+        }
+        Ok(())
+    }
+
+    fn write_data(&mut self, data: u8) -> Result<()> {
+        self.write_byte(data, true)
+    }
+
+    fn write_byte(&mut self, value: u8, is_data: bool) -> Result<()> {
+        self.bus.write_pin(self.pins.rs, is_data)?;
+        if self.pins.is_eight_bit() {
+            self.latch(value)?;
+        } else {
+            self.latch(value >> 4)?;
+            self.latch(value & 0x0f)?;
+        }
+        // Table 6: 37 µs for everything except clear/home.
+        sleep_us(40);
+        Ok(())
+    }
+
+    /// Put `bits` on the data lines (bit 0 on the first pin) and pulse E.
+    fn latch(&mut self, bits: u8) -> Result<()> {
+        for idx in 0..self.pins.data.len() {
+            let pin = self.pins.data[idx];
+            self.bus.write_pin(pin, bits & (1 << idx) != 0)?;
+        }
+        // Enable pulse width is 230 ns minimum; a microsecond keeps slow level shifters happy.
+        let enable = match (self.controller, self.pins.e2) {
+            (1, Some(e2)) => e2,
+            _ => self.pins.e,
+        };
+        self.bus.write_pin(enable, true)?;
+        sleep_us(1);
+        self.bus.write_pin(enable, false)?;
+        sleep_us(1);
+        Ok(())
+    }
+
+    fn apply_backlight(&mut self) -> Result<()> {
+        match self.pins.backlight {
+            Some(pin) => self.bus.write_pin(pin, self.backlight == Backlight::On),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Linux implementation using rppal's GPIO; pins are claimed as outputs up front.
+#[cfg(target_os = "linux")]
+pub struct RppalGpioBus {
+    outputs: std::collections::HashMap<u8, rppal::gpio::OutputPin>,
+}
+
+#[cfg(target_os = "linux")]
+impl RppalGpioBus {
+    pub fn new(pins: &ParallelPins) -> Result<Self> {
+        let gpio = rppal::gpio::Gpio::new().map_err(map_gpio_err)?;
+        let mut outputs = std::collections::HashMap::new();
+        for pin in pins.all() {
+            let output = gpio.get(pin).map_err(map_gpio_err)?.into_output_low();
+            outputs.insert(pin, output);
+        }
+        Ok(Self { outputs })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl GpioBus for RppalGpioBus {
+    fn write_pin(&mut self, pin: u8, high: bool) -> Result<()> {
+        let output = self.outputs.get_mut(&pin).ok_or_else(|| {
+            Error::InvalidArgs(format!("gpio pin {pin} was not claimed for the LCD"))
+        })?;
+        if high {
+            output.set_high();
+        } else {
+            output.set_low();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn map_gpio_err(err: rppal::gpio::Error) -> Error {
+    Error::Io(std::io::Error::other(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Records pin levels and decodes whatever is on the data lines at each E falling edge.
+    #[derive(Debug, Default)]
+    struct PinRecorder {
+        levels: HashMap<u8, bool>,
+        data_pins: Vec<u8>,
+        e: u8,
+        rs: u8,
+        latched: Vec<(bool, u8)>,
+    }
+
+    impl PinRecorder {
+        fn for_pins(pins: &ParallelPins) -> Self {
+            Self {
+                data_pins: pins.data.clone(),
+                e: pins.e,
+                rs: pins.rs,
+                ..Self::default()
+            }
+        }
+
+        fn level(&self, pin: u8) -> bool {
+            self.levels.get(&pin).copied().unwrap_or(false)
+        }
+
+        /// Pair 4-bit latches into bytes, skipping the `skip` init nibbles.
+        fn decoded_nibbles(&self, skip: usize) -> Vec<(bool, u8)> {
+            self.latched[skip..]
+                .chunks(2)
+                .map(|pair| (pair[0].0, (pair[0].1 << 4) | pair[1].1))
+                .collect()
+        }
+    }
+
+    impl GpioBus for PinRecorder {
+        fn write_pin(&mut self, pin: u8, high: bool) -> Result<()> {
+            if pin == self.e && self.level(pin) && !high {
+                let value = self
+                    .data_pins
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, pin)| self.level(**pin))
+                    .fold(0u8, |acc, (idx, _)| acc | (1 << idx));
+                self.latched.push((self.level(self.rs), value));
+            }
+            self.levels.insert(pin, high);
+            Ok(())
+        }
+    }
+
+    fn four_bit_pins() -> ParallelPins {
+        "rs=26,e=19,d4=13,d5=6,d6=5,d7=11,bl=12".parse().unwrap()
+    }
+
+    fn eight_bit_pins() -> ParallelPins {
+        "rs=26,e=19,d0=2,d1=3,d2=4,d3=17,d4=13,d5=6,d6=5,d7=11"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn four_bit_init_matches_reset_by_instruction() {
+        let pins = four_bit_pins();
+        let driver = ParallelHd44780::new(PinRecorder::for_pins(&pins), pins, 16, 2).unwrap();
+        let latched = &driver.bus.latched;
+        let init: Vec<u8> = latched[..4].iter().map(|(_, v)| *v).collect();
+        assert_eq!(init, vec![0x3, 0x3, 0x3, 0x2]);
+        let commands: Vec<u8> = driver
+            .bus
+            .decoded_nibbles(4)
+            .into_iter()
+            .map(|(rs, v)| {
+                assert!(!rs, "init must only send commands");
+                v
+            })
+            .collect();
+        assert_eq!(
+            commands,
+            vec![
+                LCD_FUNCTION | LCD_FUNCTION_2LINES,
+                LCD_ON_CTRL,
+                LCD_CLR,
+                LCD_HOME,
+                LCD_ENTRY_MODE | LCD_ENTRY_INC,
+                LCD_ON_CTRL | LCD_ON_DISPLAY,
+            ]
+        );
+        assert!(driver.bus.level(12), "backlight pin driven high");
+    }
+
+    #[test]
+    fn eight_bit_init_sets_dl_and_sends_whole_bytes() {
+        let pins = eight_bit_pins();
+        let driver = ParallelHd44780::new(PinRecorder::for_pins(&pins), pins, 20, 4).unwrap();
+        let values: Vec<u8> = driver.bus.latched.iter().map(|(_, v)| *v).collect();
+        assert_eq!(&values[..3], &[0x30, 0x30, 0x30]);
+        assert_eq!(
+            values[3],
+            LCD_FUNCTION | LCD_FUNCTION_8BIT | LCD_FUNCTION_2LINES
+        );
+        assert_eq!(values.len(), 3 + 6, "one latch per byte in 8-bit mode");
+    }
+
+    #[test]
+    fn write_at_moves_once_then_sends_data() {
+        let pins = four_bit_pins();
+        let mut driver = ParallelHd44780::new(PinRecorder::for_pins(&pins), pins, 16, 2).unwrap();
+        driver.bus.latched.clear();
+        driver.write_at(5, 1, "ab").unwrap();
+        assert_eq!(
+            driver.bus.decoded_nibbles(0),
+            vec![(false, LCD_DDRAM | 0x45), (true, b'a'), (true, b'b')]
+        );
+    }
+
+    #[test]
+    fn backlight_follows_configured_pin() {
+        let pins = four_bit_pins();
+        let mut driver = ParallelHd44780::new(PinRecorder::for_pins(&pins), pins, 16, 2).unwrap();
+        driver.backlight_off().unwrap();
+        assert!(!driver.bus.level(12));
+        driver.backlight_on().unwrap();
+        assert!(driver.bus.level(12));
+    }
+
+    #[test]
+    fn dual_enable_splits_rows_between_controllers() {
+        let pins: ParallelPins = "rs=26,e=19,e2=16,d4=13,d5=6,d6=5,d7=11".parse().unwrap();
+        assert_eq!(pins.to_string().parse::<ParallelPins>().unwrap(), pins);
+        let mut driver = ParallelHd44780::new(PinRecorder::for_pins(&pins), pins, 40, 4).unwrap();
+        assert_eq!(driver.controllers(), 2);
+        let first_controller_latches = driver.bus.latched.len();
+        assert_eq!(
+            first_controller_latches,
+            4 + 2 * 6,
+            "E2 latches are not decoded"
+        );
+
+        driver.bus.latched.clear();
+        driver.write_at(0, 2, "x").unwrap();
+        assert!(
+            driver.bus.latched.is_empty(),
+            "row 2 goes to the second controller"
+        );
+        driver.write_at(0, 1, "y").unwrap();
+        assert_eq!(
+            driver.bus.decoded_nibbles(0),
+            vec![(false, LCD_DDRAM | 0x40), (true, b'y')]
+        );
+    }
+
+    #[test]
+    fn parses_and_formats_pin_maps() {
+        let pins = four_bit_pins();
+        assert_eq!(pins.data, vec![13, 6, 5, 11]);
+        assert_eq!(pins.backlight, Some(12));
+        assert_eq!(pins.to_string().parse::<ParallelPins>().unwrap(), pins);
+
+        let eight = eight_bit_pins();
+        assert!(eight.is_eight_bit());
+        assert_eq!(eight.data, vec![2, 3, 4, 17, 13, 6, 5, 11]);
+        assert_eq!(eight.to_string().parse::<ParallelPins>().unwrap(), eight);
+    }
+
+    #[test]
+    fn rejects_incomplete_or_overlapping_pin_maps() {
+        for (raw, needle) in [
+            ("rs=1,e=2,d4=3,d5=4,d6=5", "missing d7"),
+            ("e=2,d4=3,d5=4,d6=5,d7=6", "missing rs"),
+            ("rs=1,e=2,d0=7,d4=3,d5=4,d6=5,d7=6", "d0-d3"),
+            ("rs=1,e=2,d4=3,d5=4,d6=5,d7=1", "only be used once"),
+            ("rs=1,e=2,d4=3,d5=4,d6=5,d7=6,d9=8", "unknown pin"),
+            ("rs=1,rs=2", "more than once"),
+        ] {
+            let err = raw.parse::<ParallelPins>().unwrap_err();
+            assert!(err.contains(needle), "{raw}: {err}");
+        }
+    }
+}
@@ -39,14 +39,16 @@ impl RppalBus {
         Ok(Self { inner })
     }
 
-    /// Auto-detect a PCF8574 address by probing common backpack ranges (0x20-0x27).
-    /// Returns the bus and the detected address (or the fallback if none respond).
+    /// Auto-detect a PCF8574 address by probing common backpack ranges (0x20-0x27, then
+    /// PCF8574A at 0x38-0x3F). Returns the bus and the detected address (or the fallback if
+    /// none respond).
     pub fn autodetect_default() -> Result<(Self, u8)> {
         let mut inner = rppal::i2c::I2c::new().map_err(map_i2c_err)?;
+        let backpack = super::backpack::Backpack::Pcf8574;
         let addr = detect_address(
             &mut inner,
-            &[0x27, 0x26, 0x25, 0x24, 0x23, 0x22, 0x21, 0x20],
-            0x27,
+            backpack.address_candidates(),
+            backpack.default_address(),
         );
         Ok((Self { inner }, addr))
     }
@@ -68,6 +70,13 @@ impl I2cBus for RppalBus {
             .map_err(map_i2c_err)?;
         self.inner.block_write(byte, &[]).map_err(map_i2c_err)
     }
+
+    fn write_bytes(&mut self, addr: u8, bytes: &[u8]) -> Result<()> {
+        self.inner
+            .set_slave_address(addr.into())
+            .map_err(map_i2c_err)?;
+        self.inner.write(bytes).map(|_| ()).map_err(map_i2c_err)
+    }
 }
 
 /// Linux `I2cdev` implementation so non-Raspberry Pi hosts can exercise the LCD path.
@@ -108,6 +117,11 @@ impl I2cBus for I2cdevBus {
         EmbeddedHal1I2c::<SevenBitAddress>::write(&mut self.inner, addr, &[byte])
             .map_err(map_i2cdev_err)
     }
+
+    fn write_bytes(&mut self, addr: u8, bytes: &[u8]) -> Result<()> {
+        EmbeddedHal1I2c::<SevenBitAddress>::write(&mut self.inner, addr, bytes)
+            .map_err(map_i2cdev_err)
+    }
 }
 
 /// Non-Linux stub to satisfy builds on dev hosts; returns errors at runtime.
@@ -142,6 +156,12 @@ impl I2cBus for RppalBus {
             "RppalBus is only available on Linux targets".into(),
         ))
     }
+
+    fn write_bytes(&mut self, _addr: u8, _bytes: &[u8]) -> Result<()> {
+        Err(Error::InvalidArgs(
+            "RppalBus is only available on Linux targets".into(),
+        ))
+    }
 }
 
 #[cfg(target_os = "linux")]
@@ -0,0 +1,181 @@
+//! RGB backlight drivers: a PCA9633 I2C LED controller (Grove LCD RGB modules) and
+//! per-channel GPIO PWM.
+
+use super::I2cBus;
+use crate::Result;
+
+// PCA9633 register map (datasheet section 7.3).
+const PCA9633_MODE1: u8 = 0x00;
+const PCA9633_MODE2: u8 = 0x01;
+const PCA9633_PWM0: u8 = 0x02;
+const PCA9633_LEDOUT: u8 = 0x08;
+/// LEDOUT: every channel driven by its own PWM register.
+const PCA9633_LEDOUT_PWM_ALL: u8 = 0xaa;
+/// MODE1 with SLEEP cleared; the oscillator needs 500 µs to start.
+const PCA9633_MODE1_NORMAL: u8 = 0x00;
+/// MODE2: totem-pole outputs, not inverted (what Grove boards expect).
+const PCA9633_MODE2_TOTEM_POLE: u8 = 0x04;
+
+/// Channel order on Grove boards: PWM2 red, PWM1 green, PWM0 blue.
+pub const GROVE_CHANNELS: [u8; 3] = [2, 1, 0];
+
+/// PCA9633 four-channel LED controller driving an RGB backlight.
+pub struct Pca9633<B: I2cBus> {
+    bus: B,
+    addr: u8,
+    /// PWM channel for red, green, blue.
+    channels: [u8; 3],
+}
+
+impl<B: I2cBus> Pca9633<B> {
+    pub fn new(bus: B, addr: u8, channels: [u8; 3]) -> Result<Self> {
+        let mut led = Self {
+            bus,
+            addr,
+            channels,
+        };
+        led.bus
+            .write_bytes(addr, &[PCA9633_MODE1, PCA9633_MODE1_NORMAL])?;
+        super::sleep_us(500);
+        led.bus
+            .write_bytes(addr, &[PCA9633_MODE2, PCA9633_MODE2_TOTEM_POLE])?;
+        led.bus
+            .write_bytes(addr, &[PCA9633_LEDOUT, PCA9633_LEDOUT_PWM_ALL])?;
+        Ok(led)
+    }
+
+    pub fn set_rgb(&mut self, r: u8, g: u8, b: u8) -> Result<()> {
+        for (channel, duty) in self.channels.into_iter().zip([r, g, b]) {
+            self.bus
+                .write_bytes(self.addr, &[PCA9633_PWM0 + (channel & 0x03), duty])?;
+        }
+        Ok(())
+    }
+}
+
+/// Minimal trait to allow swapping the PWM backend (for tests or rppal).
+pub trait PwmBus {
+    /// Drive `pin` with `duty` in 0.0..=1.0.
+    fn set_duty(&mut self, pin: u8, duty: f64) -> Result<()>;
+}
+
+/// RGB backlight on three PWM-capable GPIO pins.
+pub struct GpioPwmRgb<P: PwmBus> {
+    bus: P,
+    pins: [u8; 3],
+}
+
+impl<P: PwmBus> GpioPwmRgb<P> {
+    pub fn new(bus: P, red: u8, green: u8, blue: u8) -> Self {
+        Self {
+            bus,
+            pins: [red, green, blue],
+        }
+    }
+
+    pub fn set_rgb(&mut self, r: u8, g: u8, b: u8) -> Result<()> {
+        for (pin, level) in self.pins.into_iter().zip([r, g, b]) {
+            self.bus.set_duty(pin, f64::from(level) / 255.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Linux software PWM via rppal; fast enough for LEDs without a hardware PWM channel
+/// per colour.
+#[cfg(target_os = "linux")]
+pub struct RppalPwmBus {
+    outputs: std::collections::HashMap<u8, rppal::gpio::OutputPin>,
+}
+
+/// High enough that the backlight does not visibly flicker.
+#[cfg(target_os = "linux")]
+const SOFT_PWM_HZ: f64 = 200.0;
+
+#[cfg(target_os = "linux")]
+impl RppalPwmBus {
+    pub fn new(pins: &[u8]) -> Result<Self> {
+        let map_err = |e: rppal::gpio::Error| crate::Error::Io(std::io::Error::other(e));
+        let gpio = rppal::gpio::Gpio::new().map_err(map_err)?;
+        let mut outputs = std::collections::HashMap::new();
+        for &pin in pins {
+            outputs.insert(pin, gpio.get(pin).map_err(map_err)?.into_output_low());
+        }
+        Ok(Self { outputs })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl PwmBus for RppalPwmBus {
+    fn set_duty(&mut self, pin: u8, duty: f64) -> Result<()> {
+        let output = self.outputs.get_mut(&pin).ok_or_else(|| {
+            crate::Error::InvalidArgs(format!("gpio pin {pin} was not claimed for the backlight"))
+        })?;
+        if duty <= 0.0 {
+            output.clear_pwm().ok();
+            output.set_low();
+            return Ok(());
+        }
+        output
+            .set_pwm_frequency(SOFT_PWM_HZ, duty.min(1.0))
+            .map_err(|e| crate::Error::Io(std::io::Error::other(e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct RegisterBus {
+        writes: Vec<(u8, Vec<u8>)>,
+    }
+
+    impl I2cBus for RegisterBus {
+        fn write_byte(&mut self, addr: u8, byte: u8) -> Result<()> {
+            self.writes.push((addr, vec![byte]));
+            Ok(())
+        }
+
+        fn write_bytes(&mut self, addr: u8, bytes: &[u8]) -> Result<()> {
+            self.writes.push((addr, bytes.to_vec()));
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct DutyRecorder {
+        duties: Vec<(u8, f64)>,
+    }
+
+    impl PwmBus for DutyRecorder {
+        fn set_duty(&mut self, pin: u8, duty: f64) -> Result<()> {
+            self.duties.push((pin, duty));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn pca9633_wakes_and_maps_grove_channels() {
+        let mut led = Pca9633::new(RegisterBus::default(), 0x62, GROVE_CHANNELS).unwrap();
+        assert_eq!(
+            led.bus.writes[..3],
+            [
+                (0x62, vec![PCA9633_MODE1, 0x00]),
+                (0x62, vec![PCA9633_MODE2, 0x04]),
+                (0x62, vec![PCA9633_LEDOUT, 0xaa]),
+            ]
+        );
+        led.bus.writes.clear();
+        led.set_rgb(255, 120, 0).unwrap();
+        let regs: Vec<Vec<u8>> = led.bus.writes.iter().map(|(_, b)| b.clone()).collect();
+        assert_eq!(regs, vec![vec![0x04, 255], vec![0x03, 120], vec![0x02, 0]]);
+    }
+
+    #[test]
+    fn gpio_pwm_scales_channels_to_duty_cycle() {
+        let mut led = GpioPwmRgb::new(DutyRecorder::default(), 17, 27, 22);
+        led.set_rgb(255, 0, 51).unwrap();
+        assert_eq!(led.bus.duties, vec![(17, 1.0), (27, 0.0), (22, 0.2)]);
+    }
+}
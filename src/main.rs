@@ -1,4 +1,4 @@
-use lifelinetty::app::serial_shell;
+use lifelinetty::app::{forward, serial_shell};
 use lifelinetty::{
     app::App,
     cli::{Command, RunMode, RunOptions},
@@ -32,6 +32,7 @@ fn try_main() -> Result<()> {
                     app.run()
                 }
                 RunMode::SerialShell => run_serial_shell(opts),
+                RunMode::Forward => forward::run_port_forward(opts),
             }
         }
         Err(err) => {
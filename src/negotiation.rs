@@ -1,11 +1,85 @@
 use serde::{Deserialize, Serialize};
 use std::{fmt, str::FromStr};
 
-/// Protocol version shared by LifelineTTY endpoints during negotiation.
+/// Oldest protocol version this build still speaks.
+pub const MIN_PROTOCOL_VERSION: u8 = 1;
+/// Newest protocol version this build speaks; sent as `proto_version` in `hello`.
 pub const PROTOCOL_VERSION: u8 = 1;
 
+/// (command frame `schema_version`, highest payload `schema_version`) for each protocol
+/// version from `MIN_PROTOCOL_VERSION` up; the length ties it to the supported range, so
+/// bumping either constant without adding an entry fails to compile.
+const SCHEMAS_BY_PROTOCOL: [(u8, u8); (PROTOCOL_VERSION - MIN_PROTOCOL_VERSION + 1) as usize] =
+    [(1, 1)];
+
+/// The protocol version both peers agreed on, and the wire schemas it implies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolVersion(u8);
+
+impl ProtocolVersion {
+    /// Spoken with peers that skip or fail negotiation.
+    pub const LEGACY: ProtocolVersion = ProtocolVersion(MIN_PROTOCOL_VERSION);
+
+    /// `None` when this build does not speak `version`.
+    pub fn new(version: u8) -> Option<Self> {
+        (MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION)
+            .contains(&version)
+            .then_some(ProtocolVersion(version))
+    }
+
+    pub fn get(self) -> u8 {
+        self.0
+    }
+
+    fn schemas(self) -> (u8, u8) {
+        // `new` only admits MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION, which the table covers.
+        SCHEMAS_BY_PROTOCOL[usize::from(self.0 - MIN_PROTOCOL_VERSION)]
+    }
+
+    /// `schema_version` written into, and required on, command frames.
+    pub fn command_schema(self) -> u8 {
+        self.schemas().0
+    }
+
+    /// Highest display payload `schema_version` accepted.
+    pub fn max_payload_schema(self) -> u8 {
+        self.schemas().1
+    }
+}
+
+impl Default for ProtocolVersion {
+    fn default() -> Self {
+        ProtocolVersion(PROTOCOL_VERSION)
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}", self.0)
+    }
+}
+
+/// Highest version inside both `[min, max]` ranges, or an explanation when they do not overlap.
+pub fn select_protocol_version(
+    local: (u8, u8),
+    remote: (u8, u8),
+) -> Result<ProtocolVersion, String> {
+    let low = local.0.max(remote.0);
+    let high = local.1.min(remote.1);
+    if low > high {
+        return Err(format!(
+            "no common protocol version: local supports {}-{}, peer supports {}-{}",
+            local.0, local.1, remote.0, remote.1
+        ));
+    }
+    ProtocolVersion::new(high).ok_or_else(|| format!("protocol version {high} is not supported"))
+}
+
 /// Role assigned to each peer after negotiation completes.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// The server owns the display and executes tunnel/command requests; the client originates
+/// payloads and runs `serialsh`. Links that skip negotiation behave as a server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Role {
     Unknown,
     Server,
@@ -22,6 +96,21 @@ impl Role {
         }
     }
 
+    /// Whether payloads from the peer are rendered on the local LCDs.
+    pub fn owns_display(&self) -> bool {
+        !matches!(self, Role::Client)
+    }
+
+    /// Whether tunnel and command requests from the peer run locally.
+    pub fn executes_commands(&self) -> bool {
+        !matches!(self, Role::Client)
+    }
+
+    /// Whether local poll snapshots are forwarded to the peer as payload frames.
+    pub fn originates_payloads(&self) -> bool {
+        matches!(self, Role::Client)
+    }
+
     pub fn opposite(&self) -> Self {
         match self {
             Role::Server => Role::Client,
@@ -102,6 +191,8 @@ pub struct Capabilities {
     pub supports_tunnel: bool,
     pub supports_compression: bool,
     pub supports_heartbeat: bool,
+    pub supports_reliable: bool,
+    pub supports_latency: bool,
 }
 
 impl Capabilities {
@@ -110,6 +201,8 @@ impl Capabilities {
     pub const CMD_TUNNEL_V1: u32 = 0b0000_0010;
     pub const LCD_V2: u32 = 0b0000_0100;
     pub const HEARTBEAT_V1: u32 = 0b0000_1000;
+    pub const RELIABLE_V1: u32 = 0b0010_0000;
+    pub const LATENCY_V1: u32 = 0b0100_0000;
 
     pub fn bits(&self) -> u32 {
         let mut bits = Self::HANDSHAKE_V1;
@@ -122,6 +215,12 @@ impl Capabilities {
         if self.supports_heartbeat {
             bits |= Self::HEARTBEAT_V1;
         }
+        if self.supports_reliable {
+            bits |= Self::RELIABLE_V1;
+        }
+        if self.supports_latency {
+            bits |= Self::LATENCY_V1;
+        }
         bits
     }
 
@@ -130,6 +229,8 @@ impl Capabilities {
             supports_tunnel: bits & Self::CMD_TUNNEL_V1 != 0,
             supports_compression: bits & Self::COMPRESSION_V1 != 0,
             supports_heartbeat: bits & Self::HEARTBEAT_V1 != 0,
+            supports_reliable: bits & Self::RELIABLE_V1 != 0,
+            supports_latency: bits & Self::LATENCY_V1 != 0,
         }
     }
 }
@@ -139,16 +240,61 @@ impl Capabilities {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ControlFrame {
     Hello {
+        /// Newest protocol version the sender speaks.
         proto_version: u8,
+        /// Oldest protocol version the sender speaks; absent from peers that only speak
+        /// `proto_version`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        proto_min: Option<u8>,
         node_id: u32,
         caps: ControlCaps,
         pref: String,
+        /// Line rates this peer can switch to after the handshake; empty disables the upgrade.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        bauds: Vec<u32>,
+        /// Human-readable node name; absent from peers that predate naming.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        node_name: Option<String>,
+        /// Random UUID persisted by the sender, used for peer pinning.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        node_uuid: Option<String>,
     },
     HelloAck {
         chosen_role: String,
         peer_caps: ControlCaps,
+        /// Protocol version the sender selected from both `hello` ranges.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        proto_version: Option<u8>,
+    },
+    LegacyFallback {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    /// Sent by both peers right after switching to a negotiated baud; the probe text and its
+    /// CRC-32 must survive the round trip or both sides drop back to the configured rate.
+    BaudCheck {
+        baud: u32,
+        probe: String,
+        crc32: u32,
+    },
+    /// Sent once the peer's `baud_check` arrived intact. A peer keeps the new rate only after
+    /// both checking and receiving this; otherwise it drops back to the configured rate.
+    BaudCommit { baud: u32 },
+    /// Ask the peer to rerun the hello exchange on the open link, e.g. after the sender's
+    /// capabilities changed. Both sides then send `hello` as in the initial handshake.
+    Renegotiate {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
     },
-    LegacyFallback,
+}
+
+/// Highest rate both peers advertised, if any.
+pub fn highest_common_baud(local: &[u32], remote: &[u32]) -> Option<u32> {
+    local
+        .iter()
+        .copied()
+        .filter(|baud| remote.contains(baud))
+        .max()
 }
 
 /// Serialized wrapper for capability bits.
@@ -167,6 +313,8 @@ mod tests {
             supports_tunnel: false,
             supports_compression: true,
             supports_heartbeat: false,
+            supports_reliable: false,
+            supports_latency: false,
         };
         let bits = caps.bits();
         assert!(bits & Capabilities::COMPRESSION_V1 != 0);
@@ -175,4 +323,57 @@ mod tests {
         assert!(!decoded.supports_tunnel);
         assert!(!decoded.supports_heartbeat);
     }
+
+    #[test]
+    fn reliable_bit_round_trips() {
+        let caps = Capabilities {
+            supports_reliable: true,
+            ..Capabilities::default()
+        };
+        let bits = caps.bits();
+        assert!(bits & Capabilities::RELIABLE_V1 != 0);
+        assert!(Capabilities::from_bits(bits).supports_reliable);
+        assert!(!Capabilities::from_bits(Capabilities::HANDSHAKE_V1).supports_reliable);
+    }
+
+    #[test]
+    fn latency_bit_round_trips() {
+        let caps = Capabilities {
+            supports_latency: true,
+            ..Capabilities::default()
+        };
+        assert!(caps.bits() & Capabilities::LATENCY_V1 != 0);
+        assert!(Capabilities::from_bits(caps.bits()).supports_latency);
+        assert!(!Capabilities::from_bits(Capabilities::HANDSHAKE_V1).supports_latency);
+    }
+
+    #[test]
+    fn selects_highest_overlapping_protocol_version() {
+        assert_eq!(
+            select_protocol_version((1, PROTOCOL_VERSION), (1, 9)).unwrap(),
+            ProtocolVersion::new(PROTOCOL_VERSION).unwrap()
+        );
+        let err = select_protocol_version((1, 1), (2, 3)).unwrap_err();
+        assert!(err.contains("local supports 1-1, peer supports 2-3"));
+        assert_eq!(ProtocolVersion::LEGACY.command_schema(), 1);
+        assert!(ProtocolVersion::new(PROTOCOL_VERSION + 1).is_none());
+
+        let fallback = serde_json::to_string(&ControlFrame::LegacyFallback { reason: None });
+        assert_eq!(fallback.unwrap(), r#"{"type":"legacy_fallback"}"#);
+    }
+
+    #[test]
+    fn hello_without_bauds_stays_compatible() {
+        let legacy =
+            r#"{"type":"hello","proto_version":1,"node_id":7,"caps":{"bits":1},"pref":"none"}"#;
+        let Ok(ControlFrame::Hello { bauds, .. }) = serde_json::from_str(legacy) else {
+            panic!("legacy hello should parse");
+        };
+        assert!(bauds.is_empty());
+        assert_eq!(
+            highest_common_baud(&[9_600, 57_600, 115_200], &[9_600, 57_600]),
+            Some(57_600)
+        );
+        assert_eq!(highest_common_baud(&[9_600], &[]), None);
+    }
 }
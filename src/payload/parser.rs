@@ -1,6 +1,7 @@
 use crate::{
     compression::{compress, decompress, CompressionCodec},
     config::DEFAULT_PROTOCOL_SCHEMA_VERSION,
+    display::backlight::{Rgb, Severity},
     Error, Result, CACHE_DIR,
 };
 use crc32fast::Hasher;
@@ -84,11 +85,19 @@ struct CommandFrameWriter<'a> {
 }
 
 pub fn encode_command_frame(msg: &CommandMessage) -> Result<String> {
+    encode_command_frame_with_schema(msg, COMMAND_SCHEMA_VERSION)
+}
+
+/// Encode with the command `schema_version` selected by protocol negotiation.
+pub fn encode_command_frame_with_schema(
+    msg: &CommandMessage,
+    schema_version: u8,
+) -> Result<String> {
     validate_command_message(msg)?;
     let crc32 = msg.crc32()?;
     let frame = CommandFrameWriter {
         channel: "command",
-        schema_version: COMMAND_SCHEMA_VERSION,
+        schema_version,
         message: msg,
         crc32,
     };
@@ -102,6 +111,11 @@ pub fn encode_command_frame(msg: &CommandMessage) -> Result<String> {
 }
 
 pub fn decode_command_frame(raw: &str) -> Result<CommandMessage> {
+    decode_command_frame_with_schema(raw, COMMAND_SCHEMA_VERSION)
+}
+
+/// Decode, accepting only the command `schema_version` selected by protocol negotiation.
+pub fn decode_command_frame_with_schema(raw: &str, schema_version: u8) -> Result<CommandMessage> {
     if raw.len() > COMMAND_MAX_FRAME_BYTES {
         return Err(Error::Parse(format!(
             "command frame exceeds {COMMAND_MAX_FRAME_BYTES} bytes"
@@ -112,9 +126,9 @@ pub fn decode_command_frame(raw: &str) -> Result<CommandMessage> {
     if frame.channel != "command" {
         return Err(Error::Parse("unsupported command channel".into()));
     }
-    if frame.schema_version != COMMAND_SCHEMA_VERSION {
+    if frame.schema_version != schema_version {
         return Err(Error::Parse(format!(
-            "unsupported command schema_version={} expected={COMMAND_SCHEMA_VERSION}",
+            "unsupported command schema_version={} expected={schema_version}",
             frame.schema_version
         )));
     }
@@ -184,6 +198,12 @@ struct FrameTypeProbe {
     kind: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct DisplayProbe {
+    display: Option<String>,
+    schema_version: Option<u8>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct CompressionEnvelopeOwned {
@@ -378,6 +398,9 @@ fn normalize_kv_payload_to_json(raw: &str) -> Result<Option<String>> {
                     .ok_or_else(|| Error::Parse("backlight must be a boolean".into()))?;
                 obj.insert("backlight".into(), serde_json::Value::Bool(v));
             }
+            "backlight_color" | "severity" | "display" => {
+                obj.insert(key.clone(), serde_json::Value::String(value));
+            }
             "blink" => {
                 let v = parse_bool_kv(&value)
                     .ok_or_else(|| Error::Parse("blink must be a boolean".into()))?;
@@ -465,6 +488,27 @@ fn normalize_kv_payload_to_json(raw: &str) -> Result<Option<String>> {
     Ok(Some(json))
 }
 
+/// Display a normalized payload is addressed to, read without building the whole frame so
+/// the daemon can pick which display's queue ingests it.
+pub fn payload_display_target(normalized: &str) -> Result<Option<String>> {
+    let probe: DisplayProbe =
+        serde_json::from_str(normalized).map_err(|e| Error::Parse(format!("json: {e}")))?;
+    Ok(probe.display)
+}
+
+/// Reject a normalized payload whose `schema_version` is newer than the negotiated protocol
+/// allows.
+pub fn check_payload_schema(normalized: &str, max_schema_version: u8) -> Result<()> {
+    let probe: DisplayProbe =
+        serde_json::from_str(normalized).map_err(|e| Error::Parse(format!("json: {e}")))?;
+    match probe.schema_version {
+        Some(v) if v > max_schema_version => Err(Error::Parse(format!(
+            "unsupported payload schema_version={v} max={max_schema_version}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
 pub fn normalize_payload_json_with_policy<'a>(
     raw: &'a str,
     policy: CompressionPolicy,
@@ -574,6 +618,17 @@ pub struct Payload {
 
     #[serde(default)]
     pub backlight: Option<bool>, // only sent when false to turn off
+    /// `#rrggbb` or a colour name for RGB-backlit panels. Skipped when absent so
+    /// checksums from older senders still match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backlight_color: Option<String>,
+    /// `ok`, `info`, `warning`, or `critical`; colours the backlight when
+    /// `backlight_color` is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    /// Name of the configured display this frame is for; the primary display when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
     #[serde(default)]
     pub blink: Option<bool>,
     #[serde(default)]
@@ -603,7 +658,13 @@ pub struct RenderFrame {
     pub line1: String,
     pub line2: String,
     pub backlight_on: bool,
+    /// Explicit `backlight_color`, else the colour for `severity`; `None` keeps the default.
+    pub backlight_color: Option<Rgb>,
+    /// Target display name from the payload; `None` means the primary display.
+    pub display: Option<String>,
     pub blink: bool,
+    /// `severity` is warning or critical; wakes a dimmed, scheduled-off, or idle backlight.
+    pub alert: bool,
     pub bar_percent: Option<u8>,
     pub bar_label: Option<String>,
     pub bar_row: Option<u8>, // 0 = top, 1 = bottom
@@ -688,6 +749,23 @@ impl RenderFrame {
                 return Err(Error::Parse("page_timeout_ms must be > 0".into()));
             }
         }
+        if let Some(color) = &payload.backlight_color {
+            color
+                .parse::<Rgb>()
+                .map_err(|e| Error::Parse(format!("backlight_color: {e}")))?;
+        }
+        if let Some(severity) = &payload.severity {
+            severity
+                .parse::<Severity>()
+                .map_err(|e| Error::Parse(format!("severity: {e}")))?;
+        }
+        if payload
+            .display
+            .as_deref()
+            .is_some_and(|name| name.trim().is_empty())
+        {
+            return Err(Error::Parse("display must not be empty".into()));
+        }
 
         if let Some(checksum_hex) = &payload.checksum {
             let canonical = Payload {
@@ -711,7 +789,22 @@ impl RenderFrame {
 
     pub fn from_payload_with_defaults(payload: Payload, defaults: Defaults) -> Self {
         let backlight_on = payload.backlight.unwrap_or(true);
+        let backlight_color = payload
+            .backlight_color
+            .as_deref()
+            .and_then(|raw| raw.parse::<Rgb>().ok())
+            .or_else(|| {
+                payload
+                    .severity
+                    .as_deref()
+                    .and_then(|raw| raw.parse::<Severity>().ok())
+                    .map(Severity::color)
+            });
         let blink = payload.blink.unwrap_or(false);
+        let alert = matches!(
+            payload.severity.as_deref().map(str::parse::<Severity>),
+            Some(Ok(Severity::Warning | Severity::Critical))
+        );
         let scroll_enabled = payload.scroll.unwrap_or(true);
         let scroll_speed_ms = payload.scroll_speed_ms.unwrap_or(defaults.scroll_speed_ms);
         let page_timeout_ms = payload.page_timeout_ms.unwrap_or(defaults.page_timeout_ms);
@@ -746,7 +839,10 @@ impl RenderFrame {
             line1,
             line2,
             backlight_on,
+            backlight_color,
+            display: payload.display,
             blink,
+            alert,
             bar_percent,
             bar_label: payload.bar_label,
             bar_row,
@@ -894,6 +990,9 @@ mod tests {
             bar_line1: None,
             bar_line2: None,
             backlight: None,
+            backlight_color: None,
+            severity: None,
+            display: None,
             blink: None,
             scroll: None,
             scroll_speed_ms: None,
@@ -933,6 +1032,9 @@ mod tests {
             bar_line1: None,
             bar_line2: None,
             backlight: None,
+            backlight_color: None,
+            severity: None,
+            display: None,
             blink: None,
             scroll: None,
             scroll_speed_ms: None,
@@ -984,6 +1086,31 @@ mod tests {
         assert!(!frame.backlight_on);
     }
 
+    #[test]
+    fn backlight_color_wins_over_severity() {
+        let frame = parse(r#"{"schema_version":1,"line1":"","line2":"","severity":"warning"}"#);
+        assert_eq!(frame.backlight_color, Some(Rgb::AMBER));
+        assert!(frame.alert);
+        assert!(!parse(r#"{"schema_version":1,"line1":"","line2":"","severity":"info"}"#).alert);
+        let frame = parse(
+            r##"{"schema_version":1,"line1":"","line2":"","severity":"critical","backlight_color":"#0000ff"}"##,
+        );
+        assert_eq!(frame.backlight_color, Some(Rgb::BLUE));
+        assert_eq!(
+            parse(r#"{"schema_version":1,"line1":"","line2":""}"#).backlight_color,
+            None
+        );
+
+        let kv = parse("schema_version=1 line1=Disk line2=91% severity=critical");
+        assert_eq!(kv.backlight_color, Some(Rgb::RED));
+
+        let err = RenderFrame::from_payload_json(
+            r#"{"schema_version":1,"line1":"","line2":"","backlight_color":"mauve"}"#,
+        )
+        .unwrap_err();
+        assert!(format!("{err}").contains("backlight_color"));
+    }
+
     #[test]
     fn blink_defaults_false_and_can_enable() {
         let raw_default = r#"{"schema_version":1,"line1":"","line2":""}"#;
@@ -8,23 +8,120 @@ pub const TUNNEL_MAX_FRAME_BYTES: usize = 4096;
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TunnelMsg<'a> {
-    CmdRequest { cmd: Cow<'a, str> },
-    Stdout { chunk: Cow<'a, [u8]> },
-    Stderr { chunk: Cow<'a, [u8]> },
-    Exit { code: i32 },
+    CmdRequest {
+        cmd: Cow<'a, str>,
+    },
+    Stdout {
+        chunk: Cow<'a, [u8]>,
+    },
+    Stderr {
+        chunk: Cow<'a, [u8]>,
+    },
+    Exit {
+        code: i32,
+    },
     Busy,
     Heartbeat,
+    Ping {
+        seq: u32,
+        sent_ms: u64,
+    },
+    Pong {
+        seq: u32,
+        sent_ms: u64,
+    },
+    AuditRequest {
+        limit: u32,
+    },
+    FilePush {
+        path: Cow<'a, str>,
+        offset: u64,
+        chunk: Cow<'a, [u8]>,
+    },
+    FilePull {
+        path: Cow<'a, str>,
+    },
+    ForwardOpen {
+        stream_id: u32,
+        target: Cow<'a, str>,
+    },
+    ForwardData {
+        stream_id: u32,
+        chunk: Cow<'a, [u8]>,
+    },
+    ForwardWindow {
+        stream_id: u32,
+        credit: u32,
+    },
+    ForwardClose {
+        stream_id: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TunnelMsgOwned {
-    CmdRequest { cmd: String },
-    Stdout { chunk: Vec<u8> },
-    Stderr { chunk: Vec<u8> },
-    Exit { code: i32 },
+    CmdRequest {
+        cmd: String,
+    },
+    Stdout {
+        chunk: Vec<u8>,
+    },
+    Stderr {
+        chunk: Vec<u8>,
+    },
+    Exit {
+        code: i32,
+    },
     Busy,
     Heartbeat,
+    /// Latency probe; `sent_ms` is the sender's own clock and only has to come back unchanged.
+    Ping {
+        seq: u32,
+        sent_ms: u64,
+    },
+    /// Answer to a `Ping`, echoing its `seq` and `sent_ms`.
+    Pong {
+        seq: u32,
+        sent_ms: u64,
+    },
+    AuditRequest {
+        limit: u32,
+    },
+    /// Write `chunk` at `offset` into a file under the peer's transfer directory.
+    FilePush {
+        path: String,
+        offset: u64,
+        chunk: Vec<u8>,
+    },
+    /// Stream a file from the peer's transfer directory back as stdout chunks.
+    FilePull {
+        path: String,
+    },
+    /// Ask the peer to open a TCP connection to an allowlisted `host:port` for `stream_id`.
+    ForwardOpen {
+        stream_id: u32,
+        target: String,
+    },
+    /// Bytes for one forwarded stream; never more than the receiver's advertised window.
+    /// An empty `chunk` half-closes the sender's direction.
+    ForwardData {
+        stream_id: u32,
+        chunk: Vec<u8>,
+    },
+    /// Grant the sender `credit` more bytes on `stream_id`.
+    ForwardWindow {
+        stream_id: u32,
+        credit: u32,
+    },
+    /// Tear down a forwarded stream; `reason` is set when it failed.
+    ForwardClose {
+        stream_id: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
 }
 
 impl<'a> TunnelMsg<'a> {
@@ -49,6 +146,35 @@ impl<'a> TunnelMsg<'a> {
             TunnelMsg::Exit { code } => TunnelMsgOwned::Exit { code },
             TunnelMsg::Busy => TunnelMsgOwned::Busy,
             TunnelMsg::Heartbeat => TunnelMsgOwned::Heartbeat,
+            TunnelMsg::Ping { seq, sent_ms } => TunnelMsgOwned::Ping { seq, sent_ms },
+            TunnelMsg::Pong { seq, sent_ms } => TunnelMsgOwned::Pong { seq, sent_ms },
+            TunnelMsg::AuditRequest { limit } => TunnelMsgOwned::AuditRequest { limit },
+            TunnelMsg::FilePush {
+                path,
+                offset,
+                chunk,
+            } => TunnelMsgOwned::FilePush {
+                path: path.into_owned(),
+                offset,
+                chunk: chunk.into_owned(),
+            },
+            TunnelMsg::FilePull { path } => TunnelMsgOwned::FilePull {
+                path: path.into_owned(),
+            },
+            TunnelMsg::ForwardOpen { stream_id, target } => TunnelMsgOwned::ForwardOpen {
+                stream_id,
+                target: target.into_owned(),
+            },
+            TunnelMsg::ForwardData { stream_id, chunk } => TunnelMsgOwned::ForwardData {
+                stream_id,
+                chunk: chunk.into_owned(),
+            },
+            TunnelMsg::ForwardWindow { stream_id, credit } => {
+                TunnelMsgOwned::ForwardWindow { stream_id, credit }
+            }
+            TunnelMsg::ForwardClose { stream_id, reason } => {
+                TunnelMsgOwned::ForwardClose { stream_id, reason }
+            }
         }
     }
 }
@@ -148,6 +274,58 @@ mod tests {
         assert!(format!("{err}").contains("tunnel frame exceeds"));
     }
 
+    #[test]
+    fn audit_request_round_trips_with_crc() {
+        let msg = TunnelMsgOwned::AuditRequest { limit: 20 };
+        let encoded = encode_tunnel_msg(&msg).unwrap();
+        assert!(encoded.contains("\"audit_request\""));
+        let decoded = decode_tunnel_frame(&encoded).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn file_push_and_pull_round_trip_with_crc() {
+        let push = TunnelMsgOwned::FilePush {
+            path: "logs/app.log".into(),
+            offset: 512,
+            chunk: vec![0, 1, 255],
+        };
+        let encoded = encode_tunnel_msg(&push).unwrap();
+        assert_eq!(decode_tunnel_frame(&encoded).unwrap(), push);
+
+        let pull = TunnelMsgOwned::FilePull {
+            path: "logs/app.log".into(),
+        };
+        let encoded = encode_tunnel_msg(&pull).unwrap();
+        assert!(encoded.contains("\"file_pull\""));
+        assert_eq!(decode_tunnel_frame(&encoded).unwrap(), pull);
+    }
+
+    #[test]
+    fn forward_frames_round_trip_with_crc() {
+        for msg in [
+            TunnelMsgOwned::ForwardOpen {
+                stream_id: 3,
+                target: "127.0.0.1:22".into(),
+            },
+            TunnelMsgOwned::ForwardData {
+                stream_id: 3,
+                chunk: b"SSH-2.0".to_vec(),
+            },
+            TunnelMsgOwned::ForwardWindow {
+                stream_id: 3,
+                credit: 2048,
+            },
+            TunnelMsgOwned::ForwardClose {
+                stream_id: 3,
+                reason: None,
+            },
+        ] {
+            let encoded = encode_tunnel_msg(&msg).unwrap();
+            assert_eq!(decode_tunnel_frame(&encoded).unwrap(), msg);
+        }
+    }
+
     #[test]
     fn heartbeat_round_trips_with_crc() {
         let msg = TunnelMsgOwned::Heartbeat;
@@ -155,4 +333,21 @@ mod tests {
         let decoded = decode_tunnel_frame(&encoded).unwrap();
         assert_eq!(decoded, msg);
     }
+
+    #[test]
+    fn ping_and_pong_round_trip_with_crc() {
+        for msg in [
+            TunnelMsgOwned::Ping {
+                seq: 7,
+                sent_ms: 123_456,
+            },
+            TunnelMsgOwned::Pong {
+                seq: 7,
+                sent_ms: 123_456,
+            },
+        ] {
+            let encoded = encode_tunnel_msg(&msg).unwrap();
+            assert_eq!(decode_tunnel_frame(&encoded).unwrap(), msg);
+        }
+    }
 }
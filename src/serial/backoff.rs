@@ -33,6 +33,12 @@ impl BackoffController {
         self.next_retry_at = now;
     }
 
+    /// Let the next attempt run immediately (e.g. a device was just plugged in) without
+    /// shrinking the delay that follows if it fails too.
+    pub fn retry_now(&mut self, now: Instant) {
+        self.next_retry_at = now;
+    }
+
     pub fn should_retry(&self, now: Instant) -> bool {
         now >= self.next_retry_at
     }
@@ -95,4 +101,16 @@ mod tests {
         b.mark_success(now);
         assert_eq!(b.current_delay_ms(), 200);
     }
+
+    #[test]
+    fn retry_now_skips_the_wait_but_keeps_the_delay() {
+        let mut b = BackoffController::new(200, 800);
+        let now = Instant::now();
+        b.mark_failure(now);
+        b.mark_failure(now);
+        assert!(!b.should_retry(now));
+        b.retry_now(now);
+        assert!(b.should_retry(now));
+        assert_eq!(b.current_delay_ms(), 800);
+    }
 }
@@ -0,0 +1,527 @@
+use super::LineIo;
+use crate::{config::DEFAULT_BAUD, Result};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Prefix for addressed frames on a shared line: `@bus:<dst>:<src>:<line>`.
+const FRAME_PREFIX: &str = "@bus:";
+/// Body the master sends to hand a node the bus.
+const POLL_TOKEN: &str = "@poll";
+/// Body a node sends to hand the bus back to the master.
+const DONE_TOKEN: &str = "@done";
+
+/// Destination every node accepts.
+pub const BUS_BROADCAST: u8 = 0;
+/// Highest unicast address; matches the Modbus RTU range so existing bus plans carry over.
+pub const BUS_MAX_ADDRESS: u8 = 247;
+pub const BUS_DEFAULT_TURN_TIMEOUT_MS: u64 = 200;
+/// Lines a node holds while waiting for its turn before the oldest are dropped.
+pub const BUS_OUTBOX_LIMIT: usize = 64;
+/// Bits per byte on the wire with 8N1 framing.
+const BITS_PER_BYTE: u64 = 10;
+
+/// Which side of the master-polled arbitration this node plays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BusStation {
+    /// Owns the bus and hands it to each of `nodes` in turn.
+    Master { nodes: Vec<u8> },
+    /// Speaks only while holding a poll from `master`.
+    Node { master: u8 },
+}
+
+/// Counters surfaced in logs so operators can spot a chatty or silent bus.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BusStats {
+    pub frames_sent: u64,
+    pub delivered: u64,
+    /// Frames addressed to other nodes, or our own transmissions echoed back.
+    pub foreign_dropped: u64,
+    /// Lines without a bus header; something on the wire is not speaking the bus protocol.
+    pub unaddressed_dropped: u64,
+    /// Queued lines lost because the outbox filled before the next turn.
+    pub outbox_dropped: u64,
+    /// Turns this node ended early to stay inside the turn's byte budget.
+    pub turns_truncated: u64,
+    pub polls: u64,
+    pub turn_timeouts: u64,
+}
+
+struct Turn {
+    node: u8,
+    deadline: Instant,
+}
+
+/// Addressed, collision-free framing for several nodes sharing one half-duplex line.
+///
+/// Every line carries a destination and source address. Frames for other nodes are dropped, so
+/// the layers above see the same point-to-point stream they always did. Only the master
+/// transmits unprompted: it sends while no node holds the bus, then polls each node in turn. A
+/// node queues its lines until polled, sends what the line rate lets it finish within one turn
+/// timeout, and ends its turn with `@done`. The master restarts the timeout on every frame from
+/// the node holding the turn, and a node that stays silent loses the turn.
+pub struct BusLink<L> {
+    inner: L,
+    /// `None` when bus mode is off and lines pass through untouched.
+    station: Option<(u8, BusStation)>,
+    turn_timeout: Duration,
+    /// Line rate, used to size how much a node may send in one turn.
+    baud: u32,
+    outbox: VecDeque<(u8, String)>,
+    turn: Option<Turn>,
+    next_node: usize,
+    activity: bool,
+    scratch: String,
+    stats: BusStats,
+}
+
+impl<L: LineIo> BusLink<L> {
+    pub fn new(
+        inner: L,
+        address: u8,
+        station: BusStation,
+        turn_timeout: Duration,
+        baud: u32,
+    ) -> Self {
+        Self {
+            inner,
+            station: Some((address, station)),
+            turn_timeout,
+            baud,
+            outbox: VecDeque::new(),
+            turn: None,
+            next_node: 0,
+            activity: false,
+            scratch: String::new(),
+            stats: BusStats::default(),
+        }
+    }
+
+    /// Point-to-point link: no headers, no polling.
+    pub fn passthrough(inner: L) -> Self {
+        let mut link = Self::new(
+            inner,
+            BUS_BROADCAST,
+            BusStation::Node {
+                master: BUS_BROADCAST,
+            },
+            Duration::from_millis(BUS_DEFAULT_TURN_TIMEOUT_MS),
+            DEFAULT_BAUD,
+        );
+        link.station = None;
+        link
+    }
+
+    pub fn is_bus(&self) -> bool {
+        self.station.is_some()
+    }
+
+    pub fn address(&self) -> Option<u8> {
+        self.station.as_ref().map(|(address, _)| *address)
+    }
+
+    pub fn stats(&self) -> &BusStats {
+        &self.stats
+    }
+
+    /// Whether any bus traffic for this node (including polls and turn hand-backs) arrived
+    /// since the last call. Lets the watchdog see a healthy but otherwise quiet bus.
+    pub fn take_activity(&mut self) -> bool {
+        std::mem::take(&mut self.activity)
+    }
+
+    pub fn get_ref(&self) -> &L {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut L {
+        &mut self.inner
+    }
+
+    /// Send `line` to a single node (or [`BUS_BROADCAST`]) instead of the default destination.
+    pub fn send_to(&mut self, destination: u8, line: &str) -> Result<()> {
+        let Some((_, station)) = &self.station else {
+            return self.inner.send_command_line(line);
+        };
+        let may_send_now = matches!(station, BusStation::Master { .. }) && self.turn.is_none();
+        if may_send_now {
+            return self.transmit(destination, line);
+        }
+        if self.outbox.len() >= BUS_OUTBOX_LIMIT {
+            self.outbox.pop_front();
+            self.stats.outbox_dropped += 1;
+        }
+        self.outbox.push_back((destination, line.to_string()));
+        Ok(())
+    }
+
+    /// Master only: reclaim the bus from a silent node and hand it to the next one.
+    pub fn poll(&mut self) -> Result<()> {
+        let Some((address, BusStation::Master { nodes })) = &self.station else {
+            return Ok(());
+        };
+        let address = *address;
+        let next = (!nodes.is_empty()).then(|| (nodes[self.next_node % nodes.len()], nodes.len()));
+        if let Some(turn) = &self.turn {
+            if Instant::now() < turn.deadline {
+                return Ok(());
+            }
+            self.stats.turn_timeouts += 1;
+            self.turn = None;
+            self.flush_outbox()?;
+        }
+        let Some((node, count)) = next else {
+            return Ok(());
+        };
+        self.next_node = (self.next_node + 1) % count;
+        self.inner
+            .send_command_line(&encode_frame(node, address, POLL_TOKEN))?;
+        self.stats.polls += 1;
+        self.turn = Some(Turn {
+            node,
+            deadline: Instant::now() + self.turn_timeout,
+        });
+        Ok(())
+    }
+
+    fn default_destination(&self) -> u8 {
+        match &self.station {
+            Some((_, BusStation::Node { master })) => *master,
+            _ => BUS_BROADCAST,
+        }
+    }
+
+    fn transmit(&mut self, destination: u8, line: &str) -> Result<()> {
+        let source = self.address().unwrap_or(BUS_BROADCAST);
+        self.inner
+            .send_command_line(&encode_frame(destination, source, line))?;
+        self.stats.frames_sent += 1;
+        Ok(())
+    }
+
+    fn flush_outbox(&mut self) -> Result<()> {
+        while let Some((destination, line)) = self.outbox.pop_front() {
+            self.transmit(destination, &line)?;
+        }
+        Ok(())
+    }
+
+    /// Node side: send the queued lines that fit in one turn, then hand the bus back to
+    /// `master`. The rest waits for the next poll so the master never talks over us.
+    fn take_turn(&mut self, master: u8) -> Result<()> {
+        let address = self.address().unwrap_or(BUS_BROADCAST);
+        let done_bytes = frame_bytes(master, address, DONE_TOKEN);
+        let mut budget = self.turn_budget_bytes().saturating_sub(done_bytes);
+        let mut sent_any = false;
+        while let Some((destination, line)) = self.outbox.front() {
+            let cost = frame_bytes(*destination, address, line);
+            // Always send one line so an oversized frame cannot wedge the queue.
+            if cost > budget && sent_any {
+                self.stats.turns_truncated += 1;
+                break;
+            }
+            budget = budget.saturating_sub(cost);
+            sent_any = true;
+            let (destination, line) = self.outbox.pop_front().expect("front checked");
+            self.transmit(destination, &line)?;
+        }
+        self.transmit(master, DONE_TOKEN)
+    }
+
+    /// Bytes the line carries in one turn timeout.
+    fn turn_budget_bytes(&self) -> usize {
+        let bytes_per_sec = u64::from(self.baud) / BITS_PER_BYTE;
+        (bytes_per_sec * self.turn_timeout.as_millis() as u64 / 1000) as usize
+    }
+
+    /// Master side: the node holding the turn is still talking, so restart its timeout.
+    fn extend_turn(&mut self, source: u8) {
+        if let Some(turn) = self.turn.as_mut().filter(|turn| turn.node == source) {
+            turn.deadline = Instant::now() + self.turn_timeout;
+        }
+    }
+
+    /// Handle a bus control token; returns `false` when `body` is ordinary data.
+    fn handle_token(&mut self, source: u8, destination: u8, body: &str) -> Result<bool> {
+        let (address, master) = match &self.station {
+            None => return Ok(false),
+            Some((address, BusStation::Node { master })) => (*address, Some(*master)),
+            Some((address, BusStation::Master { .. })) => (*address, None),
+        };
+        match (master, body) {
+            (Some(master), POLL_TOKEN) => {
+                if source == master && destination == address {
+                    self.take_turn(master)?;
+                }
+                Ok(true)
+            }
+            (None, DONE_TOKEN) => {
+                if self.turn.as_ref().is_some_and(|turn| turn.node == source) {
+                    self.turn = None;
+                    self.flush_outbox()?;
+                    self.poll()?;
+                }
+                Ok(true)
+            }
+            (_, POLL_TOKEN | DONE_TOKEN) => Ok(true),
+            _ => Ok(false),
+        }
+    }
+}
+
+impl<L: LineIo> LineIo for BusLink<L> {
+    fn send_command_line(&mut self, line: &str) -> Result<()> {
+        let destination = self.default_destination();
+        self.send_to(destination, line)
+    }
+
+    fn set_baud(&mut self, baud: u32) -> Result<()> {
+        self.inner.set_baud(baud)?;
+        self.baud = baud;
+        Ok(())
+    }
+
+    fn read_message_line(&mut self, buf: &mut String) -> Result<usize> {
+        if self.station.is_none() {
+            return self.inner.read_message_line(buf);
+        }
+        buf.clear();
+        loop {
+            self.scratch.clear();
+            let read = self.inner.read_message_line(&mut self.scratch)?;
+            if read == 0 {
+                self.poll()?;
+                return Ok(0);
+            }
+            let raw = std::mem::take(&mut self.scratch);
+            let address = self.address().unwrap_or(BUS_BROADCAST);
+            let frame = parse_frame(raw.trim_end_matches(['\r', '\n']));
+            if let Some((_, source, _)) = frame {
+                self.extend_turn(source);
+            }
+            match frame {
+                None => self.stats.unaddressed_dropped += 1,
+                Some((destination, source, _))
+                    if source == address
+                        || (destination != address && destination != BUS_BROADCAST) =>
+                {
+                    self.stats.foreign_dropped += 1;
+                }
+                Some((destination, source, body)) => {
+                    self.activity = true;
+                    if !self.handle_token(source, destination, body)? {
+                        self.stats.delivered += 1;
+                        buf.push_str(body);
+                        return Ok(buf.len());
+                    }
+                }
+            }
+            self.scratch = raw;
+        }
+    }
+}
+
+fn encode_frame(destination: u8, source: u8, line: &str) -> String {
+    format!("{FRAME_PREFIX}{destination}:{source}:{line}")
+}
+
+/// Wire bytes of one encoded frame, including the line terminator.
+fn frame_bytes(destination: u8, source: u8, line: &str) -> usize {
+    encode_frame(destination, source, line).len() + 1
+}
+
+fn parse_frame(raw: &str) -> Option<(u8, u8, &str)> {
+    let mut parts = raw.strip_prefix(FRAME_PREFIX)?.splitn(3, ':');
+    let destination = parts.next()?.parse().ok()?;
+    let source = parts.next()?.parse().ok()?;
+    Some((destination, source, parts.next()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::fake::FakeSerialPort;
+
+    fn node(script: Vec<String>) -> BusLink<FakeSerialPort> {
+        BusLink::new(
+            FakeSerialPort::new(script.into_iter().map(Ok).collect()),
+            3,
+            BusStation::Node { master: 1 },
+            Duration::from_secs(60),
+            DEFAULT_BAUD,
+        )
+    }
+
+    #[test]
+    fn node_ignores_other_addresses_and_keeps_broadcasts() {
+        let mut link = node(vec![
+            encode_frame(2, 1, "{\"line1\":\"for two\"}"),
+            "{\"line1\":\"unaddressed\"}".into(),
+            encode_frame(3, 3, "echo of our own frame"),
+            encode_frame(BUS_BROADCAST, 1, "{\"line1\":\"everyone\"}"),
+            encode_frame(3, 1, "{\"line1\":\"for three\"}"),
+        ]);
+        let mut buf = String::new();
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(buf, "{\"line1\":\"everyone\"}");
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(buf, "{\"line1\":\"for three\"}");
+        let stats = link.stats();
+        assert_eq!(stats.foreign_dropped, 2);
+        assert_eq!(stats.unaddressed_dropped, 1);
+        assert_eq!(stats.delivered, 2);
+    }
+
+    #[test]
+    fn node_speaks_only_when_polled() {
+        let mut link = node(vec![encode_frame(3, 1, POLL_TOKEN)]);
+        link.send_command_line("reply one").unwrap();
+        link.send_command_line("reply two").unwrap();
+        assert!(link.get_ref().writes().is_empty(), "held until polled");
+
+        let mut buf = String::new();
+        assert_eq!(link.read_message_line(&mut buf).unwrap(), 0);
+        assert!(link.take_activity());
+        assert_eq!(
+            link.get_ref().writes(),
+            &[
+                encode_frame(1, 3, "reply one"),
+                encode_frame(1, 3, "reply two"),
+                encode_frame(1, 3, DONE_TOKEN),
+            ]
+        );
+    }
+
+    #[test]
+    fn master_polls_round_robin_and_waits_for_the_turn_to_end() {
+        let fake = FakeSerialPort::new(vec![
+            Ok(String::new()),
+            Ok(encode_frame(1, 2, "{\"ok\":true}")),
+            Ok(encode_frame(1, 2, DONE_TOKEN)),
+        ]);
+        let mut link = BusLink::new(
+            fake,
+            1,
+            BusStation::Master { nodes: vec![2, 3] },
+            Duration::from_secs(60),
+            DEFAULT_BAUD,
+        );
+        let mut buf = String::new();
+        assert_eq!(link.read_message_line(&mut buf).unwrap(), 0);
+        link.send_command_line("{\"line1\":\"hi\"}").unwrap();
+        assert_eq!(
+            link.get_ref().writes(),
+            &[encode_frame(2, 1, POLL_TOKEN)],
+            "broadcast waits while node 2 holds the bus"
+        );
+
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(buf, "{\"ok\":true}");
+        assert_eq!(link.read_message_line(&mut buf).unwrap(), 0);
+        assert_eq!(
+            link.get_ref().writes(),
+            &[
+                encode_frame(2, 1, POLL_TOKEN),
+                encode_frame(BUS_BROADCAST, 1, "{\"line1\":\"hi\"}"),
+                encode_frame(3, 1, POLL_TOKEN),
+            ]
+        );
+    }
+
+    #[test]
+    fn master_reclaims_the_bus_from_a_silent_node() {
+        let mut link = BusLink::new(
+            FakeSerialPort::default(),
+            1,
+            BusStation::Master { nodes: vec![4] },
+            Duration::ZERO,
+            DEFAULT_BAUD,
+        );
+        let mut buf = String::new();
+        link.read_message_line(&mut buf).unwrap();
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(link.stats().polls, 2);
+        assert_eq!(link.stats().turn_timeouts, 1);
+    }
+
+    #[test]
+    fn node_keeps_lines_that_do_not_fit_the_turn_for_the_next_poll() {
+        let mut link = BusLink::new(
+            FakeSerialPort::new(vec![
+                Ok(encode_frame(3, 1, POLL_TOKEN)),
+                Ok(String::new()),
+                Ok(encode_frame(3, 1, POLL_TOKEN)),
+            ]),
+            3,
+            BusStation::Node { master: 1 },
+            // 9600 baud carries 96 bytes in 100 ms: two of these frames plus `@done`.
+            Duration::from_millis(100),
+            9_600,
+        );
+        let line = "x".repeat(30);
+        for _ in 0..3 {
+            link.send_command_line(&line).unwrap();
+        }
+        let mut buf = String::new();
+        link.read_message_line(&mut buf).unwrap();
+        let reply = encode_frame(1, 3, &line);
+        let done = encode_frame(1, 3, DONE_TOKEN);
+        assert_eq!(
+            link.get_ref().writes(),
+            &[reply.clone(), reply.clone(), done.clone()]
+        );
+        assert_eq!(link.stats().turns_truncated, 1);
+
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(link.get_ref().writes()[3..], [reply, done]);
+    }
+
+    #[test]
+    fn master_extends_the_turn_while_the_node_keeps_talking() {
+        let timeout = Duration::from_millis(100);
+        let mut script = Vec::new();
+        for index in 0..3 {
+            script.push(Ok(encode_frame(1, 2, &format!("line {index}"))));
+            script.push(Ok(String::new()));
+        }
+        script.push(Ok(encode_frame(1, 2, DONE_TOKEN)));
+        let mut link = BusLink::new(
+            FakeSerialPort::new(script),
+            1,
+            BusStation::Master { nodes: vec![2] },
+            timeout,
+            DEFAULT_BAUD,
+        );
+        link.poll().unwrap();
+        let started = Instant::now();
+        let mut buf = String::new();
+        for _ in 0..3 {
+            std::thread::sleep(timeout * 2 / 5);
+            link.read_message_line(&mut buf).unwrap();
+            assert_eq!(link.read_message_line(&mut buf).unwrap(), 0);
+        }
+        assert!(
+            started.elapsed() > timeout,
+            "the turn outlasted one timeout"
+        );
+        assert_eq!(link.stats().turn_timeouts, 0);
+        assert_eq!(link.stats().polls, 1);
+
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(
+            link.stats().polls,
+            2,
+            "@done hands the bus to the next poll"
+        );
+    }
+
+    #[test]
+    fn passthrough_leaves_lines_alone() {
+        let mut link = BusLink::passthrough(FakeSerialPort::new(vec![Ok("plain\n".into())]));
+        link.send_command_line("INIT").unwrap();
+        let mut buf = String::new();
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(buf, "plain\n");
+        assert_eq!(link.get_ref().writes(), &["INIT"]);
+        assert!(!link.is_bus());
+    }
+}
@@ -0,0 +1,412 @@
+//! Find the serial adapter by what is plugged in rather than by kernel name, and notice when
+//! one is plugged in. `ttyUSB0` becomes `ttyUSB1` after a replug; the USB IDs, serial number
+//! and `/dev/serial/by-id` name stay put.
+
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+const SYS_CLASS_TTY: &str = "/sys/class/tty";
+const DEV_DIR: &str = "/dev";
+const BY_ID_DIR: &str = "serial/by-id";
+/// How often the fallback monitor rescans `/dev` when netlink is unavailable.
+const RESCAN_INTERVAL: Duration = Duration::from_millis(500);
+
+/// `device_match` criteria; every field that is set must match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceSelector {
+    /// USB vendor and product ID, as in `lsusb`.
+    pub usb_id: Option<(u16, u16)>,
+    /// USB serial number string.
+    pub serial: Option<String>,
+    /// Glob (`*`, `?`) matched against the names in `/dev/serial/by-id`.
+    pub by_id: Option<String>,
+}
+
+impl FromStr for DeviceSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut selector = DeviceSelector::default();
+        for part in s.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected key=value, got '{part}'"))?;
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "usb" => {
+                    let parsed = value.split_once(':').and_then(|(vid, pid)| {
+                        Some((
+                            u16::from_str_radix(vid, 16).ok()?,
+                            u16::from_str_radix(pid, 16).ok()?,
+                        ))
+                    });
+                    selector.usb_id = Some(parsed.ok_or_else(|| {
+                        format!("usb expects hex VID:PID such as 0403:6001, got '{value}'")
+                    })?);
+                }
+                "serial" if !value.is_empty() => selector.serial = Some(value.to_string()),
+                "by-id" | "by_id" if !value.is_empty() => selector.by_id = Some(value.to_string()),
+                other => {
+                    return Err(format!(
+                        "unknown or empty device_match key '{other}', expected usb|serial|by-id"
+                    ))
+                }
+            }
+        }
+        if selector == DeviceSelector::default() {
+            return Err("device_match needs at least one of usb=, serial= or by-id=".into());
+        }
+        Ok(selector)
+    }
+}
+
+impl fmt::Display for DeviceSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some((vid, pid)) = self.usb_id {
+            parts.push(format!("usb={vid:04x}:{pid:04x}"));
+        }
+        if let Some(serial) = &self.serial {
+            parts.push(format!("serial={serial}"));
+        }
+        if let Some(pattern) = &self.by_id {
+            parts.push(format!("by-id={pattern}"));
+        }
+        f.write_str(&parts.join(","))
+    }
+}
+
+impl DeviceSelector {
+    /// Current `/dev` path of the first matching port, in kernel-name order.
+    pub fn resolve(&self) -> Option<PathBuf> {
+        self.resolve_in(Path::new(SYS_CLASS_TTY), Path::new(DEV_DIR))
+    }
+
+    pub fn resolve_in(&self, sys_tty: &Path, dev: &Path) -> Option<PathBuf> {
+        let by_id_targets = self
+            .by_id
+            .as_ref()
+            .map(|pattern| by_id_targets(dev, pattern));
+        let mut names: Vec<String> = fs::read_dir(sys_tty)
+            .ok()?
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .collect();
+        names.sort();
+        names
+            .into_iter()
+            .find(|name| {
+                if by_id_targets
+                    .as_ref()
+                    .is_some_and(|targets| !targets.contains(name))
+                {
+                    return false;
+                }
+                if self.usb_id.is_none() && self.serial.is_none() {
+                    return true;
+                }
+                let Some(usb) = usb_attributes(&sys_tty.join(name).join("device")) else {
+                    return false;
+                };
+                self.usb_id.is_none_or(|id| usb.id == Some(id))
+                    && self
+                        .serial
+                        .as_deref()
+                        .is_none_or(|serial| usb.serial.as_deref() == Some(serial))
+            })
+            .map(|name| dev.join(name))
+    }
+}
+
+struct UsbAttributes {
+    id: Option<(u16, u16)>,
+    serial: Option<String>,
+}
+
+/// Walk up from the tty's device node to the USB device that owns it.
+fn usb_attributes(device_link: &Path) -> Option<UsbAttributes> {
+    let device = fs::canonicalize(device_link).ok()?;
+    let usb_dir = device
+        .ancestors()
+        .take(6)
+        .find(|dir| dir.join("idVendor").is_file())?;
+    let read = |file: &str| {
+        fs::read_to_string(usb_dir.join(file))
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+    let hex = |file: &str| read(file).and_then(|value| u16::from_str_radix(&value, 16).ok());
+    Some(UsbAttributes {
+        id: hex("idVendor").zip(hex("idProduct")),
+        serial: read("serial"),
+    })
+}
+
+/// Kernel names (`ttyUSB1`) that `/dev/serial/by-id` entries matching `pattern` point at.
+fn by_id_targets(dev: &Path, pattern: &str) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dev.join(BY_ID_DIR)) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            if !glob_match(pattern, &name) {
+                return None;
+            }
+            let target = fs::read_link(entry.path()).ok()?;
+            target.file_name()?.to_str().map(str::to_string)
+        })
+        .collect()
+}
+
+/// Shell-style match supporting `*` and `?`.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Reports newly plugged-in tty devices so a reconnect does not wait out the backoff.
+///
+/// Listens for udev's uevents over netlink on Linux, and also rescans `/dev` for new `tty*`
+/// nodes every half second, which covers systems without udev and other platforms.
+pub struct HotplugMonitor {
+    #[cfg(target_os = "linux")]
+    uevents: Option<std::os::fd::OwnedFd>,
+    known: Vec<String>,
+    next_scan: Instant,
+}
+
+impl Default for HotplugMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HotplugMonitor {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(target_os = "linux")]
+            uevents: open_uevent_socket(),
+            known: scan_tty_nodes(),
+            next_scan: Instant::now() + RESCAN_INTERVAL,
+        }
+    }
+
+    /// Whether a tty device appeared since the last call. Never blocks.
+    pub fn tty_added(&mut self) -> bool {
+        #[cfg(target_os = "linux")]
+        if let Some(fd) = &self.uevents {
+            if drain_uevents(fd) {
+                self.known = scan_tty_nodes();
+                return true;
+            }
+        }
+        let now = Instant::now();
+        if now < self.next_scan {
+            return false;
+        }
+        self.next_scan = now + RESCAN_INTERVAL;
+        let current = scan_tty_nodes();
+        let added = current.iter().any(|name| !self.known.contains(name));
+        self.known = current;
+        added
+    }
+}
+
+fn scan_tty_nodes() -> Vec<String> {
+    fs::read_dir(DEV_DIR)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .filter(|name| name.starts_with("tty"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `ACTION=add` for the tty subsystem in a uevent: NUL-separated `KEY=value` fields, behind a
+/// binary `libudev` header when udev sent it.
+fn is_tty_add(message: &[u8]) -> bool {
+    let (mut add, mut tty) = (false, false);
+    for field in uevent_properties(message).split(|&b| b == 0) {
+        add |= field == b"ACTION=add";
+        tty |= field == b"SUBSYSTEM=tty";
+    }
+    add && tty
+}
+
+/// udev's multicast group. Its events arrive once rules have run, so `/dev` nodes and the
+/// `by-id` links a `device_match` may rely on already exist; the kernel's own group 1 fires
+/// before that.
+#[cfg(target_os = "linux")]
+const UDEV_MONITOR_GROUP: u32 = 2;
+
+/// The property block of a udev monitor message (header offsets are in host byte order), or
+/// the whole message for a kernel uevent.
+fn uevent_properties(message: &[u8]) -> &[u8] {
+    let Some(header) = message.strip_prefix(b"libudev\0") else {
+        return message;
+    };
+    let field = |at: usize| {
+        header
+            .get(at..at + 4)
+            .map(|bytes| u32::from_ne_bytes(bytes.try_into().unwrap()) as usize)
+    };
+    // After the prefix: magic, header size, properties offset, properties length.
+    match (field(8), field(12)) {
+        (Some(offset), Some(len)) => message.get(offset..offset + len).unwrap_or_default(),
+        _ => &[],
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn open_uevent_socket() -> Option<std::os::fd::OwnedFd> {
+    use rustix::net::{bind, netlink, socket_with, AddressFamily, SocketFlags, SocketType};
+
+    let fd = socket_with(
+        AddressFamily::NETLINK,
+        SocketType::DGRAM,
+        SocketFlags::NONBLOCK | SocketFlags::CLOEXEC,
+        Some(netlink::KOBJECT_UEVENT),
+    )
+    .ok()?;
+    bind(&fd, &netlink::SocketAddrNetlink::new(0, UDEV_MONITOR_GROUP)).ok()?;
+    Some(fd)
+}
+
+#[cfg(target_os = "linux")]
+fn drain_uevents(fd: &std::os::fd::OwnedFd) -> bool {
+    use rustix::net::{recv, RecvFlags};
+
+    let mut buf = [0u8; 8192];
+    let mut added = false;
+    while let Ok((read, _)) = recv(fd, &mut buf[..], RecvFlags::DONTWAIT) {
+        if read == 0 {
+            break;
+        }
+        added |= is_tty_add(&buf[..read]);
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    /// Fake sysfs with a CH340 on ttyUSB0 and an FTDI adapter (serial A50285BI) on ttyUSB1.
+    fn fake_tree() -> tempfile::TempDir {
+        let root = tempfile::tempdir().unwrap();
+        let sys = root.path().join("sys");
+        let dev = root.path().join("dev");
+        for (tty, usb, vid, pid, serial) in [
+            ("ttyUSB0", "1-1", "1a86", "7523", None),
+            ("ttyUSB1", "1-2", "0403", "6001", Some("A50285BI")),
+        ] {
+            let usb_dir = sys.join("devices").join(usb);
+            let port_dir = usb_dir.join(format!("{usb}:1.0")).join(tty);
+            fs::create_dir_all(&port_dir).unwrap();
+            fs::write(usb_dir.join("idVendor"), format!("{vid}\n")).unwrap();
+            fs::write(usb_dir.join("idProduct"), format!("{pid}\n")).unwrap();
+            if let Some(serial) = serial {
+                fs::write(usb_dir.join("serial"), format!("{serial}\n")).unwrap();
+            }
+            let class_dir = sys.join("class/tty").join(tty);
+            fs::create_dir_all(&class_dir).unwrap();
+            symlink(&port_dir, class_dir.join("device")).unwrap();
+        }
+        fs::create_dir_all(sys.join("class/tty/tty0")).unwrap();
+        fs::create_dir_all(dev.join(BY_ID_DIR)).unwrap();
+        symlink(
+            "../../ttyUSB1",
+            dev.join(BY_ID_DIR)
+                .join("usb-FTDI_FT232R_USB_UART_A50285BI-if00-port0"),
+        )
+        .unwrap();
+        root
+    }
+
+    fn resolve(root: &tempfile::TempDir, selector: &str) -> Option<PathBuf> {
+        let selector: DeviceSelector = selector.parse().unwrap();
+        selector.resolve_in(&root.path().join("sys/class/tty"), &root.path().join("dev"))
+    }
+
+    #[test]
+    fn resolves_by_usb_id_serial_and_by_id_pattern() {
+        let root = fake_tree();
+        let dev = root.path().join("dev");
+        assert_eq!(resolve(&root, "usb=1a86:7523"), Some(dev.join("ttyUSB0")));
+        assert_eq!(resolve(&root, "serial=A50285BI"), Some(dev.join("ttyUSB1")));
+        assert_eq!(
+            resolve(&root, "by-id=usb-FTDI_*"),
+            Some(dev.join("ttyUSB1"))
+        );
+        assert_eq!(
+            resolve(&root, "usb=0403:6001, by-id=*A50285BI*"),
+            Some(dev.join("ttyUSB1"))
+        );
+        assert_eq!(resolve(&root, "usb=1a86:7523,serial=A50285BI"), None);
+        assert_eq!(resolve(&root, "by-id=usb-Prolific*"), None);
+    }
+
+    #[test]
+    fn parses_and_formats_selectors() {
+        let selector: DeviceSelector = "usb=0403:6001,serial=A50285BI".parse().unwrap();
+        assert_eq!(selector.usb_id, Some((0x0403, 0x6001)));
+        assert_eq!(selector.to_string(), "usb=0403:6001,serial=A50285BI");
+        assert!("usb=0403".parse::<DeviceSelector>().is_err());
+        assert!("path=/dev/ttyUSB0".parse::<DeviceSelector>().is_err());
+        assert!("".parse::<DeviceSelector>().is_err());
+    }
+
+    #[test]
+    fn recognises_tty_add_uevents() {
+        assert!(is_tty_add(
+            b"add@/devices/usb1/1-1/1-1:1.0/ttyUSB0/tty/ttyUSB0\0ACTION=add\0SUBSYSTEM=tty\0"
+        ));
+        assert!(!is_tty_add(b"remove@/x\0ACTION=remove\0SUBSYSTEM=tty\0"));
+        assert!(!is_tty_add(b"add@/x\0ACTION=add\0SUBSYSTEM=usb\0"));
+
+        // udev monitor message: prefix, magic, then offset and length of the properties.
+        let properties = b"ACTION=add\0SUBSYSTEM=tty\0DEVNAME=/dev/ttyUSB0\0";
+        let mut udev = b"libudev\0".to_vec();
+        udev.extend_from_slice(&0xfeed_cafe_u32.to_be_bytes());
+        udev.extend_from_slice(&40u32.to_ne_bytes());
+        udev.extend_from_slice(&40u32.to_ne_bytes());
+        udev.extend_from_slice(&(properties.len() as u32).to_ne_bytes());
+        udev.resize(39, 0xff);
+        udev.push(b'x');
+        udev.extend_from_slice(properties);
+        assert!(is_tty_add(&udev));
+        assert!(!is_tty_add(&udev[..30]), "truncated header");
+        assert!(glob_match("usb-*-if0?-port*", "usb-FTDI-if00-port0"));
+        assert!(!glob_match("usb-*", "pci-0000"));
+    }
+}
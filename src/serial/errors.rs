@@ -14,6 +14,8 @@ pub enum SerialFailureKind {
     Framing,
     Busy,
     Config,
+    /// The peer on the cable is not the pinned `negotiation.peer_uuid`.
+    PeerRejected,
     Unknown,
 }
 
@@ -27,6 +29,7 @@ impl SerialFailureKind {
             SerialFailureKind::Framing => "framing",
             SerialFailureKind::Busy => "busy",
             SerialFailureKind::Config => "config",
+            SerialFailureKind::PeerRejected => "peer_rejected",
             SerialFailureKind::Unknown => "unknown",
         }
     }
@@ -1,8 +1,11 @@
 #[cfg(feature = "async-serial")]
 pub mod r#async;
 pub mod backoff;
+pub mod bus;
+pub mod discovery;
 pub mod errors;
 pub mod fake;
+pub mod reliable;
 pub mod sync;
 pub mod telemetry;
 
@@ -157,6 +160,8 @@ pub struct SerialOptions {
     pub parity: ParityMode,
     pub stop_bits: StopBitsMode,
     pub dtr: DtrBehavior,
+    /// Hold RTS high only while transmitting (RS-485 driver enable on half-duplex lines).
+    pub rts_driver_enable: bool,
 }
 
 impl SerialOptions {
@@ -177,10 +182,16 @@ impl Default for SerialOptions {
             parity: ParityMode::None,
             stop_bits: StopBitsMode::One,
             dtr: DtrBehavior::Preserve,
+            rts_driver_enable: false,
         }
     }
 }
 
+/// Line rates offered during a negotiated baud upgrade, slowest first.
+pub const STANDARD_BAUD_RATES: &[u32] = &[
+    9_600, 19_200, 38_400, 57_600, 115_200, 230_400, 460_800, 921_600,
+];
+
 pub use errors::{classify_error, classify_io_error, SerialFailureKind};
 pub use sync::SerialPort;
 
@@ -188,4 +199,11 @@ pub use sync::SerialPort;
 pub trait LineIo {
     fn send_command_line(&mut self, line: &str) -> crate::Result<()>;
     fn read_message_line(&mut self, buf: &mut String) -> crate::Result<usize>;
+
+    /// Switch the line rate once pending output has drained (negotiated baud upgrade).
+    fn set_baud(&mut self, baud: u32) -> crate::Result<()> {
+        Err(crate::Error::InvalidArgs(format!(
+            "this link cannot switch to {baud} baud"
+        )))
+    }
 }
@@ -0,0 +1,704 @@
+use super::LineIo;
+use crate::{Error, Result};
+use crc32fast::Hasher;
+use std::collections::VecDeque;
+use std::io;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Prefix for sequenced data frames: `@rel:<epoch>:<base>:<seq>:<crc32>:<line>`.
+///
+/// `epoch` names the sender's session and `base` is its oldest unacknowledged sequence, so a
+/// receiver meeting a new epoch knows where to start. The CRC covers everything but itself.
+const DATA_PREFIX: &str = "@rel:";
+/// Prefix for cumulative ACKs: `@ack:<epoch>:<ack>:<crc32>`, where `epoch` is the session of
+/// the data being acknowledged.
+const ACK_PREFIX: &str = "@ack:";
+
+pub const RELIABLE_DEFAULT_WINDOW: usize = 8;
+pub const RELIABLE_DEFAULT_MAX_RETRIES: u32 = 8;
+/// Floor for the retransmit timer so fast links do not retransmit on scheduler jitter.
+pub const RELIABLE_MIN_RETRANSMIT_MS: u64 = 300;
+
+/// Tuning for the reliable-delivery layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReliableConfig {
+    /// Frames that may be in flight before new lines wait in the backlog.
+    pub window: usize,
+    /// Time without an ACK before the whole window is resent (go-back-N).
+    pub retransmit_after: Duration,
+    /// Consecutive unanswered retransmits before the link is declared dead.
+    pub max_retries: u32,
+}
+
+impl Default for ReliableConfig {
+    fn default() -> Self {
+        Self::for_baud(9600)
+    }
+}
+
+impl ReliableConfig {
+    /// Size the retransmit timer so a 1 KiB round trip fits comfortably at `baud`.
+    pub fn for_baud(baud: u32) -> Self {
+        let round_trip_ms = 2 * 1024 * 10 * 1000 / u64::from(baud.max(1));
+        Self {
+            window: RELIABLE_DEFAULT_WINDOW,
+            retransmit_after: Duration::from_millis(round_trip_ms.max(RELIABLE_MIN_RETRANSMIT_MS)),
+            max_retries: RELIABLE_DEFAULT_MAX_RETRIES,
+        }
+    }
+}
+
+/// Counters surfaced in logs so operators can see how lossy the link is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReliableStats {
+    pub frames_sent: u64,
+    pub retransmits: u64,
+    pub delivered: u64,
+    pub duplicates_dropped: u64,
+    pub out_of_order_dropped: u64,
+    pub corrupt_dropped: u64,
+    /// Times the receive side restarted its sequence for a new peer epoch.
+    pub resyncs: u64,
+}
+
+struct Pending {
+    seq: u32,
+    line: String,
+    sent_at: Instant,
+    /// Retransmitted frames give ambiguous round trips and are not sampled (Karn's rule).
+    retransmitted: bool,
+}
+
+enum Wire<'a> {
+    Data {
+        epoch: u32,
+        base: u32,
+        seq: u32,
+        line: &'a str,
+    },
+    Ack {
+        epoch: u32,
+        ack: u32,
+    },
+    /// A link frame whose CRC did not match; nothing in it can be trusted.
+    Corrupt,
+    Plain,
+}
+
+/// Sequenced, acknowledged delivery over any [`LineIo`].
+///
+/// Outgoing lines are only wrapped once both peers advertised
+/// [`Capabilities::RELIABLE_V1`](crate::negotiation::Capabilities::RELIABLE_V1); incoming
+/// sequenced frames are always unwrapped and acknowledged, so a peer that enabled the layer
+/// is never left retransmitting into the void. Unframed lines pass through untouched.
+pub struct ReliableLink<L> {
+    inner: L,
+    enabled: bool,
+    config: ReliableConfig,
+    /// This side's session; changes on every construction and reset.
+    epoch: u32,
+    next_seq: u32,
+    /// The peer session `recv_next` counts in, and the one it replaced.
+    peer_epoch: Option<u32>,
+    retired_peer_epoch: Option<u32>,
+    recv_next: u32,
+    unacked: VecDeque<Pending>,
+    backlog: VecDeque<String>,
+    last_sent_at: Option<Instant>,
+    retries: u32,
+    scratch: String,
+    stats: ReliableStats,
+    rtt_sample: Option<Duration>,
+}
+
+impl<L: LineIo> ReliableLink<L> {
+    pub fn new(inner: L, config: ReliableConfig) -> Self {
+        Self {
+            inner,
+            enabled: true,
+            config,
+            epoch: new_epoch(),
+            next_seq: 1,
+            peer_epoch: None,
+            retired_peer_epoch: None,
+            recv_next: 1,
+            unacked: VecDeque::new(),
+            backlog: VecDeque::new(),
+            last_sent_at: None,
+            retries: 0,
+            scratch: String::new(),
+            stats: ReliableStats::default(),
+            rtt_sample: None,
+        }
+    }
+
+    /// Send lines as-is; only the receive side of the protocol is active.
+    pub fn passthrough(inner: L) -> Self {
+        let mut link = Self::new(inner, ReliableConfig::default());
+        link.enabled = false;
+        link
+    }
+
+    /// Restart sequencing after the link was renegotiated in place. Frames still awaiting an
+    /// ACK or queued behind the window belong to the old session and are dropped; the new
+    /// epoch tells the peer to resynchronise, and the peer's next frame does the same here.
+    pub fn reset(&mut self, enabled: bool, config: ReliableConfig) {
+        self.enabled = enabled;
+        self.config = config;
+        self.epoch = new_epoch();
+        self.next_seq = 1;
+        self.retired_peer_epoch = self.peer_epoch.take();
+        self.recv_next = 1;
+        self.unacked.clear();
+        self.backlog.clear();
+        self.last_sent_at = None;
+        self.retries = 0;
+        self.rtt_sample = None;
+    }
+
+    pub fn is_reliable(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn stats(&self) -> &ReliableStats {
+        &self.stats
+    }
+
+    /// Send-to-ACK time of the most recently acknowledged frame that was sent only once.
+    pub fn take_rtt_sample(&mut self) -> Option<Duration> {
+        self.rtt_sample.take()
+    }
+
+    /// Frames sent but not yet acknowledged, plus those waiting for window space.
+    pub fn pending(&self) -> usize {
+        self.unacked.len() + self.backlog.len()
+    }
+
+    pub fn get_ref(&self) -> &L {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut L {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> L {
+        self.inner
+    }
+
+    /// Resend the window when the oldest frame has gone unacknowledged for too long.
+    pub fn poll(&mut self) -> Result<()> {
+        let Some(sent_at) = self.last_sent_at else {
+            return Ok(());
+        };
+        if self.unacked.is_empty() || sent_at.elapsed() < self.config.retransmit_after {
+            return Ok(());
+        }
+        if self.retries >= self.config.max_retries {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!(
+                    "reliable link: peer stopped acknowledging after {} retransmits",
+                    self.retries
+                ),
+            )));
+        }
+        self.retries += 1;
+        let base = self
+            .unacked
+            .front()
+            .map_or(self.next_seq, |front| front.seq);
+        for pending in &mut self.unacked {
+            self.inner.send_command_line(&encode_data(
+                self.epoch,
+                base,
+                pending.seq,
+                &pending.line,
+            ))?;
+            pending.retransmitted = true;
+            self.stats.retransmits += 1;
+        }
+        self.last_sent_at = Some(Instant::now());
+        Ok(())
+    }
+
+    fn last_received(&self) -> u32 {
+        self.recv_next.wrapping_sub(1)
+    }
+
+    fn transmit(&mut self, line: String) -> Result<()> {
+        let seq = self.next_seq;
+        self.next_seq = self.next_seq.wrapping_add(1);
+        let base = self.unacked.front().map_or(seq, |front| front.seq);
+        self.inner
+            .send_command_line(&encode_data(self.epoch, base, seq, &line))?;
+        self.stats.frames_sent += 1;
+        if self.unacked.is_empty() {
+            self.last_sent_at = Some(Instant::now());
+        }
+        self.unacked.push_back(Pending {
+            seq,
+            line,
+            sent_at: Instant::now(),
+            retransmitted: false,
+        });
+        Ok(())
+    }
+
+    fn handle_ack(&mut self, epoch: u32, ack: u32) -> Result<()> {
+        // ACKs for an earlier session of ours must not retire frames of this one.
+        if epoch != self.epoch {
+            return Ok(());
+        }
+        let mut progressed = false;
+        while let Some(front) = self.unacked.front() {
+            if !seq_before_or_eq(front.seq, ack) {
+                break;
+            }
+            if let Some(acked) = self.unacked.pop_front() {
+                if !acked.retransmitted {
+                    self.rtt_sample = Some(acked.sent_at.elapsed());
+                }
+            }
+            progressed = true;
+        }
+        if !progressed {
+            return Ok(());
+        }
+        self.retries = 0;
+        self.last_sent_at = Some(Instant::now());
+        while self.unacked.len() < self.config.window {
+            let Some(line) = self.backlog.pop_front() else {
+                break;
+            };
+            self.transmit(line)?;
+        }
+        Ok(())
+    }
+
+    fn send_ack(&mut self) -> Result<()> {
+        let Some(epoch) = self.peer_epoch else {
+            return Ok(());
+        };
+        self.inner
+            .send_command_line(&encode_ack(epoch, self.last_received()))
+    }
+
+    /// Follow the peer into a new session, starting at its oldest unacknowledged frame.
+    /// Returns `false` for frames from the session that was just replaced.
+    fn sync_peer(&mut self, epoch: u32, base: u32) -> bool {
+        if self.peer_epoch == Some(epoch) {
+            return true;
+        }
+        if self.retired_peer_epoch == Some(epoch) {
+            return false;
+        }
+        self.retired_peer_epoch = self.peer_epoch.replace(epoch);
+        self.recv_next = base;
+        self.stats.resyncs += 1;
+        true
+    }
+}
+
+impl<L: LineIo> LineIo for ReliableLink<L> {
+    fn send_command_line(&mut self, line: &str) -> Result<()> {
+        if !self.enabled {
+            return self.inner.send_command_line(line);
+        }
+        self.poll()?;
+        if self.unacked.len() >= self.config.window {
+            self.backlog.push_back(line.to_string());
+            return Ok(());
+        }
+        self.transmit(line.to_string())
+    }
+
+    fn set_baud(&mut self, baud: u32) -> Result<()> {
+        self.inner.set_baud(baud)
+    }
+
+    fn read_message_line(&mut self, buf: &mut String) -> Result<usize> {
+        buf.clear();
+        loop {
+            self.scratch.clear();
+            let read = self.inner.read_message_line(&mut self.scratch)?;
+            if read == 0 {
+                self.poll()?;
+                return Ok(0);
+            }
+            let raw = std::mem::take(&mut self.scratch);
+            match parse_wire(raw.trim_end_matches(['\r', '\n'])) {
+                Wire::Plain => {
+                    *buf = raw;
+                    return Ok(buf.len());
+                }
+                Wire::Corrupt => {
+                    // Stay silent; the sender's timer recovers the frame.
+                    self.stats.corrupt_dropped += 1;
+                }
+                Wire::Ack { epoch, ack } => self.handle_ack(epoch, ack)?,
+                Wire::Data {
+                    epoch,
+                    base,
+                    seq,
+                    line,
+                } => {
+                    if !self.sync_peer(epoch, base) {
+                        self.stats.duplicates_dropped += 1;
+                        self.scratch = raw;
+                        continue;
+                    }
+                    if seq == self.recv_next {
+                        self.recv_next = self.recv_next.wrapping_add(1);
+                        self.stats.delivered += 1;
+                        self.send_ack()?;
+                        buf.push_str(line);
+                        return Ok(buf.len());
+                    }
+                    if seq_before_or_eq(seq, self.last_received()) {
+                        self.stats.duplicates_dropped += 1;
+                    } else {
+                        self.stats.out_of_order_dropped += 1;
+                    }
+                    // Re-ACK so the sender learns where we are and retransmits from there.
+                    self.send_ack()?;
+                }
+            }
+            self.scratch = raw;
+        }
+    }
+}
+
+/// A fresh session id; only has to differ from the previous ones on this link.
+fn new_epoch() -> u32 {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let mut hasher = Hasher::new();
+    hasher.update(&nanos.to_le_bytes());
+    hasher.update(&std::process::id().to_le_bytes());
+    hasher.update(&COUNTER.fetch_add(1, Ordering::Relaxed).to_le_bytes());
+    hasher.finalize()
+}
+
+/// CRC-32 over the `:`-joined fields, i.e. the frame body without the CRC itself.
+fn checksum(fields: &[&str]) -> u32 {
+    let mut hasher = Hasher::new();
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            hasher.update(b":");
+        }
+        hasher.update(field.as_bytes());
+    }
+    hasher.finalize()
+}
+
+fn encode_data(epoch: u32, base: u32, seq: u32, line: &str) -> String {
+    let (epoch, base, seq) = (format!("{epoch:08x}"), base.to_string(), seq.to_string());
+    let crc32 = checksum(&[&epoch, &base, &seq, line]);
+    format!("{DATA_PREFIX}{epoch}:{base}:{seq}:{crc32:08x}:{line}")
+}
+
+fn encode_ack(epoch: u32, ack: u32) -> String {
+    let (epoch, ack) = (format!("{epoch:08x}"), ack.to_string());
+    let crc32 = checksum(&[&epoch, &ack]);
+    format!("{ACK_PREFIX}{epoch}:{ack}:{crc32:08x}")
+}
+
+fn parse_wire(raw: &str) -> Wire<'_> {
+    if let Some(rest) = raw.strip_prefix(ACK_PREFIX) {
+        let mut parts = rest.trim().splitn(3, ':');
+        let (Some(epoch), Some(ack), Some(crc32)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Wire::Corrupt;
+        };
+        if u32::from_str_radix(crc32, 16).ok() != Some(checksum(&[epoch, ack])) {
+            return Wire::Corrupt;
+        }
+        return match (u32::from_str_radix(epoch, 16), ack.parse()) {
+            (Ok(epoch), Ok(ack)) => Wire::Ack { epoch, ack },
+            _ => Wire::Corrupt,
+        };
+    }
+    let Some(rest) = raw.strip_prefix(DATA_PREFIX) else {
+        return Wire::Plain;
+    };
+    let mut parts = rest.splitn(5, ':');
+    let (Some(epoch), Some(base), Some(seq), Some(crc32), Some(line)) = (
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+    ) else {
+        return Wire::Corrupt;
+    };
+    // Verify before trusting any field, so a flipped bit never moves the sequence.
+    if u32::from_str_radix(crc32, 16).ok() != Some(checksum(&[epoch, base, seq, line])) {
+        return Wire::Corrupt;
+    }
+    match (u32::from_str_radix(epoch, 16), base.parse(), seq.parse()) {
+        (Ok(epoch), Ok(base), Ok(seq)) if seq_before_or_eq(base, seq) => Wire::Data {
+            epoch,
+            base,
+            seq,
+            line,
+        },
+        _ => Wire::Corrupt,
+    }
+}
+
+/// `a <= b` in wrapping sequence space.
+fn seq_before_or_eq(a: u32, b: u32) -> bool {
+    (b.wrapping_sub(a) as i32) >= 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::fake::FakeSerialPort;
+
+    /// Epoch used for the scripted peer in these tests.
+    const PEER: u32 = 0x0bad_cafe;
+
+    fn instant_config() -> ReliableConfig {
+        ReliableConfig {
+            window: 2,
+            retransmit_after: Duration::ZERO,
+            max_retries: 3,
+        }
+    }
+
+    /// A link whose own epoch is fixed so scripted ACKs can name it.
+    fn link_with(
+        script: Vec<Result<String>>,
+        config: ReliableConfig,
+    ) -> ReliableLink<FakeSerialPort> {
+        let mut link = ReliableLink::new(FakeSerialPort::new(script), config);
+        link.epoch = 7;
+        link
+    }
+
+    #[test]
+    fn wraps_outgoing_lines_and_waits_for_window_space() {
+        let mut link = link_with(
+            vec![Ok(encode_ack(7, 1))],
+            ReliableConfig {
+                retransmit_after: Duration::from_secs(60),
+                ..instant_config()
+            },
+        );
+        for line in ["one", "two", "three"] {
+            link.send_command_line(line).unwrap();
+        }
+        assert_eq!(link.get_ref().writes().len(), 2, "third line is held back");
+        assert_eq!(link.pending(), 3);
+
+        let mut buf = String::new();
+        assert_eq!(link.read_message_line(&mut buf).unwrap(), 0);
+        let writes = link.get_ref().writes();
+        assert_eq!(writes.len(), 3);
+        assert_eq!(writes[0], encode_data(7, 1, 1, "one"));
+        assert_eq!(writes[1], encode_data(7, 1, 2, "two"));
+        assert_eq!(writes[2], encode_data(7, 2, 3, "three"));
+        assert_eq!(link.pending(), 2);
+        assert!(link.take_rtt_sample().is_some());
+        assert_eq!(link.take_rtt_sample(), None);
+    }
+
+    #[test]
+    fn retransmits_dropped_frames_until_acknowledged() {
+        // The peer never sees the first transmission; the scripted ACK arrives after a retry.
+        let mut link = link_with(
+            vec![Ok(String::new()), Ok(encode_ack(7, 1))],
+            instant_config(),
+        );
+        link.send_command_line("payload").unwrap();
+
+        let mut buf = String::new();
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(link.stats().retransmits, 1);
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(link.pending(), 0);
+        assert_eq!(
+            link.take_rtt_sample(),
+            None,
+            "retransmitted frames are not timed"
+        );
+        let writes = link.get_ref().writes();
+        assert_eq!(
+            writes,
+            &[
+                encode_data(7, 1, 1, "payload"),
+                encode_data(7, 1, 1, "payload")
+            ]
+        );
+    }
+
+    #[test]
+    fn gives_up_after_max_retries() {
+        let mut link = ReliableLink::new(FakeSerialPort::default(), instant_config());
+        link.send_command_line("lost").unwrap();
+        let mut buf = String::new();
+        for _ in 0..3 {
+            assert_eq!(link.read_message_line(&mut buf).unwrap(), 0);
+        }
+        let err = link.read_message_line(&mut buf).unwrap_err();
+        assert!(format!("{err}").contains("stopped acknowledging"));
+    }
+
+    #[test]
+    fn suppresses_duplicates_and_out_of_order_frames() {
+        let fake = FakeSerialPort::new(vec![
+            Ok(encode_data(PEER, 1, 1, "first")),
+            Ok(encode_data(PEER, 1, 1, "first")),
+            Ok(encode_data(PEER, 2, 3, "third")),
+            Ok(encode_data(PEER, 2, 2, "second")),
+        ]);
+        let mut link = ReliableLink::passthrough(fake);
+        let mut buf = String::new();
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(buf, "first");
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(buf, "second");
+
+        let stats = link.stats();
+        assert_eq!(stats.duplicates_dropped, 1);
+        assert_eq!(stats.out_of_order_dropped, 1);
+        let ack = |n| encode_ack(PEER, n);
+        assert_eq!(link.get_ref().writes(), &[ack(1), ack(1), ack(1), ack(2)]);
+    }
+
+    #[test]
+    fn corrupt_frames_are_dropped_without_ack() {
+        let mut tampered = encode_data(PEER, 1, 1, "hello");
+        tampered.push('!');
+        let fake = FakeSerialPort::new(vec![Ok(tampered), Ok(encode_data(PEER, 1, 1, "hello"))]);
+        let mut link = ReliableLink::passthrough(fake);
+        let mut buf = String::new();
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(buf, "hello");
+        assert_eq!(link.stats().corrupt_dropped, 1);
+        assert_eq!(link.get_ref().writes(), &[encode_ack(PEER, 1)]);
+    }
+
+    #[test]
+    fn corrupted_headers_never_move_sequence_state() {
+        // A flipped digit in the ACK number or the data sequence must fail the CRC.
+        let forged_ack = encode_ack(7, 1).replacen(":1:", ":2:", 1);
+        let forged_seq = encode_data(PEER, 1, 1, "x").replacen(":1:1:", ":1:2:", 1);
+        let mut link = link_with(
+            vec![Ok(forged_ack), Ok(forged_seq), Ok("@ack:5".into())],
+            ReliableConfig {
+                retransmit_after: Duration::from_secs(60),
+                ..instant_config()
+            },
+        );
+        link.send_command_line("one").unwrap();
+        link.send_command_line("two").unwrap();
+        let mut buf = String::new();
+        assert_eq!(link.read_message_line(&mut buf).unwrap(), 0);
+        assert_eq!(link.pending(), 2, "nothing was acknowledged");
+        assert_eq!(link.stats().corrupt_dropped, 3);
+        assert_eq!(link.stats().delivered, 0);
+    }
+
+    #[test]
+    fn acks_for_an_earlier_epoch_are_ignored() {
+        let mut link = link_with(
+            vec![
+                Ok(encode_ack(6, 1)),
+                Ok(String::new()),
+                Ok(encode_ack(7, 1)),
+            ],
+            ReliableConfig {
+                retransmit_after: Duration::from_secs(60),
+                ..instant_config()
+            },
+        );
+        link.send_command_line("fresh").unwrap();
+        let mut buf = String::new();
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(link.pending(), 1);
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(link.pending(), 0);
+    }
+
+    #[test]
+    fn resyncs_when_the_peer_restarts() {
+        const RESTARTED: u32 = 0x1234_5678;
+        let fake = FakeSerialPort::new(vec![
+            Ok(encode_data(PEER, 1, 1, "a")),
+            Ok(encode_data(PEER, 2, 2, "b")),
+            // The peer restarted: its sequence begins at 1 again under a new epoch.
+            Ok(encode_data(RESTARTED, 1, 1, "again")),
+            // A straggler from the old session is not delivered.
+            Ok(encode_data(PEER, 3, 3, "stale")),
+            Ok(encode_data(RESTARTED, 1, 2, "next")),
+        ]);
+        let mut link = ReliableLink::passthrough(fake);
+        let mut delivered = Vec::new();
+        let mut buf = String::new();
+        while link.read_message_line(&mut buf).unwrap() > 0 {
+            delivered.push(buf.clone());
+        }
+        assert_eq!(delivered, ["a", "b", "again", "next"]);
+        assert_eq!(link.stats().resyncs, 2);
+        assert_eq!(link.stats().duplicates_dropped, 1);
+        assert_eq!(
+            link.get_ref().writes().last(),
+            Some(&encode_ack(RESTARTED, 2))
+        );
+    }
+
+    #[test]
+    fn joining_a_running_peer_starts_at_its_oldest_unacked_frame() {
+        // This side restarted; the peer still has frames 40.. outstanding.
+        let fake = FakeSerialPort::new(vec![
+            Ok(encode_data(PEER, 40, 41, "later")),
+            Ok(encode_data(PEER, 40, 40, "first")),
+            Ok(encode_data(PEER, 40, 41, "later")),
+        ]);
+        let mut link = ReliableLink::passthrough(fake);
+        let mut buf = String::new();
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(buf, "first");
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(buf, "later");
+        assert_eq!(link.stats().out_of_order_dropped, 1);
+    }
+
+    #[test]
+    fn reset_starts_a_new_epoch() {
+        let mut link = ReliableLink::new(FakeSerialPort::default(), instant_config());
+        let before = link.epoch;
+        link.reset(true, instant_config());
+        assert_ne!(link.epoch, before);
+        link.send_command_line("hi").unwrap();
+        assert_eq!(
+            link.get_ref().writes(),
+            &[encode_data(link.epoch, 1, 1, "hi")]
+        );
+    }
+
+    #[test]
+    fn passthrough_leaves_plain_lines_alone() {
+        let fake = FakeSerialPort::new(vec![Ok("{\"line1\":\"hi\"}\n".into())]);
+        let mut link = ReliableLink::passthrough(fake);
+        link.send_command_line("INIT").unwrap();
+        let mut buf = String::new();
+        link.read_message_line(&mut buf).unwrap();
+        assert_eq!(buf, "{\"line1\":\"hi\"}\n");
+        assert_eq!(link.get_ref().writes(), &["INIT"]);
+    }
+
+    #[test]
+    fn sequence_comparison_handles_wraparound() {
+        assert!(seq_before_or_eq(u32::MAX, 0));
+        assert!(seq_before_or_eq(5, 5));
+        assert!(!seq_before_or_eq(6, 5));
+    }
+}
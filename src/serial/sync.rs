@@ -10,9 +10,9 @@ use super::{DtrBehavior, FlowControlMode, ParityMode, SerialOptions, StopBitsMod
 pub struct SerialPort {
     #[allow(dead_code)]
     device: String,
-    #[allow(dead_code)]
     baud: u32,
     port: Option<Box<dyn serialport::SerialPort>>,
+    rts_driver_enable: bool,
 }
 
 impl SerialPort {
@@ -36,12 +36,18 @@ impl SerialPort {
             DtrBehavior::Deassert => builder.dtr_on_open(false),
         };
 
-        let port = builder.open().map_err(map_serial_error)?;
+        let mut port = builder.open().map_err(map_serial_error)?;
+        if options.rts_driver_enable {
+            // Start in receive mode so the transceiver does not hold the bus.
+            port.write_request_to_send(false)
+                .map_err(map_serial_error)?;
+        }
 
         Ok(Self {
             device: device.to_string(),
             baud: options.baud,
             port: Some(port),
+            rts_driver_enable: options.rts_driver_enable,
         })
     }
 
@@ -54,8 +60,17 @@ impl SerialPort {
 
         let mut buf = line.as_bytes().to_vec();
         buf.push(b'\n');
-        port.write_all(&buf)?;
-        port.flush()?;
+        if !self.rts_driver_enable {
+            port.write_all(&buf)?;
+            port.flush()?;
+            return Ok(());
+        }
+        port.write_request_to_send(true).map_err(map_serial_error)?;
+        // `flush` waits for the UART to drain, so the driver is not released mid-frame.
+        let written = port.write_all(&buf).and_then(|_| port.flush());
+        port.write_request_to_send(false)
+            .map_err(map_serial_error)?;
+        written?;
         Ok(())
     }
 
@@ -100,6 +115,23 @@ impl SerialPort {
         }
     }
 
+    /// Current line rate, including any negotiated upgrade.
+    pub fn baud(&self) -> u32 {
+        self.baud
+    }
+
+    /// Drain pending output, then reprogram the UART to `baud`.
+    pub fn set_baud(&mut self, baud: u32) -> Result<()> {
+        let port = self
+            .port
+            .as_mut()
+            .ok_or_else(|| Error::InvalidArgs("serial port not connected".into()))?;
+        port.flush()?;
+        port.set_baud_rate(baud).map_err(map_serial_error)?;
+        self.baud = baud;
+        Ok(())
+    }
+
     /// Provide a temporary reader over the serial port.
     pub fn borrow_reader(&mut self) -> Result<SerialReader<'_>> {
         let port = self
@@ -118,6 +150,10 @@ impl super::LineIo for SerialPort {
     fn read_message_line(&mut self, buf: &mut String) -> crate::Result<usize> {
         SerialPort::read_message_line(self, buf)
     }
+
+    fn set_baud(&mut self, baud: u32) -> crate::Result<()> {
+        SerialPort::set_baud(self, baud)
+    }
 }
 
 pub struct SerialReader<'a> {
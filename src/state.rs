@@ -51,9 +51,13 @@ impl RenderState {
 
     /// Ingest a JSON frame string. Returns Some(frame) if it is new, None if duplicate.
     pub fn ingest(&mut self, raw: &str) -> Result<Option<RenderFrame>> {
-        self.prune_expired(Instant::now());
         let normalized = normalize_payload_json_with_policy(raw, self.compression_policy)?;
-        let canonical = normalized.as_ref();
+        self.ingest_normalized(normalized.as_ref())
+    }
+
+    /// Ingest a frame that already went through `normalize_payload_json_with_policy`.
+    pub fn ingest_normalized(&mut self, canonical: &str) -> Result<Option<RenderFrame>> {
+        self.prune_expired(Instant::now());
         if canonical.len() > MAX_FRAME_BYTES {
             return Err(Error::Parse(format!(
                 "frame exceeds {MAX_FRAME_BYTES} bytes"
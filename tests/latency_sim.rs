@@ -1,3 +1,4 @@
+use lifelinetty::app::{latency::LatencyTracker, watchdog::WatchdogMonitor};
 use lifelinetty::payload::{
     decode_tunnel_frame, encode_tunnel_msg, Defaults as PayloadDefaults, TunnelMsgOwned,
     DEFAULT_PAGE_TIMEOUT_MS, DEFAULT_SCROLL_MS,
@@ -50,3 +51,77 @@ fn tunnel_latency_allows_lcd_frames() {
     assert_eq!(frames.len(), 2);
     assert!(elapsed >= Duration::from_millis(8));
 }
+
+/// Answer a ping the way the remote tunnel does, through the wire encoding both ways.
+fn echo(ping: TunnelMsgOwned) -> (u32, u64) {
+    let wire = encode_tunnel_msg(&ping).unwrap();
+    let TunnelMsgOwned::Ping { seq, sent_ms } = decode_tunnel_frame(&wire).unwrap() else {
+        panic!("expected ping on the wire: {wire}");
+    };
+    let wire = encode_tunnel_msg(&TunnelMsgOwned::Pong { seq, sent_ms }).unwrap();
+    match decode_tunnel_frame(&wire).expect("valid pong") {
+        TunnelMsgOwned::Pong { seq, sent_ms } => (seq, sent_ms),
+        other => panic!("expected pong, got {other:?}"),
+    }
+}
+
+/// Delayed and lost pongs feed the tracker's estimate, and the watchdog follows it: expiry
+/// tightens on a fast link, stretches on a slow one, and returns to the configured values
+/// once the estimate is dropped.
+#[test]
+fn delayed_and_lost_pongs_drive_rtt_and_watchdog_expiry() {
+    let mut tracker = LatencyTracker::new();
+    let start = Instant::now();
+    let at = |ms: u64| start + Duration::from_millis(ms);
+    let ping_interval = Duration::from_secs(1);
+    let configured = (Duration::from_secs(12), Duration::from_secs(5));
+    let mut watchdog = WatchdogMonitor::new(12_000, 5_000);
+    watchdog.adapt_to_rtt(tracker.rto(), ping_interval);
+    assert_eq!(watchdog.timeouts(), configured);
+
+    for (sent, delay) in [(0, 40), (1_000, 60), (2_000, 40)] {
+        let (seq, sent_ms) = echo(tracker.ping(at(sent)));
+        let rtt = tracker.pong(seq, sent_ms, at(sent + delay));
+        assert_eq!(rtt, Some(Duration::from_millis(delay)));
+    }
+    // The next pong never arrives; the one after it comes back 50 ms late.
+    let (lost_seq, lost_sent_ms) = echo(tracker.ping(at(3_000)));
+    let (seq, sent_ms) = echo(tracker.ping(at(4_000)));
+    assert_eq!(
+        tracker.pong(seq, sent_ms, at(4_050)),
+        Some(Duration::from_millis(50))
+    );
+    assert_eq!(tracker.pong(lost_seq, lost_sent_ms, at(4_100)), None);
+
+    let snapshot = tracker.snapshot();
+    assert_eq!((snapshot.samples, snapshot.lost), (4, 1));
+    assert_eq!(snapshot.min_ms, Some(40.0));
+    assert_eq!(snapshot.max_ms, Some(60.0));
+    assert_eq!(snapshot.last_ms, Some(50.0));
+    // srtt: 40 -> 42.5 -> 42.1875 -> 43.164; jitter: 1.25 -> 2.422 -> 2.896.
+    assert!((snapshot.srtt_ms.unwrap() - 43.164).abs() < 0.01);
+    assert!((snapshot.jitter_ms.unwrap() - 2.896).abs() < 0.01);
+
+    // Three missed pings plus four RTOs is well inside both configured timeouts.
+    watchdog.adapt_to_rtt(tracker.rto(), ping_interval);
+    let (serial, tunnel) = watchdog.timeouts();
+    assert!(serial >= Duration::from_secs(3) && serial < configured.0);
+    assert!(tunnel >= Duration::from_secs(3) && tunnel < configured.1);
+
+    // The link slows to 2 s round trips: expiry stretches past the configured tunnel
+    // timeout, but never beyond twice the configured value.
+    for i in 0..8 {
+        let sent = 10_000 + i * 3_000;
+        let (seq, sent_ms) = echo(tracker.ping(at(sent)));
+        tracker.pong(seq, sent_ms, at(sent + 2_000)).unwrap();
+    }
+    watchdog.adapt_to_rtt(tracker.rto(), ping_interval);
+    let (serial, tunnel) = watchdog.timeouts();
+    assert!(tunnel > configured.1 && tunnel <= configured.1 * 2);
+    assert!(serial <= configured.0 * 2);
+
+    tracker.reset_link();
+    watchdog.adapt_to_rtt(tracker.rto(), ping_interval);
+    assert_eq!(watchdog.timeouts(), configured);
+    assert_eq!(tracker.snapshot().samples, 12);
+}
@@ -461,6 +461,7 @@ fn pty_spawn_daemon_handshake_payload_and_command_frames() {
                     peer_caps: ControlCaps {
                         bits: Capabilities::default().bits(),
                     },
+                    proto_version: Some(1),
                 };
                 let encoded = serde_json::to_string(&ack).unwrap();
                 write_line(&master, &encoded);